@@ -0,0 +1,9 @@
+-- Migration 012: Per-user DJ settings (deck setup preferences used by scoring)
+
+CREATE TABLE IF NOT EXISTS user_settings (
+    user_id TEXT PRIMARY KEY,
+    key_lock BOOLEAN NOT NULL DEFAULT TRUE,  -- FALSE: pitch fader shifts the key (vinyl / master tempo off)
+    updated_at TIMESTAMP DEFAULT NOW()
+);
+
+-- NOTE: No FK to users — the X-User-Id header may name users not yet provisioned.
@@ -0,0 +1,15 @@
+-- Migration 013: Custom energy curves (breakpoints: position fraction → target energy)
+
+CREATE TABLE IF NOT EXISTS energy_curves (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    breakpoints TEXT NOT NULL,  -- JSON array of {"position": 0.0-1.0, "energy": 1-10}
+    created_at TIMESTAMP DEFAULT NOW(),
+    UNIQUE(user_id, name)
+);
+
+CREATE INDEX IF NOT EXISTS idx_energy_curves_user_id ON energy_curves(user_id);
+
+-- Curve a setlist was generated with (same JSON shape), used as the arrange fallback
+ALTER TABLE setlists ADD COLUMN IF NOT EXISTS energy_curve TEXT;
@@ -0,0 +1,4 @@
+-- Migration 014: Per-user transition scoring weights and BPM tolerance bands
+
+-- JSON-encoded ScoringProfile; NULL means the built-in weights (key 50%, BPM 30%, energy 20%).
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS scoring_profile TEXT;
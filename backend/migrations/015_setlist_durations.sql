@@ -0,0 +1,7 @@
+-- Migration 015: Track durations and target runtime for time-slot setlists
+
+-- Catalog duration for catalog tracks, LLM estimate for suggestions; NULL if unknown.
+ALTER TABLE setlist_tracks ADD COLUMN IF NOT EXISTS duration_ms INTEGER;
+
+-- Requested set length when generated from a target duration instead of a track count.
+ALTER TABLE setlists ADD COLUMN IF NOT EXISTS target_duration_ms BIGINT;
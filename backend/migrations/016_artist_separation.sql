@@ -0,0 +1,4 @@
+-- Migration 016: Per-user artist separation for arrangement
+
+-- Tracks sharing an artist must be more than this many positions apart; 0 turns the rule off.
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS artist_separation INTEGER NOT NULL DEFAULT 1;
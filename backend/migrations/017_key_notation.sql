@@ -0,0 +1,4 @@
+-- Migration 017: Per-user key notation for API responses
+
+-- camelot, open-key, musical or beatport; NULL returns keys as stored.
+ALTER TABLE user_settings ADD COLUMN IF NOT EXISTS key_notation TEXT;
@@ -0,0 +1,4 @@
+-- Migration 018: Target key path for setlists
+
+-- JSON {"waypoints": [{"position": 0.0, "key": "5A"}, ...]}; NULL when none was given.
+ALTER TABLE setlists ADD COLUMN IF NOT EXISTS key_path TEXT;
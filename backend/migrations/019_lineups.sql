@@ -0,0 +1,24 @@
+-- Migration 019: Multi-DJ lineups (one generated setlist per slot)
+
+CREATE TABLE IF NOT EXISTS lineups (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT,
+    prompt TEXT NOT NULL,
+    created_at TIMESTAMP DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_lineups_user_id ON lineups(user_id);
+
+CREATE TABLE IF NOT EXISTS lineup_slots (
+    id TEXT PRIMARY KEY,
+    lineup_id TEXT NOT NULL REFERENCES lineups(id) ON DELETE CASCADE,
+    position INTEGER NOT NULL,  -- 1-based play order
+    dj TEXT NOT NULL,
+    b2b_with TEXT,  -- JSON array of DJs alternating with `dj`; NULL for a solo slot
+    alternate_every INTEGER NOT NULL DEFAULT 2,
+    duration_minutes INTEGER NOT NULL,
+    energy_profile TEXT,
+    setlist_id TEXT REFERENCES setlists(id) ON DELETE SET NULL,
+    UNIQUE(lineup_id, position)
+);
@@ -0,0 +1,8 @@
+-- Migration 020: Alternative setlists generated from one request
+
+-- Candidates from one variants request share a group id.
+ALTER TABLE setlists ADD COLUMN IF NOT EXISTS variant_group TEXT;
+-- Variants stay drafts until the user keeps one.
+ALTER TABLE setlists ADD COLUMN IF NOT EXISTS is_draft BOOLEAN NOT NULL DEFAULT FALSE;
+
+CREATE INDEX IF NOT EXISTS idx_setlists_variant_group ON setlists(variant_group);
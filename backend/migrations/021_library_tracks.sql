@@ -0,0 +1,10 @@
+-- Migration 021: Tracks imported from DJ library exports
+
+-- Star rating (0-5) and colour label as set in the DJ's library.
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS rating SMALLINT;
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS colour TEXT;
+-- Audio file path on the DJ's machine; identifies library tracks on re-import.
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS file_location TEXT;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tracks_file_location
+    ON tracks(file_location) WHERE file_location IS NOT NULL;
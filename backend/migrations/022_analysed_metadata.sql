@@ -0,0 +1,8 @@
+-- Migration 022: Record which DJ software analysed a track's BPM and key
+
+-- "rekordbox", "traktor" or "serato"; NULL when BPM/key were estimated.
+-- Enrichment leaves analysed values alone.
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS analysed_by TEXT;
+
+UPDATE tracks SET analysed_by = source
+    WHERE analysed_by IS NULL AND source = 'rekordbox' AND (bpm IS NOT NULL OR camelot_key IS NOT NULL);
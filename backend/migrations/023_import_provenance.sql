@@ -0,0 +1,28 @@
+-- Migration 023: Source-agnostic import provenance
+-- Imports now come from Spotify, DJ library exports and uploaded files.
+
+ALTER TABLE spotify_imports RENAME TO imports;
+ALTER TABLE imports RENAME COLUMN spotify_playlist_id TO source_ref;
+ALTER TABLE imports RENAME COLUMN spotify_playlist_name TO name;
+
+-- spotify, rekordbox, traktor, serato, m3u, pls or csv
+ALTER TABLE imports ADD COLUMN IF NOT EXISTS source TEXT NOT NULL DEFAULT 'spotify';
+-- Name of the uploaded file, for file imports.
+ALTER TABLE imports ADD COLUMN IF NOT EXISTS file_name TEXT;
+
+-- Library imports recorded their source as a "source:ref" prefix.
+UPDATE imports
+    SET source = split_part(source_ref, ':', 1),
+        source_ref = substr(source_ref, strpos(source_ref, ':') + 1)
+    WHERE split_part(source_ref, ':', 1) IN ('rekordbox', 'traktor', 'serato');
+
+CREATE INDEX IF NOT EXISTS idx_imports_user_id ON imports(user_id);
+
+-- Rows of an import that could not be imported, and why.
+CREATE TABLE IF NOT EXISTS import_failures (
+    import_id TEXT NOT NULL REFERENCES imports(id),
+    row_number INTEGER NOT NULL,
+    item TEXT,
+    reason TEXT NOT NULL,
+    PRIMARY KEY (import_id, row_number)
+);
@@ -0,0 +1,14 @@
+-- Migration 024: Tracks scanned from a local music folder
+
+-- Size in bytes and modification time (Unix seconds) of the audio file when
+-- it was last scanned; re-scans skip files where both are unchanged.
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS file_size BIGINT;
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS file_modified BIGINT;
+
+-- Cover art embedded in a scanned file, served at /api/tracks/{id}/artwork.
+CREATE TABLE IF NOT EXISTS track_artwork (
+    track_id TEXT PRIMARY KEY REFERENCES tracks(id) ON DELETE CASCADE,
+    media_type TEXT NOT NULL,
+    data BYTEA NOT NULL,
+    updated_at TIMESTAMP DEFAULT NOW()
+);
@@ -0,0 +1,9 @@
+-- Migration 025: Playlists imported from Deezer and SoundCloud
+
+-- SoundCloud track URN ("soundcloud:tracks:123"), matched on re-import the
+-- way deezer_id is for Deezer playlists.
+ALTER TABLE tracks ADD COLUMN IF NOT EXISTS soundcloud_urn TEXT;
+CREATE UNIQUE INDEX IF NOT EXISTS idx_tracks_soundcloud_urn
+    ON tracks(soundcloud_urn) WHERE soundcloud_urn IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_tracks_deezer_id
+    ON tracks(deezer_id) WHERE deezer_id IS NOT NULL;
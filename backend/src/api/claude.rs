@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
-use crate::services::camelot::EnergyProfile;
+use crate::services::camelot::{EnergyCurve, EnergyProfile, KeyPath};
 
 // ---------------------------------------------------------------------------
 // Error
@@ -48,6 +48,9 @@ pub struct LlmTrackEntry {
     pub source: Option<String>,
     pub track_id: Option<String>,
     pub confidence: Option<String>,
+    /// Track length; the model's estimate for suggestions.
+    #[serde(default)]
+    pub duration_seconds: Option<u32>,
 }
 
 // ---------------------------------------------------------------------------
@@ -323,6 +326,8 @@ const MUSIC_SKILL: &str = include_str!("../prompts/music_skill.md");
 pub fn build_enhanced_system_prompt(
     catalog_text: &str,
     energy_profile: Option<&EnergyProfile>,
+    energy_curve: Option<&EnergyCurve>,
+    key_path: Option<&KeyPath>,
     creative_mode: bool,
 ) -> Vec<RequestContentBlock> {
     let mut persona = String::from(
@@ -340,7 +345,13 @@ pub fn build_enhanced_system_prompt(
 - EQ blending: use low-end swap for kicks, high-pass for melodic transitions"#,
     );
 
-    if let Some(profile) = energy_profile {
+    if let Some(curve) = energy_curve {
+        persona.push_str("\n\n## Energy Profile\n");
+        persona.push_str(&format!(
+            "Follow this custom energy curve (position in the set → target energy 1-10): {}. Interpolate linearly between points.",
+            curve.describe()
+        ));
+    } else if let Some(profile) = energy_profile {
         persona.push_str("\n\n## Energy Profile\n");
         match profile {
             EnergyProfile::WarmUp => persona.push_str(
@@ -358,6 +369,14 @@ pub fn build_enhanced_system_prompt(
         }
     }
 
+    if let Some(path) = key_path {
+        persona.push_str("\n\n## Key Journey\n");
+        persona.push_str(&format!(
+            "Follow this harmonic journey (position in the set → target Camelot key): {}. Between points, move around the wheel one step at a time the short way round, switching between A and B halfway. Prefer tracks whose key is on or next to the target for their position.",
+            path.describe()
+        ));
+    }
+
     persona.push_str("\n\n## Artist Diversity\nAvoid repeating artists unless the user requests a specific artist's set. A 15-track set should have at least 12 unique artists.");
 
     if creative_mode {
@@ -382,7 +401,8 @@ Respond with ONLY valid JSON (no markdown fences, no explanation):
       "transition_note": "Blend low-end, match kick",
       "source": "catalog",
       "track_id": "uuid-or-null",
-      "confidence": "high"
+      "confidence": "high",
+      "duration_seconds": 390
     }
   ],
   "notes": "Brief description of the set flow..."
@@ -439,7 +459,10 @@ pub fn build_enhanced_user_prompt(
     }
 
     if let Some((min, max)) = bpm_range {
-        text.push_str(&format!("\n\nConstrain BPM to {}-{} range.", min, max));
+        text.push_str(&format!(
+            "\n\nConstrain BPM to {}-{} range. Half-time or double-time tracks that land in this range count.",
+            min, max
+        ));
     }
 
     vec![RequestContentBlock::Text {
@@ -692,8 +715,13 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_includes_energy_profile() {
-        let blocks =
-            build_enhanced_system_prompt("track1\ntrack2", Some(&EnergyProfile::WarmUp), false);
+        let blocks = build_enhanced_system_prompt(
+            "track1\ntrack2",
+            Some(&EnergyProfile::WarmUp),
+            None,
+            None,
+            false,
+        );
         assert_eq!(blocks.len(), 3);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(
@@ -705,35 +733,102 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_peak_time_profile() {
-        let blocks = build_enhanced_system_prompt("catalog", Some(&EnergyProfile::PeakTime), false);
+        let blocks = build_enhanced_system_prompt(
+            "catalog",
+            Some(&EnergyProfile::PeakTime),
+            None,
+            None,
+            false,
+        );
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("Maintain high energy (7-9)"));
     }
 
     #[test]
     fn test_enhanced_system_prompt_journey_profile() {
-        let blocks = build_enhanced_system_prompt("catalog", Some(&EnergyProfile::Journey), false);
+        let blocks = build_enhanced_system_prompt(
+            "catalog",
+            Some(&EnergyProfile::Journey),
+            None,
+            None,
+            false,
+        );
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("Start low (3), build to a peak (9)"));
     }
 
     #[test]
     fn test_enhanced_system_prompt_steady_profile() {
-        let blocks = build_enhanced_system_prompt("catalog", Some(&EnergyProfile::Steady), false);
+        let blocks = build_enhanced_system_prompt(
+            "catalog",
+            Some(&EnergyProfile::Steady),
+            None,
+            None,
+            false,
+        );
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("consistent medium energy (5-7)"));
     }
 
+    #[test]
+    fn test_enhanced_system_prompt_renders_energy_curve() {
+        use crate::services::camelot::EnergyBreakpoint;
+
+        let curve = EnergyCurve {
+            breakpoints: vec![
+                EnergyBreakpoint {
+                    position: 0.0,
+                    energy: 4.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.5,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 1.0,
+                    energy: 6.0,
+                },
+            ],
+        };
+        let blocks = build_enhanced_system_prompt("catalog", None, Some(&curve), None, false);
+        let RequestContentBlock::Text { ref text, .. } = blocks[1];
+        assert!(text.contains("## Energy Profile"));
+        assert!(text.contains("0% → 4, 50% → 9, 100% → 6"));
+    }
+
     #[test]
     fn test_enhanced_system_prompt_no_energy_profile() {
-        let blocks = build_enhanced_system_prompt("catalog", None, false);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, false);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(!text.contains("Energy Profile"));
+        assert!(!text.contains("Key Journey"));
+    }
+
+    #[test]
+    fn test_enhanced_system_prompt_renders_key_path() {
+        use crate::services::camelot::KeyWaypoint;
+
+        let path = KeyPath {
+            waypoints: vec![
+                KeyWaypoint {
+                    position: 0.0,
+                    key: "5A".to_string(),
+                },
+                KeyWaypoint {
+                    position: 1.0,
+                    key: "Db major".to_string(),
+                },
+            ],
+        };
+        let blocks = build_enhanced_system_prompt("catalog", None, None, Some(&path), false);
+        let RequestContentBlock::Text { ref text, .. } = blocks[1];
+        assert!(text.contains("## Key Journey"));
+        assert!(text.contains("0% → 5A, 100% → 3B"));
     }
 
     #[test]
     fn test_enhanced_system_prompt_creative_mode() {
-        let blocks = build_enhanced_system_prompt("catalog", None, true);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, true);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("Creative Mode"));
         assert!(text.contains("FAILURE in creative mode"));
@@ -741,7 +836,7 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_no_creative_mode() {
-        let blocks = build_enhanced_system_prompt("catalog", None, false);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, false);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(!text.contains("Creative Mode"));
     }
@@ -749,7 +844,7 @@ mod tests {
     #[test]
     fn test_enhanced_system_prompt_catalog_in_third_block() {
         let catalog = "t1 | Desert Rose | 102 | 8A\nt2 | Habibi | 128 | 9A";
-        let blocks = build_enhanced_system_prompt(catalog, None, false);
+        let blocks = build_enhanced_system_prompt(catalog, None, None, None, false);
         assert_eq!(blocks.len(), 3);
         let RequestContentBlock::Text { ref text, .. } = blocks[2];
         assert!(text.contains("Desert Rose"));
@@ -759,7 +854,7 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_skill_block() {
-        let blocks = build_enhanced_system_prompt("catalog", None, false);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, false);
         let RequestContentBlock::Text { ref text, .. } = blocks[0];
         assert!(
             text.contains("Track Verification Skill"),
@@ -773,7 +868,8 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_cache_control_on_all_blocks() {
-        let blocks = build_enhanced_system_prompt("catalog", Some(&EnergyProfile::WarmUp), true);
+        let blocks =
+            build_enhanced_system_prompt("catalog", Some(&EnergyProfile::WarmUp), None, None, true);
         for block in &blocks {
             let RequestContentBlock::Text {
                 ref cache_control, ..
@@ -1189,7 +1285,7 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_includes_camelot_rules() {
-        let blocks = build_enhanced_system_prompt("catalog", None, false);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, false);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("Camelot Wheel Rules"));
         assert!(text.contains("Transition Techniques"));
@@ -1197,7 +1293,7 @@ mod tests {
 
     #[test]
     fn test_enhanced_system_prompt_includes_output_format() {
-        let blocks = build_enhanced_system_prompt("catalog", None, false);
+        let blocks = build_enhanced_system_prompt("catalog", None, None, None, false);
         let RequestContentBlock::Text { ref text, .. } = blocks[1];
         assert!(text.contains("Output Format"));
         assert!(text.contains("ONLY valid JSON"));
@@ -10,6 +10,8 @@ pub struct AppConfig {
     pub dev_mode: bool,
     pub bind_address: String,
     pub frontend_url: String,
+    /// Music folder scanned by `POST /api/import/local`.
+    pub local_music_dir: Option<String>,
 }
 
 impl AppConfig {
@@ -42,6 +44,9 @@ impl AppConfig {
                 .unwrap_or(false),
             bind_address: std::env::var("BIND_ADDRESS").unwrap_or_else(|_| "0.0.0.0".to_string()),
             frontend_url: std::env::var("FRONTEND_URL").unwrap_or_default(),
+            local_music_dir: std::env::var("LOCAL_MUSIC_DIR")
+                .ok()
+                .filter(|d| !d.is_empty()),
         }
     }
 }
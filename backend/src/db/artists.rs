@@ -51,6 +51,31 @@ pub async fn upsert_track_artist(
     Ok(())
 }
 
+/// Id of the artist with this name (case-insensitive), inserting one
+/// without a Spotify URI if none exists.
+pub async fn find_or_create_artist_by_name(
+    pool: &PgPool,
+    name: &str,
+) -> Result<String, sqlx::Error> {
+    let existing = sqlx::query_scalar::<_, String>(
+        "SELECT id FROM artists WHERE LOWER(name) = LOWER($1) ORDER BY created_at ASC LIMIT 1",
+    )
+    .bind(name)
+    .fetch_optional(pool)
+    .await?;
+    if let Some(id) = existing {
+        return Ok(id);
+    }
+
+    let id = uuid::Uuid::new_v4().to_string();
+    sqlx::query("INSERT INTO artists (id, name, updated_at) VALUES ($1, $2, NOW())")
+        .bind(&id)
+        .bind(name)
+        .execute(pool)
+        .await?;
+    Ok(id)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -2,6 +2,7 @@ use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::db::crate_models::{CrateRow, CrateSummary, CrateTrackRow};
+use crate::services::camelot;
 
 // ---------------------------------------------------------------------------
 // Insert
@@ -24,10 +25,51 @@ pub async fn create_crate(
     Ok(())
 }
 
+/// Add one track to a crate. Returns `false` if the crate already holds a
+/// track with the same title and artist.
+pub async fn insert_crate_track(
+    pool: &PgPool,
+    crate_id: &str,
+    title: &str,
+    artist: &str,
+    bpm: Option<f64>,
+    camelot: Option<&str>,
+) -> Result<bool, sqlx::Error> {
+    let result = sqlx::query(
+        "INSERT INTO crate_tracks (id, crate_id, title, artist, bpm, key, camelot) \
+         VALUES ($1, $2, $3, $4, $5, $6, $6) ON CONFLICT DO NOTHING",
+    )
+    .bind(Uuid::new_v4().to_string())
+    .bind(crate_id)
+    .bind(title)
+    .bind(artist)
+    .bind(bpm)
+    .bind(camelot)
+    .execute(pool)
+    .await?;
+    Ok(result.rows_affected() > 0)
+}
+
 // ---------------------------------------------------------------------------
 // Read
 // ---------------------------------------------------------------------------
 
+/// The user's oldest crate with exactly this name.
+pub async fn find_crate_by_name(
+    pool: &PgPool,
+    user_id: &str,
+    name: &str,
+) -> Result<Option<CrateRow>, sqlx::Error> {
+    sqlx::query_as::<_, CrateRow>(
+        "SELECT id, user_id, name, description, created_at FROM crates \
+         WHERE user_id = $1 AND name = $2 ORDER BY created_at ASC LIMIT 1",
+    )
+    .bind(user_id)
+    .bind(name)
+    .fetch_optional(pool)
+    .await
+}
+
 pub async fn list_crates(pool: &PgPool, user_id: &str) -> Result<Vec<CrateSummary>, sqlx::Error> {
     sqlx::query_as::<_, CrateSummary>(
         r#"SELECT c.id, c.name, c.description, c.created_at,
@@ -143,7 +185,10 @@ pub async fn add_tracks_from_setlist(
         .bind(&row.artist)
         .bind(row.bpm)
         .bind(&row.key)
-        .bind(&row.camelot)
+        .bind(
+            camelot::normalize_key(row.camelot.as_deref())
+                .or_else(|| camelot::normalize_key(row.key.as_deref())),
+        )
         .bind(row.energy)
         .bind(&row.spotify_uri)
         .bind(setlist_id)
@@ -294,4 +339,76 @@ mod tests {
         assert_eq!(tracks.len(), 2);
         pool.close().await;
     }
+
+    #[tokio::test]
+    async fn test_add_tracks_from_setlist_normalizes_keys() {
+        let pool = crate::db::create_test_pool().await;
+        create_crate(&pool, "c1", "user-1", "Alpha", None)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO setlists (id, user_id, prompt, model) VALUES ($1, $2, $3, $4)")
+            .bind("sl-1")
+            .bind("user-1")
+            .bind("test")
+            .bind("test")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // One key only in Open Key, one only as a musical key name
+        for (id, pos, key, camelot) in [
+            ("st1", 1, None, Some("1m")),
+            ("st2", 2, Some("Db major"), None),
+        ] {
+            sqlx::query(
+                "INSERT INTO setlist_tracks (id, setlist_id, position, original_position, title, artist, source, key, camelot) \
+                 VALUES ($1, $2, $3, $3, $1, 'Artist', 'suggestion', $4, $5)",
+            )
+            .bind(id)
+            .bind("sl-1")
+            .bind(pos)
+            .bind(key)
+            .bind(camelot)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        add_tracks_from_setlist(&pool, "c1", "sl-1").await.unwrap();
+        let mut tracks = get_crate_tracks(&pool, "c1").await.unwrap();
+        tracks.sort_by(|a, b| a.title.cmp(&b.title));
+        assert_eq!(tracks[0].camelot.as_deref(), Some("8A"));
+        assert_eq!(tracks[1].camelot.as_deref(), Some("3B"));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_find_crate_by_name_and_insert_track() {
+        let pool = crate::db::create_test_pool().await;
+        create_crate(&pool, "c1", "user-1", "Gigs/Friday", None)
+            .await
+            .unwrap();
+
+        let found = find_crate_by_name(&pool, "user-1", "Gigs/Friday")
+            .await
+            .unwrap();
+        assert_eq!(found.map(|c| c.id).as_deref(), Some("c1"));
+        assert!(find_crate_by_name(&pool, "user-2", "Gigs/Friday")
+            .await
+            .unwrap()
+            .is_none());
+
+        let added = insert_crate_track(&pool, "c1", "Tama", "Mory Kante", Some(120.0), Some("8A"))
+            .await
+            .unwrap();
+        assert!(added);
+        let again = insert_crate_track(&pool, "c1", "Tama", "Mory Kante", None, None)
+            .await
+            .unwrap();
+        assert!(!again, "duplicate title and artist is ignored");
+
+        let tracks = get_crate_tracks(&pool, "c1").await.unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].camelot.as_deref(), Some("8A"));
+        pool.close().await;
+    }
 }
@@ -0,0 +1,123 @@
+use sqlx::PgPool;
+
+use crate::db::models::EnergyCurveRow;
+
+// ---------------------------------------------------------------------------
+// Insert
+// ---------------------------------------------------------------------------
+
+/// Save a curve. `breakpoints` is the JSON-serialized breakpoint array.
+/// Fails with a unique violation if the user already has a curve named `name`.
+pub async fn create_energy_curve(
+    pool: &PgPool,
+    id: &str,
+    user_id: &str,
+    name: &str,
+    breakpoints: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO energy_curves (id, user_id, name, breakpoints) VALUES ($1, $2, $3, $4)",
+    )
+    .bind(id)
+    .bind(user_id)
+    .bind(name)
+    .bind(breakpoints)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Read
+// ---------------------------------------------------------------------------
+
+pub async fn list_energy_curves(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Vec<EnergyCurveRow>, sqlx::Error> {
+    sqlx::query_as::<_, EnergyCurveRow>(
+        "SELECT id, user_id, name, breakpoints, created_at FROM energy_curves \
+         WHERE user_id = $1 ORDER BY name ASC",
+    )
+    .bind(user_id)
+    .fetch_all(pool)
+    .await
+}
+
+pub async fn get_energy_curve(
+    pool: &PgPool,
+    id: &str,
+) -> Result<Option<EnergyCurveRow>, sqlx::Error> {
+    sqlx::query_as::<_, EnergyCurveRow>(
+        "SELECT id, user_id, name, breakpoints, created_at FROM energy_curves WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Delete
+// ---------------------------------------------------------------------------
+
+/// Delete a user's curve. Returns the number of rows removed (0 or 1).
+pub async fn delete_energy_curve(
+    pool: &PgPool,
+    id: &str,
+    user_id: &str,
+) -> Result<u64, sqlx::Error> {
+    let result = sqlx::query("DELETE FROM energy_curves WHERE id = $1 AND user_id = $2")
+        .bind(id)
+        .bind(user_id)
+        .execute(pool)
+        .await?;
+    Ok(result.rows_affected())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINTS: &str = r#"[{"position":0.0,"energy":4.0},{"position":1.0,"energy":8.0}]"#;
+
+    #[tokio::test]
+    async fn test_energy_curves_are_per_user() {
+        let pool = crate::db::create_test_pool().await;
+        create_energy_curve(&pool, "c1", "user-1", "Festival", POINTS)
+            .await
+            .unwrap();
+        create_energy_curve(&pool, "c2", "user-2", "Festival", POINTS)
+            .await
+            .unwrap();
+
+        let mine = list_energy_curves(&pool, "user-1").await.unwrap();
+        assert_eq!(mine.len(), 1);
+        assert_eq!(mine[0].id, "c1");
+        assert_eq!(mine[0].breakpoints, POINTS);
+
+        // Name is unique per user
+        assert!(
+            create_energy_curve(&pool, "c3", "user-1", "Festival", POINTS)
+                .await
+                .is_err()
+        );
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_delete_energy_curve_checks_owner() {
+        let pool = crate::db::create_test_pool().await;
+        create_energy_curve(&pool, "c1", "user-1", "Closing", POINTS)
+            .await
+            .unwrap();
+
+        assert_eq!(delete_energy_curve(&pool, "c1", "user-2").await.unwrap(), 0);
+        assert_eq!(delete_energy_curve(&pool, "c1", "user-1").await.unwrap(), 1);
+        assert!(get_energy_curve(&pool, "c1").await.unwrap().is_none());
+        pool.close().await;
+    }
+}
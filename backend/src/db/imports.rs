@@ -1,22 +1,24 @@
 use sqlx::PgPool;
 
-use super::models::{SpotifyImport, TrackRow};
+use super::models::{ImportRow, TrackRow};
+use crate::services::import::{ImportFailure, ImportOrigin};
 
 pub async fn create_import(
     pool: &PgPool,
     id: &str,
     user_id: &str,
-    playlist_id: &str,
-    playlist_name: Option<&str>,
-) -> Result<SpotifyImport, sqlx::Error> {
+    origin: &ImportOrigin,
+) -> Result<ImportRow, sqlx::Error> {
     sqlx::query(
-        "INSERT INTO spotify_imports (id, user_id, spotify_playlist_id, spotify_playlist_name, status)
-         VALUES ($1, $2, $3, $4, 'in_progress')",
+        "INSERT INTO imports (id, user_id, source, source_ref, name, file_name, status)
+         VALUES ($1, $2, $3, $4, $5, $6, 'in_progress')",
     )
     .bind(id)
     .bind(user_id)
-    .bind(playlist_id)
-    .bind(playlist_name)
+    .bind(&origin.source)
+    .bind(&origin.source_ref)
+    .bind(&origin.name)
+    .bind(&origin.file_name)
     .execute(pool)
     .await?;
 
@@ -32,7 +34,7 @@ pub async fn update_import_counts(
     failed: i32,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "UPDATE spotify_imports
+        "UPDATE imports
          SET tracks_found = $1, tracks_inserted = $2, tracks_updated = $3, tracks_failed = $4
          WHERE id = $5",
     )
@@ -54,7 +56,7 @@ pub async fn complete_import(
     error_msg: Option<&str>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "UPDATE spotify_imports
+        "UPDATE imports
          SET status = $1, error_message = $2, completed_at = NOW()
          WHERE id = $3",
     )
@@ -67,13 +69,54 @@ pub async fn complete_import(
     Ok(())
 }
 
-pub async fn get_import(pool: &PgPool, id: &str) -> Result<Option<SpotifyImport>, sqlx::Error> {
-    sqlx::query_as::<_, SpotifyImport>("SELECT * FROM spotify_imports WHERE id = $1")
+pub async fn get_import(pool: &PgPool, id: &str) -> Result<Option<ImportRow>, sqlx::Error> {
+    sqlx::query_as::<_, ImportRow>("SELECT * FROM imports WHERE id = $1")
         .bind(id)
         .fetch_optional(pool)
         .await
 }
 
+/// Record the rows of an import that could not be imported.
+pub async fn insert_import_failures(
+    pool: &PgPool,
+    import_id: &str,
+    failures: &[ImportFailure],
+) -> Result<(), sqlx::Error> {
+    for failure in failures {
+        sqlx::query(
+            "INSERT INTO import_failures (import_id, row_number, item, reason) VALUES ($1, $2, $3, $4)
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(import_id)
+        .bind(failure.row as i32)
+        .bind(&failure.item)
+        .bind(&failure.reason)
+        .execute(pool)
+        .await?;
+    }
+    Ok(())
+}
+
+pub async fn get_import_failures(
+    pool: &PgPool,
+    import_id: &str,
+) -> Result<Vec<ImportFailure>, sqlx::Error> {
+    let rows: Vec<(i32, Option<String>, String)> = sqlx::query_as(
+        "SELECT row_number, item, reason FROM import_failures WHERE import_id = $1 ORDER BY row_number",
+    )
+    .bind(import_id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows
+        .into_iter()
+        .map(|(row, item, reason)| ImportFailure {
+            row: row as u32,
+            item,
+            reason,
+        })
+        .collect())
+}
+
 // ---------------------------------------------------------------------------
 // Import-track linkage operations
 // ---------------------------------------------------------------------------
@@ -150,9 +193,14 @@ mod tests {
         let pool = create_test_pool().await;
         let user_id = create_test_user(&pool).await;
 
-        create_import(&pool, "imp-dup", &user_id, "pl3", None)
-            .await
-            .unwrap();
+        create_import(
+            &pool,
+            "imp-dup",
+            &user_id,
+            &ImportOrigin::new("spotify", "pl3", None),
+        )
+        .await
+        .unwrap();
 
         sqlx::query("INSERT INTO tracks (id, title, source) VALUES ($1, $2, $3)")
             .bind("t4")
@@ -170,4 +218,43 @@ mod tests {
         assert_eq!(tracks.len(), 1);
         pool.close().await;
     }
+
+    #[tokio::test]
+    async fn test_import_origin_and_failures_round_trip() {
+        let pool = create_test_pool().await;
+        let user_id = create_test_user(&pool).await;
+
+        let origin = ImportOrigin {
+            file_name: Some("friday.m3u8".to_string()),
+            ..ImportOrigin::new("m3u", "friday.m3u8", Some("friday"))
+        };
+        let row = create_import(&pool, "imp-file", &user_id, &origin)
+            .await
+            .unwrap();
+        assert_eq!(row.source, "m3u");
+        assert_eq!(row.source_ref, "friday.m3u8");
+        assert_eq!(row.file_name.as_deref(), Some("friday.m3u8"));
+
+        let failures = vec![
+            ImportFailure {
+                row: 4,
+                item: Some("Unknown - ???".to_string()),
+                reason: "missing title".to_string(),
+            },
+            ImportFailure {
+                row: 2,
+                item: None,
+                reason: "invalid bpm".to_string(),
+            },
+        ];
+        insert_import_failures(&pool, "imp-file", &failures)
+            .await
+            .unwrap();
+
+        let stored = get_import_failures(&pool, "imp-file").await.unwrap();
+        assert_eq!(stored.len(), 2);
+        assert_eq!(stored[0].row, 2);
+        assert_eq!(stored[1], failures[0]);
+        pool.close().await;
+    }
 }
@@ -0,0 +1,127 @@
+use sqlx::PgPool;
+
+use crate::db::models::{LineupRow, LineupSlotRow};
+
+// ---------------------------------------------------------------------------
+// Insert
+// ---------------------------------------------------------------------------
+
+pub async fn insert_lineup(pool: &PgPool, row: &LineupRow) -> Result<(), sqlx::Error> {
+    sqlx::query("INSERT INTO lineups (id, user_id, name, prompt) VALUES ($1, $2, $3, $4)")
+        .bind(&row.id)
+        .bind(&row.user_id)
+        .bind(&row.name)
+        .bind(&row.prompt)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+pub async fn insert_lineup_slot(pool: &PgPool, row: &LineupSlotRow) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO lineup_slots (id, lineup_id, position, dj, b2b_with, alternate_every, duration_minutes, energy_profile, setlist_id) \
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+    )
+    .bind(&row.id)
+    .bind(&row.lineup_id)
+    .bind(row.position)
+    .bind(&row.dj)
+    .bind(&row.b2b_with)
+    .bind(row.alternate_every)
+    .bind(row.duration_minutes)
+    .bind(&row.energy_profile)
+    .bind(&row.setlist_id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Read
+// ---------------------------------------------------------------------------
+
+pub async fn get_lineup(pool: &PgPool, id: &str) -> Result<Option<LineupRow>, sqlx::Error> {
+    sqlx::query_as::<_, LineupRow>(
+        "SELECT id, user_id, name, prompt, created_at FROM lineups WHERE id = $1",
+    )
+    .bind(id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Slots of a lineup in play order.
+pub async fn get_lineup_slots(
+    pool: &PgPool,
+    lineup_id: &str,
+) -> Result<Vec<LineupSlotRow>, sqlx::Error> {
+    sqlx::query_as::<_, LineupSlotRow>(
+        "SELECT id, lineup_id, position, dj, b2b_with, alternate_every, duration_minutes, energy_profile, setlist_id \
+         FROM lineup_slots WHERE lineup_id = $1 ORDER BY position ASC",
+    )
+    .bind(lineup_id)
+    .fetch_all(pool)
+    .await
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn slot(id: &str, position: i32, dj: &str) -> LineupSlotRow {
+        LineupSlotRow {
+            id: id.to_string(),
+            lineup_id: "l1".to_string(),
+            position,
+            dj: dj.to_string(),
+            b2b_with: None,
+            alternate_every: 2,
+            duration_minutes: 60,
+            energy_profile: None,
+            setlist_id: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_lineup_slots_come_back_in_play_order() {
+        let pool = crate::db::create_test_pool().await;
+        insert_lineup(
+            &pool,
+            &LineupRow {
+                id: "l1".to_string(),
+                user_id: "user-1".to_string(),
+                name: Some("Warehouse".to_string()),
+                prompt: "all-nighter".to_string(),
+                created_at: None,
+            },
+        )
+        .await
+        .unwrap();
+        insert_lineup_slot(&pool, &slot("s2", 2, "Closer"))
+            .await
+            .unwrap();
+        insert_lineup_slot(&pool, &slot("s1", 1, "Opener"))
+            .await
+            .unwrap();
+
+        let lineup = get_lineup(&pool, "l1").await.unwrap().unwrap();
+        assert_eq!(lineup.name.as_deref(), Some("Warehouse"));
+        let djs: Vec<String> = get_lineup_slots(&pool, "l1")
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|s| s.dj)
+            .collect();
+        assert_eq!(djs, vec!["Opener", "Closer"]);
+
+        // Positions are unique within a lineup
+        assert!(insert_lineup_slot(&pool, &slot("s3", 1, "Other"))
+            .await
+            .is_err());
+        assert!(get_lineup(&pool, "missing").await.unwrap().is_none());
+        pool.close().await;
+    }
+}
@@ -1,10 +1,13 @@
 pub mod artists;
 pub mod crate_models;
 pub mod crates;
+pub mod energy_curves;
 pub mod imports;
+pub mod lineups;
 pub mod models;
 pub mod refinement;
 pub mod setlists;
+pub mod settings;
 pub mod tokens;
 pub mod tracks;
 
@@ -38,19 +41,25 @@ pub async fn create_test_pool() -> PgPool {
     for table in &[
         "crate_tracks",
         "crates",
+        "energy_curves",
+        "lineup_slots",
+        "lineups",
         "setlist_conversations",
         "setlist_version_tracks",
         "setlist_versions",
         "setlist_tracks",
         "setlists",
         "import_tracks",
-        "spotify_imports",
+        "import_failures",
+        "imports",
+        "track_artwork",
         "track_occasions",
         "track_tags",
         "track_artists",
         "playlist_tracks",
         "user_spotify_tokens",
         "user_usage",
+        "user_settings",
         "tracks",
         "artists",
         "occasions",
@@ -30,11 +30,13 @@ pub struct Artist {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
-pub struct SpotifyImport {
+pub struct ImportRow {
     pub id: String,
     pub user_id: String,
-    pub spotify_playlist_id: String,
-    pub spotify_playlist_name: Option<String>,
+    pub source: String,
+    pub source_ref: String,
+    pub name: Option<String>,
+    pub file_name: Option<String>,
     pub tracks_found: i32,
     pub tracks_inserted: i32,
     pub tracks_updated: i32,
@@ -117,6 +119,8 @@ pub struct SetlistSummary {
     pub name: Option<String>,
     pub prompt: String,
     pub track_count: i64,
+    /// An unkept alternative from a variants request.
+    pub is_draft: bool,
     pub created_at: Option<chrono::NaiveDateTime>,
 }
 
@@ -130,6 +134,12 @@ pub struct SetlistRow {
     pub notes: Option<String>,
     pub harmonic_flow_score: Option<f64>,
     pub energy_profile: Option<String>,
+    /// Custom energy curve as JSON (`{"breakpoints": [...]}`), if one was used.
+    pub energy_curve: Option<String>,
+    /// Target key path as JSON (`{"waypoints": [...]}`), if one was given.
+    pub key_path: Option<String>,
+    /// Requested set length, for setlists generated from a time slot.
+    pub target_duration_ms: Option<i64>,
     pub created_at: Option<NaiveDateTime>,
 }
 
@@ -154,4 +164,68 @@ pub struct SetlistTrackRow {
     pub confidence: Option<String>,
     pub verification_flag: Option<String>,
     pub verification_note: Option<String>,
+    /// Catalog duration, or the LLM's estimate for suggestions.
+    pub duration_ms: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct UserSettingsRow {
+    pub user_id: String,
+    pub key_lock: bool,
+    /// JSON-encoded scoring profile; `None` means the built-in weights.
+    pub scoring_profile: Option<String>,
+    /// Minimum distance between tracks sharing an artist; 0 means off.
+    pub artist_separation: i32,
+    /// Notation for keys in responses; `None` returns keys as stored.
+    pub key_notation: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+impl UserSettingsRow {
+    /// Settings for a user who has never saved any.
+    pub fn defaults(user_id: &str) -> Self {
+        Self {
+            user_id: user_id.to_string(),
+            key_lock: true,
+            scoring_profile: None,
+            artist_separation: crate::services::arrangement::DEFAULT_ARTIST_SEPARATION as i32,
+            key_notation: None,
+            updated_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct EnergyCurveRow {
+    pub id: String,
+    pub user_id: String,
+    pub name: String,
+    /// JSON array of breakpoints.
+    pub breakpoints: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LineupRow {
+    pub id: String,
+    pub user_id: String,
+    pub name: Option<String>,
+    pub prompt: String,
+    pub created_at: Option<NaiveDateTime>,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LineupSlotRow {
+    pub id: String,
+    pub lineup_id: String,
+    /// 1-based play order within the lineup.
+    pub position: i32,
+    pub dj: String,
+    /// JSON array of DJs playing back-to-back with `dj`, if any.
+    pub b2b_with: Option<String>,
+    pub alternate_every: i32,
+    pub duration_minutes: i32,
+    pub energy_profile: Option<String>,
+    /// `None` once the slot's setlist has been deleted.
+    pub setlist_id: Option<String>,
 }
@@ -211,6 +211,7 @@ mod tests {
         let mut tx2 = pool.begin().await.unwrap();
         let result = insert_version(&mut tx2, &v1_dup).await;
         assert!(result.is_err());
+        tx2.rollback().await.unwrap();
         pool.close().await;
     }
 }
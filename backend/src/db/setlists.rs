@@ -6,9 +6,14 @@ use crate::db::models::{SetlistRow, SetlistSummary, SetlistTrackRow, TrackRow};
 // Insert operations
 // ---------------------------------------------------------------------------
 
-pub async fn insert_setlist(pool: &PgPool, row: &SetlistRow) -> Result<(), sqlx::Error> {
+/// Takes the pool or a transaction, so a setlist and its tracks can be
+/// saved together.
+pub async fn insert_setlist(
+    executor: impl sqlx::PgExecutor<'_>,
+    row: &SetlistRow,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO setlists (id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)"
+        "INSERT INTO setlists (id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, energy_curve, key_path, target_duration_ms) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)"
     )
     .bind(&row.id)
     .bind(&row.user_id)
@@ -18,14 +23,20 @@ pub async fn insert_setlist(pool: &PgPool, row: &SetlistRow) -> Result<(), sqlx:
     .bind(&row.notes)
     .bind(row.harmonic_flow_score)
     .bind(&row.energy_profile)
-    .execute(pool)
+    .bind(&row.energy_curve)
+    .bind(&row.key_path)
+    .bind(row.target_duration_ms)
+    .execute(executor)
     .await?;
     Ok(())
 }
 
-pub async fn insert_setlist_track(pool: &PgPool, row: &SetlistTrackRow) -> Result<(), sqlx::Error> {
+pub async fn insert_setlist_track(
+    executor: impl sqlx::PgExecutor<'_>,
+    row: &SetlistTrackRow,
+) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "INSERT INTO setlist_tracks (id, setlist_id, track_id, position, original_position, title, artist, bpm, key, camelot, energy, transition_note, transition_score, source, acquisition_info, confidence, verification_flag, verification_note) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)"
+        "INSERT INTO setlist_tracks (id, setlist_id, track_id, position, original_position, title, artist, bpm, key, camelot, energy, transition_note, transition_score, source, acquisition_info, confidence, verification_flag, verification_note, duration_ms) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)"
     )
     .bind(&row.id)
     .bind(&row.setlist_id)
@@ -45,7 +56,8 @@ pub async fn insert_setlist_track(pool: &PgPool, row: &SetlistTrackRow) -> Resul
     .bind(&row.confidence)
     .bind(&row.verification_flag)
     .bind(&row.verification_note)
-    .execute(pool)
+    .bind(row.duration_ms)
+    .execute(executor)
     .await?;
     Ok(())
 }
@@ -56,7 +68,7 @@ pub async fn insert_setlist_track(pool: &PgPool, row: &SetlistTrackRow) -> Resul
 
 pub async fn get_setlist(pool: &PgPool, id: &str) -> Result<Option<SetlistRow>, sqlx::Error> {
     sqlx::query_as::<_, SetlistRow>(
-        "SELECT id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, created_at FROM setlists WHERE id = $1",
+        "SELECT id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, energy_curve, key_path, target_duration_ms, created_at FROM setlists WHERE id = $1",
     )
     .bind(id)
     .fetch_optional(pool)
@@ -71,7 +83,8 @@ pub async fn get_setlist_tracks(
         "SELECT st.id, st.setlist_id, st.track_id, st.position, st.original_position, \
          st.title, st.artist, st.bpm, st.key, st.camelot, st.energy, \
          st.transition_note, st.transition_score, st.source, st.acquisition_info, \
-         t.spotify_uri, st.confidence, st.verification_flag, st.verification_note \
+         t.spotify_uri, st.confidence, st.verification_flag, st.verification_note, \
+         st.duration_ms \
          FROM setlist_tracks st LEFT JOIN tracks t ON st.track_id = t.id \
          WHERE st.setlist_id = $1 ORDER BY st.position ASC",
     )
@@ -102,13 +115,17 @@ pub async fn update_setlist_track_position(
     track_id: &str,
     new_position: i32,
     transition_score: Option<f64>,
+    transition_note: Option<&str>,
 ) -> Result<(), sqlx::Error> {
-    sqlx::query("UPDATE setlist_tracks SET position = $1, transition_score = $2 WHERE id = $3")
-        .bind(new_position)
-        .bind(transition_score)
-        .bind(track_id)
-        .execute(pool)
-        .await?;
+    sqlx::query(
+        "UPDATE setlist_tracks SET position = $1, transition_score = $2, transition_note = $3 WHERE id = $4",
+    )
+    .bind(new_position)
+    .bind(transition_score)
+    .bind(transition_note)
+    .bind(track_id)
+    .execute(pool)
+    .await?;
     Ok(())
 }
 
@@ -123,7 +140,7 @@ pub async fn list_setlists(
     offset: i64,
 ) -> Result<Vec<SetlistSummary>, sqlx::Error> {
     sqlx::query_as::<_, SetlistSummary>(
-        "SELECT s.id, s.name, s.prompt, s.created_at, \
+        "SELECT s.id, s.name, s.prompt, s.is_draft, s.created_at, \
          COUNT(st.id) as track_count \
          FROM setlists s \
          LEFT JOIN setlist_tracks st ON st.setlist_id = s.id \
@@ -176,6 +193,56 @@ pub async fn delete_setlist(pool: &PgPool, id: &str) -> Result<bool, sqlx::Error
     Ok(result.rows_affected() > 0)
 }
 
+/// Mark a setlist as an unkept draft in variant group `group`.
+pub async fn mark_setlist_variant(pool: &PgPool, id: &str, group: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE setlists SET variant_group = $1, is_draft = TRUE WHERE id = $2")
+        .bind(group)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Ids of the other setlists in `id`'s variant group that are still drafts.
+pub async fn list_sibling_drafts(pool: &PgPool, id: &str) -> Result<Vec<String>, sqlx::Error> {
+    let rows: Vec<(String,)> = sqlx::query_as(
+        "SELECT s.id FROM setlists s JOIN setlists kept ON kept.variant_group = s.variant_group \
+         WHERE kept.id = $1 AND s.id <> $1 AND s.is_draft ORDER BY s.created_at",
+    )
+    .bind(id)
+    .fetch_all(pool)
+    .await?;
+    Ok(rows.into_iter().map(|(id,)| id).collect())
+}
+
+/// Clear the draft flag on one setlist.
+pub async fn keep_setlist(pool: &PgPool, id: &str) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE setlists SET is_draft = FALSE WHERE id = $1")
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `(track_id, artist, title)` of every track in the user's `limit` most
+/// recent kept setlists. Unkept variant drafts are skipped.
+pub async fn recent_setlist_tracks(
+    pool: &PgPool,
+    user_id: &str,
+    limit: i64,
+) -> Result<Vec<(Option<String>, String, String)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT st.track_id, st.artist, st.title FROM setlist_tracks st \
+         WHERE st.setlist_id IN (SELECT id FROM setlists WHERE user_id = $1 AND NOT is_draft \
+         ORDER BY created_at DESC LIMIT $2) \
+         ORDER BY st.setlist_id, st.position",
+    )
+    .bind(user_id)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
 pub async fn update_setlist_name(pool: &PgPool, id: &str, name: &str) -> Result<(), sqlx::Error> {
     sqlx::query("UPDATE setlists SET name = $1 WHERE id = $2")
         .bind(name)
@@ -192,7 +259,7 @@ pub async fn duplicate_setlist(
 ) -> Result<Option<String>, sqlx::Error> {
     // Load original
     let original = sqlx::query_as::<_, SetlistRow>(
-        "SELECT id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, created_at FROM setlists WHERE id = $1",
+        "SELECT id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, energy_curve, key_path, target_duration_ms, created_at FROM setlists WHERE id = $1",
     )
     .bind(id)
     .fetch_optional(pool)
@@ -218,7 +285,8 @@ pub async fn duplicate_setlist(
         "SELECT st.id, st.setlist_id, st.track_id, st.position, st.original_position, \
          st.title, st.artist, st.bpm, st.key, st.camelot, st.energy, \
          st.transition_note, st.transition_score, st.source, st.acquisition_info, \
-         t.spotify_uri, st.confidence, st.verification_flag, st.verification_note \
+         t.spotify_uri, st.confidence, st.verification_flag, st.verification_note, \
+         st.duration_ms \
          FROM setlist_tracks st LEFT JOIN tracks t ON st.track_id = t.id \
          WHERE st.setlist_id = $1 ORDER BY st.position ASC",
     )
@@ -230,8 +298,8 @@ pub async fn duplicate_setlist(
     let mut tx = pool.begin().await?;
 
     sqlx::query(
-        "INSERT INTO setlists (id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile) \
-         SELECT $1, user_id, prompt, model, $2, notes, harmonic_flow_score, energy_profile FROM setlists WHERE id = $3",
+        "INSERT INTO setlists (id, user_id, prompt, model, name, notes, harmonic_flow_score, energy_profile, energy_curve, key_path, target_duration_ms) \
+         SELECT $1, user_id, prompt, model, $2, notes, harmonic_flow_score, energy_profile, energy_curve, key_path, target_duration_ms FROM setlists WHERE id = $3",
     )
     .bind(&new_id)
     .bind(&resolved_name)
@@ -245,8 +313,8 @@ pub async fn duplicate_setlist(
             "INSERT INTO setlist_tracks \
              (id, setlist_id, track_id, position, original_position, title, artist, bpm, key, camelot, \
              energy, transition_note, transition_score, source, acquisition_info, confidence, \
-             verification_flag, verification_note) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)",
+             verification_flag, verification_note, duration_ms) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)",
         )
         .bind(&new_track_id)
         .bind(&new_id)
@@ -266,6 +334,7 @@ pub async fn duplicate_setlist(
         .bind(&track.confidence)
         .bind(&track.verification_flag)
         .bind(&track.verification_note)
+        .bind(track.duration_ms)
         .execute(&mut *tx)
         .await?;
     }
@@ -370,6 +439,9 @@ mod tests {
                 notes: None,
                 harmonic_flow_score: None,
                 energy_profile: None,
+                energy_curve: None,
+                key_path: None,
+                target_duration_ms: None,
                 created_at: None,
             };
             insert_setlist(&pool, &row).await.unwrap();
@@ -387,6 +459,45 @@ mod tests {
         pool.close().await;
     }
 
+    #[tokio::test]
+    async fn test_variant_drafts() {
+        let pool = crate::db::create_test_pool().await;
+        for id in ["v1", "v2", "v3", "other"] {
+            let row = SetlistRow {
+                id: id.to_string(),
+                user_id: "user-1".to_string(),
+                prompt: "Variants".to_string(),
+                model: "test".to_string(),
+                name: None,
+                notes: None,
+                harmonic_flow_score: None,
+                energy_profile: None,
+                energy_curve: None,
+                key_path: None,
+                target_duration_ms: None,
+                created_at: None,
+            };
+            insert_setlist(&pool, &row).await.unwrap();
+        }
+        for id in ["v1", "v2", "v3"] {
+            mark_setlist_variant(&pool, id, "g1").await.unwrap();
+        }
+
+        keep_setlist(&pool, "v2").await.unwrap();
+        let mut siblings = list_sibling_drafts(&pool, "v2").await.unwrap();
+        siblings.sort();
+        assert_eq!(siblings, vec!["v1", "v3"]);
+        assert!(list_sibling_drafts(&pool, "other")
+            .await
+            .unwrap()
+            .is_empty());
+
+        let list = list_setlists(&pool, "user-1", 10, 0).await.unwrap();
+        let draft = |id: &str| list.iter().find(|s| s.id == id).unwrap().is_draft;
+        assert!(draft("v1") && !draft("v2") && !draft("other"));
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_delete_setlist() {
         let pool = crate::db::create_test_pool().await;
@@ -400,6 +511,9 @@ mod tests {
             notes: None,
             harmonic_flow_score: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            target_duration_ms: None,
             created_at: None,
         };
         insert_setlist(&pool, &row).await.unwrap();
@@ -429,6 +543,9 @@ mod tests {
             notes: None,
             harmonic_flow_score: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            target_duration_ms: None,
             created_at: None,
         };
         insert_setlist(&pool, &row).await.unwrap();
@@ -454,6 +571,7 @@ mod tests {
             confidence: None,
             verification_flag: None,
             verification_note: None,
+            duration_ms: None,
         };
         insert_setlist_track(&pool, &track).await.unwrap();
 
@@ -0,0 +1,292 @@
+use sqlx::PgPool;
+
+use crate::db::models::UserSettingsRow;
+use crate::services::arrangement::DEFAULT_ARTIST_SEPARATION;
+use crate::services::camelot::{KeyNotation, ScoringProfile};
+
+// ---------------------------------------------------------------------------
+// Read
+// ---------------------------------------------------------------------------
+
+/// Load a user's settings. `None` means the user has never saved any, so
+/// callers fall back to [`UserSettingsRow::defaults`].
+pub async fn get_user_settings(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Option<UserSettingsRow>, sqlx::Error> {
+    sqlx::query_as::<_, UserSettingsRow>(
+        r#"SELECT user_id, key_lock, scoring_profile, artist_separation, key_notation, updated_at
+           FROM user_settings WHERE user_id = $1"#,
+    )
+    .bind(user_id)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Every setting that shapes a generated set, decoded from one query.
+#[derive(Debug, Clone)]
+pub struct MixSettings {
+    pub key_lock: bool,
+    pub scoring: ScoringProfile,
+    pub artist_separation: usize,
+    pub key_notation: Option<KeyNotation>,
+}
+
+/// Load all of a user's mix settings at once, with the same defaults as the
+/// single-setting getters below.
+pub async fn get_mix_settings(pool: &PgPool, user_id: &str) -> Result<MixSettings, sqlx::Error> {
+    let row = get_user_settings(pool, user_id)
+        .await?
+        .unwrap_or_else(|| UserSettingsRow::defaults(user_id));
+    Ok(MixSettings {
+        key_lock: row.key_lock,
+        scoring: decode_scoring_profile(row.scoring_profile.as_deref())?,
+        artist_separation: row.artist_separation.max(0) as usize,
+        key_notation: decode_key_notation(row.key_notation.as_deref())?,
+    })
+}
+
+/// Whether the user mixes with key lock on. Defaults to `true`.
+pub async fn get_key_lock(pool: &PgPool, user_id: &str) -> Result<bool, sqlx::Error> {
+    Ok(get_user_settings(pool, user_id)
+        .await?
+        .map(|s| s.key_lock)
+        .unwrap_or(true))
+}
+
+/// The user's transition scoring profile, or the built-in one if unset.
+pub async fn get_scoring_profile(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<ScoringProfile, sqlx::Error> {
+    let row = get_user_settings(pool, user_id).await?;
+    decode_scoring_profile(row.and_then(|s| s.scoring_profile).as_deref())
+}
+
+/// How far apart tracks sharing an artist must be. Defaults to
+/// [`DEFAULT_ARTIST_SEPARATION`].
+pub async fn get_artist_separation(pool: &PgPool, user_id: &str) -> Result<usize, sqlx::Error> {
+    Ok(get_user_settings(pool, user_id)
+        .await?
+        .map(|s| s.artist_separation.max(0) as usize)
+        .unwrap_or(DEFAULT_ARTIST_SEPARATION))
+}
+
+/// The notation the user wants keys returned in, if they picked one.
+pub async fn get_key_notation(
+    pool: &PgPool,
+    user_id: &str,
+) -> Result<Option<KeyNotation>, sqlx::Error> {
+    let row = get_user_settings(pool, user_id).await?;
+    decode_key_notation(row.and_then(|s| s.key_notation).as_deref())
+}
+
+fn decode_scoring_profile(json: Option<&str>) -> Result<ScoringProfile, sqlx::Error> {
+    match json {
+        Some(json) => serde_json::from_str(json).map_err(|e| sqlx::Error::Decode(Box::new(e))),
+        None => Ok(ScoringProfile::default()),
+    }
+}
+
+fn decode_key_notation(notation: Option<&str>) -> Result<Option<KeyNotation>, sqlx::Error> {
+    notation
+        .map(|n| n.parse().map_err(|e: String| sqlx::Error::Decode(e.into())))
+        .transpose()
+}
+
+// ---------------------------------------------------------------------------
+// Write
+// ---------------------------------------------------------------------------
+
+pub async fn set_key_lock(pool: &PgPool, user_id: &str, key_lock: bool) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO user_settings (user_id, key_lock) VALUES ($1, $2)
+           ON CONFLICT (user_id) DO UPDATE SET key_lock = EXCLUDED.key_lock, updated_at = NOW()"#,
+    )
+    .bind(user_id)
+    .bind(key_lock)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store a scoring profile, or clear it with `None` to go back to the
+/// built-in weights. Callers validate the profile first.
+pub async fn set_scoring_profile(
+    pool: &PgPool,
+    user_id: &str,
+    profile: Option<&ScoringProfile>,
+) -> Result<(), sqlx::Error> {
+    let json = profile
+        .map(serde_json::to_string)
+        .transpose()
+        .map_err(|e| sqlx::Error::Encode(Box::new(e)))?;
+    sqlx::query(
+        r#"INSERT INTO user_settings (user_id, scoring_profile) VALUES ($1, $2)
+           ON CONFLICT (user_id) DO UPDATE
+           SET scoring_profile = EXCLUDED.scoring_profile, updated_at = NOW()"#,
+    )
+    .bind(user_id)
+    .bind(json)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Store the artist separation gap. Callers check it against
+/// [`crate::services::arrangement::MAX_ARTIST_SEPARATION`] first.
+pub async fn set_artist_separation(
+    pool: &PgPool,
+    user_id: &str,
+    artist_separation: usize,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO user_settings (user_id, artist_separation) VALUES ($1, $2)
+           ON CONFLICT (user_id) DO UPDATE
+           SET artist_separation = EXCLUDED.artist_separation, updated_at = NOW()"#,
+    )
+    .bind(user_id)
+    .bind(artist_separation as i32)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn set_key_notation(
+    pool: &PgPool,
+    user_id: &str,
+    notation: KeyNotation,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"INSERT INTO user_settings (user_id, key_notation) VALUES ($1, $2)
+           ON CONFLICT (user_id) DO UPDATE
+           SET key_notation = EXCLUDED.key_notation, updated_at = NOW()"#,
+    )
+    .bind(user_id)
+    .bind(notation.to_string())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_key_lock_defaults_to_on() {
+        let pool = crate::db::create_test_pool().await;
+        assert!(get_user_settings(&pool, "user-1").await.unwrap().is_none());
+        assert!(get_key_lock(&pool, "user-1").await.unwrap());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_set_key_lock_upserts() {
+        let pool = crate::db::create_test_pool().await;
+        set_key_lock(&pool, "user-1", false).await.unwrap();
+        assert!(!get_key_lock(&pool, "user-1").await.unwrap());
+        set_key_lock(&pool, "user-1", true).await.unwrap();
+        assert!(get_key_lock(&pool, "user-1").await.unwrap());
+        // Other users are unaffected
+        assert!(get_user_settings(&pool, "user-2").await.unwrap().is_none());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_scoring_profile_round_trip() {
+        let pool = crate::db::create_test_pool().await;
+        assert_eq!(
+            get_scoring_profile(&pool, "user-1").await.unwrap(),
+            ScoringProfile::default()
+        );
+
+        let profile = ScoringProfile {
+            key_weight: 0.2,
+            bpm_weight: 0.7,
+            ..Default::default()
+        };
+        set_key_lock(&pool, "user-1", false).await.unwrap();
+        set_scoring_profile(&pool, "user-1", Some(&profile))
+            .await
+            .unwrap();
+        assert_eq!(get_scoring_profile(&pool, "user-1").await.unwrap(), profile);
+        // Saving the profile leaves key lock alone
+        assert!(!get_key_lock(&pool, "user-1").await.unwrap());
+
+        set_scoring_profile(&pool, "user-1", None).await.unwrap();
+        assert_eq!(
+            get_scoring_profile(&pool, "user-1").await.unwrap(),
+            ScoringProfile::default()
+        );
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_artist_separation_round_trip() {
+        let pool = crate::db::create_test_pool().await;
+        assert_eq!(
+            get_artist_separation(&pool, "user-1").await.unwrap(),
+            DEFAULT_ARTIST_SEPARATION
+        );
+        set_key_lock(&pool, "user-1", false).await.unwrap();
+        // A row created by another setting still gets the default gap
+        assert_eq!(
+            get_artist_separation(&pool, "user-1").await.unwrap(),
+            DEFAULT_ARTIST_SEPARATION
+        );
+        set_artist_separation(&pool, "user-1", 3).await.unwrap();
+        assert_eq!(get_artist_separation(&pool, "user-1").await.unwrap(), 3);
+        set_artist_separation(&pool, "user-1", 0).await.unwrap();
+        assert_eq!(get_artist_separation(&pool, "user-1").await.unwrap(), 0);
+        assert!(!get_key_lock(&pool, "user-1").await.unwrap());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_key_notation_round_trip() {
+        let pool = crate::db::create_test_pool().await;
+        assert_eq!(get_key_notation(&pool, "user-1").await.unwrap(), None);
+        set_key_notation(&pool, "user-1", KeyNotation::OpenKey)
+            .await
+            .unwrap();
+        assert_eq!(
+            get_key_notation(&pool, "user-1").await.unwrap(),
+            Some(KeyNotation::OpenKey)
+        );
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_mix_settings_match_single_getters() {
+        let pool = crate::db::create_test_pool().await;
+        let fresh = get_mix_settings(&pool, "user-1").await.unwrap();
+        assert!(fresh.key_lock);
+        assert_eq!(fresh.scoring, ScoringProfile::default());
+        assert_eq!(fresh.artist_separation, DEFAULT_ARTIST_SEPARATION);
+        assert_eq!(fresh.key_notation, None);
+
+        let profile = ScoringProfile {
+            key_weight: 0.2,
+            ..Default::default()
+        };
+        set_key_lock(&pool, "user-1", false).await.unwrap();
+        set_scoring_profile(&pool, "user-1", Some(&profile))
+            .await
+            .unwrap();
+        set_artist_separation(&pool, "user-1", 3).await.unwrap();
+        set_key_notation(&pool, "user-1", KeyNotation::OpenKey)
+            .await
+            .unwrap();
+        let saved = get_mix_settings(&pool, "user-1").await.unwrap();
+        assert!(!saved.key_lock);
+        assert_eq!(saved.scoring, profile);
+        assert_eq!(saved.artist_separation, 3);
+        assert_eq!(saved.key_notation, Some(KeyNotation::OpenKey));
+        pool.close().await;
+    }
+}
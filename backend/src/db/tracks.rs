@@ -1,6 +1,7 @@
 use sqlx::PgPool;
 
 use super::models::{Track, TrackRow, UpsertResult};
+use crate::services::import::{LibraryTrackRecord, StreamingTrackRecord, DEEZER_SOURCE};
 
 #[allow(clippy::too_many_arguments)]
 pub async fn upsert_track(
@@ -145,7 +146,8 @@ pub async fn get_unenriched_tracks(
     .await
 }
 
-/// Update a track's DJ metadata after enrichment.
+/// Update a track's DJ metadata after enrichment. BPM and key analysed by DJ
+/// software are kept over the estimate.
 pub async fn update_track_dj_metadata(
     pool: &PgPool,
     id: &str,
@@ -155,7 +157,7 @@ pub async fn update_track_dj_metadata(
     album_art_url: Option<&str>,
 ) -> Result<(), sqlx::Error> {
     sqlx::query(
-        "UPDATE tracks SET bpm = COALESCE($1, bpm), camelot_key = COALESCE($2, camelot_key), energy = COALESCE($3, energy), album_art_url = COALESCE($4, album_art_url), needs_enrichment = FALSE, enriched_at = NOW() WHERE id = $5",
+        "UPDATE tracks SET bpm = CASE WHEN analysed_by IS NULL THEN COALESCE($1, bpm) ELSE bpm END, camelot_key = CASE WHEN analysed_by IS NULL THEN COALESCE($2, camelot_key) ELSE camelot_key END, energy = COALESCE($3, energy), album_art_url = COALESCE($4, album_art_url), needs_enrichment = FALSE, enriched_at = NOW() WHERE id = $5",
     )
     .bind(bpm)
     .bind(camelot_key)
@@ -257,6 +259,313 @@ pub async fn increment_enrichment_usage(
     Ok(())
 }
 
+/// Id of the track stored at a library file location.
+pub async fn find_track_by_location(
+    pool: &PgPool,
+    file_location: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM tracks WHERE file_location = $1")
+        .bind(file_location)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Tracks with this title (case-insensitive) that are not tied to a library
+/// file yet, as `(id, artist credit)`.
+pub async fn find_unlocated_tracks_by_title(
+    pool: &PgPool,
+    title: &str,
+) -> Result<Vec<(String, Option<String>)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT t.id, STRING_AGG(a.name, ', ') AS artist
+         FROM tracks t
+         LEFT JOIN track_artists ta ON t.id = ta.track_id
+         LEFT JOIN artists a ON ta.artist_id = a.id
+         WHERE LOWER(t.title) = LOWER($1) AND t.file_location IS NULL
+         GROUP BY t.id
+         ORDER BY t.created_at ASC",
+    )
+    .bind(title.trim())
+    .fetch_all(pool)
+    .await
+}
+
+/// Insert a track read from a DJ library export.
+pub async fn insert_library_track(
+    pool: &PgPool,
+    id: &str,
+    track: &LibraryTrackRecord,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tracks (id, title, album, duration_ms, bpm, camelot_key, energy, rating, colour, file_location, source, analysed_by, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, NOW())",
+    )
+    .bind(id)
+    .bind(&track.title)
+    .bind(&track.album)
+    .bind(track.duration_ms)
+    .bind(track.bpm)
+    .bind(&track.camelot_key)
+    .bind(track.energy)
+    .bind(track.rating)
+    .bind(&track.colour)
+    .bind(&track.file_location)
+    .bind(&track.source)
+    .bind(track.analysed_by())
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Apply a library export's metadata to an existing track. The library's
+/// BPM, key and energy replace stored values; fields the export leaves
+/// empty keep what the catalog already has.
+pub async fn update_library_track(
+    pool: &PgPool,
+    id: &str,
+    track: &LibraryTrackRecord,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE tracks SET
+           album = COALESCE(album, $1),
+           duration_ms = COALESCE($2, duration_ms),
+           bpm = COALESCE($3, bpm),
+           camelot_key = COALESCE($4, camelot_key),
+           energy = COALESCE($5, energy),
+           rating = COALESCE($6, rating),
+           colour = COALESCE($7, colour),
+           file_location = COALESCE($8, file_location),
+           analysed_by = COALESCE($9, analysed_by),
+           updated_at = NOW()
+         WHERE id = $10",
+    )
+    .bind(&track.album)
+    .bind(track.duration_ms)
+    .bind(track.bpm)
+    .bind(&track.camelot_key)
+    .bind(track.energy)
+    .bind(track.rating)
+    .bind(&track.colour)
+    .bind(&track.file_location)
+    .bind(track.analysed_by())
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// `(file_location, file_size, file_modified)` of every track scanned from
+/// a local folder.
+pub async fn get_local_file_stamps(pool: &PgPool) -> Result<Vec<(String, i64, i64)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT file_location, file_size, file_modified FROM tracks
+         WHERE file_location IS NOT NULL AND file_size IS NOT NULL AND file_modified IS NOT NULL",
+    )
+    .fetch_all(pool)
+    .await
+}
+
+/// Record the size and modification time a scanned file had.
+pub async fn set_file_stamp(
+    pool: &PgPool,
+    id: &str,
+    file_size: i64,
+    file_modified: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query("UPDATE tracks SET file_size = $1, file_modified = $2 WHERE id = $3")
+        .bind(file_size)
+        .bind(file_modified)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// Store a track's embedded cover art, pointing `album_art_url` at it unless
+/// the track already has artwork from elsewhere.
+pub async fn upsert_track_artwork(
+    pool: &PgPool,
+    id: &str,
+    media_type: &str,
+    data: &[u8],
+    artwork_url: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO track_artwork (track_id, media_type, data, updated_at)
+         VALUES ($1, $2, $3, NOW())
+         ON CONFLICT (track_id) DO UPDATE SET
+           media_type = EXCLUDED.media_type,
+           data = EXCLUDED.data,
+           updated_at = NOW()",
+    )
+    .bind(id)
+    .bind(media_type)
+    .bind(data)
+    .execute(pool)
+    .await?;
+
+    sqlx::query("UPDATE tracks SET album_art_url = COALESCE(album_art_url, $1) WHERE id = $2")
+        .bind(artwork_url)
+        .bind(id)
+        .execute(pool)
+        .await?;
+    Ok(())
+}
+
+/// `(media_type, data)` of a track's embedded cover art.
+pub async fn get_track_artwork(
+    pool: &PgPool,
+    id: &str,
+) -> Result<Option<(String, Vec<u8>)>, sqlx::Error> {
+    sqlx::query_as("SELECT media_type, data FROM track_artwork WHERE track_id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Track id stored under a Deezer track id.
+pub async fn find_track_by_deezer_id(
+    pool: &PgPool,
+    deezer_id: i32,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM tracks WHERE deezer_id = $1 ORDER BY created_at ASC LIMIT 1")
+        .bind(deezer_id)
+        .fetch_optional(pool)
+        .await
+}
+
+/// Track id stored under a SoundCloud track URN.
+pub async fn find_track_by_soundcloud_urn(
+    pool: &PgPool,
+    urn: &str,
+) -> Result<Option<String>, sqlx::Error> {
+    sqlx::query_scalar("SELECT id FROM tracks WHERE soundcloud_urn = $1")
+        .bind(urn)
+        .fetch_optional(pool)
+        .await
+}
+
+/// `(id, artist)` of tracks titled `title` that have no id from `source`
+/// yet, oldest first.
+pub async fn find_streaming_candidates_by_title(
+    pool: &PgPool,
+    title: &str,
+    source: &str,
+) -> Result<Vec<(String, Option<String>)>, sqlx::Error> {
+    sqlx::query_as(
+        "SELECT t.id, STRING_AGG(a.name, ', ') AS artist
+         FROM tracks t
+         LEFT JOIN track_artists ta ON t.id = ta.track_id
+         LEFT JOIN artists a ON ta.artist_id = a.id
+         WHERE LOWER(t.title) = LOWER($1)
+           AND CASE WHEN $2 = 'deezer' THEN t.deezer_id IS NULL ELSE t.soundcloud_urn IS NULL END
+         GROUP BY t.id
+         ORDER BY t.created_at ASC",
+    )
+    .bind(title.trim())
+    .bind(source)
+    .fetch_all(pool)
+    .await
+}
+
+/// The Deezer id of a streaming record, when it fits `tracks.deezer_id`.
+fn deezer_id(track: &StreamingTrackRecord) -> Option<i32> {
+    (track.source == DEEZER_SOURCE)
+        .then(|| track.provider_id.parse().ok())
+        .flatten()
+}
+
+/// The SoundCloud URN of a streaming record.
+fn soundcloud_urn(track: &StreamingTrackRecord) -> Option<&str> {
+    (track.source != DEEZER_SOURCE).then_some(track.provider_id.as_str())
+}
+
+/// Insert a track from a Deezer playlist or SoundCloud set. A BPM from the
+/// provider is recorded as analysed by it.
+pub async fn insert_streaming_track(
+    pool: &PgPool,
+    id: &str,
+    track: &StreamingTrackRecord,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO tracks (id, title, album, duration_ms, bpm, deezer_id, deezer_preview_url, soundcloud_urn, album_art_url, source, analysed_by, updated_at)
+         VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, CASE WHEN $5 IS NOT NULL THEN $10 END, NOW())",
+    )
+    .bind(id)
+    .bind(&track.title)
+    .bind(&track.album)
+    .bind(track.duration_ms)
+    .bind(track.bpm)
+    .bind(deezer_id(track))
+    .bind(&track.preview_url)
+    .bind(soundcloud_urn(track))
+    .bind(&track.album_art_url)
+    .bind(&track.source)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Fill in an existing track from a streaming record. Only empty fields are
+/// filled; a stored BPM is never replaced.
+pub async fn update_streaming_track(
+    pool: &PgPool,
+    id: &str,
+    track: &StreamingTrackRecord,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "UPDATE tracks SET
+           album = COALESCE(album, $1),
+           duration_ms = COALESCE(duration_ms, $2),
+           analysed_by = CASE WHEN bpm IS NULL AND $3::DOUBLE PRECISION IS NOT NULL THEN $4 ELSE analysed_by END,
+           bpm = COALESCE(bpm, $3),
+           deezer_id = COALESCE(deezer_id, $5),
+           deezer_preview_url = COALESCE($6, deezer_preview_url),
+           soundcloud_urn = COALESCE(soundcloud_urn, $7),
+           album_art_url = COALESCE(album_art_url, $8),
+           updated_at = NOW()
+         WHERE id = $9",
+    )
+    .bind(&track.album)
+    .bind(track.duration_ms)
+    .bind(track.bpm)
+    .bind(&track.source)
+    .bind(deezer_id(track))
+    .bind(&track.preview_url)
+    .bind(soundcloud_urn(track))
+    .bind(&track.album_art_url)
+    .bind(id)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Tag a track unless it already carries the same category and value.
+pub async fn add_track_tag(
+    pool: &PgPool,
+    track_id: &str,
+    category: &str,
+    value: &str,
+    source: &str,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        "INSERT INTO track_tags (id, track_id, category, value, source)
+         SELECT $1, $2, $3, $4, $5
+         WHERE NOT EXISTS (
+             SELECT 1 FROM track_tags
+             WHERE track_id = $2 AND category = $3 AND LOWER(value) = LOWER($4)
+         )",
+    )
+    .bind(uuid::Uuid::new_v4().to_string())
+    .bind(track_id)
+    .bind(category)
+    .bind(value)
+    .bind(source)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -431,4 +740,109 @@ mod tests {
         assert!(artist.contains("Artist B"));
         pool.close().await;
     }
+
+    #[tokio::test]
+    async fn test_library_track_insert_and_update() {
+        let pool = create_test_pool().await;
+        let mut track = LibraryTrackRecord {
+            title: "Yeke Yeke".to_string(),
+            duration_ms: Some(381_000),
+            bpm: Some(124.5),
+            camelot_key: Some("11A".to_string()),
+            rating: Some(4),
+            colour: Some("#FF007F".to_string()),
+            file_location: Some("/music/yeke.flac".to_string()),
+            source: "rekordbox".to_string(),
+            ..Default::default()
+        };
+        insert_library_track(&pool, "lib1", &track).await.unwrap();
+
+        let found = find_track_by_location(&pool, "/music/yeke.flac")
+            .await
+            .unwrap();
+        assert_eq!(found.as_deref(), Some("lib1"));
+        assert!(find_unlocated_tracks_by_title(&pool, "yeke yeke")
+            .await
+            .unwrap()
+            .is_empty());
+
+        track.bpm = Some(125.0);
+        track.rating = None;
+        update_library_track(&pool, "lib1", &track).await.unwrap();
+
+        let (bpm, rating, source): (Option<f64>, Option<i16>, String) =
+            sqlx::query_as("SELECT bpm, rating, source FROM tracks WHERE id = 'lib1'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(bpm, Some(125.0));
+        assert_eq!(rating, Some(4), "missing rating keeps the stored one");
+        assert_eq!(source, "rekordbox");
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_find_unlocated_tracks_by_title() {
+        let pool = create_test_pool().await;
+        upsert_track(
+            &pool,
+            "sp1",
+            "Desert Rose",
+            None,
+            None,
+            "spotify:track:sp1",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO artists (id, name) VALUES ('a1', 'Sting')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO track_artists (track_id, artist_id) VALUES ('sp1', 'a1')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let found = find_unlocated_tracks_by_title(&pool, " desert rose ")
+            .await
+            .unwrap();
+        assert_eq!(found, vec![("sp1".to_string(), Some("Sting".to_string()))]);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_enrichment_keeps_analysed_bpm_and_key() {
+        let pool = create_test_pool().await;
+        let track = LibraryTrackRecord {
+            title: "Yeke Yeke".to_string(),
+            bpm: Some(124.0),
+            camelot_key: Some("11A".to_string()),
+            source: "traktor".to_string(),
+            ..Default::default()
+        };
+        insert_library_track(&pool, "lib1", &track).await.unwrap();
+
+        update_track_dj_metadata(&pool, "lib1", Some(118.0), Some("4A"), Some(7.0), None)
+            .await
+            .unwrap();
+
+        let (bpm, key, energy, analysed_by): (
+            Option<f64>,
+            Option<String>,
+            Option<f64>,
+            Option<String>,
+        ) = sqlx::query_as(
+            "SELECT bpm, camelot_key, energy, analysed_by FROM tracks WHERE id = 'lib1'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(bpm, Some(124.0));
+        assert_eq!(key.as_deref(), Some("11A"));
+        assert_eq!(energy, Some(7.0), "energy still comes from enrichment");
+        assert_eq!(analysed_by.as_deref(), Some("traktor"));
+        pool.close().await;
+    }
 }
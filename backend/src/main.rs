@@ -14,12 +14,17 @@ use ethnomusicology_backend::config::AppConfig;
 use ethnomusicology_backend::repo::PgImportRepository;
 use ethnomusicology_backend::routes;
 use ethnomusicology_backend::routes::auth::{AuthState, TokenExchangeResult, TokenExchanger};
+use ethnomusicology_backend::routes::energy_curves::EnergyCurveRouteState;
 use ethnomusicology_backend::routes::enrich::EnrichRouteState;
 use ethnomusicology_backend::routes::import::ImportState;
+use ethnomusicology_backend::routes::lineups::LineupRouteState;
 use ethnomusicology_backend::routes::purchase_links::PurchaseLinkRouteState;
 use ethnomusicology_backend::routes::refinement::RefinementRouteState;
 use ethnomusicology_backend::routes::setlist::SetlistRouteState;
+use ethnomusicology_backend::routes::settings::SettingsRouteState;
+use ethnomusicology_backend::services::deezer::DeezerClient;
 use ethnomusicology_backend::services::purchase_links::AffiliateConfig;
+use ethnomusicology_backend::services::soundcloud::SoundCloudClient;
 
 // ---------------------------------------------------------------------------
 // Real Spotify token exchanger
@@ -226,6 +231,10 @@ async fn main() -> anyhow::Result<()> {
         pool: pool.clone(),
         encryption_key,
         claude: claude_client.clone(),
+        local_music_dir: cfg.local_music_dir.as_ref().map(std::path::PathBuf::from),
+        deezer: DeezerClient::new(),
+        soundcloud: SoundCloudClient::new_from_env().map(tokio::sync::Mutex::new),
+        http: reqwest::Client::new(),
     });
 
     // --- Setlist routes state ---
@@ -234,6 +243,12 @@ async fn main() -> anyhow::Result<()> {
         claude: claude_client.clone(),
     });
 
+    // --- Lineup routes state ---
+    let lineup_state = Arc::new(LineupRouteState {
+        pool: pool.clone(),
+        claude: claude_client.clone(),
+    });
+
     // --- Enrich routes state ---
     let enrich_state = Arc::new(EnrichRouteState {
         pool: pool.clone(),
@@ -246,6 +261,12 @@ async fn main() -> anyhow::Result<()> {
         claude: claude_client.clone(),
     });
 
+    // --- Settings routes state ---
+    let settings_state = Arc::new(SettingsRouteState { pool: pool.clone() });
+
+    // --- Energy curve routes state ---
+    let energy_curve_state = Arc::new(EnergyCurveRouteState { pool: pool.clone() });
+
     // --- Purchase links state ---
     let purchase_link_state = Arc::new(PurchaseLinkRouteState {
         affiliate_config: AffiliateConfig::from_env(),
@@ -263,11 +284,17 @@ async fn main() -> anyhow::Result<()> {
         .nest("/api", routes::auth::auth_routes(auth_state))
         .nest("/api", routes::import::import_router(import_state))
         .nest("/api", routes::setlist::setlist_router(setlist_state))
+        .nest("/api", routes::lineups::lineup_router(lineup_state))
         .nest("/api", routes::enrich::enrich_router(enrich_state))
         .nest(
             "/api",
             routes::refinement::refinement_router(refinement_state),
         )
+        .nest("/api", routes::settings::settings_router(settings_state))
+        .nest(
+            "/api",
+            routes::energy_curves::energy_curve_router(energy_curve_state),
+        )
         .nest("/api", routes::tracks::tracks_router(pool.clone()))
         .nest("/api", routes::audio::audio_router(pool.clone()))
         .nest("/api", routes::admin::admin_router(pool.clone()))
@@ -1,10 +1,15 @@
+use std::collections::HashMap;
+
 use sqlx::PgPool;
 use uuid::Uuid;
 
-use crate::db::{artists, imports, tracks};
+use crate::db::{artists, crates, imports, tracks};
+use crate::services::arrangement::shared_artist;
 use crate::services::import::{
-    ArtistRecord, ImportError, ImportRepository, ImportSummary, TrackRecord, UpsertResult,
+    ArtistRecord, ImportError, ImportOrigin, ImportRepository, ImportSummary, LibraryTrackRecord,
+    StreamingTrackRecord, TrackRecord, UpsertResult, DEEZER_SOURCE,
 };
+use crate::services::local_scan::{self, Artwork, FileStamp};
 
 /// Production implementation of ImportRepository backed by Postgres.
 pub struct PgImportRepository {
@@ -17,16 +22,24 @@ impl PgImportRepository {
     }
 }
 
+/// Whether an imported track's artist matches a stored track's credits.
+fn same_artist(ours: Option<&str>, theirs: Option<&str>) -> bool {
+    match (ours, theirs) {
+        (Some(ours), Some(theirs)) => shared_artist(ours, theirs).is_some(),
+        (None, None) => true,
+        _ => false,
+    }
+}
+
 #[async_trait::async_trait]
 impl ImportRepository for PgImportRepository {
     async fn create_import(
         &self,
         user_id: &str,
-        playlist_id: &str,
-        playlist_name: Option<&str>,
+        origin: &ImportOrigin,
     ) -> Result<String, ImportError> {
         let id = Uuid::new_v4().to_string();
-        imports::create_import(&self.pool, &id, user_id, playlist_id, playlist_name)
+        imports::create_import(&self.pool, &id, user_id, origin)
             .await
             .map_err(|e| ImportError::Database(e.to_string()))?;
         Ok(id)
@@ -91,6 +104,10 @@ impl ImportRepository for PgImportRepository {
         .await
         .map_err(|e| ImportError::Database(e.to_string()))?;
 
+        imports::insert_import_failures(&self.pool, import_id, &summary.failures)
+            .await
+            .map_err(|e| ImportError::Database(e.to_string()))?;
+
         imports::complete_import(&self.pool, import_id, &summary.status, None)
             .await
             .map_err(|e| ImportError::Database(e.to_string()))?;
@@ -108,4 +125,170 @@ impl ImportRepository for PgImportRepository {
             .map_err(|e| ImportError::Database(e.to_string()))?;
         Ok(())
     }
+
+    async fn upsert_library_track(
+        &self,
+        track: &LibraryTrackRecord,
+    ) -> Result<(String, UpsertResult), ImportError> {
+        let db_err = |e: sqlx::Error| ImportError::Database(e.to_string());
+
+        let mut existing = match track.file_location.as_deref() {
+            Some(location) => tracks::find_track_by_location(&self.pool, location)
+                .await
+                .map_err(db_err)?,
+            None => None,
+        };
+        if existing.is_none() {
+            // Fall back to a catalog track with the same title and artist,
+            // e.g. one imported from Spotify before the DJ bought the file.
+            existing = tracks::find_unlocated_tracks_by_title(&self.pool, &track.title)
+                .await
+                .map_err(db_err)?
+                .into_iter()
+                .find(|(_, artist)| same_artist(track.artist.as_deref(), artist.as_deref()))
+                .map(|(id, _)| id);
+        }
+
+        match existing {
+            Some(id) => {
+                tracks::update_library_track(&self.pool, &id, track)
+                    .await
+                    .map_err(db_err)?;
+                Ok((id, UpsertResult::Updated))
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                tracks::insert_library_track(&self.pool, &id, track)
+                    .await
+                    .map_err(db_err)?;
+                Ok((id, UpsertResult::Inserted))
+            }
+        }
+    }
+
+    async fn upsert_streaming_track(
+        &self,
+        track: &StreamingTrackRecord,
+    ) -> Result<(String, UpsertResult), ImportError> {
+        let db_err = |e: sqlx::Error| ImportError::Database(e.to_string());
+
+        let mut existing = if track.source == DEEZER_SOURCE {
+            match track.provider_id.parse::<i32>() {
+                Ok(id) => tracks::find_track_by_deezer_id(&self.pool, id)
+                    .await
+                    .map_err(db_err)?,
+                Err(_) => None,
+            }
+        } else {
+            tracks::find_track_by_soundcloud_urn(&self.pool, &track.provider_id)
+                .await
+                .map_err(db_err)?
+        };
+        if existing.is_none() {
+            // The same recording may already be in the catalog from another
+            // source, e.g. a Spotify playlist or the DJ's own files.
+            existing =
+                tracks::find_streaming_candidates_by_title(&self.pool, &track.title, &track.source)
+                    .await
+                    .map_err(db_err)?
+                    .into_iter()
+                    .find(|(_, artist)| same_artist(track.artist.as_deref(), artist.as_deref()))
+                    .map(|(id, _)| id);
+        }
+
+        let (id, result) = match existing {
+            Some(id) => {
+                tracks::update_streaming_track(&self.pool, &id, track)
+                    .await
+                    .map_err(db_err)?;
+                (id, UpsertResult::Updated)
+            }
+            None => {
+                let id = Uuid::new_v4().to_string();
+                tracks::insert_streaming_track(&self.pool, &id, track)
+                    .await
+                    .map_err(db_err)?;
+                (id, UpsertResult::Inserted)
+            }
+        };
+
+        if let Some(genre) = track.genre.as_deref() {
+            tracks::add_track_tag(&self.pool, &id, "genre", genre, &track.source)
+                .await
+                .map_err(db_err)?;
+        }
+        Ok((id, result))
+    }
+
+    async fn upsert_library_artist(&self, name: &str) -> Result<String, ImportError> {
+        artists::find_or_create_artist_by_name(&self.pool, name)
+            .await
+            .map_err(|e| ImportError::Database(e.to_string()))
+    }
+
+    async fn upsert_crate(&self, user_id: &str, name: &str) -> Result<String, ImportError> {
+        let db_err = |e: sqlx::Error| ImportError::Database(e.to_string());
+        if let Some(existing) = crates::find_crate_by_name(&self.pool, user_id, name)
+            .await
+            .map_err(db_err)?
+        {
+            return Ok(existing.id);
+        }
+        let id = Uuid::new_v4().to_string();
+        crates::create_crate(&self.pool, &id, user_id, name, None)
+            .await
+            .map_err(db_err)?;
+        Ok(id)
+    }
+
+    async fn insert_crate_track(
+        &self,
+        crate_id: &str,
+        track: &LibraryTrackRecord,
+    ) -> Result<bool, ImportError> {
+        crates::insert_crate_track(
+            &self.pool,
+            crate_id,
+            &track.title,
+            track.artist.as_deref().unwrap_or_default(),
+            track.bpm,
+            track.camelot_key.as_deref(),
+        )
+        .await
+        .map_err(|e| ImportError::Database(e.to_string()))
+    }
+
+    async fn local_file_stamps(&self) -> Result<HashMap<String, FileStamp>, ImportError> {
+        let rows = tracks::get_local_file_stamps(&self.pool)
+            .await
+            .map_err(|e| ImportError::Database(e.to_string()))?;
+        Ok(rows
+            .into_iter()
+            .map(|(location, size, modified)| (location, FileStamp { size, modified }))
+            .collect())
+    }
+
+    async fn record_local_file(
+        &self,
+        track_id: &str,
+        stamp: &FileStamp,
+        artwork: Option<&Artwork>,
+    ) -> Result<(), ImportError> {
+        let db_err = |e: sqlx::Error| ImportError::Database(e.to_string());
+        tracks::set_file_stamp(&self.pool, track_id, stamp.size, stamp.modified)
+            .await
+            .map_err(db_err)?;
+        if let Some(artwork) = artwork {
+            tracks::upsert_track_artwork(
+                &self.pool,
+                track_id,
+                &artwork.media_type,
+                &artwork.data,
+                &local_scan::artwork_url(track_id),
+            )
+            .await
+            .map_err(db_err)?;
+        }
+        Ok(())
+    }
 }
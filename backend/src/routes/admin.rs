@@ -102,11 +102,15 @@ async fn wipe_catalog(
     .await
     .map_err(|e| internal_error(e.to_string()))?;
 
-    // 3. Clear import_tracks join table
-    sqlx::query("DELETE FROM import_tracks")
-        .execute(&mut *tx)
-        .await
-        .map_err(|e| internal_error(e.to_string()))?;
+    // 3. Clear import_tracks links for spotify imports and spotify tracks
+    sqlx::query(
+        "DELETE FROM import_tracks \
+         WHERE import_id IN (SELECT id FROM imports WHERE source = 'spotify') \
+            OR track_id IN (SELECT id FROM tracks WHERE spotify_uri IS NOT NULL)",
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| internal_error(e.to_string()))?;
 
     // 4-6. Remove tag/occasion/artist associations for spotify tracks
     sqlx::query(
@@ -155,7 +159,15 @@ async fn wipe_catalog(
     .rows_affected();
 
     // 10. Delete all spotify import records
-    let imports_deleted = sqlx::query("DELETE FROM spotify_imports")
+    sqlx::query(
+        "DELETE FROM import_failures \
+         WHERE import_id IN (SELECT id FROM imports WHERE source = 'spotify')",
+    )
+    .execute(&mut *tx)
+    .await
+    .map_err(|e| internal_error(e.to_string()))?;
+
+    let imports_deleted = sqlx::query("DELETE FROM imports WHERE source = 'spotify'")
         .execute(&mut *tx)
         .await
         .map_err(|e| internal_error(e.to_string()))?
@@ -302,7 +314,7 @@ mod tests {
             .unwrap();
 
         sqlx::query(
-            "INSERT INTO spotify_imports (id, user_id, spotify_playlist_id, status) VALUES ($1, $2, $3, $4)",
+            "INSERT INTO imports (id, user_id, source_ref, status) VALUES ($1, $2, $3, $4)",
         )
         .bind("imp1")
         .bind("u1")
@@ -590,7 +590,7 @@ mod tests {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(&format!(
+                    .uri(format!(
                         "/api/auth/spotify/callback?code=test_code&state={}",
                         urlencoding::encode(&state_token)
                     ))
@@ -664,7 +664,7 @@ mod tests {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(&format!(
+                    .uri(format!(
                         "/api/auth/spotify/callback?code=test_code&state={}",
                         urlencoding::encode(&state_token)
                     ))
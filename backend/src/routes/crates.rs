@@ -10,7 +10,9 @@ use uuid::Uuid;
 use crate::db::crate_models::{CrateRow, CrateSummary, CrateTrackRow};
 use crate::db::crates;
 use crate::db::setlists as db_setlists;
+use crate::db::settings;
 use crate::error::AppError;
+use crate::services::camelot::display_key;
 
 // ---------------------------------------------------------------------------
 // State
@@ -112,9 +114,21 @@ async fn get_crate_handler(
     if crate_row.user_id != user_id {
         return Err(AppError::NotFound(format!("crate {id} not found")));
     }
-    let tracks = crates::get_crate_tracks(&state.pool, &id)
+    let mut tracks = crates::get_crate_tracks(&state.pool, &id)
         .await
         .map_err(AppError::Database)?;
+    let key_notation = settings::get_key_notation(&state.pool, user_id)
+        .await
+        .map_err(AppError::Database)?;
+    if let Some(notation) = key_notation {
+        for track in &mut tracks {
+            track.key = track
+                .camelot
+                .as_deref()
+                .or(track.key.as_deref())
+                .map(|k| display_key(k, notation));
+        }
+    }
     Ok(Json(CrateDetailResponse { crate_row, tracks }))
 }
 
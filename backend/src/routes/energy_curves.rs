@@ -0,0 +1,299 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+use crate::db::energy_curves;
+use crate::db::models::EnergyCurveRow;
+use crate::error::AppError;
+use crate::services::camelot::{EnergyBreakpoint, EnergyCurve};
+
+// ---------------------------------------------------------------------------
+// State
+// ---------------------------------------------------------------------------
+
+pub struct EnergyCurveRouteState {
+    pub pool: PgPool,
+}
+
+// ---------------------------------------------------------------------------
+// Request types
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+struct CreateEnergyCurveRequest {
+    name: String,
+    breakpoints: Vec<EnergyBreakpoint>,
+}
+
+// ---------------------------------------------------------------------------
+// Response types
+// ---------------------------------------------------------------------------
+
+/// A saved curve. The curve itself is flattened in, so `breakpoints` has
+/// the same shape as an inline `energy_curve` on a setlist.
+#[derive(Serialize)]
+struct EnergyCurveResponse {
+    id: String,
+    name: String,
+    #[serde(flatten)]
+    curve: EnergyCurve,
+    created_at: Option<chrono::NaiveDateTime>,
+}
+
+impl TryFrom<EnergyCurveRow> for EnergyCurveResponse {
+    type Error = AppError;
+
+    fn try_from(row: EnergyCurveRow) -> Result<Self, Self::Error> {
+        let breakpoints: Vec<EnergyBreakpoint> = serde_json::from_str(&row.breakpoints)
+            .map_err(|e| AppError::Internal(anyhow::anyhow!("corrupt energy curve: {e}")))?;
+        Ok(Self {
+            id: row.id,
+            name: row.name,
+            curve: EnergyCurve { breakpoints },
+            created_at: row.created_at,
+        })
+    }
+}
+
+#[derive(Serialize)]
+struct ListEnergyCurvesResponse {
+    energy_curves: Vec<EnergyCurveResponse>,
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+fn extract_user_id(headers: &HeaderMap) -> &str {
+    headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user")
+}
+
+async fn list_energy_curves_handler(
+    State(state): State<Arc<EnergyCurveRouteState>>,
+    headers: HeaderMap,
+) -> Result<Json<ListEnergyCurvesResponse>, AppError> {
+    let user_id = extract_user_id(&headers);
+    let rows = energy_curves::list_energy_curves(&state.pool, user_id)
+        .await
+        .map_err(AppError::Database)?;
+    let energy_curves = rows
+        .into_iter()
+        .map(EnergyCurveResponse::try_from)
+        .collect::<Result<_, _>>()?;
+    Ok(Json(ListEnergyCurvesResponse { energy_curves }))
+}
+
+async fn create_energy_curve_handler(
+    State(state): State<Arc<EnergyCurveRouteState>>,
+    headers: HeaderMap,
+    Json(req): Json<CreateEnergyCurveRequest>,
+) -> Result<(StatusCode, Json<EnergyCurveResponse>), AppError> {
+    if req.name.trim().is_empty() {
+        return Err(AppError::BadRequest("name is required".to_string()));
+    }
+    let curve = EnergyCurve {
+        breakpoints: req.breakpoints,
+    };
+    curve.validate().map_err(AppError::BadRequest)?;
+
+    let user_id = extract_user_id(&headers);
+    let id = Uuid::new_v4().to_string();
+    let breakpoints = serde_json::to_string(&curve.breakpoints)
+        .map_err(|e| AppError::Internal(anyhow::anyhow!(e)))?;
+    // The (user_id, name) constraint catches duplicates, including two
+    // creates racing each other.
+    energy_curves::create_energy_curve(&state.pool, &id, user_id, &req.name, &breakpoints)
+        .await
+        .map_err(|e| match e {
+            sqlx::Error::Database(db) if db.is_unique_violation() => AppError::BadRequest(format!(
+                "an energy curve named '{}' already exists",
+                req.name
+            )),
+            other => AppError::Database(other),
+        })?;
+    let row = energy_curves::get_energy_curve(&state.pool, &id)
+        .await
+        .map_err(AppError::Database)?
+        .ok_or_else(|| AppError::NotFound("energy curve not found after insert".to_string()))?;
+    Ok((StatusCode::CREATED, Json(row.try_into()?)))
+}
+
+async fn get_energy_curve_handler(
+    State(state): State<Arc<EnergyCurveRouteState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<EnergyCurveResponse>, AppError> {
+    let user_id = extract_user_id(&headers);
+    let row = energy_curves::get_energy_curve(&state.pool, &id)
+        .await
+        .map_err(AppError::Database)?
+        .filter(|r| r.user_id == user_id)
+        .ok_or_else(|| AppError::NotFound(format!("energy curve {id} not found")))?;
+    Ok(Json(row.try_into()?))
+}
+
+async fn delete_energy_curve_handler(
+    State(state): State<Arc<EnergyCurveRouteState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+) -> Result<StatusCode, AppError> {
+    let user_id = extract_user_id(&headers);
+    let affected = energy_curves::delete_energy_curve(&state.pool, &id, user_id)
+        .await
+        .map_err(AppError::Database)?;
+    if affected == 0 {
+        return Err(AppError::NotFound(format!("energy curve {id} not found")));
+    }
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// ---------------------------------------------------------------------------
+// Router
+// ---------------------------------------------------------------------------
+
+pub fn energy_curve_router(state: Arc<EnergyCurveRouteState>) -> Router {
+    Router::new()
+        .route(
+            "/energy-curves",
+            get(list_energy_curves_handler).post(create_energy_curve_handler),
+        )
+        .route(
+            "/energy-curves/{id}",
+            get(get_energy_curve_handler).delete(delete_energy_curve_handler),
+        )
+        .with_state(state)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn setup() -> (Router, PgPool) {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(EnergyCurveRouteState { pool: pool.clone() });
+        (energy_curve_router(state), pool)
+    }
+
+    async fn send(
+        app: Router,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (u16, serde_json::Value) {
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("X-User-Id", "user-1");
+        let body = match body {
+            Some(b) => Body::from(serde_json::to_vec(&b).unwrap()),
+            None => Body::empty(),
+        };
+        let resp = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+        let status = resp.status().as_u16();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json = serde_json::from_slice(&bytes).unwrap_or(serde_json::Value::Null);
+        (status, json)
+    }
+
+    fn double_peak() -> serde_json::Value {
+        serde_json::json!({
+            "name": "Double peak",
+            "breakpoints": [
+                {"position": 0.0, "energy": 4},
+                {"position": 0.3, "energy": 9},
+                {"position": 0.5, "energy": 5},
+                {"position": 0.8, "energy": 10},
+                {"position": 1.0, "energy": 6}
+            ]
+        })
+    }
+
+    #[tokio::test]
+    async fn test_create_list_and_delete_energy_curve() {
+        let (app, pool) = setup().await;
+
+        let (status, created) =
+            send(app.clone(), "POST", "/energy-curves", Some(double_peak())).await;
+        assert_eq!(status, 201);
+        assert_eq!(created["name"], "Double peak");
+        assert_eq!(created["breakpoints"].as_array().unwrap().len(), 5);
+        let id = created["id"].as_str().unwrap().to_string();
+
+        let uri = format!("/energy-curves/{id}");
+        let (status, fetched) = send(app.clone(), "GET", &uri, None).await;
+        assert_eq!(status, 200);
+        assert_eq!(fetched, created, "create and get return the same shape");
+
+        let (status, list) = send(app.clone(), "GET", "/energy-curves", None).await;
+        assert_eq!(status, 200);
+        assert_eq!(list["energy_curves"].as_array().unwrap().len(), 1);
+
+        let (status, _) = send(app.clone(), "DELETE", &uri, None).await;
+        assert_eq!(status, 204);
+        let (status, _) = send(app, "GET", &uri, None).await;
+        assert_eq!(status, 404);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_create_energy_curve_rejects_invalid_and_duplicate() {
+        let (app, pool) = setup().await;
+
+        let bad = serde_json::json!({
+            "name": "Backwards",
+            "breakpoints": [
+                {"position": 0.8, "energy": 4},
+                {"position": 0.2, "energy": 9}
+            ]
+        });
+        let (status, json) = send(app.clone(), "POST", "/energy-curves", Some(bad)).await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+
+        let (status, _) = send(app.clone(), "POST", "/energy-curves", Some(double_peak())).await;
+        assert_eq!(status, 201);
+        let (status, _) = send(app, "POST", "/energy-curves", Some(double_peak())).await;
+        assert_eq!(status, 400);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_duplicate_creates_return_400() {
+        let (app, pool) = setup().await;
+        let mut curve = double_peak();
+        curve["name"] = serde_json::json!("Racing curve");
+
+        let ((a, _), (b, json_b)) = tokio::join!(
+            send(app.clone(), "POST", "/energy-curves", Some(curve.clone())),
+            send(app, "POST", "/energy-curves", Some(curve)),
+        );
+        let mut statuses = [a, b];
+        statuses.sort();
+        assert_eq!(statuses, [201, 400]);
+        if b == 400 {
+            assert_eq!(json_b["error"]["code"], "INVALID_REQUEST");
+        }
+
+        pool.close().await;
+    }
+}
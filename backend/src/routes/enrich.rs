@@ -226,7 +226,7 @@ mod tests {
         // Pre-fill usage to the cap (250)
         let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
         sqlx::query(
-            "INSERT INTO user_usage (id, user_id, date, enrichment_count) VALUES ('u1', 'dev-user', $1, 250)",
+            "INSERT INTO user_usage (id, user_id, date, enrichment_count) VALUES ('u1', 'default-user', $1, 250)",
         )
         .bind(&today)
         .execute(&pool)
@@ -1,17 +1,30 @@
-use axum::extract::State;
+use axum::extract::{DefaultBodyLimit, State};
 use axum::http::{HeaderMap, StatusCode};
 use axum::response::{IntoResponse, Response};
 use axum::routing::post;
 use axum::{Json, Router};
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
+use std::path::PathBuf;
 use std::sync::Arc;
 
 use crate::api::claude::ClaudeClientTrait;
 use crate::api::spotify::SpotifyClient;
 use crate::db::tokens;
 use crate::routes::auth::decrypt_token;
-use crate::services::import::{self, ImportError, ImportRepository, ImportSummary};
+use crate::services::deezer::DeezerClient;
+use crate::services::file_import::{self, CsvMapping, FilePreview};
+use crate::services::import::{
+    self, ImportError, ImportFailure, ImportRepository, ImportSummary, LibraryImportSummary,
+    PlaylistTarget, PlaylistUrl,
+};
+use crate::services::local_scan::{self, LocalScanSummary};
+use crate::services::soundcloud::SoundCloudClient;
+use crate::services::{rekordbox, serato, traktor};
+
+/// Library exports run to tens of megabytes for large collections.
+const MAX_LIBRARY_BYTES: usize = 64 * 1024 * 1024;
 
 // ---------------------------------------------------------------------------
 // Request / Response types
@@ -22,25 +35,59 @@ pub struct ImportRequest {
     pub playlist_url: String,
 }
 
+/// Serato library upload: `_Serato_/database V2` and the `.crate` files from
+/// `_Serato_/Subcrates`, base64-encoded.
+#[derive(Deserialize)]
+pub struct SeratoImportRequest {
+    pub database: String,
+    #[serde(default)]
+    pub crates: Vec<SeratoCrateUpload>,
+}
+
+#[derive(Deserialize)]
+pub struct SeratoCrateUpload {
+    /// File name, e.g. "House%%Deep.crate".
+    pub name: String,
+    pub data: String,
+}
+
+/// Playlist or tracklist file upload (M3U/M3U8, PLS or CSV) as text.
+#[derive(Deserialize)]
+pub struct FileImportRequest {
+    pub file_name: String,
+    pub content: String,
+    /// "m3u", "m3u8", "pls" or "csv"; taken from `file_name` when absent.
+    #[serde(default)]
+    pub format: Option<String>,
+    /// CSV column mapping; suggested from the header when absent.
+    #[serde(default)]
+    pub mapping: Option<CsvMapping>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct ImportResponse {
     pub import_id: String,
+    pub source: String,
     pub total: u32,
     pub inserted: u32,
     pub updated: u32,
     pub failed: u32,
     pub status: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<ImportFailure>,
 }
 
 impl From<ImportSummary> for ImportResponse {
     fn from(s: ImportSummary) -> Self {
         Self {
             import_id: s.import_id,
+            source: s.source,
             total: s.total,
             inserted: s.inserted,
             updated: s.updated,
             failed: s.failed,
             status: s.status,
+            failures: s.failures,
         }
     }
 }
@@ -52,7 +99,9 @@ impl From<ImportSummary> for ImportResponse {
 impl IntoResponse for ImportError {
     fn into_response(self) -> Response {
         let (status, code, msg) = match &self {
-            ImportError::InvalidUrl(m) => (StatusCode::BAD_REQUEST, "INVALID_REQUEST", m.clone()),
+            ImportError::InvalidUrl(m) | ImportError::InvalidFile(m) => {
+                (StatusCode::BAD_REQUEST, "INVALID_REQUEST", m.clone())
+            }
             ImportError::NotFound(m) => (StatusCode::NOT_FOUND, "NOT_FOUND", m.clone()),
             ImportError::AccessDenied(m) => (StatusCode::FORBIDDEN, "ACCESS_DENIED", m.clone()),
             ImportError::SpotifyError(e) => {
@@ -63,6 +112,10 @@ impl IntoResponse for ImportError {
                     format!("Spotify API error: {e}"),
                 )
             }
+            ImportError::Upstream(m) => {
+                tracing::error!("Upstream error during import: {m}");
+                (StatusCode::BAD_GATEWAY, "UPSTREAM_ERROR", m.clone())
+            }
             ImportError::Database(m) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 "INTERNAL_ERROR",
@@ -90,18 +143,26 @@ pub struct ImportState {
     pub pool: PgPool,
     pub encryption_key: [u8; 32],
     pub claude: Arc<dyn ClaudeClientTrait>,
+    /// Folder `POST /import/local` scans; scanning is off when unset.
+    pub local_music_dir: Option<PathBuf>,
+    pub deezer: DeezerClient,
+    /// SoundCloud set imports are off when no API credentials are set.
+    pub soundcloud: Option<tokio::sync::Mutex<SoundCloudClient>>,
+    pub http: reqwest::Client,
 }
 
 // ---------------------------------------------------------------------------
 // Handlers
 // ---------------------------------------------------------------------------
 
-async fn import_spotify(
+/// POST /import (also mounted at /import/spotify) — import a Spotify or
+/// Deezer playlist, or a SoundCloud set, by URL.
+async fn import_playlist(
     State(state): State<Arc<ImportState>>,
     headers: HeaderMap,
     Json(req): Json<ImportRequest>,
 ) -> Result<Json<ImportResponse>, ImportError> {
-    let playlist_id = import::validate_playlist_url(&req.playlist_url)?;
+    let playlist = import::parse_playlist_url(&req.playlist_url)?;
 
     // Extract user_id from header (temporary until UC-008 adds real auth)
     let user_id = headers
@@ -109,6 +170,31 @@ async fn import_spotify(
         .and_then(|v| v.to_str().ok())
         .unwrap_or("default-user");
 
+    let playlist_id = match playlist {
+        PlaylistUrl::Spotify(id) => id,
+        PlaylistUrl::Deezer(id) => {
+            let summary =
+                import::import_deezer_playlist(state.repo.as_ref(), &state.deezer, user_id, &id)
+                    .await?;
+            return Ok(Json(ImportResponse::from(summary)));
+        }
+        PlaylistUrl::SoundCloud(url) => {
+            let soundcloud = state.soundcloud.as_ref().ok_or_else(|| {
+                ImportError::Upstream("SoundCloud import is not configured".into())
+            })?;
+            let mut soundcloud = soundcloud.lock().await;
+            let summary = import::import_soundcloud_set(
+                state.repo.as_ref(),
+                &mut soundcloud,
+                &state.http,
+                user_id,
+                &url,
+            )
+            .await?;
+            return Ok(Json(ImportResponse::from(summary)));
+        }
+    };
+
     // Fetch stored access token from DB
     let token_row = tokens::get_tokens(&state.pool, user_id)
         .await
@@ -132,13 +218,181 @@ async fn import_spotify(
     Ok(Json(ImportResponse::from(summary)))
 }
 
+/// POST /import/rekordbox — import a Rekordbox `rekordbox.xml` collection
+/// export sent as the request body.
+async fn import_rekordbox(
+    State(state): State<Arc<ImportState>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<LibraryImportSummary>, ImportError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let library = rekordbox::parse_library(&body)?;
+    let summary = import::import_library(
+        state.repo.as_ref(),
+        user_id,
+        rekordbox::SOURCE,
+        &library,
+        PlaylistTarget::Imports,
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+/// POST /import/traktor — import a Traktor `collection.nml` sent as the
+/// request body. Playlists become crates.
+async fn import_traktor(
+    State(state): State<Arc<ImportState>>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<LibraryImportSummary>, ImportError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let library = traktor::parse_library(&body)?;
+    let summary = import::import_library(
+        state.repo.as_ref(),
+        user_id,
+        traktor::SOURCE,
+        &library,
+        PlaylistTarget::Crates,
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+/// POST /import/serato — import a Serato database and its crates. Serato
+/// crates become crates here too.
+async fn import_serato(
+    State(state): State<Arc<ImportState>>,
+    headers: HeaderMap,
+    Json(req): Json<SeratoImportRequest>,
+) -> Result<Json<LibraryImportSummary>, ImportError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let decode = |label: &str, data: &str| {
+        base64::engine::general_purpose::STANDARD
+            .decode(data.trim())
+            .map_err(|e| ImportError::InvalidFile(format!("{label} is not valid base64: {e}")))
+    };
+    let database = decode("database", &req.database)?;
+    let crates = req
+        .crates
+        .iter()
+        .map(|c| {
+            Ok(serato::CrateFile {
+                name: c.name.clone(),
+                data: decode(&c.name, &c.data)?,
+            })
+        })
+        .collect::<Result<Vec<_>, ImportError>>()?;
+
+    let library = serato::parse_library(&database, &crates)?;
+    let summary = import::import_library(
+        state.repo.as_ref(),
+        user_id,
+        serato::SOURCE,
+        &library,
+        PlaylistTarget::Crates,
+    )
+    .await?;
+
+    Ok(Json(summary))
+}
+
+/// POST /import/file/preview — parse an uploaded file without importing,
+/// returning sample rows, per-row problems and, for CSV, the column mapping.
+async fn preview_file_import(
+    Json(req): Json<FileImportRequest>,
+) -> Result<Json<FilePreview>, ImportError> {
+    let format = file_import::resolve_format(&req.file_name, req.format.as_deref())?;
+    let preview = file_import::preview_file(format, &req.content, req.mapping.as_ref())?;
+    Ok(Json(preview))
+}
+
+/// POST /import/file — import an uploaded M3U/M3U8, PLS or CSV file.
+async fn import_file(
+    State(state): State<Arc<ImportState>>,
+    headers: HeaderMap,
+    Json(req): Json<FileImportRequest>,
+) -> Result<Json<ImportResponse>, ImportError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let format = file_import::resolve_format(&req.file_name, req.format.as_deref())?;
+    let summary = file_import::import_file(
+        state.repo.as_ref(),
+        user_id,
+        &req.file_name,
+        format,
+        &req.content,
+        req.mapping.as_ref(),
+    )
+    .await?;
+
+    Ok(Json(ImportResponse::from(summary)))
+}
+
+/// POST /import/local — scan the configured music folder, reading tags from
+/// new and changed audio files.
+async fn import_local(
+    State(state): State<Arc<ImportState>>,
+    headers: HeaderMap,
+) -> Result<Json<LocalScanSummary>, ImportError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let root = state.local_music_dir.as_deref().ok_or_else(|| {
+        ImportError::InvalidFile("No music folder configured (set LOCAL_MUSIC_DIR)".to_string())
+    })?;
+    let summary = local_scan::scan_folder(state.repo.as_ref(), user_id, root).await?;
+
+    Ok(Json(summary))
+}
+
 // ---------------------------------------------------------------------------
 // Router
 // ---------------------------------------------------------------------------
 
 pub fn import_router(state: Arc<ImportState>) -> Router {
     Router::new()
-        .route("/import/spotify", post(import_spotify))
+        .route("/import", post(import_playlist))
+        .route("/import/spotify", post(import_playlist))
+        .route(
+            "/import/rekordbox",
+            post(import_rekordbox).layer(DefaultBodyLimit::max(MAX_LIBRARY_BYTES)),
+        )
+        .route(
+            "/import/traktor",
+            post(import_traktor).layer(DefaultBodyLimit::max(MAX_LIBRARY_BYTES)),
+        )
+        .route(
+            "/import/serato",
+            post(import_serato).layer(DefaultBodyLimit::max(MAX_LIBRARY_BYTES)),
+        )
+        .route(
+            "/import/file/preview",
+            post(preview_file_import).layer(DefaultBodyLimit::max(MAX_LIBRARY_BYTES)),
+        )
+        .route("/import/local", post(import_local))
+        .route(
+            "/import/file",
+            post(import_file).layer(DefaultBodyLimit::max(MAX_LIBRARY_BYTES)),
+        )
         .with_state(state)
 }
 
@@ -154,7 +408,7 @@ mod tests {
     use std::sync::Mutex;
     use tower::ServiceExt;
 
-    use crate::services::import::{ArtistRecord, TrackRecord, UpsertResult};
+    use crate::services::import::{ArtistRecord, ImportOrigin, TrackRecord, UpsertResult};
     use crate::services::setlist::test_utils::MockClaude;
 
     // -- Simple mock repo for handler tests --
@@ -176,8 +430,7 @@ mod tests {
         async fn create_import(
             &self,
             _user_id: &str,
-            _playlist_id: &str,
-            _playlist_name: Option<&str>,
+            _origin: &ImportOrigin,
         ) -> Result<String, ImportError> {
             Ok("test-import-001".to_string())
         }
@@ -227,6 +480,10 @@ mod tests {
             claude: Arc::new(MockClaude {
                 response: "{}".to_string(),
             }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
         });
 
         let app = import_router(state);
@@ -312,6 +569,10 @@ mod tests {
             claude: Arc::new(MockClaude {
                 response: "{}".to_string(),
             }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
         });
 
         let app = import_router(state);
@@ -357,6 +618,10 @@ mod tests {
             claude: Arc::new(MockClaude {
                 response: "{}".to_string(),
             }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
         });
         // Verify all fields are accessible
         assert_eq!(state.encryption_key, [0u8; 32]);
@@ -364,4 +629,584 @@ mod tests {
         let _ = &state.repo;
         let _ = &state.pool;
     }
+
+    const REKORDBOX_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<DJ_PLAYLISTS Version="1.0.0">
+  <COLLECTION Entries="2">
+    <TRACK TrackID="1" Name="Desert Rose" Artist="Sting" TotalTime="285" AverageBpm="102.00"
+           Tonality="Am" Rating="255" Location="file://localhost/music/rose.mp3"/>
+    <TRACK TrackID="2" Name="Yeke Yeke" Artist="Mory Kante" TotalTime="381" AverageBpm="124.00"
+           Tonality="F#m" Location="file://localhost/music/yeke.mp3"/>
+  </COLLECTION>
+  <PLAYLISTS>
+    <NODE Type="0" Name="ROOT" Count="1">
+      <NODE Name="Warmup" Type="1" KeyType="0" Entries="1">
+        <TRACK Key="2"/>
+      </NODE>
+    </NODE>
+  </PLAYLISTS>
+</DJ_PLAYLISTS>"#;
+
+    #[tokio::test]
+    async fn test_import_rekordbox_creates_playlist_imports() {
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+
+        // Already in the catalog from Spotify: the Rekordbox copy should update it.
+        crate::db::tracks::upsert_track(
+            &pool,
+            "sp-rose",
+            "Desert Rose",
+            None,
+            None,
+            "spotify:track:sp-rose",
+            None,
+            None,
+        )
+        .await
+        .unwrap();
+        let sting = crate::db::artists::find_or_create_artist_by_name(&pool, "Sting")
+            .await
+            .unwrap();
+        crate::db::artists::upsert_track_artist(&pool, "sp-rose", &sting)
+            .await
+            .unwrap();
+
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(crate::repo::PgImportRepository::new(pool.clone())),
+            pool: pool.clone(),
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/rekordbox")
+                    .header("content-type", "application/xml")
+                    .header("X-User-Id", &user_id)
+                    .body(Body::from(REKORDBOX_XML))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let summary: LibraryImportSummary = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(summary.collection.total, 2);
+        assert_eq!(summary.collection.inserted, 1);
+        assert_eq!(summary.collection.updated, 1);
+        assert_eq!(summary.playlists.len(), 1);
+        assert_eq!(summary.playlists[0].missing, 0);
+        let warmup_id = summary.playlists[0].import_id.clone().unwrap();
+
+        let (bpm, key, rating): (Option<f64>, Option<String>, Option<i16>) =
+            sqlx::query_as("SELECT bpm, camelot_key, rating FROM tracks WHERE id = 'sp-rose'")
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(bpm, Some(102.0));
+        assert_eq!(key.as_deref(), Some("8A"));
+        assert_eq!(rating, Some(5));
+
+        let warmup = crate::db::imports::get_tracks_by_import_id(&pool, &warmup_id)
+            .await
+            .unwrap();
+        assert_eq!(warmup.len(), 1);
+        assert_eq!(warmup[0].title, "Yeke Yeke");
+        assert_eq!(warmup[0].artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(warmup[0].camelot_key.as_deref(), Some("11A"));
+        assert_eq!(warmup[0].source, "rekordbox");
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_import_rekordbox_invalid_xml_returns_400() {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(TestRepo::new()),
+            pool,
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/rekordbox")
+                    .body(Body::from("<playlist/>"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_import_traktor_playlists_become_crates() {
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+        let nml = r#"<NML VERSION="19">
+  <COLLECTION ENTRIES="1">
+    <ENTRY TITLE="Yeke Yeke" ARTIST="Mory Kante">
+      <LOCATION DIR="/:Music/:" FILE="yeke.mp3" VOLUME="Macintosh HD"/>
+      <TEMPO BPM="124.0"/>
+      <MUSICAL_KEY VALUE="18"/>
+    </ENTRY>
+  </COLLECTION>
+  <PLAYLISTS>
+    <NODE TYPE="FOLDER" NAME="$ROOT"><SUBNODES COUNT="1">
+      <NODE TYPE="PLAYLIST" NAME="Peak">
+        <PLAYLIST ENTRIES="1" TYPE="LIST">
+          <ENTRY><PRIMARYKEY TYPE="TRACK" KEY="Macintosh HD/:Music/:yeke.mp3"/></ENTRY>
+        </PLAYLIST>
+      </NODE>
+    </SUBNODES></NODE>
+  </PLAYLISTS>
+</NML>"#;
+
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(crate::repo::PgImportRepository::new(pool.clone())),
+            pool: pool.clone(),
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let mut crate_ids = Vec::new();
+        for _ in 0..2 {
+            let response = import_router(state.clone())
+                .oneshot(
+                    Request::builder()
+                        .method("POST")
+                        .uri("/import/traktor")
+                        .header("X-User-Id", &user_id)
+                        .body(Body::from(nml))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+                .await
+                .unwrap();
+            let summary: LibraryImportSummary = serde_json::from_slice(&body_bytes).unwrap();
+            assert_eq!(summary.playlists[0].missing, 0);
+            crate_ids.push(summary.playlists[0].crate_id.clone().unwrap());
+        }
+        assert_eq!(crate_ids[0], crate_ids[1], "re-import reuses the crate");
+
+        let crate_tracks = crate::db::crates::get_crate_tracks(&pool, &crate_ids[0])
+            .await
+            .unwrap();
+        assert_eq!(crate_tracks.len(), 1);
+        assert_eq!(crate_tracks[0].artist, "Mory Kante");
+        assert_eq!(crate_tracks[0].camelot.as_deref(), Some("11A"));
+
+        let analysed_by: Option<String> = sqlx::query_scalar(
+            "SELECT analysed_by FROM tracks WHERE file_location = '/Music/yeke.mp3'",
+        )
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+        assert_eq!(analysed_by.as_deref(), Some("traktor"));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_import_serato_invalid_base64_returns_400() {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(TestRepo::new()),
+            pool,
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let body = serde_json::json!({ "database": "not base64!", "crates": [] });
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/serato")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_import_csv_file_records_failures() {
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(crate::repo::PgImportRepository::new(pool.clone())),
+            pool: pool.clone(),
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let body = serde_json::json!({
+            "file_name": "friday.csv",
+            "content": "Song;Performer;Tempo;Key\nYeke Yeke;Mory Kante;124;F#m\nDesert Rose;Sting;fast;Am\n",
+            "mapping": { "title": "Song", "artist": "Performer", "bpm": "Tempo", "key": "Key" }
+        });
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/file")
+                    .header("content-type", "application/json")
+                    .header("X-User-Id", &user_id)
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let summary: ImportResponse = serde_json::from_slice(&body_bytes).unwrap();
+        assert_eq!(summary.source, "csv");
+        assert_eq!(summary.total, 2);
+        assert_eq!(summary.inserted, 1);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.failures[0].row, 3);
+
+        let import = crate::db::imports::get_import(&pool, &summary.import_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(import.source, "csv");
+        assert_eq!(import.file_name.as_deref(), Some("friday.csv"));
+        assert_eq!(import.name.as_deref(), Some("friday"));
+
+        let failures = crate::db::imports::get_import_failures(&pool, &summary.import_id)
+            .await
+            .unwrap();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].item.as_deref(), Some("Sting - Desert Rose"));
+        assert!(failures[0].reason.contains("BPM"));
+
+        let tracks = crate::db::imports::get_tracks_by_import_id(&pool, &summary.import_id)
+            .await
+            .unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0].camelot_key.as_deref(), Some("11A"));
+        assert_eq!(tracks[0].source, "csv");
+
+        pool.close().await;
+    }
+
+    fn streaming_state(
+        pool: PgPool,
+        deezer: DeezerClient,
+        soundcloud: Option<SoundCloudClient>,
+    ) -> Arc<ImportState> {
+        Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(crate::repo::PgImportRepository::new(pool.clone())),
+            pool,
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer,
+            soundcloud: soundcloud.map(tokio::sync::Mutex::new),
+            http: reqwest::Client::new(),
+        })
+    }
+
+    async fn post_playlist_url(
+        state: Arc<ImportState>,
+        user_id: &str,
+        playlist_url: &str,
+    ) -> Response {
+        let body = serde_json::json!({ "playlist_url": playlist_url });
+        import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import")
+                    .header("content-type", "application/json")
+                    .header("X-User-Id", user_id)
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn summary_of(response: Response) -> ImportResponse {
+        assert_eq!(response.status(), StatusCode::OK);
+        let body_bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        serde_json::from_slice(&body_bytes).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_import_deezer_url_stores_bpm_and_matches_on_reimport() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/playlist/5150"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 5150, "title": "Sahel Grooves", "nb_tracks": 1
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlist/5150/tracks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [{
+                    "id": 715_001,
+                    "title": "Tamatant Tilay",
+                    "duration": 301,
+                    "preview": "https://cdn.example.com/715001.mp3",
+                    "artist": { "name": "Tinariwen" },
+                    "album": { "title": "Emmaar", "cover_medium": null }
+                }],
+                "total": 1
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/track/715001"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": 715_001, "bpm": 118.0
+            })))
+            .mount(&server)
+            .await;
+
+        let deezer = || DeezerClient::new().with_base_url(server.uri());
+        let url = "https://www.deezer.com/en/playlist/5150";
+
+        let first = summary_of(
+            post_playlist_url(streaming_state(pool.clone(), deezer(), None), &user_id, url).await,
+        )
+        .await;
+        assert_eq!(first.source, "deezer");
+        assert_eq!(first.inserted, 1);
+
+        let second = summary_of(
+            post_playlist_url(streaming_state(pool.clone(), deezer(), None), &user_id, url).await,
+        )
+        .await;
+        assert_eq!(second.inserted, 0);
+        assert_eq!(second.updated, 1, "matched by deezer_id");
+
+        let import = crate::db::imports::get_import(&pool, &first.import_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(import.source, "deezer");
+        assert_eq!(import.name.as_deref(), Some("Sahel Grooves"));
+
+        let (bpm, analysed_by, preview, source): (Option<f64>, Option<String>, Option<String>, String) =
+            sqlx::query_as(
+                "SELECT bpm, analysed_by, deezer_preview_url, source FROM tracks WHERE deezer_id = 715001",
+            )
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(bpm, Some(118.0));
+        assert_eq!(analysed_by.as_deref(), Some("deezer"));
+        assert_eq!(
+            preview.as_deref(),
+            Some("https://cdn.example.com/715001.mp3")
+        );
+        assert_eq!(source, "deezer");
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_import_soundcloud_url_tags_genre() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "tok", "expires_in": 3600
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/resolve"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "urn": "soundcloud:playlists:8080", "title": "Amapiano Edits", "track_count": 1
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlists/soundcloud:playlists:8080/tracks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "collection": [{
+                    "urn": "soundcloud:tracks:8080001",
+                    "title": "Log Drum Sunrise",
+                    "duration": 312000,
+                    "genre": "Amapiano",
+                    "user": { "username": "kabza-edits" }
+                }],
+                "next_href": null
+            })))
+            .mount(&server)
+            .await;
+
+        let soundcloud = || Some(SoundCloudClient::new("id", "secret").with_api_base(server.uri()));
+        let url = "https://soundcloud.com/kabza-edits/sets/amapiano-edits";
+        for _ in 0..2 {
+            let state = streaming_state(pool.clone(), DeezerClient::new(), soundcloud());
+            let summary = summary_of(post_playlist_url(state, &user_id, url).await).await;
+            assert_eq!(summary.source, "soundcloud");
+        }
+
+        let tags: Vec<(String, String)> = sqlx::query_as(
+            "SELECT tt.value, tt.source FROM track_tags tt
+             JOIN tracks t ON t.id = tt.track_id
+             WHERE t.soundcloud_urn = 'soundcloud:tracks:8080001' AND tt.category = 'genre'",
+        )
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+        assert_eq!(
+            tags,
+            vec![("Amapiano".to_string(), "soundcloud".to_string())],
+            "re-import does not duplicate the tag"
+        );
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_import_soundcloud_url_unconfigured_returns_502() {
+        let pool = crate::db::create_test_pool().await;
+        let state = streaming_state(pool.clone(), DeezerClient::new(), None);
+        let response = post_playlist_url(
+            state,
+            "default-user",
+            "https://soundcloud.com/kabza-edits/sets/amapiano-edits",
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_GATEWAY);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_preview_unknown_format_returns_400() {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(TestRepo::new()),
+            pool,
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let body = serde_json::json!({ "file_name": "set.xspf", "content": "<playlist/>" });
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/file/preview")
+                    .header("content-type", "application/json")
+                    .body(Body::from(serde_json::to_vec(&body).unwrap()))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn test_import_local_without_folder_returns_400() {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(ImportState {
+            spotify: SpotifyClient::new("id", "secret"),
+            repo: Arc::new(TestRepo::new()),
+            pool,
+            encryption_key: [0u8; 32],
+            claude: Arc::new(MockClaude {
+                response: "{}".to_string(),
+            }),
+            local_music_dir: None,
+            deezer: DeezerClient::new(),
+            soundcloud: None,
+            http: reqwest::Client::new(),
+        });
+
+        let response = import_router(state)
+            .oneshot(
+                Request::builder()
+                    .method("POST")
+                    .uri("/import/local")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
 }
@@ -0,0 +1,233 @@
+use axum::extract::{Path, State};
+use axum::http::{HeaderMap, StatusCode};
+use axum::routing::{get, post};
+use axum::{Json, Router};
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::api::claude::ClaudeClientTrait;
+use crate::services::camelot::EnergyProfile;
+use crate::services::lineup::{
+    self, GenerateLineupRequest, LineupResponse, LineupSlot, DEFAULT_ALTERNATE_EVERY,
+};
+use crate::services::setlist::SetlistError;
+
+// ---------------------------------------------------------------------------
+// State
+// ---------------------------------------------------------------------------
+
+pub struct LineupRouteState {
+    pub pool: PgPool,
+    pub claude: Arc<dyn ClaudeClientTrait>,
+}
+
+// ---------------------------------------------------------------------------
+// Request types
+// ---------------------------------------------------------------------------
+
+#[derive(Deserialize)]
+pub struct GenerateLineupBody {
+    pub prompt: String,
+    #[serde(default)]
+    pub name: Option<String>,
+    pub slots: Vec<LineupSlotRequest>,
+}
+
+#[derive(Deserialize)]
+pub struct LineupSlotRequest {
+    pub dj: String,
+    /// Other DJs playing back-to-back with `dj`, in turn order.
+    #[serde(default)]
+    pub b2b_with: Vec<String>,
+    /// Tracks per turn when playing back-to-back (default 2).
+    #[serde(default)]
+    pub alternate_every: Option<u32>,
+    pub duration_minutes: u32,
+    #[serde(default)]
+    pub energy_profile: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+async fn generate_lineup_handler(
+    State(state): State<Arc<LineupRouteState>>,
+    headers: HeaderMap,
+    Json(req): Json<GenerateLineupBody>,
+) -> Result<(StatusCode, Json<LineupResponse>), SetlistError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let slots = req
+        .slots
+        .into_iter()
+        .map(|s| {
+            let energy_profile = match s.energy_profile {
+                Some(ref p) => Some(
+                    p.parse::<EnergyProfile>()
+                        .map_err(SetlistError::InvalidEnergyProfile)?,
+                ),
+                None => None,
+            };
+            Ok(LineupSlot {
+                dj: s.dj,
+                b2b_with: s.b2b_with,
+                alternate_every: s.alternate_every.unwrap_or(DEFAULT_ALTERNATE_EVERY),
+                duration_minutes: s.duration_minutes,
+                energy_profile,
+            })
+        })
+        .collect::<Result<Vec<_>, SetlistError>>()?;
+
+    let response = lineup::generate_lineup(
+        &state.pool,
+        state.claude.as_ref(),
+        GenerateLineupRequest {
+            user_id: user_id.to_string(),
+            prompt: req.prompt,
+            name: req.name,
+            slots,
+        },
+    )
+    .await?;
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+async fn get_lineup_handler(
+    State(state): State<Arc<LineupRouteState>>,
+    Path(id): Path<String>,
+) -> Result<Json<LineupResponse>, SetlistError> {
+    let response = lineup::get_lineup(&state.pool, &id).await?;
+    Ok(Json(response))
+}
+
+// ---------------------------------------------------------------------------
+// Router
+// ---------------------------------------------------------------------------
+
+pub fn lineup_router(state: Arc<LineupRouteState>) -> Router {
+    Router::new()
+        .route("/lineups/generate", post(generate_lineup_handler))
+        .route("/lineups/{id}", get(get_lineup_handler))
+        .with_state(state)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::setlist::test_utils::MockClaude;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn setup_app() -> (Router, PgPool) {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(LineupRouteState {
+            pool: pool.clone(),
+            claude: Arc::new(MockClaude {
+                response: r#"{"tracks": [{"position": 1, "title": "Only", "artist": "One",
+                    "bpm": 124.0, "camelot": "8A", "energy": 5}], "notes": null}"#
+                    .to_string(),
+            }),
+        });
+        (lineup_router(state), pool)
+    }
+
+    async fn send(app: Router, request: Request<Body>) -> (u16, serde_json::Value) {
+        let response = app.oneshot(request).await.unwrap();
+        let status = response.status().as_u16();
+        let bytes = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&bytes).unwrap())
+    }
+
+    fn post(body: serde_json::Value) -> Request<Body> {
+        Request::builder()
+            .method("POST")
+            .uri("/lineups/generate")
+            .header("content-type", "application/json")
+            .body(Body::from(serde_json::to_vec(&body).unwrap()))
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_generate_lineup_returns_201_and_reads_back() {
+        let (app, pool) = setup_app().await;
+        let (status, json) = send(
+            app.clone(),
+            post(serde_json::json!({
+                "prompt": "rooftop party",
+                "slots": [
+                    {"dj": "Ana", "duration_minutes": 30, "energy_profile": "warm-up"},
+                    {"dj": "Ben", "b2b_with": ["Cy"], "duration_minutes": 60}
+                ]
+            })),
+        )
+        .await;
+        assert_eq!(status, 201);
+        assert_eq!(json["slots"][0]["energy_profile"], "warm-up");
+        assert_eq!(json["slots"][1]["alternate_every"], 2);
+        assert!(json["slots"][0]["setlist"]["id"].is_string());
+        // The mock returns the same track every time; it is never repeated
+        assert!(json["slots"][1]["setlist"]["tracks"]
+            .as_array()
+            .unwrap()
+            .is_empty());
+
+        let id = json["id"].as_str().unwrap();
+        let (status, reread) = send(
+            app,
+            Request::builder()
+                .uri(format!("/lineups/{id}"))
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(reread["slots"][1]["b2b_with"][0], "Cy");
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_lineup_rejects_bad_slots() {
+        let (app, pool) = setup_app().await;
+        let (status, json) = send(
+            app.clone(),
+            post(serde_json::json!({
+                "prompt": "rooftop party",
+                "slots": [{"dj": "Ana", "duration_minutes": 30, "energy_profile": "loud"}]
+            })),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_ENERGY_PROFILE");
+
+        let (status, json) = send(
+            app.clone(),
+            post(serde_json::json!({"prompt": "rooftop party", "slots": []})),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+
+        let (status, _) = send(
+            app,
+            Request::builder()
+                .uri("/lineups/missing")
+                .body(Body::empty())
+                .unwrap(),
+        )
+        .await;
+        assert_eq!(status, 404);
+        pool.close().await;
+    }
+}
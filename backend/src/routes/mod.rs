@@ -3,9 +3,12 @@ pub mod audio;
 pub mod auth;
 pub mod crates;
 pub mod dev;
+pub mod energy_curves;
 pub mod enrich;
 pub mod import;
+pub mod lineups;
 pub mod purchase_links;
 pub mod refinement;
 pub mod setlist;
+pub mod settings;
 pub mod tracks;
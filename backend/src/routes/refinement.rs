@@ -8,6 +8,7 @@ use sqlx::PgPool;
 use std::sync::Arc;
 
 use crate::api::claude::ClaudeClientTrait;
+use crate::services::bridge::MAX_BRIDGE_TRACKS;
 use crate::services::refinement::{self, HistoryResponse, RefinementError, RefinementResponse};
 
 // ---------------------------------------------------------------------------
@@ -28,6 +29,13 @@ pub struct RefineRequest {
     pub message: String,
 }
 
+#[derive(Deserialize)]
+pub struct BridgeRequest {
+    /// Bridge tracks go between this position and the next.
+    pub after_position: usize,
+    pub max_tracks: Option<usize>,
+}
+
 // ---------------------------------------------------------------------------
 // Handlers
 // ---------------------------------------------------------------------------
@@ -56,6 +64,21 @@ async fn revert_handler(
     Ok(Json(response))
 }
 
+async fn bridge_handler(
+    State(state): State<Arc<RefinementRouteState>>,
+    Path(setlist_id): Path<String>,
+    Json(body): Json<BridgeRequest>,
+) -> Result<Json<RefinementResponse>, RefinementError> {
+    let response = refinement::bridge_setlist(
+        &state.pool,
+        &setlist_id,
+        body.after_position,
+        body.max_tracks.unwrap_or(MAX_BRIDGE_TRACKS),
+    )
+    .await?;
+    Ok(Json(response))
+}
+
 async fn history_handler(
     State(state): State<Arc<RefinementRouteState>>,
     Path(setlist_id): Path<String>,
@@ -75,6 +98,7 @@ pub fn refinement_router(state: Arc<RefinementRouteState>) -> Router {
             "/setlists/{id}/revert/{version_number}",
             post(revert_handler),
         )
+        .route("/setlists/{id}/bridge", post(bridge_handler))
         .route("/setlists/{id}/history", get(history_handler))
         .with_state(state)
 }
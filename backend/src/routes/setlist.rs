@@ -1,5 +1,6 @@
 use axum::extract::{Path, Query, State};
 use axum::http::{HeaderMap, StatusCode};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
@@ -9,10 +10,16 @@ use std::sync::Arc;
 use crate::api::claude::ClaudeClientTrait;
 use crate::db::models::SetlistSummary;
 use crate::db::setlists as db;
-use crate::services::camelot::EnergyProfile;
+use crate::services::camelot::{EnergyCurve, EnergyProfile, KeyPath};
+use crate::services::constraints::{TrackConstraints, TrackRef};
+use crate::services::local_generation::{self, GenerationMode};
 use crate::services::setlist::{
-    self, BpmRange, GenerateSetlistRequest, SetlistError, SetlistResponse,
+    self, BpmRange, GenerateSetlistRequest, LintResponse, SetlistError, SetlistResponse,
+    TrackConstraint,
 };
+use crate::services::solver::SolverConfig;
+use crate::services::tracklist::{TracklistEntry, TracklistScore};
+use crate::services::variants::{self, KeepVariantResponse};
 
 // ---------------------------------------------------------------------------
 // State (M1: renamed from SetlistState to SetlistRouteState)
@@ -31,10 +38,25 @@ pub struct SetlistRouteState {
 pub struct GenerateRequest {
     pub prompt: String,
     pub track_count: Option<u32>,
+    /// e.g. 90 for a 90-minute slot; mutually exclusive with `track_count`.
+    #[serde(default)]
+    pub target_duration_minutes: Option<u32>,
     #[serde(default)]
     pub energy_profile: Option<String>,
+    /// Inline custom curve; alternative to `energy_profile`.
+    #[serde(default)]
+    pub energy_curve: Option<EnergyCurve>,
+    /// Id of one of the user's saved curves.
+    #[serde(default)]
+    pub energy_curve_id: Option<String>,
+    /// Target key journey, e.g. 5A at the start to 10B at the end.
+    #[serde(default)]
+    pub key_path: Option<KeyPath>,
     #[serde(default)]
     pub source_playlist_id: Option<String>,
+    /// Draw only from the tracks in one of the user's crates.
+    #[serde(default)]
+    pub source_crate_id: Option<String>,
     #[serde(default)]
     pub seed_tracklist: Option<String>,
     #[serde(default)]
@@ -45,6 +67,23 @@ pub struct GenerateRequest {
     pub verify: Option<bool>,
     #[serde(default)]
     pub name: Option<String>,
+    /// Generate this many ranked alternatives (2-5) instead of one setlist.
+    #[serde(default)]
+    pub variants: Option<u32>,
+    /// Catalog track ids or `{artist, title}` pairs that must appear.
+    #[serde(default)]
+    pub must_include: Vec<TrackRef>,
+    #[serde(default)]
+    pub exclude_tracks: Vec<TrackRef>,
+    #[serde(default)]
+    pub exclude_artists: Vec<String>,
+    /// Leave out tracks from the user's last N setlists.
+    #[serde(default)]
+    pub exclude_recent_setlists: Option<u32>,
+    /// llm (default), local (catalog only, no LLM) or auto (llm, falling
+    /// back to local when the LLM is unavailable).
+    #[serde(default)]
+    pub mode: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -71,16 +110,48 @@ pub struct DuplicateRequest {
     pub name: Option<String>,
 }
 
+#[derive(Deserialize)]
+pub struct KeepRequest {
+    /// Delete the other drafts generated alongside this one (default true).
+    #[serde(default)]
+    pub discard_others: Option<bool>,
+}
+
 #[derive(Deserialize)]
 pub struct BpmRangeRequest {
     pub min: f64,
     pub max: f64,
 }
 
+#[derive(Deserialize)]
+pub struct ScoreTracklistRequest {
+    pub tracks: Vec<TracklistEntry>,
+    #[serde(default)]
+    pub energy_profile: Option<String>,
+    /// Also arrange the tracks and return the best order found.
+    #[serde(default)]
+    pub suggest_order: bool,
+}
+
 #[derive(Deserialize)]
 pub struct ArrangeRequest {
     #[serde(default)]
     pub energy_profile: Option<String>,
+    #[serde(default)]
+    pub energy_curve: Option<EnergyCurve>,
+    #[serde(default)]
+    pub energy_curve_id: Option<String>,
+    /// Overrides the key path stored on the setlist.
+    #[serde(default)]
+    pub key_path: Option<KeyPath>,
+    /// Position rules, e.g. `{"track_position": 3, "type": "first"}`.
+    #[serde(default)]
+    pub constraints: Vec<TrackConstraint>,
+    /// greedy (default), held-karp, annealing, or-opt or auto.
+    #[serde(default)]
+    pub solver: Option<String>,
+    #[serde(default)]
+    pub time_budget_ms: Option<u64>,
 }
 
 // ---------------------------------------------------------------------------
@@ -102,7 +173,7 @@ async fn generate_setlist_handler(
     State(state): State<Arc<SetlistRouteState>>,
     headers: HeaderMap,
     Json(req): Json<GenerateRequest>,
-) -> Result<(axum::http::StatusCode, Json<SetlistResponse>), SetlistError> {
+) -> Result<Response, SetlistError> {
     let user_id = headers
         .get("X-User-Id")
         .and_then(|v| v.to_str().ok())
@@ -116,13 +187,31 @@ async fn generate_setlist_handler(
         ),
         None => None,
     };
+    let energy_curve = setlist::resolve_energy_curve(
+        &state.pool,
+        user_id,
+        req.energy_curve,
+        req.energy_curve_id.as_deref(),
+    )
+    .await?;
+
+    let mode = match req.mode {
+        Some(ref m) => m
+            .parse::<GenerationMode>()
+            .map_err(SetlistError::InvalidRequest)?,
+        None => GenerationMode::default(),
+    };
 
     let service_req = GenerateSetlistRequest {
         user_id: user_id.to_string(),
         prompt: req.prompt,
         track_count: req.track_count,
+        target_duration_minutes: req.target_duration_minutes,
         energy_profile,
+        energy_curve,
+        key_path: req.key_path,
         source_playlist_id: req.source_playlist_id,
+        source_crate_id: req.source_crate_id,
         seed_tracklist: req.seed_tracklist,
         creative_mode: req.creative_mode,
         bpm_range: req.bpm_range.map(|r| BpmRange {
@@ -131,35 +220,110 @@ async fn generate_setlist_handler(
         }),
         verify: req.verify.unwrap_or(false),
         name: req.name,
+        constraints: TrackConstraints {
+            must_include: req.must_include,
+            exclude_tracks: req.exclude_tracks,
+            exclude_artists: req.exclude_artists,
+            exclude_recent_setlists: req.exclude_recent_setlists,
+        },
     };
 
-    let response =
-        setlist::generate_setlist_from_request(&state.pool, state.claude.as_ref(), service_req)
-            .await?;
+    if let Some(count) = req.variants.filter(|&n| n != 1) {
+        if mode == GenerationMode::Local {
+            return Err(SetlistError::InvalidRequest(
+                "variants are not available in local mode".to_string(),
+            ));
+        }
+        let response =
+            variants::generate_variants(&state.pool, state.claude.as_ref(), service_req, count)
+                .await?;
+        return Ok((StatusCode::CREATED, Json(response)).into_response());
+    }
+
+    let response = local_generation::generate_setlist_with_mode(
+        &state.pool,
+        state.claude.as_ref(),
+        service_req,
+        mode,
+    )
+    .await?;
 
-    Ok((axum::http::StatusCode::CREATED, Json(response)))
+    Ok((StatusCode::CREATED, Json(response)).into_response())
 }
 
 /// L1: Route handler now delegates entirely to service function.
-/// T8: Accepts optional JSON body with energy_profile for arrangement.
+/// T8: Accepts optional JSON body with energy_profile, energy_curve or energy_curve_id.
 /// Uses Option<Json<ArrangeRequest>> so clients sending no body don't get 400.
 async fn arrange_setlist_handler(
     State(state): State<Arc<SetlistRouteState>>,
     Path(id): Path<String>,
+    headers: HeaderMap,
     body: Option<Json<ArrangeRequest>>,
 ) -> Result<Json<SetlistResponse>, SetlistError> {
-    let energy_profile = match body {
-        Some(Json(req)) => match req.energy_profile {
-            Some(ref s) => Some(
-                s.parse::<EnergyProfile>()
-                    .map_err(SetlistError::InvalidEnergyProfile)?,
-            ),
-            None => None,
-        },
-        None => None,
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+
+    let (energy_profile, energy_curve, key_path, constraints, solver) = match body {
+        Some(Json(req)) => {
+            let profile = match req.energy_profile {
+                Some(ref s) => Some(
+                    s.parse::<EnergyProfile>()
+                        .map_err(SetlistError::InvalidEnergyProfile)?,
+                ),
+                None => None,
+            };
+            let curve = setlist::resolve_energy_curve(
+                &state.pool,
+                user_id,
+                req.energy_curve,
+                req.energy_curve_id.as_deref(),
+            )
+            .await?;
+            let solver = setlist::parse_solver_config(req.solver.as_deref(), req.time_budget_ms)?;
+            (profile, curve, req.key_path, req.constraints, solver)
+        }
+        None => (None, None, None, vec![], SolverConfig::default()),
     };
 
-    let response = setlist::arrange_setlist(&state.pool, &id, energy_profile).await?;
+    let response = setlist::arrange_setlist(
+        &state.pool,
+        &id,
+        energy_profile,
+        energy_curve,
+        key_path,
+        &constraints,
+        solver,
+    )
+    .await?;
+    Ok(Json(response))
+}
+
+async fn score_tracklist_handler(
+    State(state): State<Arc<SetlistRouteState>>,
+    headers: HeaderMap,
+    Json(req): Json<ScoreTracklistRequest>,
+) -> Result<Json<TracklistScore>, SetlistError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+    let energy_profile = match req.energy_profile {
+        Some(ref s) => Some(
+            s.parse::<EnergyProfile>()
+                .map_err(SetlistError::InvalidEnergyProfile)?,
+        ),
+        None => None,
+    };
+    let response = setlist::score_tracklist(
+        &state.pool,
+        user_id,
+        &req.tracks,
+        energy_profile,
+        req.suggest_order,
+    )
+    .await?;
     Ok(Json(response))
 }
 
@@ -171,6 +335,21 @@ async fn get_setlist_handler(
     Ok(Json(response))
 }
 
+#[derive(Deserialize)]
+pub struct LintQuery {
+    /// Refinement version to check; defaults to the latest.
+    pub version: Option<i32>,
+}
+
+async fn lint_setlist_handler(
+    State(state): State<Arc<SetlistRouteState>>,
+    Path(id): Path<String>,
+    Query(query): Query<LintQuery>,
+) -> Result<Json<LintResponse>, SetlistError> {
+    let response = setlist::lint_setlist(&state.pool, &id, query.version).await?;
+    Ok(Json(response))
+}
+
 async fn list_setlists_handler(
     State(state): State<Arc<SetlistRouteState>>,
     headers: HeaderMap,
@@ -275,6 +454,28 @@ async fn duplicate_setlist_handler(
     Ok((StatusCode::CREATED, Json(response)))
 }
 
+async fn keep_setlist_handler(
+    State(state): State<Arc<SetlistRouteState>>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    body: Option<Json<KeepRequest>>,
+) -> Result<Json<KeepVariantResponse>, SetlistError> {
+    let user_id = headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user");
+    let row = db::get_setlist(&state.pool, &id)
+        .await
+        .map_err(|e| SetlistError::Database(e.to_string()))?
+        .ok_or_else(|| SetlistError::NotFound(format!("Setlist {id} not found")))?;
+    if row.user_id != user_id {
+        return Err(SetlistError::NotFound(format!("Setlist {id} not found")));
+    }
+    let discard_others = body.and_then(|Json(b)| b.discard_others).unwrap_or(true);
+    let response = variants::keep_variant(&state.pool, &id, discard_others).await?;
+    Ok(Json(response))
+}
+
 // ---------------------------------------------------------------------------
 // Router
 // ---------------------------------------------------------------------------
@@ -283,8 +484,11 @@ pub fn setlist_router(state: Arc<SetlistRouteState>) -> Router {
     Router::new()
         .route("/setlists", get(list_setlists_handler))
         .route("/setlists/generate", post(generate_setlist_handler))
+        .route("/setlists/score", post(score_tracklist_handler))
         .route("/setlists/{id}/arrange", post(arrange_setlist_handler))
         .route("/setlists/{id}/duplicate", post(duplicate_setlist_handler))
+        .route("/setlists/{id}/keep", post(keep_setlist_handler))
+        .route("/setlists/{id}/lint", get(lint_setlist_handler))
         .route(
             "/setlists/{id}",
             get(get_setlist_handler)
@@ -472,6 +676,78 @@ mod tests {
         pool.close().await;
     }
 
+    #[tokio::test]
+    async fn test_score_tracklist_persists_nothing() {
+        let (app, pool) = setup_app(&valid_llm_json()).await;
+        let before = db::count_setlists(&pool, "default-user").await.unwrap();
+
+        let (status, json) = post_json(
+            app.clone(),
+            "/setlists/score",
+            serde_json::json!({
+                "tracks": [
+                    {"title": "One", "artist": "A", "bpm": 124, "key": "8A", "energy": 5},
+                    {"title": "Two", "artist": "B", "bpm": 124, "key": "2B", "energy": 6},
+                    {"title": "Three", "artist": "C", "bpm": 125, "key": "Am", "energy": 7}
+                ],
+                "suggest_order": true
+            }),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(json["tracks"].as_array().unwrap().len(), 3);
+        assert_eq!(json["tracks"][2]["camelot"], "8A");
+        assert!(json["harmonic_flow_score"].is_number());
+        assert_eq!(
+            json["score_breakdown"]["key_transitions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(
+            json["suggested_order"]["positions"]
+                .as_array()
+                .unwrap()
+                .len(),
+            3
+        );
+        assert_eq!(
+            db::count_setlists(&pool, "default-user").await.unwrap(),
+            before
+        );
+
+        let (status, _) = post_json(
+            app.clone(),
+            "/setlists/score",
+            serde_json::json!({"tracks": [{"title": "Solo", "artist": "A"}]}),
+        )
+        .await;
+        assert_eq!(status, 400);
+
+        for bad in [
+            serde_json::json!({"bpm": 0}),
+            serde_json::json!({"bpm": -120}),
+            serde_json::json!({"energy": 11}),
+            serde_json::json!({"energy": 0}),
+        ] {
+            let mut entry = serde_json::json!({"title": "Two", "artist": "B"});
+            entry
+                .as_object_mut()
+                .unwrap()
+                .extend(bad.as_object().unwrap().clone());
+            let (status, json) = post_json(
+                app.clone(),
+                "/setlists/score",
+                serde_json::json!({"tracks": [{"title": "One", "artist": "A"}, entry]}),
+            )
+            .await;
+            assert_eq!(status, 400, "{bad}");
+            assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+        }
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_arrange_returns_200() {
         let (_app, pool) = setup_app(&valid_llm_json()).await;
@@ -758,6 +1034,40 @@ mod tests {
         assert!(json["harmonic_flow_score"].is_number());
     }
 
+    #[tokio::test]
+    async fn test_arrange_with_constraints() {
+        let (app, pool) = setup_app(&valid_llm_json()).await;
+        let (status, gen_json) = post_json(
+            app.clone(),
+            "/setlists/generate",
+            serde_json::json!({ "prompt": "test" }),
+        )
+        .await;
+        assert_eq!(status, 201);
+        let uri = format!("/setlists/{}/arrange", gen_json["id"].as_str().unwrap());
+
+        let (status, _) = post_json(
+            app.clone(),
+            &uri,
+            serde_json::json!({ "constraints": [{"track_position": 1, "type": "last"}] }),
+        )
+        .await;
+        assert_eq!(status, 200);
+
+        let (status, json) = post_json(
+            app,
+            &uri,
+            serde_json::json!({
+                "constraints": [{"track_position": 1, "type": "pinned", "position": 3}]
+            }),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_arrange_with_empty_body_backward_compat() {
         let (_app, pool) = setup_app(&valid_llm_json()).await;
@@ -844,4 +1154,62 @@ mod tests {
         // energy_profile should not be present (None serialized as skip)
         assert!(json.get("energy_profile").is_none() || json["energy_profile"].is_null());
     }
+
+    #[tokio::test]
+    async fn test_generate_variants_and_keep_one() {
+        let (app, pool) = setup_app(&valid_llm_json()).await;
+        let (status, json) = post_json(
+            app.clone(),
+            "/setlists/generate",
+            serde_json::json!({"prompt": "warehouse", "variants": 2}),
+        )
+        .await;
+        assert_eq!(status, 201);
+        assert!(json["variant_group"].is_string());
+        let variants = json["variants"].as_array().unwrap();
+        assert_eq!(variants.len(), 2);
+        assert_eq!(variants[0]["rank"], 1);
+        assert!(variants[0]["lint"]["counts"].is_object());
+
+        let id = variants[0]["setlist"]["id"].as_str().unwrap();
+        let (status, json) = post_empty(app.clone(), &format!("/setlists/{id}/keep")).await;
+        assert_eq!(status, 200);
+        assert_eq!(json["setlist"]["id"], id);
+        assert_eq!(json["discarded"].as_array().unwrap().len(), 1);
+        assert_eq!(db::count_setlists(&pool, "default-user").await.unwrap(), 1);
+
+        let (status, json) = post_json(
+            app,
+            "/setlists/generate",
+            serde_json::json!({"prompt": "warehouse", "variants": 6}),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_local_mode() {
+        let (app, pool) = setup_app("not json at all").await;
+        let (status, json) = post_json(
+            app.clone(),
+            "/setlists/generate",
+            serde_json::json!({"prompt": "anything", "track_count": 5, "mode": "local"}),
+        )
+        .await;
+        assert_eq!(status, 201);
+        assert_eq!(json["model"], "local");
+        assert_eq!(json["tracks"][0]["track_id"], "t1");
+
+        let (status, json) = post_json(
+            app,
+            "/setlists/generate",
+            serde_json::json!({"prompt": "anything", "mode": "offline"}),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+        pool.close().await;
+    }
 }
@@ -0,0 +1,333 @@
+use axum::extract::State;
+use axum::http::HeaderMap;
+use axum::routing::{delete, get};
+use axum::{Json, Router};
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+use crate::db::models::UserSettingsRow;
+use crate::db::settings;
+use crate::error::AppError;
+use crate::services::arrangement::MAX_ARTIST_SEPARATION;
+use crate::services::camelot::{KeyNotation, ScoringProfile};
+
+// ---------------------------------------------------------------------------
+// State
+// ---------------------------------------------------------------------------
+
+pub struct SettingsRouteState {
+    pub pool: PgPool,
+}
+
+// ---------------------------------------------------------------------------
+// Request types
+// ---------------------------------------------------------------------------
+
+/// Partial update — omitted fields keep their current value.
+#[derive(Deserialize)]
+struct UpdateSettingsRequest {
+    key_lock: Option<bool>,
+    /// Replaces the whole profile; omitted profile fields take their defaults.
+    scoring_profile: Option<ScoringProfile>,
+    /// Tracks sharing an artist must be more than this many positions apart.
+    artist_separation: Option<usize>,
+    key_notation: Option<KeyNotation>,
+}
+
+// ---------------------------------------------------------------------------
+// Response types
+// ---------------------------------------------------------------------------
+
+#[derive(Serialize)]
+struct SettingsResponse {
+    user_id: String,
+    key_lock: bool,
+    scoring_profile: ScoringProfile,
+    /// `false` when `scoring_profile` is the built-in default.
+    custom_scoring_profile: bool,
+    artist_separation: usize,
+    /// `None` until the user picks one; keys are then returned as stored.
+    key_notation: Option<KeyNotation>,
+    updated_at: Option<NaiveDateTime>,
+}
+
+// ---------------------------------------------------------------------------
+// Handlers
+// ---------------------------------------------------------------------------
+
+fn extract_user_id(headers: &HeaderMap) -> &str {
+    headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user")
+}
+
+async fn load_settings(pool: &PgPool, user_id: &str) -> Result<SettingsResponse, AppError> {
+    let row = settings::get_user_settings(pool, user_id)
+        .await
+        .map_err(AppError::Database)?
+        .unwrap_or_else(|| UserSettingsRow::defaults(user_id));
+    let scoring_profile = settings::get_scoring_profile(pool, user_id)
+        .await
+        .map_err(AppError::Database)?;
+    let key_notation = settings::get_key_notation(pool, user_id)
+        .await
+        .map_err(AppError::Database)?;
+    Ok(SettingsResponse {
+        user_id: row.user_id,
+        key_lock: row.key_lock,
+        scoring_profile,
+        custom_scoring_profile: row.scoring_profile.is_some(),
+        artist_separation: row.artist_separation.max(0) as usize,
+        key_notation,
+        updated_at: row.updated_at,
+    })
+}
+
+async fn get_settings_handler(
+    State(state): State<Arc<SettingsRouteState>>,
+    headers: HeaderMap,
+) -> Result<Json<SettingsResponse>, AppError> {
+    let user_id = extract_user_id(&headers);
+    Ok(Json(load_settings(&state.pool, user_id).await?))
+}
+
+async fn update_settings_handler(
+    State(state): State<Arc<SettingsRouteState>>,
+    headers: HeaderMap,
+    Json(req): Json<UpdateSettingsRequest>,
+) -> Result<Json<SettingsResponse>, AppError> {
+    let user_id = extract_user_id(&headers);
+    if let Some(profile) = &req.scoring_profile {
+        profile.validate().map_err(AppError::BadRequest)?;
+    }
+    if req.artist_separation > Some(MAX_ARTIST_SEPARATION) {
+        return Err(AppError::BadRequest(format!(
+            "artist_separation must be between 0 and {MAX_ARTIST_SEPARATION}"
+        )));
+    }
+    if let Some(key_lock) = req.key_lock {
+        settings::set_key_lock(&state.pool, user_id, key_lock)
+            .await
+            .map_err(AppError::Database)?;
+    }
+    if let Some(profile) = &req.scoring_profile {
+        settings::set_scoring_profile(&state.pool, user_id, Some(profile))
+            .await
+            .map_err(AppError::Database)?;
+    }
+    if let Some(gap) = req.artist_separation {
+        settings::set_artist_separation(&state.pool, user_id, gap)
+            .await
+            .map_err(AppError::Database)?;
+    }
+    if let Some(notation) = req.key_notation {
+        settings::set_key_notation(&state.pool, user_id, notation)
+            .await
+            .map_err(AppError::Database)?;
+    }
+    Ok(Json(load_settings(&state.pool, user_id).await?))
+}
+
+/// Go back to the built-in scoring weights.
+async fn reset_scoring_profile_handler(
+    State(state): State<Arc<SettingsRouteState>>,
+    headers: HeaderMap,
+) -> Result<Json<SettingsResponse>, AppError> {
+    let user_id = extract_user_id(&headers);
+    settings::set_scoring_profile(&state.pool, user_id, None)
+        .await
+        .map_err(AppError::Database)?;
+    Ok(Json(load_settings(&state.pool, user_id).await?))
+}
+
+// ---------------------------------------------------------------------------
+// Router
+// ---------------------------------------------------------------------------
+
+pub fn settings_router(state: Arc<SettingsRouteState>) -> Router {
+    Router::new()
+        .route(
+            "/settings",
+            get(get_settings_handler).put(update_settings_handler),
+        )
+        .route(
+            "/settings/scoring-profile",
+            delete(reset_scoring_profile_handler),
+        )
+        .with_state(state)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::body::Body;
+    use axum::http::Request;
+    use tower::ServiceExt;
+
+    async fn setup() -> (Router, PgPool) {
+        let pool = crate::db::create_test_pool().await;
+        let state = Arc::new(SettingsRouteState { pool: pool.clone() });
+        (settings_router(state), pool)
+    }
+
+    async fn send(
+        app: Router,
+        method: &str,
+        body: Option<serde_json::Value>,
+    ) -> (u16, serde_json::Value) {
+        send_to(app, method, "/settings", body).await
+    }
+
+    async fn send_to(
+        app: Router,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> (u16, serde_json::Value) {
+        let builder = Request::builder()
+            .method(method)
+            .uri(uri)
+            .header("content-type", "application/json")
+            .header("X-User-Id", "user-1");
+        let body = match body {
+            Some(b) => Body::from(serde_json::to_vec(&b).unwrap()),
+            None => Body::empty(),
+        };
+        let resp = app.oneshot(builder.body(body).unwrap()).await.unwrap();
+        let status = resp.status().as_u16();
+        let bytes = axum::body::to_bytes(resp.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        (status, json)
+    }
+
+    #[tokio::test]
+    async fn test_get_settings_defaults() {
+        let (app, pool) = setup().await;
+        let (status, json) = send(app, "GET", None).await;
+        assert_eq!(status, 200);
+        assert_eq!(json["user_id"], "user-1");
+        assert_eq!(json["key_lock"], true);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_key_lock() {
+        let (app, pool) = setup().await;
+        let (status, json) = send(
+            app.clone(),
+            "PUT",
+            Some(serde_json::json!({"key_lock": false})),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(json["key_lock"], false);
+
+        // Empty update leaves the stored value alone
+        let (_, json) = send(app, "PUT", Some(serde_json::json!({}))).await;
+        assert_eq!(json["key_lock"], false);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_scoring_profile_update_and_reset() {
+        let (app, pool) = setup().await;
+        let (_, json) = send(app.clone(), "GET", None).await;
+        assert_eq!(json["custom_scoring_profile"], false);
+        assert_eq!(json["scoring_profile"]["key_weight"], 0.5);
+
+        let (status, json) = send(
+            app.clone(),
+            "PUT",
+            Some(serde_json::json!({
+                "scoring_profile": {"key_weight": 0.2, "bpm_weight": 0.8, "bpm_bands": {"perfect": 1.0}}
+            })),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(json["custom_scoring_profile"], true);
+        assert_eq!(json["scoring_profile"]["bpm_weight"], 0.8);
+        assert_eq!(json["scoring_profile"]["bpm_bands"]["perfect"], 1.0);
+        assert_eq!(json["scoring_profile"]["bpm_bands"]["close"], 4.0);
+        assert_eq!(json["key_lock"], true);
+
+        let (status, json) = send_to(app, "DELETE", "/settings/scoring-profile", None).await;
+        assert_eq!(status, 200);
+        assert_eq!(json["custom_scoring_profile"], false);
+        assert_eq!(json["scoring_profile"]["key_weight"], 0.5);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_invalid_scoring_profile_rejected() {
+        let (app, pool) = setup().await;
+        let (status, json) = send(
+            app.clone(),
+            "PUT",
+            Some(serde_json::json!({"scoring_profile": {"bpm_bands": {"perfect": 5.0, "close": 4.0}}})),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+
+        let (status, _) = send(
+            app,
+            "PUT",
+            Some(serde_json::json!({"scoring_profile": {"key_weight": 0, "bpm_weight": 0, "energy_weight": 0}})),
+        )
+        .await;
+        assert_eq!(status, 400);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_artist_separation() {
+        let (app, pool) = setup().await;
+        let (_, json) = send(app.clone(), "GET", None).await;
+        assert_eq!(json["artist_separation"], 1);
+
+        let (status, json) = send(
+            app.clone(),
+            "PUT",
+            Some(serde_json::json!({"artist_separation": 3})),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(json["artist_separation"], 3);
+
+        let (status, json) = send(
+            app,
+            "PUT",
+            Some(serde_json::json!({"artist_separation": 11})),
+        )
+        .await;
+        assert_eq!(status, 400);
+        assert_eq!(json["error"]["code"], "INVALID_REQUEST");
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_update_key_notation() {
+        let (app, pool) = setup().await;
+        let (_, json) = send(app.clone(), "GET", None).await;
+        assert!(json["key_notation"].is_null());
+
+        let (status, json) = send(
+            app,
+            "PUT",
+            Some(serde_json::json!({"key_notation": "open-key"})),
+        )
+        .await;
+        assert_eq!(status, 200);
+        assert_eq!(json["key_notation"], "open-key");
+        pool.close().await;
+    }
+}
@@ -1,4 +1,8 @@
-use axum::extract::{Query, State};
+use std::collections::HashSet;
+
+use axum::extract::{Path, Query, State};
+use axum::http::{header, HeaderMap};
+use axum::response::{IntoResponse, Response};
 use axum::routing::{get, post};
 use axum::{Json, Router};
 use serde::{Deserialize, Serialize};
@@ -6,6 +10,11 @@ use sqlx::PgPool;
 
 use crate::db::models::TrackRow;
 use crate::error::AppError;
+use crate::services::bridge::{self, Bridge, BridgeEnd, BridgeOptions, MAX_BRIDGE_TRACKS};
+use crate::services::camelot::display_key;
+use crate::services::play_next::{
+    self, EnergyDirection, NextCandidate, PlayNextOptions, MAX_PLAY_NEXT_LIMIT,
+};
 
 // ---------------------------------------------------------------------------
 // Response types (matching openapi.yaml TrackListResponse)
@@ -102,8 +111,16 @@ const VALID_ORDERS: &[&str] = &["asc", "desc"];
 // Handler
 // ---------------------------------------------------------------------------
 
+fn extract_user_id(headers: &HeaderMap) -> &str {
+    headers
+        .get("X-User-Id")
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("default-user")
+}
+
 async fn list_tracks(
     State(pool): State<PgPool>,
+    headers: HeaderMap,
     Query(params): Query<ListTracksParams>,
 ) -> Result<Json<TrackListResponse>, AppError> {
     // Validate params
@@ -144,7 +161,15 @@ async fn list_tracks(
         (total + params.per_page as i64 - 1) / params.per_page as i64
     };
 
-    let data: Vec<TrackResponse> = rows.into_iter().map(TrackResponse::from).collect();
+    let mut data: Vec<TrackResponse> = rows.into_iter().map(TrackResponse::from).collect();
+    let key_notation = crate::db::settings::get_key_notation(&pool, extract_user_id(&headers))
+        .await
+        .map_err(AppError::Database)?;
+    if let Some(notation) = key_notation {
+        for track in &mut data {
+            track.key = track.key.as_deref().map(|k| display_key(k, notation));
+        }
+    }
 
     Ok(Json(TrackListResponse {
         data,
@@ -155,6 +180,167 @@ async fn list_tracks(
     }))
 }
 
+// ---------------------------------------------------------------------------
+// What to play next
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct PlayNextParams {
+    /// up, hold or down; defaults to hold.
+    pub direction: Option<String>,
+    /// Comma-separated track ids to leave out, e.g. tracks already played.
+    pub exclude: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PlayNextResponse {
+    pub track_id: String,
+    pub direction: EnergyDirection,
+    pub candidates: Vec<NextCandidate>,
+}
+
+/// Rank the catalog by how well each track mixes out of `id`.
+async fn play_next(
+    State(pool): State<PgPool>,
+    Path(id): Path<String>,
+    headers: HeaderMap,
+    Query(params): Query<PlayNextParams>,
+) -> Result<Json<PlayNextResponse>, AppError> {
+    let direction = params
+        .direction
+        .as_deref()
+        .map(str::parse::<EnergyDirection>)
+        .transpose()
+        .map_err(AppError::BadRequest)?
+        .unwrap_or_default();
+    let limit = params.limit.unwrap_or(play_next::DEFAULT_PLAY_NEXT_LIMIT);
+    if !(1..=MAX_PLAY_NEXT_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!(
+            "limit must be between 1 and {MAX_PLAY_NEXT_LIMIT}"
+        )));
+    }
+    let exclude = parse_id_list(params.exclude.as_deref());
+
+    let catalog = crate::db::setlists::load_catalog_tracks(&pool)
+        .await
+        .map_err(AppError::Database)?;
+    let current = catalog
+        .iter()
+        .find(|t| t.id == id)
+        .ok_or_else(|| AppError::NotFound(format!("Track {id} not found")))?;
+
+    let user_id = extract_user_id(&headers);
+    let options = PlayNextOptions {
+        direction,
+        exclude,
+        limit,
+        key_lock: crate::db::settings::get_key_lock(&pool, user_id)
+            .await
+            .map_err(AppError::Database)?,
+        scoring: crate::db::settings::get_scoring_profile(&pool, user_id)
+            .await
+            .map_err(AppError::Database)?,
+    };
+
+    Ok(Json(PlayNextResponse {
+        track_id: id.clone(),
+        direction,
+        candidates: play_next::rank_next_tracks(current, &catalog, &options),
+    }))
+}
+
+/// Split a comma-separated list of track ids, ignoring blanks.
+fn parse_id_list(raw: Option<&str>) -> HashSet<String> {
+    raw.unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+// ---------------------------------------------------------------------------
+// Harmonic bridge
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Deserialize)]
+pub struct BridgeParams {
+    /// Longest chain to search for, 1-3; defaults to 3.
+    pub max_tracks: Option<usize>,
+    /// Weakest hop allowed, 0-1.
+    pub min_score: Option<f64>,
+    /// Comma-separated track ids never to use as a bridge.
+    pub exclude: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BridgeResponse {
+    pub from_track_id: String,
+    pub to_track_id: String,
+    pub bridge: Bridge,
+}
+
+/// Find the shortest chain of catalog tracks that mixes from `id` into `to_id`.
+async fn find_bridge(
+    State(pool): State<PgPool>,
+    Path((id, to_id)): Path<(String, String)>,
+    headers: HeaderMap,
+    Query(params): Query<BridgeParams>,
+) -> Result<Json<BridgeResponse>, AppError> {
+    let max_tracks = params.max_tracks.unwrap_or(MAX_BRIDGE_TRACKS);
+    if !(1..=MAX_BRIDGE_TRACKS).contains(&max_tracks) {
+        return Err(AppError::BadRequest(format!(
+            "max_tracks must be between 1 and {MAX_BRIDGE_TRACKS}"
+        )));
+    }
+    let min_score = params.min_score.unwrap_or(bridge::DEFAULT_MIN_BRIDGE_SCORE);
+    if !(0.0..=1.0).contains(&min_score) {
+        return Err(AppError::BadRequest(
+            "min_score must be between 0 and 1".to_string(),
+        ));
+    }
+    let mut exclude = parse_id_list(params.exclude.as_deref());
+    exclude.insert(id.clone());
+    exclude.insert(to_id.clone());
+
+    let catalog = crate::db::setlists::load_catalog_tracks(&pool)
+        .await
+        .map_err(AppError::Database)?;
+    let find = |track_id: &str| {
+        catalog
+            .iter()
+            .find(|t| t.id == track_id)
+            .map(BridgeEnd::from)
+            .ok_or_else(|| AppError::NotFound(format!("Track {track_id} not found")))
+    };
+    let (from, to) = (find(&id)?, find(&to_id)?);
+
+    let user_id = extract_user_id(&headers);
+    let options = BridgeOptions {
+        max_tracks,
+        min_score,
+        exclude,
+        key_lock: crate::db::settings::get_key_lock(&pool, user_id)
+            .await
+            .map_err(AppError::Database)?,
+        scoring: crate::db::settings::get_scoring_profile(&pool, user_id)
+            .await
+            .map_err(AppError::Database)?,
+    };
+
+    let bridge = bridge::find_bridge(from, to, &catalog, &options).ok_or_else(|| {
+        AppError::NotFound(format!(
+            "No chain of up to {max_tracks} catalog tracks connects {id} to {to_id}"
+        ))
+    })?;
+    Ok(Json(BridgeResponse {
+        from_track_id: id,
+        to_track_id: to_id,
+        bridge,
+    }))
+}
+
 // ---------------------------------------------------------------------------
 // Retry errored tracks handler
 // ---------------------------------------------------------------------------
@@ -173,6 +359,17 @@ async fn retry_errored_tracks(
     Ok(Json(RetryErroredResponse { reset }))
 }
 
+/// GET /tracks/{id}/artwork — cover art embedded in a scanned audio file.
+async fn track_artwork(
+    State(pool): State<PgPool>,
+    Path(id): Path<String>,
+) -> Result<Response, AppError> {
+    let (media_type, data) = crate::db::tracks::get_track_artwork(&pool, &id)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("No artwork for track {id}")))?;
+    Ok(([(header::CONTENT_TYPE, media_type)], data).into_response())
+}
+
 // ---------------------------------------------------------------------------
 // Router
 // ---------------------------------------------------------------------------
@@ -181,6 +378,9 @@ pub fn tracks_router(pool: PgPool) -> Router {
     Router::new()
         .route("/tracks", get(list_tracks))
         .route("/tracks/retry-errored", post(retry_errored_tracks))
+        .route("/tracks/{id}/next", get(play_next))
+        .route("/tracks/{id}/artwork", get(track_artwork))
+        .route("/tracks/{id}/bridge/{to_id}", get(find_bridge))
         .with_state(pool)
 }
 
@@ -428,4 +628,151 @@ mod tests {
         assert!(needs);
         assert!(error.is_none());
     }
+
+    async fn get_status_json(app: Router, uri: &str) -> (StatusCode, serde_json::Value) {
+        let response = app
+            .oneshot(Request::builder().uri(uri).body(Body::empty()).unwrap())
+            .await
+            .unwrap();
+        let status = response.status();
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    async fn setup_play_next() -> (Router, PgPool) {
+        let pool = crate::db::create_test_pool().await;
+        for (id, key, bpm, energy) in [
+            ("pn-now", "8A", 124.0, 5.0),
+            ("pn-same", "8A", 124.0, 5.0),
+            ("pn-up", "9A", 125.0, 7.0),
+            ("pn-clash", "2B", 140.0, 5.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO tracks (id, title, source, bpm, camelot_key, energy) VALUES ($1, $1, 'spotify', $2, $3, $4)",
+            )
+            .bind(id)
+            .bind(bpm)
+            .bind(key)
+            .bind(energy)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        (tracks_router(pool.clone()), pool)
+    }
+
+    #[tokio::test]
+    async fn test_play_next_ranks_catalog() {
+        let (app, pool) = setup_play_next().await;
+        let (status, json) = get_status_json(app.clone(), "/tracks/pn-now/next").await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(json["direction"], "hold");
+        let candidates = json["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 3);
+        assert_eq!(candidates[0]["track_id"], "pn-same");
+        assert_eq!(candidates[2]["track_id"], "pn-clash");
+        assert_eq!(candidates[0]["breakdown"]["key_transition"], "same-key");
+
+        let (_, json) = get_status_json(
+            app,
+            "/tracks/pn-now/next?direction=up&exclude=pn-same&limit=1",
+        )
+        .await;
+        let candidates = json["candidates"].as_array().unwrap();
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0]["track_id"], "pn-up");
+        assert_eq!(candidates[0]["breakdown"]["energy_delta"], 2.0);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_play_next_errors() {
+        let (app, pool) = setup_play_next().await;
+        let (status, json) = get_status_json(app.clone(), "/tracks/missing/next").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(json["error"]["code"], "NOT_FOUND");
+
+        let (status, _) =
+            get_status_json(app.clone(), "/tracks/pn-now/next?direction=sideways").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        let (status, _) = get_status_json(app, "/tracks/pn-now/next?limit=0").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_bridge_between_clashing_tracks() {
+        let (app, pool) = setup_play_next().await;
+        for (id, key, bpm) in [("br-from", "8A", 124.0), ("br-to", "10A", 124.0)] {
+            sqlx::query(
+                "INSERT INTO tracks (id, title, source, bpm, camelot_key) VALUES ($1, $1, 'spotify', $2, $3)",
+            )
+            .bind(id)
+            .bind(bpm)
+            .bind(key)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let (status, json) = get_status_json(app.clone(), "/tracks/br-from/bridge/br-to").await;
+        assert_eq!(status, StatusCode::OK);
+        let tracks = json["bridge"]["tracks"].as_array().unwrap();
+        assert_eq!(tracks.len(), 1);
+        assert_eq!(tracks[0]["track_id"], "pn-up");
+        assert_eq!(
+            json["bridge"]["transition_scores"]
+                .as_array()
+                .unwrap()
+                .len(),
+            2
+        );
+
+        let (status, json) =
+            get_status_json(app.clone(), "/tracks/br-from/bridge/br-to?exclude=pn-up").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        assert_eq!(json["error"]["code"], "NOT_FOUND");
+
+        let (status, _) = get_status_json(app.clone(), "/tracks/br-from/bridge/missing").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        let (status, _) = get_status_json(app, "/tracks/br-from/bridge/br-to?max_tracks=4").await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_track_artwork() {
+        let pool = crate::db::create_test_pool().await;
+        sqlx::query("INSERT INTO tracks (id, title, source) VALUES ('art1', 'Yeke Yeke', 'local')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        crate::db::tracks::upsert_track_artwork(&pool, "art1", "image/png", b"png", "/art")
+            .await
+            .unwrap();
+        let app = tracks_router(pool.clone());
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/tracks/art1/artwork")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["content-type"], "image/png");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX)
+            .await
+            .unwrap();
+        assert_eq!(&body[..], b"png");
+
+        let (status, _) = get_status_json(app, "/tracks/missing/artwork").await;
+        assert_eq!(status, StatusCode::NOT_FOUND);
+        pool.close().await;
+    }
 }
@@ -1,14 +1,104 @@
-use crate::services::camelot::{self, CamelotKey, EnergyProfile, ScoreBreakdown};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::time::Instant;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::camelot::{
+    self, CamelotKey, EnergyCurve, EnergyProfile, KeyPath, ScoreBreakdown, ScoringProfile,
+};
+use crate::services::solver::{
+    self, Problem, Separation, Solver, SolverConfig, HELD_KARP_MAX_TRACKS,
+};
 
 // ---------------------------------------------------------------------------
 // Types
 // ---------------------------------------------------------------------------
 
+/// Artist separation for users who have not set their own: no back-to-back
+/// tracks by the same artist.
+pub const DEFAULT_ARTIST_SEPARATION: usize = 1;
+
+/// Largest separation a user may ask for; wider gaps are rarely satisfiable
+/// in a typical 15-25 track set.
+pub const MAX_ARTIST_SEPARATION: usize = 10;
+
 pub struct ArrangementTrack {
     pub index: usize,
     pub camelot: Option<CamelotKey>,
     pub bpm: Option<f64>,
     pub energy: Option<i32>,
+    /// Artist credit as shown on the track, e.g. "Sting feat. Cheb Mami".
+    pub artist: Option<String>,
+    /// Where this track may land in the final order; `None` means anywhere.
+    pub constraint: Option<PositionConstraint>,
+}
+
+/// Placement rule for a single track. Positions are 1-based, like setlist
+/// positions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum PositionConstraint {
+    /// Exactly at `position` (e.g., the track that must land at midnight).
+    Pinned { position: usize },
+    /// The opener.
+    First,
+    /// The closer.
+    Last,
+    /// Anywhere from `min` to `max` inclusive. A `max` past the end of the
+    /// set is clamped.
+    Window { min: usize, max: usize },
+}
+
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+pub enum ArrangementError {
+    /// A single constraint makes no sense for this set (e.g., pinned to 12 of 10).
+    #[error("invalid constraint on track {index}: {reason}")]
+    InvalidConstraint { index: usize, reason: String },
+
+    /// Each constraint is valid alone but they cannot all hold at once.
+    #[error("position constraints cannot all be satisfied: {0}")]
+    Infeasible(String),
+
+    /// The requested solver cannot handle a set this size.
+    #[error("held-karp handles at most {max} tracks, got {tracks}")]
+    TooManyTracks { tracks: usize, max: usize },
+}
+
+/// Knobs for [`arrange_tracks_with_options`].
+#[derive(Debug, Clone)]
+pub struct ArrangementOptions {
+    pub energy_profile: Option<EnergyProfile>,
+    /// Custom energy curve; takes precedence over `energy_profile`.
+    pub energy_curve: Option<EnergyCurve>,
+    /// Target harmonic journey. How well each track's key fits it at its
+    /// slot is part of the objective, weighted like key is in a transition
+    /// score, and reported in `ScoreBreakdown::key_journey`.
+    pub key_path: Option<KeyPath>,
+    /// When `false`, harmony is scored on the key each track sounds in after
+    /// the pitch fader beatmatches it (see [`camelot::effective_key`]).
+    pub key_lock: bool,
+    /// Weights and BPM tolerance bands for every transition score.
+    pub scoring: ScoringProfile,
+    /// Which solver refines the greedy order, and for how long.
+    pub solver: SolverConfig,
+    /// Tracks sharing an artist must be more than this many positions apart;
+    /// 1 forbids back-to-back, 0 turns the rule off.
+    pub artist_separation: usize,
+}
+
+impl Default for ArrangementOptions {
+    fn default() -> Self {
+        Self {
+            energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            key_lock: true,
+            scoring: ScoringProfile::default(),
+            solver: SolverConfig::default(),
+            artist_separation: DEFAULT_ARTIST_SEPARATION,
+        }
+    }
 }
 
 pub struct ArrangementResult {
@@ -16,6 +106,16 @@ pub struct ArrangementResult {
     pub transition_scores: Vec<f64>,
     pub harmonic_flow_score: f64,
     pub score_breakdown: ScoreBreakdown,
+    /// The solver that produced the order (`Auto` is resolved).
+    pub solver: Solver,
+    /// Harmonic flow of the greedy + 2-opt order the solver started from.
+    pub greedy_flow_score: f64,
+    /// `harmonic_flow_score - greedy_flow_score`. Can be negative: solvers
+    /// also weigh energy and key placement and artist separation, so they
+    /// may trade some flow for a better order overall.
+    pub improvement_over_greedy: f64,
+    /// The solver ran out of time budget and returned its best so far.
+    pub timed_out: bool,
 }
 
 // ---------------------------------------------------------------------------
@@ -26,12 +126,48 @@ pub struct ArrangementResult {
 ///
 /// If `energy_profile` is `Some`, uses the profiled energy arc scoring.
 /// If `None`, uses the default energy arc scoring for backward compatibility.
+/// Fails only when the tracks' position constraints cannot be met.
 pub fn arrange_tracks(
     tracks: &[ArrangementTrack],
     energy_profile: Option<EnergyProfile>,
-) -> ArrangementResult {
+) -> Result<ArrangementResult, ArrangementError> {
+    arrange_tracks_with_options(
+        tracks,
+        &ArrangementOptions {
+            energy_profile,
+            ..Default::default()
+        },
+    )
+}
+
+/// [`arrange_tracks`] with the full set of [`ArrangementOptions`].
+///
+/// Tracks carrying a [`PositionConstraint`] are only ever placed where their
+/// constraint allows, in both the greedy and 2-opt phases. The greedy order is
+/// then refined by `options.solver` (see [`crate::services::solver`]).
+///
+/// Artist separation is a soft rule: every phase avoids violating it, but
+/// when the set cannot be spread out (e.g., half the tracks are by one
+/// artist) the order keeps as few violations as it can find. Returns
+/// an error if the constraints are malformed or cannot all be met together,
+/// or if Held-Karp is requested for more than [`HELD_KARP_MAX_TRACKS`] tracks.
+pub fn arrange_tracks_with_options(
+    tracks: &[ArrangementTrack],
+    options: &ArrangementOptions,
+) -> Result<ArrangementResult, ArrangementError> {
+    let ranges = allowed_ranges(tracks)?;
+    let all: Vec<usize> = (0..tracks.len()).collect();
+    check_feasible(&ranges, &all, 0)?;
+
+    let solver_kind = options.solver.kind.resolve(tracks.len());
+    if solver_kind == Solver::HeldKarp && tracks.len() > HELD_KARP_MAX_TRACKS {
+        return Err(ArrangementError::TooManyTracks {
+            tracks: tracks.len(),
+            max: HELD_KARP_MAX_TRACKS,
+        });
+    }
     if tracks.is_empty() {
-        return ArrangementResult {
+        return Ok(ArrangementResult {
             ordered_indices: vec![],
             transition_scores: vec![],
             harmonic_flow_score: 0.0,
@@ -39,12 +175,20 @@ pub fn arrange_tracks(
                 key_compatibility: 0.0,
                 bpm_continuity: 0.0,
                 energy_arc: 0.0,
+                key_journey: None,
+                key_transitions: vec![],
+                tempo_relations: vec![],
+                pitch_adjustments: vec![],
             },
-        };
+            solver: options.solver.kind.resolve(tracks.len()),
+            greedy_flow_score: 0.0,
+            improvement_over_greedy: 0.0,
+            timed_out: false,
+        });
     }
 
     if tracks.len() == 1 {
-        return ArrangementResult {
+        return Ok(ArrangementResult {
             ordered_indices: vec![tracks[0].index],
             transition_scores: vec![],
             harmonic_flow_score: 100.0,
@@ -52,50 +196,70 @@ pub fn arrange_tracks(
                 key_compatibility: 100.0,
                 bpm_continuity: 100.0,
                 energy_arc: 100.0,
+                key_journey: options.key_path.as_ref().map(|_| 100.0),
+                key_transitions: vec![],
+                tempo_relations: vec![],
+                pitch_adjustments: vec![],
             },
-        };
+            solver: options.solver.kind.resolve(tracks.len()),
+            greedy_flow_score: 100.0,
+            improvement_over_greedy: 0.0,
+            timed_out: false,
+        });
     }
 
-    // Step 1: Greedy start — pick the track with lowest energy as opener
+    let problem = build_problem(tracks, &ranges, options);
+    let gap = options.artist_separation;
+    let clashes_with_recent = |order: &[usize], i: usize| -> bool {
+        problem.separation.as_ref().is_some_and(|sep| {
+            order
+                .iter()
+                .rev()
+                .take(gap)
+                .any(|&prev| sep.conflicts[prev][i])
+        })
+    };
+
+    // Step 1 + 2: Greedy nearest-neighbor, one position at a time. The opener
+    // is the lowest-energy track; every later slot takes the best transition
+    // from the previous track. Both add the track's placement score at the
+    // slot, so the energy arc and key path steer the pick. Only tracks allowed at the slot are considered,
+    // and a choice is skipped if it would leave the remaining constraints
+    // unsatisfiable. Candidates that would repeat a recent artist are tried
+    // last.
     let mut order: Vec<usize> = Vec::with_capacity(tracks.len());
     let mut visited = vec![false; tracks.len()];
 
-    let start_idx = tracks
-        .iter()
-        .enumerate()
-        .min_by_key(|(_, t)| t.energy.unwrap_or(5))
-        .map(|(i, _)| i)
-        .unwrap_or(0);
-
-    order.push(start_idx);
-    visited[start_idx] = true;
-
-    // Step 2: Greedy nearest-neighbor by transition score
-    for _ in 1..tracks.len() {
-        let last = order[order.len() - 1];
-        let mut best_idx = None;
-        let mut best_score = -1.0;
-
-        for (i, track) in tracks.iter().enumerate() {
-            if visited[i] {
-                continue;
-            }
-            let score = camelot::transition_score(
-                tracks[last].camelot.as_ref(),
-                track.camelot.as_ref(),
-                tracks[last].bpm,
-                track.bpm,
-            );
-            if score > best_score {
-                best_score = score;
-                best_idx = Some(i);
-            }
-        }
+    for pos in 0..tracks.len() {
+        let mut candidates: Vec<(usize, f64)> = (0..tracks.len())
+            .filter(|&i| !visited[i] && ranges[i].0 <= pos && pos <= ranges[i].1)
+            .map(|i| {
+                let preference = match order.last() {
+                    Some(&last) => problem.scores[last][i],
+                    None => -(tracks[i].energy.unwrap_or(5) as f64),
+                };
+                (i, preference + problem.place(i, pos))
+            })
+            .collect();
+        // Stable sort: ties keep input order, as the unconstrained greedy did
+        candidates.sort_by(|a, b| b.1.total_cmp(&a.1));
+        candidates.sort_by_key(|&(i, _)| clashes_with_recent(&order, i));
 
-        if let Some(idx) = best_idx {
-            order.push(idx);
-            visited[idx] = true;
-        }
+        let remaining = |pick: usize| -> Vec<usize> {
+            (0..tracks.len())
+                .filter(|&i| !visited[i] && i != pick)
+                .collect()
+        };
+        let pick = candidates
+            .iter()
+            .map(|&(i, _)| i)
+            .find(|&i| check_feasible(&ranges, &remaining(i), pos + 1).is_ok())
+            .ok_or_else(|| {
+                ArrangementError::Infeasible(format!("no track can fill position {}", pos + 1))
+            })?;
+
+        order.push(pick);
+        visited[pick] = true;
     }
 
     // Step 3: 2-opt improvement
@@ -104,11 +268,19 @@ pub fn arrange_tracks(
         let mut improved = false;
         for i in 1..order.len().saturating_sub(1) {
             for j in (i + 1)..order.len() {
-                let current_cost = segment_cost(tracks, &order, i, j);
+                let current_cost = segment_cost(&problem, &order, i, j);
+                let current_violations = problem.segment_violations(&order, i, j);
                 // Reverse segment [i..=j]
                 order[i..=j].reverse();
-                let new_cost = segment_cost(tracks, &order, i, j);
-                if new_cost > current_cost {
+                if !within_ranges(&ranges, &order, i, j) {
+                    order[i..=j].reverse();
+                    continue;
+                }
+                let new_cost = segment_cost(&problem, &order, i, j);
+                let new_violations = problem.segment_violations(&order, i, j);
+                if new_violations < current_violations
+                    || (new_violations == current_violations && new_cost > current_cost)
+                {
                     improved = true; // keep the reversal
                 } else {
                     order[i..=j].reverse(); // revert
@@ -120,41 +292,103 @@ pub fn arrange_tracks(
         }
     }
 
-    // Step 4: Compute scores
-    let total = tracks.len();
+    // Step 4: Hand the greedy order to the selected solver. Every solver
+    // maximises the same sum of transition and placement scores, less a
+    // penalty per separation violation, and never returns an order worse
+    // than the one it was given. The time budget starts now, so the greedy
+    // pass does not eat into it.
+    let edges = (tracks.len() - 1) as f64;
+    let greedy_flow_score = problem.flow(&order) / edges * 100.0;
+    let deadline = Instant::now() + options.solver.time_budget;
+    let outcome = solver::solver_for(solver_kind).solve(&problem, order, deadline);
+    let order = outcome.order;
+
+    // Step 5: Compute scores
+    let scored = score_order(tracks, &order, options);
+
+    let ordered_indices: Vec<usize> = order.iter().map(|&i| tracks[i].index).collect();
+
+    Ok(ArrangementResult {
+        ordered_indices,
+        improvement_over_greedy: scored.harmonic_flow_score - greedy_flow_score,
+        transition_scores: scored.transition_scores,
+        harmonic_flow_score: scored.harmonic_flow_score,
+        score_breakdown: scored.score_breakdown,
+        solver: solver_kind,
+        greedy_flow_score,
+        timed_out: outcome.timed_out,
+    })
+}
+
+/// Transition scores and breakdown of one fixed order.
+pub struct ScoredOrder {
+    pub transition_scores: Vec<f64>,
+    pub harmonic_flow_score: f64,
+    pub score_breakdown: ScoreBreakdown,
+}
+
+/// Score `tracks` played in `order` (indices into `tracks`) under `options`,
+/// exactly as an arrangement result is scored.
+pub fn score_order(
+    tracks: &[ArrangementTrack],
+    order: &[usize],
+    options: &ArrangementOptions,
+) -> ScoredOrder {
+    let key_lock = options.key_lock;
+    let total = order.len();
     let mut t_scores = Vec::with_capacity(total.saturating_sub(1));
     let mut key_scores = Vec::new();
     let mut bpm_scores = Vec::new();
+    let mut key_transitions = Vec::new();
+    let mut tempo_relations = Vec::new();
+    let mut pitch_adjustments = Vec::new();
 
     for i in 0..total.saturating_sub(1) {
         let a = &tracks[order[i]];
         let b = &tracks[order[i + 1]];
-        let ts = camelot::transition_score(a.camelot.as_ref(), b.camelot.as_ref(), a.bpm, b.bpm);
-        t_scores.push(ts);
+        t_scores.push(pair_score(a, b, options));
 
         // Individual component scores
         match (a.camelot.as_ref(), b.camelot.as_ref()) {
-            (Some(ka), Some(kb)) => key_scores.push(camelot::camelot_score(ka, kb)),
-            _ => key_scores.push(0.5),
+            (Some(ka), Some(kb)) => {
+                let kb = camelot::effective_key(kb, a.bpm, b.bpm, key_lock);
+                let kind = camelot::classify_transition(ka, &kb);
+                key_scores.push(kind.score());
+                key_transitions.push(Some(kind));
+            }
+            _ => {
+                key_scores.push(0.5);
+                key_transitions.push(None);
+            }
         }
         match (a.bpm, b.bpm) {
-            (Some(ba), Some(bb)) => bpm_scores.push(camelot::bpm_score(ba, bb)),
-            _ => bpm_scores.push(0.5),
+            (Some(ba), Some(bb)) => {
+                let m = camelot::bpm_match_with_bands(ba, bb, &options.scoring.bpm_bands);
+                bpm_scores.push(m.score);
+                tempo_relations.push(Some(m.relation));
+                pitch_adjustments.push(Some(camelot::pitch_percent(ba, bb)));
+            }
+            _ => {
+                bpm_scores.push(0.5);
+                tempo_relations.push(None);
+                pitch_adjustments.push(None);
+            }
         }
     }
 
-    let mut energy_scores = Vec::new();
-    for (pos, &idx) in order.iter().enumerate() {
-        if let Some(e) = tracks[idx].energy {
-            let score = match energy_profile {
-                Some(profile) => camelot::energy_arc_score_with_profile(e, pos, total, profile),
-                None => camelot::energy_arc_score(e, pos, total),
-            };
-            energy_scores.push(score);
-        } else {
-            energy_scores.push(0.5);
-        }
-    }
+    let energy_scores: Vec<f64> = order
+        .iter()
+        .enumerate()
+        .map(|(pos, &idx)| energy_fit(&tracks[idx], pos, total, options))
+        .collect();
+
+    let key_journey_scores: Option<Vec<f64>> = options.key_path.as_ref().map(|path| {
+        order
+            .iter()
+            .enumerate()
+            .map(|(pos, &idx)| key_fit(&tracks[idx], pos, total, path))
+            .collect()
+    });
 
     let avg = |v: &[f64]| -> f64 {
         if v.is_empty() {
@@ -164,42 +398,285 @@ pub fn arrange_tracks(
         }
     };
 
-    let harmonic_flow = avg(&t_scores) * 100.0;
-    let score_breakdown = ScoreBreakdown {
-        key_compatibility: avg(&key_scores) * 100.0,
-        bpm_continuity: avg(&bpm_scores) * 100.0,
-        energy_arc: avg(&energy_scores) * 100.0,
-    };
+    ScoredOrder {
+        harmonic_flow_score: avg(&t_scores) * 100.0,
+        transition_scores: t_scores,
+        score_breakdown: ScoreBreakdown {
+            key_compatibility: avg(&key_scores) * 100.0,
+            bpm_continuity: avg(&bpm_scores) * 100.0,
+            energy_arc: avg(&energy_scores) * 100.0,
+            key_journey: key_journey_scores.map(|s| avg(&s) * 100.0),
+            key_transitions,
+            tempo_relations,
+            pitch_adjustments,
+        },
+    }
+}
 
-    let ordered_indices: Vec<usize> = order.iter().map(|&i| tracks[i].index).collect();
+// ---------------------------------------------------------------------------
+// Position constraints
+// ---------------------------------------------------------------------------
 
-    ArrangementResult {
-        ordered_indices,
-        transition_scores: t_scores,
-        harmonic_flow_score: harmonic_flow,
-        score_breakdown,
+/// 0-based inclusive `(earliest, latest)` slot for every track.
+fn allowed_ranges(tracks: &[ArrangementTrack]) -> Result<Vec<(usize, usize)>, ArrangementError> {
+    let n = tracks.len();
+    tracks
+        .iter()
+        .map(|t| {
+            let invalid = |reason: String| ArrangementError::InvalidConstraint {
+                index: t.index,
+                reason,
+            };
+            match t.constraint {
+                None => Ok((0, n.saturating_sub(1))),
+                Some(PositionConstraint::First) => Ok((0, 0)),
+                Some(PositionConstraint::Last) => Ok((n - 1, n - 1)),
+                Some(PositionConstraint::Pinned { position }) => {
+                    if position == 0 || position > n {
+                        Err(invalid(format!(
+                            "pinned position {position} is outside 1-{n}"
+                        )))
+                    } else {
+                        Ok((position - 1, position - 1))
+                    }
+                }
+                Some(PositionConstraint::Window { min, max }) => {
+                    if min == 0 || min > max || min > n {
+                        Err(invalid(format!(
+                            "window {min}-{max} does not fit a {n}-track set"
+                        )))
+                    } else {
+                        Ok((min - 1, max.min(n) - 1))
+                    }
+                }
+            }
+        })
+        .collect()
+}
+
+/// Can `remaining` tracks fill slots `start..start + remaining.len()`?
+///
+/// Earliest-deadline-first: at each slot, place the eligible track whose
+/// window closes soonest. This is exact for interval constraints.
+fn check_feasible(
+    ranges: &[(usize, usize)],
+    remaining: &[usize],
+    start: usize,
+) -> Result<(), ArrangementError> {
+    let mut by_earliest: Vec<usize> = remaining.to_vec();
+    by_earliest.sort_by_key(|&i| ranges[i].0);
+
+    let mut eligible: BinaryHeap<Reverse<usize>> = BinaryHeap::new();
+    let mut next = 0;
+    for pos in start..start + remaining.len() {
+        while next < by_earliest.len() && ranges[by_earliest[next]].0 <= pos {
+            eligible.push(Reverse(ranges[by_earliest[next]].1));
+            next += 1;
+        }
+        match eligible.pop() {
+            Some(Reverse(latest)) if latest >= pos => {}
+            Some(Reverse(latest)) => {
+                return Err(ArrangementError::Infeasible(format!(
+                    "more tracks must be placed by position {} than there are slots",
+                    latest + 1
+                )));
+            }
+            None => {
+                return Err(ArrangementError::Infeasible(format!(
+                    "no track is allowed at position {}",
+                    pos + 1
+                )));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Does every track in `order[i..=j]` sit inside its allowed range?
+fn within_ranges(ranges: &[(usize, usize)], order: &[usize], i: usize, j: usize) -> bool {
+    (i..=j).all(|pos| {
+        let (lo, hi) = ranges[order[pos]];
+        lo <= pos && pos <= hi
+    })
+}
+
+/// The objective the greedy pass, 2-opt and every solver share.
+fn build_problem<'a>(
+    tracks: &[ArrangementTrack],
+    ranges: &'a [(usize, usize)],
+    options: &ArrangementOptions,
+) -> Problem<'a> {
+    Problem {
+        scores: tracks
+            .iter()
+            .map(|a| tracks.iter().map(|b| pair_score(a, b, options)).collect())
+            .collect(),
+        placement: Some(
+            tracks
+                .iter()
+                .map(|t| {
+                    (0..tracks.len())
+                        .map(|pos| placement_score(t, pos, tracks.len(), options))
+                        .collect()
+                })
+                .collect(),
+        ),
+        ranges,
+        separation: (options.artist_separation > 0).then(|| Separation {
+            conflicts: artist_conflicts(tracks),
+            min_gap: options.artist_separation,
+        }),
+    }
+}
+
+/// Cost of the segment `[i..=j]` plus its boundary edges for 2-opt evaluation.
+///
+/// Key scoring is directional (8A→3A is an energy boost, 3A→8A is a clash), so
+/// reversing a segment changes every edge inside it — not just the two at its
+/// boundaries. All edges from `i - 1` through `j + 1` are summed, plus the
+/// placement of every track in the segment, since each one changes slot.
+fn segment_cost(problem: &Problem, order: &[usize], i: usize, j: usize) -> f64 {
+    let start = i.saturating_sub(1);
+    let end = (j + 1).min(order.len() - 1);
+
+    let transitions: f64 = (start..end)
+        .map(|k| problem.scores[order[k]][order[k + 1]])
+        .sum();
+    let placement: f64 = (i..=j).map(|k| problem.place(order[k], k)).sum();
+    transitions + placement
+}
+
+/// Per-slot objective term for `track` at `pos`: how well its energy fits
+/// the target arc there, weighted like energy is in a transition score,
+/// plus how well its key fits the key path, weighted like key.
+fn placement_score(
+    track: &ArrangementTrack,
+    pos: usize,
+    total: usize,
+    options: &ArrangementOptions,
+) -> f64 {
+    let s = &options.scoring;
+    let weight_sum = s.key_weight + s.bpm_weight + s.energy_weight;
+    let energy = energy_fit(track, pos, total, options) * s.energy_weight;
+    let key = options
+        .key_path
+        .as_ref()
+        .map_or(0.0, |path| key_fit(track, pos, total, path) * s.key_weight);
+    (energy + key) / weight_sum
+}
+
+/// Key path score of `track` at `pos`; 0.5 when its key is unknown.
+fn key_fit(track: &ArrangementTrack, pos: usize, total: usize, path: &KeyPath) -> f64 {
+    match track.camelot.as_ref() {
+        Some(k) => camelot::key_path_score(k, pos, total, path),
+        None => 0.5,
     }
 }
 
-/// Cost of the segment at boundaries i and j for 2-opt evaluation.
-fn segment_cost(tracks: &[ArrangementTrack], order: &[usize], i: usize, j: usize) -> f64 {
-    let mut cost = 0.0;
+/// Energy arc score of `track` at `pos`; 0.5 when its energy is unknown.
+fn energy_fit(
+    track: &ArrangementTrack,
+    pos: usize,
+    total: usize,
+    options: &ArrangementOptions,
+) -> f64 {
+    match track.energy {
+        Some(e) => match (options.energy_curve.as_ref(), options.energy_profile) {
+            (Some(curve), _) => camelot::energy_arc_score_with_curve(e, pos, total, curve),
+            (None, Some(profile)) => camelot::energy_arc_score_with_profile(e, pos, total, profile),
+            (None, None) => camelot::energy_arc_score(e, pos, total),
+        },
+        None => 0.5,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Artist separation
+// ---------------------------------------------------------------------------
+
+/// Separators between credited artists, matched after lowercasing.
+const ARTIST_SEPARATORS: &[&str] = &[
+    " featuring ",
+    " feat. ",
+    " feat ",
+    " ft. ",
+    " ft ",
+    " vs. ",
+    " vs ",
+    " x ",
+    " & ",
+    ",",
+];
 
-    // Edge before i
-    if i > 0 {
-        let a = &tracks[order[i - 1]];
-        let b = &tracks[order[i]];
-        cost += camelot::transition_score(a.camelot.as_ref(), b.camelot.as_ref(), a.bpm, b.bpm);
+/// Individual artists in a credit, lowercased: "Sting feat. Cheb Mami"
+/// becomes `["sting", "cheb mami"]`.
+pub fn artist_names(credit: &str) -> Vec<String> {
+    let mut normalized = credit.to_lowercase();
+    for sep in ARTIST_SEPARATORS {
+        normalized = normalized.replace(sep, ",");
     }
+    normalized
+        .split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// First artist credited on both tracks, if any.
+pub fn shared_artist(a: &str, b: &str) -> Option<String> {
+    let theirs = artist_names(b);
+    artist_names(a)
+        .into_iter()
+        .find(|name| theirs.contains(name))
+}
 
-    // Edge after j
-    if j + 1 < order.len() {
-        let a = &tracks[order[j]];
-        let b = &tracks[order[j + 1]];
-        cost += camelot::transition_score(a.camelot.as_ref(), b.camelot.as_ref(), a.bpm, b.bpm);
+/// Every pair of positions that share an artist and sit `min_gap` or fewer
+/// positions apart, as `(earlier, later, artist)`. Positions are indices into
+/// `artists`; tracks with no artist never conflict.
+pub fn separation_violations(
+    artists: &[Option<&str>],
+    min_gap: usize,
+) -> Vec<(usize, usize, String)> {
+    let mut violations = Vec::new();
+    for i in 0..artists.len() {
+        for j in (i + 1)..artists.len().min(i + min_gap + 1) {
+            if let (Some(a), Some(b)) = (artists[i], artists[j]) {
+                if let Some(artist) = shared_artist(a, b) {
+                    violations.push((i, j, artist));
+                }
+            }
+        }
     }
+    violations
+}
+
+/// `conflicts[a][b]` is true when tracks `a` and `b` share an artist.
+fn artist_conflicts(tracks: &[ArrangementTrack]) -> Vec<Vec<bool>> {
+    let names: Vec<Vec<String>> = tracks
+        .iter()
+        .map(|t| t.artist.as_deref().map(artist_names).unwrap_or_default())
+        .collect();
+    (0..tracks.len())
+        .map(|a| {
+            (0..tracks.len())
+                .map(|b| a != b && names[a].iter().any(|n| names[b].contains(n)))
+                .collect()
+        })
+        .collect()
+}
 
-    cost
+/// Transition score from `a` into `b` under the chosen pitch model and
+/// scoring profile.
+fn pair_score(a: &ArrangementTrack, b: &ArrangementTrack, options: &ArrangementOptions) -> f64 {
+    camelot::transition_score_with_profile(
+        a.camelot.as_ref(),
+        b.camelot.as_ref(),
+        a.bpm,
+        b.bpm,
+        options.key_lock,
+        &options.scoring,
+    )
 }
 
 // ---------------------------------------------------------------------------
@@ -209,7 +686,7 @@ fn segment_cost(tracks: &[ArrangementTrack], order: &[usize], i: usize, j: usize
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::services::camelot::parse_camelot;
+    use crate::services::camelot::{parse_camelot, TempoRelation, TransitionType};
 
     fn make_track(
         index: usize,
@@ -222,12 +699,14 @@ mod tests {
             camelot: camelot.and_then(parse_camelot),
             bpm,
             energy,
+            artist: None,
+            constraint: None,
         }
     }
 
     #[test]
     fn test_empty_input() {
-        let result = arrange_tracks(&[], None);
+        let result = arrange_tracks(&[], None).unwrap();
         assert!(result.ordered_indices.is_empty());
         assert!(result.transition_scores.is_empty());
     }
@@ -235,7 +714,7 @@ mod tests {
     #[test]
     fn test_single_track() {
         let tracks = vec![make_track(0, Some("8A"), Some(128.0), Some(5))];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices, vec![0]);
         assert!(result.transition_scores.is_empty());
         assert_eq!(result.harmonic_flow_score, 100.0);
@@ -247,7 +726,7 @@ mod tests {
             make_track(0, Some("8A"), Some(128.0), Some(5)),
             make_track(1, Some("9A"), Some(130.0), Some(7)),
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices.len(), 2);
         assert_eq!(result.transition_scores.len(), 1);
         assert!(result.harmonic_flow_score > 0.0);
@@ -262,7 +741,7 @@ mod tests {
             make_track(2, Some("9A"), Some(130.0), Some(6)), // adjacent to 8A
             make_track(3, Some("7A"), Some(126.0), Some(4)), // adjacent to 8A
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices.len(), 4);
 
         // The distant key (1A) should not be between compatible keys.
@@ -311,9 +790,9 @@ mod tests {
             make_track(3, Some("7A"), Some(126.0), Some(4)),
         ];
 
-        let first_result = arrange_tracks(&tracks, None);
+        let first_result = arrange_tracks(&tracks, None).unwrap();
         for _ in 0..10 {
-            let result = arrange_tracks(&tracks, None);
+            let result = arrange_tracks(&tracks, None).unwrap();
             assert_eq!(
                 result.ordered_indices, first_result.ordered_indices,
                 "Arrangement should be deterministic"
@@ -326,7 +805,7 @@ mod tests {
         let tracks: Vec<_> = (0..8)
             .map(|i| make_track(i, Some("8A"), Some(128.0), Some(5)))
             .collect();
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices.len(), 8);
         // All indices present
         let mut sorted = result.ordered_indices.clone();
@@ -341,7 +820,7 @@ mod tests {
             make_track(1, None, None, None),
             make_track(2, None, None, None),
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices.len(), 3);
         // Should still produce a result with neutral scores
         assert!(result.harmonic_flow_score >= 0.0);
@@ -354,7 +833,7 @@ mod tests {
             make_track(1, None, None, None),
             make_track(2, Some("9A"), Some(130.0), None),
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         assert_eq!(result.ordered_indices.len(), 3);
     }
 
@@ -366,7 +845,7 @@ mod tests {
             make_track(1, Some("8A"), Some(128.0), Some(2)), // low energy
             make_track(2, Some("8A"), Some(128.0), Some(5)), // medium energy
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         // First track should be the lowest energy one (index 1, energy=2)
         assert_eq!(result.ordered_indices[0], 1, "Lowest energy should open");
     }
@@ -381,7 +860,7 @@ mod tests {
             make_track(2, Some("3A"), Some(124.0), Some(6)),
             make_track(3, Some("4A"), Some(126.0), Some(8)),
         ];
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         // With ascending keys, the score should be high
         assert!(
             result.harmonic_flow_score > 50.0,
@@ -390,6 +869,382 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_segment_cost_counts_interior_edges() {
+        // 8A→3A is an energy boost but 3A→8A is a clash. Reversing the middle
+        // pair improves both boundary edges on BPM, but flipping the interior
+        // boost into a clash costs more than that gain.
+        let tracks = [
+            make_track(0, None, Some(130.0), Some(3)),
+            make_track(1, Some("8A"), Some(120.0), Some(5)),
+            make_track(2, Some("3A"), Some(130.0), Some(7)),
+            make_track(3, None, Some(124.0), Some(6)),
+        ];
+        let forward = vec![0, 1, 2, 3];
+        let reversed = vec![0, 2, 1, 3];
+
+        let boundary_only = |order: &[usize]| {
+            let edge = |a: usize, b: usize| {
+                let (ta, tb) = (&tracks[order[a]], &tracks[order[b]]);
+                camelot::transition_score(ta.camelot.as_ref(), tb.camelot.as_ref(), ta.bpm, tb.bpm)
+            };
+            edge(0, 1) + edge(2, 3)
+        };
+        // The old boundary-only comparison would have accepted the reversal
+        assert!(boundary_only(&reversed) > boundary_only(&forward));
+
+        let options = ArrangementOptions::default();
+        let ranges = vec![(0, 3); 4];
+        let problem = Problem {
+            scores: tracks
+                .iter()
+                .map(|a| tracks.iter().map(|b| pair_score(a, b, &options)).collect())
+                .collect(),
+            placement: None,
+            ranges: &ranges,
+            separation: None,
+        };
+        assert!(
+            segment_cost(&problem, &forward, 1, 2) > segment_cost(&problem, &reversed, 1, 2),
+            "Reversal that breaks an interior energy boost must not count as an improvement"
+        );
+    }
+
+    #[test]
+    fn test_key_transitions_follow_final_order() {
+        let tracks = vec![
+            make_track(0, Some("8A"), Some(128.0), Some(3)),
+            make_track(1, Some("3A"), Some(128.0), Some(7)),
+            make_track(2, None, Some(128.0), Some(4)),
+        ];
+        let result = arrange_tracks(&tracks, None).unwrap();
+        let transitions = &result.score_breakdown.key_transitions;
+        assert_eq!(transitions.len(), tracks.len() - 1);
+
+        for (pos, kind) in transitions.iter().enumerate() {
+            let a = &tracks[result.ordered_indices[pos]];
+            let b = &tracks[result.ordered_indices[pos + 1]];
+            let expected = match (a.camelot.as_ref(), b.camelot.as_ref()) {
+                (Some(ka), Some(kb)) => Some(camelot::classify_transition(ka, kb)),
+                _ => None,
+            };
+            assert_eq!(*kind, expected, "transition {pos} mislabelled");
+        }
+        assert!(transitions.contains(&Some(TransitionType::EnergyBoost)));
+        assert!(transitions.contains(&None));
+    }
+
+    #[test]
+    fn test_arrange_pairs_double_time_tracks() {
+        // A 70 BPM track sits naturally next to 140 BPM tracks via double-time.
+        let tracks = vec![
+            make_track(0, Some("8A"), Some(140.0), Some(3)),
+            make_track(1, Some("8A"), Some(100.0), Some(4)),
+            make_track(2, Some("8A"), Some(70.0), Some(5)),
+        ];
+        let result = arrange_tracks(&tracks, None).unwrap();
+        assert_eq!(result.ordered_indices, vec![0, 2, 1]);
+        assert_eq!(
+            result.score_breakdown.tempo_relations[0],
+            Some(TempoRelation::HalfTime)
+        );
+        assert_eq!(result.score_breakdown.tempo_relations.len(), 2);
+    }
+
+    #[test]
+    fn test_energy_curve_drives_energy_score() {
+        use crate::services::camelot::EnergyBreakpoint;
+
+        let tracks: Vec<ArrangementTrack> = (0..5)
+            .map(|i| make_track(i, Some("8A"), Some(128.0), Some([3, 9, 5, 9, 3][i])))
+            .collect();
+        let double_peak = EnergyCurve {
+            breakpoints: vec![
+                EnergyBreakpoint {
+                    position: 0.0,
+                    energy: 3.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.25,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.5,
+                    energy: 5.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.75,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 1.0,
+                    energy: 3.0,
+                },
+            ],
+        };
+        let result = arrange_tracks_with_options(
+            &tracks,
+            &ArrangementOptions {
+                energy_curve: Some(double_peak),
+                // Ignored when a curve is given
+                energy_profile: Some(EnergyProfile::Steady),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        // Keys and BPMs are identical, so the order is the input order and
+        // every track sits exactly on the curve.
+        assert!((result.score_breakdown.energy_arc - 100.0).abs() < 1e-9);
+
+        let steady = arrange_tracks(&tracks, Some(EnergyProfile::Steady)).unwrap();
+        assert!(steady.score_breakdown.energy_arc < 100.0);
+    }
+
+    #[test]
+    fn test_key_path_drives_key_journey_score() {
+        use crate::services::camelot::KeyWaypoint;
+
+        let tracks: Vec<ArrangementTrack> = ["5A", "6A", "7A", "8A"]
+            .iter()
+            .enumerate()
+            .map(|(i, k)| make_track(i, Some(k), Some(124.0), Some(6)))
+            .collect();
+        let options = ArrangementOptions {
+            key_path: Some(KeyPath {
+                waypoints: vec![
+                    KeyWaypoint {
+                        position: 0.0,
+                        key: "5A".to_string(),
+                    },
+                    KeyWaypoint {
+                        position: 1.0,
+                        key: "8A".to_string(),
+                    },
+                ],
+            }),
+            ..Default::default()
+        };
+        let climbing = score_order(&tracks, &[0, 1, 2, 3], &options);
+        assert_eq!(climbing.score_breakdown.key_journey, Some(100.0));
+        let falling = score_order(&tracks, &[3, 2, 1, 0], &options);
+        // Three steps off at either end, one step off in the middle
+        let journey = falling.score_breakdown.key_journey.unwrap();
+        assert!((journey - 500.0 / 7.0).abs() < 1e-9);
+
+        let no_path = score_order(&tracks, &[0, 1, 2, 3], &ArrangementOptions::default());
+        assert_eq!(no_path.score_breakdown.key_journey, None);
+    }
+
+    #[test]
+    fn test_key_path_changes_order() {
+        use crate::services::camelot::KeyWaypoint;
+
+        let tracks: Vec<ArrangementTrack> = ["8A", "9A", "10A", "11A"]
+            .iter()
+            .enumerate()
+            .map(|(i, k)| make_track(i, Some(k), Some(124.0), Some(6)))
+            .collect();
+        let unguided = arrange_tracks_with_options(&tracks, &ArrangementOptions::default())
+            .unwrap()
+            .ordered_indices;
+        assert_eq!(unguided, vec![0, 1, 2, 3]);
+
+        // Ask for the same keys walked the other way round the wheel
+        let options = ArrangementOptions {
+            key_path: Some(KeyPath {
+                waypoints: vec![
+                    KeyWaypoint {
+                        position: 0.0,
+                        key: "11A".to_string(),
+                    },
+                    KeyWaypoint {
+                        position: 1.0,
+                        key: "8A".to_string(),
+                    },
+                ],
+            }),
+            ..Default::default()
+        };
+        for kind in [Solver::Greedy, Solver::HeldKarp, Solver::Annealing] {
+            let options = ArrangementOptions {
+                solver: SolverConfig {
+                    kind,
+                    ..Default::default()
+                },
+                ..options.clone()
+            };
+            let result = arrange_tracks_with_options(&tracks, &options).unwrap();
+            assert_eq!(result.ordered_indices, vec![3, 2, 1, 0], "{kind:?}");
+            assert_eq!(result.score_breakdown.key_journey, Some(100.0));
+        }
+    }
+
+    // --- Position constraints ---
+
+    fn constrained(mut t: ArrangementTrack, c: PositionConstraint) -> ArrangementTrack {
+        t.constraint = Some(c);
+        t
+    }
+
+    fn six_tracks() -> Vec<ArrangementTrack> {
+        vec![
+            make_track(0, Some("8A"), Some(124.0), Some(2)),
+            make_track(1, Some("9A"), Some(125.0), Some(4)),
+            make_track(2, Some("10A"), Some(126.0), Some(6)),
+            make_track(3, Some("11A"), Some(127.0), Some(8)),
+            make_track(4, Some("12A"), Some(128.0), Some(9)),
+            make_track(5, Some("1A"), Some(129.0), Some(7)),
+        ]
+    }
+
+    #[test]
+    fn test_constraints_fix_opener_and_closer() {
+        let mut tracks = six_tracks();
+        tracks[4] = constrained(
+            make_track(4, Some("12A"), Some(128.0), Some(9)),
+            PositionConstraint::First,
+        );
+        tracks[0] = constrained(
+            make_track(0, Some("8A"), Some(124.0), Some(2)),
+            PositionConstraint::Last,
+        );
+        let result = arrange_tracks(&tracks, None).unwrap();
+        assert_eq!(result.ordered_indices.first(), Some(&4));
+        assert_eq!(result.ordered_indices.last(), Some(&0));
+        assert_eq!(result.ordered_indices.len(), 6);
+    }
+
+    #[test]
+    fn test_constraints_pinned_and_window_survive_two_opt() {
+        let mut tracks = six_tracks();
+        // The natural 8A→9A→10A chain wants track 2 at slot 3; pin it to 5
+        tracks[2] = constrained(
+            make_track(2, Some("10A"), Some(126.0), Some(6)),
+            PositionConstraint::Pinned { position: 5 },
+        );
+        tracks[1] = constrained(
+            make_track(1, Some("9A"), Some(125.0), Some(4)),
+            PositionConstraint::Window { min: 4, max: 6 },
+        );
+        let result = arrange_tracks(&tracks, None).unwrap();
+        let pos = |idx: usize| {
+            result
+                .ordered_indices
+                .iter()
+                .position(|&i| i == idx)
+                .unwrap()
+        };
+        assert_eq!(pos(2), 4);
+        assert!((3..=5).contains(&pos(1)));
+    }
+
+    #[test]
+    fn test_unconstrained_order_is_unchanged_by_constraint_support() {
+        // Greedy from the lowest-energy opener, same as before constraints
+        let result = arrange_tracks(&six_tracks(), None).unwrap();
+        assert_eq!(result.ordered_indices, vec![0, 1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn test_constraints_infeasible() {
+        let mut tracks = six_tracks();
+        tracks[0].constraint = Some(PositionConstraint::First);
+        tracks[1].constraint = Some(PositionConstraint::Pinned { position: 1 });
+        let err = arrange_tracks(&tracks, None).err().unwrap();
+        assert!(matches!(err, ArrangementError::Infeasible(_)));
+
+        // Three tracks squeezed into a two-slot window
+        let mut tracks = six_tracks();
+        for t in tracks.iter_mut().take(3) {
+            t.constraint = Some(PositionConstraint::Window { min: 2, max: 3 });
+        }
+        let err = arrange_tracks(&tracks, None).err().unwrap();
+        assert!(matches!(err, ArrangementError::Infeasible(_)));
+    }
+
+    #[test]
+    fn test_constraints_greedy_looks_ahead() {
+        // Track 3 may only go in slots 1-2. If the greedy spent slot 2 on the
+        // best transition it would strand track 3, so it must look ahead.
+        let mut tracks = six_tracks();
+        tracks[3].constraint = Some(PositionConstraint::Window { min: 1, max: 2 });
+        tracks[0].constraint = Some(PositionConstraint::First);
+        let result = arrange_tracks(&tracks, None).unwrap();
+        assert_eq!(result.ordered_indices[..2], [0, 3]);
+    }
+
+    #[test]
+    fn test_constraints_invalid() {
+        let mut tracks = six_tracks();
+        tracks[2].constraint = Some(PositionConstraint::Pinned { position: 7 });
+        let err = arrange_tracks(&tracks, None).err().unwrap();
+        assert_eq!(
+            err,
+            ArrangementError::InvalidConstraint {
+                index: 2,
+                reason: "pinned position 7 is outside 1-6".to_string()
+            }
+        );
+
+        tracks[2].constraint = Some(PositionConstraint::Window { min: 4, max: 2 });
+        assert!(matches!(
+            arrange_tracks(&tracks, None),
+            Err(ArrangementError::InvalidConstraint { .. })
+        ));
+    }
+
+    #[test]
+    fn test_position_constraint_serde() {
+        let json = serde_json::json!({"type": "window", "min": 3, "max": 5});
+        let c: PositionConstraint = serde_json::from_value(json).unwrap();
+        assert_eq!(c, PositionConstraint::Window { min: 3, max: 5 });
+        let first: PositionConstraint =
+            serde_json::from_value(serde_json::json!({"type": "first"})).unwrap();
+        assert_eq!(first, PositionConstraint::First);
+    }
+
+    #[test]
+    fn test_key_lock_off_scores_pitched_key() {
+        // 1A pitched from 121 up to 128 BPM sounds as 8A
+        let tracks = vec![
+            make_track(0, Some("8A"), Some(128.0), Some(3)),
+            make_track(1, Some("1A"), Some(121.0), Some(5)),
+        ];
+        let locked = arrange_tracks(&tracks, None).unwrap();
+        let unlocked = arrange_tracks_with_options(
+            &tracks,
+            &ArrangementOptions {
+                key_lock: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+
+        assert_eq!(
+            locked.score_breakdown.key_transitions,
+            vec![Some(TransitionType::Clash)]
+        );
+        assert_eq!(
+            unlocked.score_breakdown.key_transitions,
+            vec![Some(TransitionType::SameKey)]
+        );
+        assert!(unlocked.harmonic_flow_score > locked.harmonic_flow_score);
+
+        let pitch = unlocked.score_breakdown.pitch_adjustments[0].unwrap();
+        assert!((pitch - 5.785).abs() < 0.01);
+        assert_eq!(locked.score_breakdown.pitch_adjustments.len(), 1);
+    }
+
+    #[test]
+    fn test_key_transitions_empty_for_trivial_inputs() {
+        let empty = arrange_tracks(&[], None).unwrap();
+        assert!(empty.score_breakdown.key_transitions.is_empty());
+
+        let single =
+            arrange_tracks(&[make_track(0, Some("8A"), Some(128.0), Some(5))], None).unwrap();
+        assert!(single.score_breakdown.key_transitions.is_empty());
+    }
+
     #[test]
     fn test_performance_20_tracks() {
         let tracks: Vec<_> = (0..20)
@@ -407,7 +1262,7 @@ mod tests {
             .collect();
 
         let start = std::time::Instant::now();
-        let result = arrange_tracks(&tracks, None);
+        let result = arrange_tracks(&tracks, None).unwrap();
         let elapsed = start.elapsed();
 
         assert_eq!(result.ordered_indices.len(), 20);
@@ -418,6 +1273,175 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_scoring_profile_drives_arrangement_scores() {
+        let tracks = vec![
+            make_track(0, Some("8A"), Some(120.0), Some(3)),
+            make_track(1, Some("8A"), Some(127.0), Some(5)),
+            make_track(2, Some("9A"), Some(134.0), Some(7)),
+        ];
+        let default = arrange_tracks(&tracks, None).unwrap();
+        let options = ArrangementOptions {
+            scoring: ScoringProfile {
+                key_weight: 0.0,
+                bpm_weight: 1.0,
+                energy_weight: 0.0,
+                bpm_bands: camelot::BpmBands {
+                    perfect: 8.0,
+                    close: 9.0,
+                    workable: 10.0,
+                    stretch: 11.0,
+                },
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let bpm_only = arrange_tracks_with_options(&tracks, &options).unwrap();
+        assert_eq!(bpm_only.ordered_indices, vec![0, 1, 2]);
+        // Every 7 BPM step sits inside the widened "perfect" band
+        assert!((bpm_only.harmonic_flow_score - 100.0).abs() < 1e-9);
+        assert!((bpm_only.score_breakdown.bpm_continuity - 100.0).abs() < 1e-9);
+        assert!(default.harmonic_flow_score < bpm_only.harmonic_flow_score);
+    }
+
+    // --- Solvers ---
+
+    fn scattered_tracks(n: usize) -> Vec<ArrangementTrack> {
+        (0..n)
+            .map(|i| {
+                let key = format!("{}{}", (i * 5) % 12 + 1, if i % 3 == 0 { "B" } else { "A" });
+                make_track(
+                    i,
+                    Some(&key),
+                    Some(118.0 + ((i * 7) % 13) as f64),
+                    Some((i % 10 + 1) as i32),
+                )
+            })
+            .collect()
+    }
+
+    fn with_solver(kind: Solver) -> ArrangementOptions {
+        ArrangementOptions {
+            solver: SolverConfig {
+                kind,
+                time_budget: std::time::Duration::from_secs(10),
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_greedy_solver_reports_zero_improvement() {
+        let tracks = scattered_tracks(10);
+        let result = arrange_tracks(&tracks, None).unwrap();
+        assert_eq!(result.solver, Solver::Greedy);
+        assert_eq!(result.improvement_over_greedy, 0.0);
+        assert!((result.greedy_flow_score - result.harmonic_flow_score).abs() < 1e-9);
+        assert!(!result.timed_out);
+    }
+
+    #[test]
+    fn test_held_karp_never_worse_than_greedy() {
+        let tracks = scattered_tracks(10);
+        let greedy = arrange_tracks(&tracks, None).unwrap();
+        let exact = arrange_tracks_with_options(&tracks, &with_solver(Solver::HeldKarp)).unwrap();
+        assert_eq!(exact.solver, Solver::HeldKarp);
+        assert!(exact.harmonic_flow_score >= greedy.harmonic_flow_score - 1e-9);
+        assert!((exact.greedy_flow_score - greedy.harmonic_flow_score).abs() < 1e-9);
+        assert!(
+            (exact.improvement_over_greedy
+                - (exact.harmonic_flow_score - greedy.harmonic_flow_score))
+                .abs()
+                < 1e-9
+        );
+    }
+
+    #[test]
+    fn test_held_karp_rejects_large_sets() {
+        let tracks = scattered_tracks(HELD_KARP_MAX_TRACKS + 1);
+        let err = arrange_tracks_with_options(&tracks, &with_solver(Solver::HeldKarp))
+            .err()
+            .unwrap();
+        assert!(matches!(err, ArrangementError::TooManyTracks { .. }));
+    }
+
+    #[test]
+    fn test_auto_solver_picks_by_size() {
+        let small =
+            arrange_tracks_with_options(&scattered_tracks(8), &with_solver(Solver::Auto)).unwrap();
+        assert_eq!(small.solver, Solver::HeldKarp);
+        let large =
+            arrange_tracks_with_options(&scattered_tracks(30), &with_solver(Solver::Auto)).unwrap();
+        assert_eq!(large.solver, Solver::Annealing);
+    }
+
+    #[test]
+    fn test_metaheuristics_keep_constraints_and_count() {
+        let mut tracks = scattered_tracks(40);
+        tracks[7].constraint = Some(PositionConstraint::First);
+        tracks[3].constraint = Some(PositionConstraint::Pinned { position: 20 });
+        tracks[11].constraint = Some(PositionConstraint::Last);
+        let options = with_solver(Solver::Greedy);
+        let ranges = allowed_ranges(&tracks).unwrap();
+        let problem = build_problem(&tracks, &ranges, &options);
+        let greedy = arrange_tracks_with_options(&tracks, &options).unwrap();
+        for kind in [Solver::Annealing, Solver::OrOpt] {
+            let result = arrange_tracks_with_options(&tracks, &with_solver(kind)).unwrap();
+            assert_eq!(result.solver, kind);
+            assert_eq!(result.ordered_indices.len(), 40);
+            assert_eq!(result.ordered_indices[0], 7);
+            assert_eq!(result.ordered_indices[19], 3);
+            assert_eq!(result.ordered_indices[39], 11);
+            // Solvers trade flow for energy placement, but never lose overall
+            assert!(
+                problem.total(&result.ordered_indices)
+                    >= problem.total(&greedy.ordered_indices) - 1e-9
+            );
+        }
+    }
+
+    #[test]
+    fn test_held_karp_optimises_energy_arc_too() {
+        // Every transition scores the same, so only the energy arc separates
+        // orders: the exact solver must improve on the greedy arc.
+        let tracks: Vec<ArrangementTrack> = [9, 2, 5, 8, 3, 6, 1]
+            .iter()
+            .enumerate()
+            .map(|(i, &e)| make_track(i, Some("8A"), Some(124.0), Some(e)))
+            .collect();
+        let greedy = arrange_tracks_with_options(&tracks, &with_solver(Solver::Greedy)).unwrap();
+        let exact = arrange_tracks_with_options(&tracks, &with_solver(Solver::HeldKarp)).unwrap();
+
+        let ranges = allowed_ranges(&tracks).unwrap();
+        let problem = build_problem(&tracks, &ranges, &with_solver(Solver::HeldKarp));
+        assert!(
+            exact.score_breakdown.energy_arc > greedy.score_breakdown.energy_arc,
+            "exact {} <= greedy {}",
+            exact.score_breakdown.energy_arc,
+            greedy.score_breakdown.energy_arc
+        );
+        assert!(
+            problem.total(&exact.ordered_indices) >= problem.total(&greedy.ordered_indices) - 1e-9
+        );
+    }
+
+    #[test]
+    fn test_solver_time_budget_is_honoured() {
+        let tracks = scattered_tracks(50);
+        let options = ArrangementOptions {
+            solver: SolverConfig {
+                kind: Solver::Annealing,
+                time_budget: std::time::Duration::from_millis(1),
+            },
+            ..Default::default()
+        };
+        let start = std::time::Instant::now();
+        let result = arrange_tracks_with_options(&tracks, &options).unwrap();
+        assert!(result.timed_out);
+        assert!(start.elapsed().as_millis() < 1000);
+        assert!(result.harmonic_flow_score >= result.greedy_flow_score - 1e-9);
+    }
+
     // --- arrange_tracks with EnergyProfile ---
 
     #[test]
@@ -429,8 +1453,8 @@ mod tests {
             make_track(3, Some("7A"), Some(126.0), Some(4)),
         ];
 
-        let result_none = arrange_tracks(&tracks, None);
-        let result_none2 = arrange_tracks(&tracks, None);
+        let result_none = arrange_tracks(&tracks, None).unwrap();
+        let result_none2 = arrange_tracks(&tracks, None).unwrap();
 
         assert_eq!(
             result_none.ordered_indices, result_none2.ordered_indices,
@@ -454,9 +1478,9 @@ mod tests {
             make_track(7, Some("8A"), Some(128.0), Some(5)), // medium
         ];
 
-        let result_warmup = arrange_tracks(&tracks, Some(EnergyProfile::WarmUp));
-        let result_peaktime = arrange_tracks(&tracks, Some(EnergyProfile::PeakTime));
-        let result_steady = arrange_tracks(&tracks, Some(EnergyProfile::Steady));
+        let result_warmup = arrange_tracks(&tracks, Some(EnergyProfile::WarmUp)).unwrap();
+        let result_peaktime = arrange_tracks(&tracks, Some(EnergyProfile::PeakTime)).unwrap();
+        let result_steady = arrange_tracks(&tracks, Some(EnergyProfile::Steady)).unwrap();
 
         // Different profiles should produce different energy arc scores
         let warmup_energy = result_warmup.score_breakdown.energy_arc;
@@ -481,7 +1505,7 @@ mod tests {
             make_track(2, Some("8A"), Some(128.0), Some(5)),
         ];
 
-        let result = arrange_tracks(&tracks, Some(EnergyProfile::WarmUp));
+        let result = arrange_tracks(&tracks, Some(EnergyProfile::WarmUp)).unwrap();
         // First track should be the lowest energy (3)
         assert_eq!(
             result.ordered_indices[0], 1,
@@ -501,7 +1525,7 @@ mod tests {
             EnergyProfile::Journey,
             EnergyProfile::Steady,
         ] {
-            let result = arrange_tracks(&tracks, Some(profile));
+            let result = arrange_tracks(&tracks, Some(profile)).unwrap();
             assert_eq!(
                 result.ordered_indices.len(),
                 6,
@@ -516,15 +1540,133 @@ mod tests {
 
     #[test]
     fn test_arrange_with_profile_empty_tracks() {
-        let result = arrange_tracks(&[], Some(EnergyProfile::Journey));
+        let result = arrange_tracks(&[], Some(EnergyProfile::Journey)).unwrap();
         assert!(result.ordered_indices.is_empty());
     }
 
     #[test]
     fn test_arrange_with_profile_single_track() {
         let tracks = vec![make_track(0, Some("8A"), Some(128.0), Some(5))];
-        let result = arrange_tracks(&tracks, Some(EnergyProfile::PeakTime));
+        let result = arrange_tracks(&tracks, Some(EnergyProfile::PeakTime)).unwrap();
         assert_eq!(result.ordered_indices, vec![0]);
         assert_eq!(result.harmonic_flow_score, 100.0);
     }
+
+    fn by_artist(index: usize, artist: &str) -> ArrangementTrack {
+        ArrangementTrack {
+            artist: Some(artist.to_string()),
+            ..make_track(index, Some("8A"), Some(124.0), Some(5))
+        }
+    }
+
+    fn separated(result: &ArrangementResult, tracks: &[ArrangementTrack], gap: usize) -> usize {
+        let artists: Vec<Option<&str>> = result
+            .ordered_indices
+            .iter()
+            .map(|&i| tracks[i].artist.as_deref())
+            .collect();
+        separation_violations(&artists, gap).len()
+    }
+
+    #[test]
+    fn test_artist_names_splits_credits() {
+        assert_eq!(
+            artist_names("Sting feat. Cheb Mami"),
+            vec!["sting", "cheb mami"]
+        );
+        assert_eq!(
+            artist_names("Bicep, Clara La San & Hammer"),
+            vec!["bicep", "clara la san", "hammer"]
+        );
+        assert_eq!(artist_names("  "), Vec::<String>::new());
+        assert_eq!(
+            shared_artist("Bicep", "Hammer x BICEP"),
+            Some("bicep".to_string())
+        );
+        assert_eq!(shared_artist("Bicep", "Bicep Tribute"), None);
+    }
+
+    #[test]
+    fn test_separation_violations_respects_gap() {
+        let artists = [Some("A"), Some("B"), Some("A"), None, Some("A feat. C")];
+        assert!(separation_violations(&artists, 1).is_empty());
+        assert_eq!(
+            separation_violations(&artists, 2),
+            vec![(0, 2, "a".to_string()), (2, 4, "a".to_string())]
+        );
+        assert_eq!(separation_violations(&artists, 4).len(), 3);
+    }
+
+    #[test]
+    fn test_artist_separation_spreads_same_artist() {
+        // Identical keys and BPMs: without the rule the input order stands,
+        // with both tracks of each artist back to back.
+        let tracks: Vec<ArrangementTrack> = ["A", "A", "B", "B", "C", "C"]
+            .iter()
+            .enumerate()
+            .map(|(i, a)| by_artist(i, a))
+            .collect();
+        let unconstrained = arrange_tracks_with_options(
+            &tracks,
+            &ArrangementOptions {
+                artist_separation: 0,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(separated(&unconstrained, &tracks, 1), 3);
+        // The default keeps same-artist tracks apart
+        let default = arrange_tracks(&tracks, None).unwrap();
+        assert_eq!(separated(&default, &tracks, DEFAULT_ARTIST_SEPARATION), 0);
+
+        for gap in [1, 2] {
+            let result = arrange_tracks_with_options(
+                &tracks,
+                &ArrangementOptions {
+                    artist_separation: gap,
+                    ..Default::default()
+                },
+            )
+            .unwrap();
+            assert_eq!(separated(&result, &tracks, gap), 0, "gap {gap}");
+        }
+    }
+
+    #[test]
+    fn test_artist_separation_holds_under_every_solver() {
+        let mut tracks = scattered_tracks(10);
+        for (i, t) in tracks.iter_mut().enumerate() {
+            t.artist = Some(format!("Artist {}", i % 5));
+        }
+        for kind in [Solver::HeldKarp, Solver::Annealing, Solver::OrOpt] {
+            let options = ArrangementOptions {
+                artist_separation: 1,
+                ..with_solver(kind)
+            };
+            let result = arrange_tracks_with_options(&tracks, &options).unwrap();
+            assert_eq!(separated(&result, &tracks, 1), 0, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_artist_separation_is_best_effort() {
+        // Three of four tracks by one artist cannot be kept apart; the set is
+        // still arranged with as few repeats as possible.
+        let tracks = vec![
+            by_artist(0, "A"),
+            by_artist(1, "A"),
+            by_artist(2, "A"),
+            by_artist(3, "B"),
+        ];
+        let result = arrange_tracks_with_options(
+            &tracks,
+            &ArrangementOptions {
+                artist_separation: 1,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(result.ordered_indices.len(), 4);
+        assert_eq!(separated(&result, &tracks, 1), 1);
+    }
 }
@@ -0,0 +1,379 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::db::models::TrackRow;
+use crate::services::camelot::{self, parse_camelot, CamelotKey, ScoringProfile};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Longest chain of bridge tracks searched for.
+pub const MAX_BRIDGE_TRACKS: usize = 3;
+
+/// A hop scoring below this is not "smooth". 0.7 needs at least a diagonal
+/// key move at a matched tempo under the default profile.
+pub const DEFAULT_MIN_BRIDGE_SCORE: f64 = 0.7;
+
+/// Candidates kept on each side of the meet-in-the-middle search for
+/// 3-track bridges, best first.
+const FRONTIER: usize = 30;
+
+/// What the search needs to know about either end of the bridge. Setlist
+/// tracks need not be in the catalog, so this is just key and tempo.
+#[derive(Debug, Clone, Copy)]
+pub struct BridgeEnd {
+    pub camelot: Option<CamelotKey>,
+    pub bpm: Option<f64>,
+}
+
+impl BridgeEnd {
+    pub fn new(camelot: Option<&str>, bpm: Option<f64>) -> Self {
+        Self {
+            camelot: camelot.and_then(parse_camelot),
+            bpm,
+        }
+    }
+}
+
+impl From<&TrackRow> for BridgeEnd {
+    fn from(t: &TrackRow) -> Self {
+        Self::new(t.camelot_key.as_deref(), t.bpm)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BridgeOptions {
+    /// 1 to [`MAX_BRIDGE_TRACKS`].
+    pub max_tracks: usize,
+    /// Every hop in the chain must score at least this.
+    pub min_score: f64,
+    /// Catalog track ids never used as a bridge (e.g., already in the set).
+    pub exclude: HashSet<String>,
+    pub key_lock: bool,
+    pub scoring: ScoringProfile,
+}
+
+impl Default for BridgeOptions {
+    fn default() -> Self {
+        Self {
+            max_tracks: MAX_BRIDGE_TRACKS,
+            min_score: DEFAULT_MIN_BRIDGE_SCORE,
+            exclude: HashSet::new(),
+            key_lock: true,
+            scoring: ScoringProfile::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Bridge {
+    /// Catalog tracks to play between the two ends, in order.
+    pub tracks: Vec<BridgeTrack>,
+    /// Score of every hop, from the first end through to the second;
+    /// one more than `tracks.len()`.
+    pub transition_scores: Vec<f64>,
+    /// The weakest hop; at least `min_score`.
+    pub weakest_transition: f64,
+    /// Score of going straight from one end to the other.
+    pub direct_score: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BridgeTrack {
+    pub track_id: String,
+    pub title: String,
+    pub artist: Option<String>,
+    pub bpm: Option<f64>,
+    pub camelot: Option<String>,
+    pub energy: Option<f64>,
+}
+
+// ---------------------------------------------------------------------------
+// Search
+// ---------------------------------------------------------------------------
+
+/// Find the shortest chain of catalog tracks leading smoothly from `from` to
+/// `to`, using the transition score as edge weight.
+///
+/// Shorter chains always win; among chains of the same length, the one
+/// whose weakest hop is strongest wins, then the highest total. Returns
+/// `None` if no chain of up to `options.max_tracks` keeps every hop at or
+/// above `options.min_score`.
+pub fn find_bridge(
+    from: BridgeEnd,
+    to: BridgeEnd,
+    catalog: &[TrackRow],
+    options: &BridgeOptions,
+) -> Option<Bridge> {
+    let pool: Vec<&TrackRow> = catalog
+        .iter()
+        .filter(|t| !options.exclude.contains(&t.id))
+        .collect();
+    let ends: Vec<BridgeEnd> = pool.iter().map(|&t| BridgeEnd::from(t)).collect();
+    let edge = |a: &BridgeEnd, b: &BridgeEnd| {
+        camelot::transition_score_with_profile(
+            a.camelot.as_ref(),
+            b.camelot.as_ref(),
+            a.bpm,
+            b.bpm,
+            options.key_lock,
+            &options.scoring,
+        )
+    };
+    let min = options.min_score;
+
+    let from_scores: Vec<f64> = ends.iter().map(|e| edge(&from, e)).collect();
+    let to_scores: Vec<f64> = ends.iter().map(|e| edge(e, &to)).collect();
+    let candidates = |scores: &[f64]| -> Vec<usize> {
+        let mut idx: Vec<usize> = (0..scores.len()).filter(|&i| scores[i] >= min).collect();
+        idx.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+        idx
+    };
+    // 1- and 2-track chains are cheap enough to search exhaustively; only
+    // the 3-track step, which also walks the whole pool, is capped.
+    let out = candidates(&from_scores);
+    let into = candidates(&to_scores);
+
+    let mut best: Option<(Vec<usize>, Vec<f64>)> = None;
+    // Both lists are sorted best first, so once a first or last hop falls
+    // below the weakest hop held, nothing later in that list can win.
+    let beaten = |best: &Option<(Vec<usize>, Vec<f64>)>, score: f64| {
+        best.as_ref().is_some_and(|(_, held)| score < rank(held).0)
+    };
+
+    for len in 1..=options.max_tracks.clamp(1, MAX_BRIDGE_TRACKS) {
+        match len {
+            1 => {
+                for &i in &out {
+                    if beaten(&best, from_scores[i]) {
+                        break;
+                    }
+                    consider(&mut best, min, &[i], &[from_scores[i], to_scores[i]]);
+                }
+            }
+            2 => {
+                for &i in &out {
+                    if beaten(&best, from_scores[i]) {
+                        break;
+                    }
+                    for &k in into.iter().filter(|&&k| k != i) {
+                        if beaten(&best, to_scores[k]) {
+                            break;
+                        }
+                        let mid = edge(&ends[i], &ends[k]);
+                        consider(
+                            &mut best,
+                            min,
+                            &[i, k],
+                            &[from_scores[i], mid, to_scores[k]],
+                        );
+                    }
+                }
+            }
+            _ => {
+                for &i in out.iter().take(FRONTIER) {
+                    if beaten(&best, from_scores[i]) {
+                        break;
+                    }
+                    for &k in into.iter().take(FRONTIER).filter(|&&k| k != i) {
+                        if beaten(&best, to_scores[k]) {
+                            break;
+                        }
+                        for j in (0..ends.len()).filter(|&j| j != i && j != k) {
+                            let first = edge(&ends[i], &ends[j]);
+                            if first < min {
+                                continue;
+                            }
+                            let second = edge(&ends[j], &ends[k]);
+                            consider(
+                                &mut best,
+                                min,
+                                &[i, j, k],
+                                &[from_scores[i], first, second, to_scores[k]],
+                            );
+                        }
+                    }
+                }
+            }
+        }
+        if best.is_some() {
+            break;
+        }
+    }
+
+    let (path, transition_scores) = best?;
+    Some(Bridge {
+        tracks: path
+            .iter()
+            .map(|&i| {
+                let t = pool[i];
+                BridgeTrack {
+                    track_id: t.id.clone(),
+                    title: t.title.clone(),
+                    artist: t.artist.clone(),
+                    bpm: t.bpm,
+                    camelot: ends[i].camelot.map(|k| k.to_string()),
+                    energy: t.energy,
+                }
+            })
+            .collect(),
+        weakest_transition: transition_scores.iter().copied().fold(f64::MAX, f64::min),
+        transition_scores,
+        direct_score: edge(&from, &to),
+    })
+}
+
+/// Keep `path` if every hop clears `min` and it beats the chain held so far.
+/// Only a winning chain is copied.
+fn consider(best: &mut Option<(Vec<usize>, Vec<f64>)>, min: f64, path: &[usize], scores: &[f64]) {
+    if scores.iter().any(|&s| s < min) {
+        return;
+    }
+    let better = match best {
+        None => true,
+        Some((_, held)) => rank(scores) > rank(held),
+    };
+    if better {
+        *best = Some((path.to_vec(), scores.to_vec()));
+    }
+}
+
+/// Ordering key for same-length chains: weakest hop, then total.
+fn rank(scores: &[f64]) -> (f64, f64) {
+    let weakest = scores.iter().copied().fold(f64::MAX, f64::min);
+    // Round so float noise in the total cannot flip an otherwise equal pair
+    let total = (scores.iter().sum::<f64>() * 1e9).round() / 1e9;
+    (weakest, total)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: &str, camelot: &str, bpm: f64) -> TrackRow {
+        TrackRow {
+            id: id.to_string(),
+            title: format!("Title {id}"),
+            artist: None,
+            album: None,
+            duration_ms: None,
+            bpm: Some(bpm),
+            camelot_key: Some(camelot.to_string()),
+            energy: None,
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        }
+    }
+
+    fn end(camelot: &str, bpm: f64) -> BridgeEnd {
+        BridgeEnd::new(Some(camelot), Some(bpm))
+    }
+
+    fn ids(bridge: &Bridge) -> Vec<&str> {
+        bridge.tracks.iter().map(|t| t.track_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_single_track_bridge() {
+        // 8A -> 10A is a two-step move; 9A sits between them
+        let catalog = vec![track("far", "3B", 124.0), track("mid", "9A", 124.0)];
+        let bridge = find_bridge(
+            end("8A", 124.0),
+            end("10A", 124.0),
+            &catalog,
+            &BridgeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(ids(&bridge), vec!["mid"]);
+        assert_eq!(bridge.transition_scores.len(), 2);
+        assert!(bridge.weakest_transition >= DEFAULT_MIN_BRIDGE_SCORE);
+        assert!(bridge.direct_score < bridge.weakest_transition);
+    }
+
+    #[test]
+    fn test_single_track_bridge_beyond_frontier() {
+        // Forty same-key tracks outscore the one real bridge on the first hop
+        let mut catalog: Vec<TrackRow> = (0..40)
+            .map(|i| track(&format!("same{i}"), "8A", 124.0))
+            .collect();
+        catalog.push(track("mid", "9A", 124.0));
+        let bridge = find_bridge(
+            end("8A", 124.0),
+            end("10A", 124.0),
+            &catalog,
+            &BridgeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(ids(&bridge), vec!["mid"]);
+    }
+
+    #[test]
+    fn test_prefers_shortest_chain() {
+        // No single track links 8A to 11A; 9A -> 10A does, so the longer
+        // detours through 8B and 9B are never considered.
+        let catalog = vec![
+            track("9a", "9A", 124.0),
+            track("10a", "10A", 124.0),
+            track("8b", "8B", 124.0),
+            track("9b", "9B", 124.0),
+        ];
+        let bridge = find_bridge(
+            end("8A", 124.0),
+            end("11A", 124.0),
+            &catalog,
+            &BridgeOptions::default(),
+        )
+        .unwrap();
+        assert_eq!(ids(&bridge), vec!["9a", "10a"]);
+    }
+
+    #[test]
+    fn test_three_track_bridge_across_tempo() {
+        // 120 -> 132 BPM is too far in one hop; walk it up in key-safe steps
+        let catalog = vec![
+            track("a", "8A", 123.0),
+            track("b", "8A", 126.0),
+            track("c", "8A", 129.0),
+        ];
+        let options = BridgeOptions {
+            min_score: 0.8,
+            ..Default::default()
+        };
+        let bridge = find_bridge(end("8A", 120.0), end("8A", 132.0), &catalog, &options).unwrap();
+        assert_eq!(ids(&bridge), vec!["a", "b", "c"]);
+        assert_eq!(bridge.transition_scores.len(), 4);
+    }
+
+    #[test]
+    fn test_no_bridge_within_limit() {
+        let catalog = vec![track("a", "8A", 123.0), track("b", "8A", 126.0)];
+        let options = BridgeOptions {
+            min_score: 0.8,
+            max_tracks: 1,
+            ..Default::default()
+        };
+        assert!(find_bridge(end("8A", 120.0), end("8A", 132.0), &catalog, &options).is_none());
+    }
+
+    #[test]
+    fn test_excluded_tracks_are_skipped() {
+        let catalog = vec![track("mid", "9A", 124.0), track("alt", "9A", 124.0)];
+        let options = BridgeOptions {
+            exclude: HashSet::from(["mid".to_string()]),
+            ..Default::default()
+        };
+        let bridge = find_bridge(end("8A", 124.0), end("10A", 124.0), &catalog, &options).unwrap();
+        assert_eq!(ids(&bridge), vec!["alt"]);
+    }
+}
@@ -18,6 +18,124 @@ impl std::fmt::Display for CamelotKey {
     }
 }
 
+impl CamelotKey {
+    pub fn is_minor(&self) -> bool {
+        self.letter == 'A'
+    }
+
+    /// Pitch class of the tonic, 0 = C through 11 = B.
+    pub fn pitch_class(&self) -> u8 {
+        let table = if self.is_minor() {
+            &MINOR_CAMELOT
+        } else {
+            &MAJOR_CAMELOT
+        };
+        table
+            .iter()
+            .position(|&(number, _)| number == self.number)
+            .unwrap_or(0) as u8
+    }
+
+    /// Open Key notation: "1m" for A minor, "1d" for C major.
+    pub fn open_key(&self) -> String {
+        let number = (self.number + 4) % 12 + 1;
+        let suffix = if self.is_minor() { 'm' } else { 'd' };
+        format!("{number}{suffix}")
+    }
+
+    /// Musical notation, e.g. "A minor", "Db major".
+    pub fn musical(&self) -> String {
+        let scale = if self.is_minor() { "minor" } else { "major" };
+        format!("{} {scale}", self.note_name())
+    }
+
+    /// Beatport / Rekordbox style, e.g. "Am", "F#m", "Abmaj".
+    pub fn beatport(&self) -> String {
+        let suffix = if self.is_minor() { "m" } else { "maj" };
+        format!("{}{suffix}", self.note_name())
+    }
+
+    /// Render the key in `notation`.
+    pub fn format(&self, notation: KeyNotation) -> String {
+        match notation {
+            KeyNotation::Camelot => self.to_string(),
+            KeyNotation::OpenKey => self.open_key(),
+            KeyNotation::Musical => self.musical(),
+            KeyNotation::Beatport => self.beatport(),
+        }
+    }
+
+    /// Tonic spelled the way DJ software usually shows it (Db major but
+    /// C# minor).
+    fn note_name(&self) -> &'static str {
+        let names = if self.is_minor() {
+            &MINOR_NOTE_NAMES
+        } else {
+            &MAJOR_NOTE_NAMES
+        };
+        names[self.pitch_class() as usize]
+    }
+}
+
+/// How keys are written in API responses. Parsing accepts every notation
+/// regardless of this choice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum KeyNotation {
+    /// "8A"
+    #[default]
+    Camelot,
+    /// "1m"
+    OpenKey,
+    /// "A minor"
+    Musical,
+    /// "Am"
+    Beatport,
+}
+
+impl fmt::Display for KeyNotation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let s = match self {
+            KeyNotation::Camelot => "camelot",
+            KeyNotation::OpenKey => "open-key",
+            KeyNotation::Musical => "musical",
+            KeyNotation::Beatport => "beatport",
+        };
+        write!(f, "{s}")
+    }
+}
+
+impl FromStr for KeyNotation {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "camelot" => Ok(KeyNotation::Camelot),
+            "open-key" => Ok(KeyNotation::OpenKey),
+            "musical" => Ok(KeyNotation::Musical),
+            "beatport" => Ok(KeyNotation::Beatport),
+            other => Err(format!(
+                "Unknown key notation '{other}'. Valid: camelot, open-key, musical, beatport"
+            )),
+        }
+    }
+}
+
+/// Canonical Camelot string ("8A") for a key written in any notation, or
+/// `None` if it does not parse. Used wherever keys enter the system.
+pub fn normalize_key(key: Option<&str>) -> Option<String> {
+    key.and_then(parse_camelot).map(|k| k.to_string())
+}
+
+/// Re-render a stored key in `notation`. Keys that do not parse are passed
+/// through untouched rather than dropped.
+pub fn display_key(key: &str, notation: KeyNotation) -> String {
+    match parse_camelot(key) {
+        Some(k) => k.format(notation),
+        None => key.to_string(),
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Energy Profile
 // ---------------------------------------------------------------------------
@@ -64,25 +182,274 @@ impl TryFrom<&str> for EnergyProfile {
     }
 }
 
-/// Parse a Camelot notation string (e.g., "8A", "11B") into a CamelotKey.
+// ---------------------------------------------------------------------------
+// Custom Energy Curves
+// ---------------------------------------------------------------------------
+
+/// One point on a custom energy curve.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnergyBreakpoint {
+    /// Position in the set: 0.0 is the opener, 1.0 the closer.
+    pub position: f64,
+    /// Target energy (1-10) at that position.
+    pub energy: f64,
+}
+
+/// User-defined energy curve (e.g., a double-peak festival set).
+///
+/// Target energy is linearly interpolated between breakpoints and held flat
+/// before the first and after the last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnergyCurve {
+    pub breakpoints: Vec<EnergyBreakpoint>,
+}
+
+/// Upper bound on breakpoints per curve.
+pub const MAX_CURVE_BREAKPOINTS: usize = 32;
+
+impl EnergyCurve {
+    /// Check the curve is usable: 2-32 breakpoints, positions strictly
+    /// increasing within 0.0-1.0, energies within 1-10.
+    pub fn validate(&self) -> Result<(), String> {
+        let n = self.breakpoints.len();
+        if !(2..=MAX_CURVE_BREAKPOINTS).contains(&n) {
+            return Err(format!(
+                "energy curve needs 2-{MAX_CURVE_BREAKPOINTS} breakpoints, got {n}"
+            ));
+        }
+        for (i, bp) in self.breakpoints.iter().enumerate() {
+            if !(0.0..=1.0).contains(&bp.position) {
+                return Err(format!(
+                    "breakpoint {i}: position must be between 0.0 and 1.0, got {}",
+                    bp.position
+                ));
+            }
+            if !(1.0..=10.0).contains(&bp.energy) {
+                return Err(format!(
+                    "breakpoint {i}: energy must be between 1 and 10, got {}",
+                    bp.energy
+                ));
+            }
+            if i > 0 && bp.position <= self.breakpoints[i - 1].position {
+                return Err(format!(
+                    "breakpoint {i}: positions must be strictly increasing"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Target energy at `fraction` (0.0-1.0) through the set.
+    pub fn target_energy(&self, fraction: f64) -> f64 {
+        let (Some(first), Some(last)) = (self.breakpoints.first(), self.breakpoints.last()) else {
+            return ENERGY_CURVE_FALLBACK;
+        };
+        if fraction <= first.position {
+            return first.energy;
+        }
+        if fraction >= last.position {
+            return last.energy;
+        }
+        for pair in self.breakpoints.windows(2) {
+            let (a, b) = (pair[0], pair[1]);
+            if fraction <= b.position {
+                let t = (fraction - a.position) / (b.position - a.position);
+                return a.energy + t * (b.energy - a.energy);
+            }
+        }
+        last.energy
+    }
+
+    /// Human-readable form for prompts, e.g. "0% → 3, 50% → 9, 100% → 4".
+    pub fn describe(&self) -> String {
+        self.breakpoints
+            .iter()
+            .map(|bp| format!("{:.0}% → {}", bp.position * 100.0, bp.energy))
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Target used for a curve with no breakpoints (never valid, but never panics).
+const ENERGY_CURVE_FALLBACK: f64 = 6.0;
+
+/// One point of a [`KeyPath`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyWaypoint {
+    /// Position in the set: 0.0 is the opener, 1.0 the closer.
+    pub position: f64,
+    /// Target key at that position, in any notation [`parse_camelot`] accepts.
+    pub key: String,
+}
+
+/// User-defined harmonic journey (e.g., start in 5A, climb to 10B).
+///
+/// Between waypoints the target walks the wheel the short way round, one
+/// number at a time; the mode switches halfway through a segment. The target
+/// is held flat before the first waypoint and after the last.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyPath {
+    pub waypoints: Vec<KeyWaypoint>,
+}
+
+/// Upper bound on waypoints per key path.
+pub const MAX_KEY_PATH_WAYPOINTS: usize = 32;
+
+impl KeyPath {
+    /// Check the path is usable: 2-32 waypoints, positions strictly
+    /// increasing within 0.0-1.0, every key parseable.
+    pub fn validate(&self) -> Result<(), String> {
+        let n = self.waypoints.len();
+        if !(2..=MAX_KEY_PATH_WAYPOINTS).contains(&n) {
+            return Err(format!(
+                "key path needs 2-{MAX_KEY_PATH_WAYPOINTS} waypoints, got {n}"
+            ));
+        }
+        for (i, wp) in self.waypoints.iter().enumerate() {
+            if !(0.0..=1.0).contains(&wp.position) {
+                return Err(format!(
+                    "waypoint {i}: position must be between 0.0 and 1.0, got {}",
+                    wp.position
+                ));
+            }
+            if parse_camelot(&wp.key).is_none() {
+                return Err(format!("waypoint {i}: unrecognised key '{}'", wp.key));
+            }
+            if i > 0 && wp.position <= self.waypoints[i - 1].position {
+                return Err(format!(
+                    "waypoint {i}: positions must be strictly increasing"
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Target key at `fraction` (0.0-1.0) through the set; `None` if the
+    /// path has no parseable waypoints.
+    pub fn target_key(&self, fraction: f64) -> Option<CamelotKey> {
+        let points: Vec<(f64, CamelotKey)> = self
+            .waypoints
+            .iter()
+            .filter_map(|wp| Some((wp.position, parse_camelot(&wp.key)?)))
+            .collect();
+        let (first, last) = (points.first()?, points.last()?);
+        if fraction <= first.0 {
+            return Some(first.1);
+        }
+        if fraction >= last.0 {
+            return Some(last.1);
+        }
+        for pair in points.windows(2) {
+            let ((pa, a), (pb, b)) = (pair[0], pair[1]);
+            if fraction <= pb {
+                let t = (fraction - pa) / (pb - pa);
+                let clockwise = wheel_step(a.number, b.number) as f64;
+                let steps = if clockwise <= 6.0 {
+                    clockwise
+                } else {
+                    clockwise - 12.0
+                };
+                let number = (a.number as f64 - 1.0 + (t * steps).round()).rem_euclid(12.0);
+                return Some(CamelotKey {
+                    number: number as u8 + 1,
+                    letter: if t < 0.5 { a.letter } else { b.letter },
+                });
+            }
+        }
+        Some(last.1)
+    }
+
+    /// Human-readable form for prompts, e.g. "0% → 5A, 100% → 10B".
+    pub fn describe(&self) -> String {
+        self.waypoints
+            .iter()
+            .map(|wp| {
+                let key = parse_camelot(&wp.key)
+                    .map(|k| k.to_string())
+                    .unwrap_or_else(|| wp.key.clone());
+                format!("{:.0}% → {key}", wp.position * 100.0)
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Parse a key in any supported notation into a CamelotKey.
+///
+/// Accepts Camelot ("8A", "8a"), Open Key ("1m", "1d"), musical ("A minor",
+/// "Db major") and Beatport/Rekordbox style ("Am", "F#m", "Abmaj"). Note
+/// names need an explicit mode; a bare "A" is rejected.
 pub fn parse_camelot(notation: &str) -> Option<CamelotKey> {
     let notation = notation.trim();
     if notation.is_empty() {
         return None;
     }
+    if notation.starts_with(|c: char| c.is_ascii_digit()) {
+        parse_numbered_key(notation)
+    } else {
+        parse_named_key(notation)
+    }
+}
 
-    let letter = notation.chars().last()?;
-    if letter != 'A' && letter != 'B' {
+/// Camelot ("8A") or Open Key ("1m"): a wheel number and a one-letter mode.
+fn parse_numbered_key(notation: &str) -> Option<CamelotKey> {
+    let suffix = notation.chars().last()?;
+    let number: u8 = notation[..notation.len() - suffix.len_utf8()]
+        .parse()
+        .ok()?;
+    if !(1..=12).contains(&number) {
         return None;
     }
 
-    let number_str = &notation[..notation.len() - 1];
-    let number: u8 = number_str.parse().ok()?;
-    if !(1..=12).contains(&number) {
+    match suffix {
+        'A' | 'a' => Some(CamelotKey {
+            number,
+            letter: 'A',
+        }),
+        'B' | 'b' => Some(CamelotKey {
+            number,
+            letter: 'B',
+        }),
+        // Open Key 1 sits at Camelot 8
+        'm' | 'M' | 'd' | 'D' => Some(CamelotKey {
+            number: (number + 6) % 12 + 1,
+            letter: if suffix.eq_ignore_ascii_case(&'m') {
+                'A'
+            } else {
+                'B'
+            },
+        }),
+        _ => None,
+    }
+}
+
+/// Note name, optional accidental, then a scale word or suffix.
+fn parse_named_key(notation: &str) -> Option<CamelotKey> {
+    let mut chars = notation.chars().peekable();
+    let letter = chars.next()?;
+    if !('a'..='g').contains(&letter.to_ascii_lowercase()) {
         return None;
     }
+    let mut note = letter.to_string();
+    match chars.peek() {
+        Some('#' | '♯') => {
+            note.push('#');
+            chars.next();
+        }
+        Some('b' | '♭') => {
+            note.push('b');
+            chars.next();
+        }
+        _ => {}
+    }
 
-    Some(CamelotKey { number, letter })
+    let rest: String = chars.collect();
+    let mode = match rest.trim().trim_end_matches('.').to_lowercase().as_str() {
+        "maj" | "major" => 1,
+        "m" | "min" | "minor" => 0,
+        _ => return None,
+    };
+    from_spotify_key(note_to_pitch_class(&note)?, mode)
 }
 
 // ---------------------------------------------------------------------------
@@ -177,71 +544,396 @@ fn note_to_pitch_class(note: &str) -> Option<i32> {
     }
 }
 
+/// Tonic names for major keys by pitch class, flats as DJ software shows them.
+const MAJOR_NOTE_NAMES: [&str; 12] = [
+    "C", "Db", "D", "Eb", "E", "F", "F#", "G", "Ab", "A", "Bb", "B",
+];
+
+/// Tonic names for minor keys by pitch class.
+const MINOR_NOTE_NAMES: [&str; 12] = [
+    "C", "C#", "D", "Eb", "E", "F", "F#", "G", "G#", "A", "Bb", "B",
+];
+
+// ---------------------------------------------------------------------------
+// Scoring Profile
+// ---------------------------------------------------------------------------
+
+/// Upper bounds, in BPM of effective change, of the bands that score 1.0,
+/// 0.8, 0.6 and 0.3. Anything wider scores 0.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BpmBands {
+    pub perfect: f64,
+    pub close: f64,
+    pub workable: f64,
+    pub stretch: f64,
+}
+
+impl Default for BpmBands {
+    fn default() -> Self {
+        Self {
+            perfect: 2.0,
+            close: 4.0,
+            workable: 6.0,
+            stretch: 10.0,
+        }
+    }
+}
+
+/// Per-user transition scoring: how much key, BPM and energy count, and how
+/// forgiving BPM changes are. The default reproduces the built-in scoring.
+///
+/// Weights are relative; [`transition_score_with_profile`] divides by their sum.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ScoringProfile {
+    pub key_weight: f64,
+    pub bpm_weight: f64,
+    pub energy_weight: f64,
+    pub bpm_bands: BpmBands,
+    /// Effective BPM change above which a transition gets a BPM warning.
+    pub bpm_warning_threshold: f64,
+}
+
+impl Default for ScoringProfile {
+    fn default() -> Self {
+        Self {
+            key_weight: 0.5,
+            bpm_weight: 0.3,
+            energy_weight: 0.2,
+            bpm_bands: BpmBands::default(),
+            bpm_warning_threshold: 6.0,
+        }
+    }
+}
+
+impl ScoringProfile {
+    /// Weights must be non-negative with a positive sum; bands must be
+    /// positive and widen strictly.
+    pub fn validate(&self) -> Result<(), String> {
+        let weights = [
+            ("key_weight", self.key_weight),
+            ("bpm_weight", self.bpm_weight),
+            ("energy_weight", self.energy_weight),
+        ];
+        for (name, w) in weights {
+            if !w.is_finite() || w < 0.0 {
+                return Err(format!("{name} must be a non-negative number, got {w}"));
+            }
+        }
+        if self.key_weight + self.bpm_weight + self.energy_weight <= 0.0 {
+            return Err("at least one weight must be positive".to_string());
+        }
+        let b = &self.bpm_bands;
+        let bands = [b.perfect, b.close, b.workable, b.stretch];
+        if bands.iter().any(|v| !v.is_finite() || *v <= 0.0)
+            || bands.windows(2).any(|w| w[0] >= w[1])
+        {
+            return Err(
+                "bpm_bands must be positive and increase: perfect < close < workable < stretch"
+                    .to_string(),
+            );
+        }
+        if !self.bpm_warning_threshold.is_finite() || self.bpm_warning_threshold <= 0.0 {
+            return Err(format!(
+                "bpm_warning_threshold must be positive, got {}",
+                self.bpm_warning_threshold
+            ));
+        }
+        Ok(())
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Scoring Functions
 // ---------------------------------------------------------------------------
 
-/// Key compatibility score between two Camelot keys based on the Camelot wheel.
+/// Named kind of move between two Camelot keys.
 ///
-/// The Camelot wheel defines three standard DJ-compatible key transitions:
+/// Mirrors the transition vocabulary DJs use when planning harmonic mixes, so
+/// setlist notes can say "energy boost" instead of just reporting a score.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TransitionType {
+    /// Same key (e.g., 8A -> 8A).
+    SameKey,
+    /// One step around the wheel, same letter (e.g., 8A -> 9A).
+    Adjacent,
+    /// Same number, cross letter (e.g., 8A -> 8B).
+    RelativeMajorMinor,
+    /// Minor up into major (8A -> 9B) or major down into minor (8B -> 7A).
+    Diagonal,
+    /// Two steps up the wheel, same letter (e.g., 8A -> 10A).
+    TwoStepBoost,
+    /// Two steps down the wheel, same letter (e.g., 10A -> 8A).
+    TwoStepDrop,
+    /// Seven steps up the wheel = one semitone up (e.g., 8A -> 3A).
+    EnergyBoost,
+    /// Anything else: not a recognized harmonic move.
+    Clash,
+}
+
+impl TransitionType {
+    /// Key compatibility score for this kind of move.
+    pub fn score(self) -> f64 {
+        match self {
+            TransitionType::SameKey => 1.0,
+            TransitionType::Adjacent | TransitionType::RelativeMajorMinor => 0.9,
+            TransitionType::Diagonal => 0.7,
+            TransitionType::EnergyBoost => 0.6,
+            TransitionType::TwoStepBoost | TransitionType::TwoStepDrop => 0.5,
+            TransitionType::Clash => 0.0,
+        }
+    }
+}
+
+impl fmt::Display for TransitionType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransitionType::SameKey => write!(f, "same key"),
+            TransitionType::Adjacent => write!(f, "adjacent"),
+            TransitionType::RelativeMajorMinor => write!(f, "relative major/minor"),
+            TransitionType::Diagonal => write!(f, "diagonal"),
+            TransitionType::TwoStepBoost => write!(f, "two-step boost"),
+            TransitionType::TwoStepDrop => write!(f, "two-step drop"),
+            TransitionType::EnergyBoost => write!(f, "energy boost"),
+            TransitionType::Clash => write!(f, "key clash"),
+        }
+    }
+}
+
+/// Classify the move from key `a` to key `b` on the Camelot wheel.
 ///
-/// 1. **Same key** (e.g., 8A -> 8A): Perfect match, score = 1.0
-/// 2. **Adjacent on the wheel** (same letter, ±1 number, e.g., 8A -> 9A):
-///    Moving one step around the wheel's outer (A) or inner (B) ring. Score = 0.9
-/// 3. **Relative major/minor** (same number, cross letter, e.g., 8A -> 8B):
-///    Switching between the outer and inner ring at the same position. Score = 0.9
+/// Direction matters: 8A -> 10A is a two-step boost while 10A -> 8A is a
+/// two-step drop, and only +7 (one semitone up) counts as an energy boost.
+pub fn classify_transition(a: &CamelotKey, b: &CamelotKey) -> TransitionType {
+    let step = wheel_step(a.number, b.number);
+
+    if a.letter == b.letter {
+        match step {
+            0 => TransitionType::SameKey,
+            1 | 11 => TransitionType::Adjacent,
+            2 => TransitionType::TwoStepBoost,
+            10 => TransitionType::TwoStepDrop,
+            7 => TransitionType::EnergyBoost,
+            _ => TransitionType::Clash,
+        }
+    } else {
+        // Diagonal moves follow the wheel's "up into major, down into minor"
+        // convention: 8A -> 9B and 8B -> 7A. The mirrored moves (8A -> 7B,
+        // 8B -> 9A) land a fifth away from the relative key and clash.
+        match (step, a.letter) {
+            (0, _) => TransitionType::RelativeMajorMinor,
+            (1, 'A') | (11, 'B') => TransitionType::Diagonal,
+            _ => TransitionType::Clash,
+        }
+    }
+}
+
+/// Key compatibility score between two Camelot keys based on the Camelot wheel.
+///
+/// Scores come from the [`TransitionType`] of the move:
 ///
-/// Same-letter ±2 numbers (e.g., 8A -> 10A) is a tolerable but noticeable key
-/// shift, scored at 0.5 as a moderate compatibility.
+/// 1. **Same key** (8A -> 8A): 1.0
+/// 2. **Adjacent** (8A -> 9A) and **relative major/minor** (8A -> 8B): 0.9
+/// 3. **Diagonal** (8A -> 9B, 8B -> 7A): 0.7 — one step around the wheel plus a mode switch
+/// 4. **Energy boost** (8A -> 3A, +7 = one semitone up): 0.6
+/// 5. **Two-step boost/drop** (8A -> 10A, 10A -> 8A): 0.5
 ///
-/// All other transitions — including cross-letter with different numbers
-/// (e.g., 8A -> 9B) — return 0.0. This is intentional: the Camelot wheel only
-/// guarantees smooth mixing along its three standard transitions. Combining a
-/// cross-letter and different-number move produces two incompatible shifts and
-/// is not a recognized DJ-safe transition.
+/// Everything else is a clash and scores 0.0.
 pub fn camelot_score(a: &CamelotKey, b: &CamelotKey) -> f64 {
-    if a.number == b.number && a.letter == b.letter {
-        return 1.0; // Transition 1: Same key
-    }
+    classify_transition(a, b).score()
+}
+
+/// How the tempo of one track relates to the next once beatmatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TempoRelation {
+    /// 1:1 — played at (roughly) the same tempo.
+    Direct,
+    /// Next track runs at half the tempo (e.g., 174 → 87).
+    HalfTime,
+    /// Next track runs at double the tempo (e.g., 70 → 140).
+    DoubleTime,
+    /// Next track runs at two thirds of the tempo (3:2, e.g., 120 → 80).
+    ThreeToTwo,
+    /// Next track runs at one and a half times the tempo (2:3, e.g., 80 → 120).
+    TwoToThree,
+}
 
-    // Transition 3: Relative major/minor (same number, cross letter)
-    if a.number == b.number && a.letter != b.letter {
-        return 0.9;
+impl TempoRelation {
+    /// Multiplier applied to the first track's BPM to get its tempo in the
+    /// second track's frame of reference.
+    fn ratio(self) -> f64 {
+        match self {
+            TempoRelation::Direct => 1.0,
+            TempoRelation::HalfTime => 0.5,
+            TempoRelation::DoubleTime => 2.0,
+            TempoRelation::ThreeToTwo => 2.0 / 3.0,
+            TempoRelation::TwoToThree => 1.5,
+        }
     }
 
-    // Transition 2 (and ±2 tolerance): Same letter, check wheel distance
-    if a.letter == b.letter {
-        let diff = wheel_distance(a.number, b.number);
-        if diff == 1 {
-            return 0.9; // Adjacent on wheel
+    /// Score multiplier: 2:1 mixes are textbook, 3:2 mixes shift the groove.
+    fn weight(self) -> f64 {
+        match self {
+            TempoRelation::Direct | TempoRelation::HalfTime | TempoRelation::DoubleTime => 1.0,
+            TempoRelation::ThreeToTwo | TempoRelation::TwoToThree => 0.8,
         }
-        if diff == 2 {
-            return 0.5; // Tolerable shift
+    }
+}
+
+impl fmt::Display for TempoRelation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TempoRelation::Direct => write!(f, "direct"),
+            TempoRelation::HalfTime => write!(f, "half-time"),
+            TempoRelation::DoubleTime => write!(f, "double-time"),
+            TempoRelation::ThreeToTwo => write!(f, "3:2"),
+            TempoRelation::TwoToThree => write!(f, "2:3"),
         }
     }
+}
 
-    // All other combinations (including cross-letter + different-number) are
-    // incompatible per Camelot wheel rules.
-    0.0
+/// Best tempo relation between two BPMs, with its score.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BpmMatch {
+    pub score: f64,
+    pub relation: TempoRelation,
+    /// Signed BPM change once the first track is scaled by the relation's ratio.
+    pub effective_delta: f64,
 }
 
-/// BPM compatibility score.
-/// 0-2 → 1.0, 3-4 → 0.8, 5-6 → 0.6, 7-10 → 0.3, >10 → 0.0
-pub fn bpm_score(a: f64, b: f64) -> f64 {
-    let diff = (a - b).abs();
-    if diff <= 2.0 {
+/// Relations tried in order; ties keep the earlier (simpler) one.
+const TEMPO_RELATIONS: [TempoRelation; 5] = [
+    TempoRelation::Direct,
+    TempoRelation::HalfTime,
+    TempoRelation::DoubleTime,
+    TempoRelation::ThreeToTwo,
+    TempoRelation::TwoToThree,
+];
+
+/// Score a raw BPM difference against the given tolerance bands.
+/// Defaults: 0-2 → 1.0, 3-4 → 0.8, 5-6 → 0.6, 7-10 → 0.3, >10 → 0.0
+fn bpm_delta_score(diff: f64, bands: &BpmBands) -> f64 {
+    let diff = diff.abs();
+    if diff <= bands.perfect {
         1.0
-    } else if diff <= 4.0 {
+    } else if diff <= bands.close {
         0.8
-    } else if diff <= 6.0 {
+    } else if diff <= bands.workable {
         0.6
-    } else if diff <= 10.0 {
+    } else if diff <= bands.stretch {
         0.3
     } else {
         0.0
     }
 }
 
+/// Find the tempo relation that makes `a → b` easiest to mix.
+///
+/// Tries 1:1, 2:1 (half/double time) and 3:2 ratios, scoring each on the
+/// effective BPM difference. 87 → 174 is a double-time mix scoring 1.0 rather
+/// than a 87 BPM jump scoring 0.0.
+pub fn bpm_match(a: f64, b: f64) -> BpmMatch {
+    bpm_match_with_bands(a, b, &BpmBands::default())
+}
+
+/// [`bpm_match`] scored against a user's tolerance bands.
+pub fn bpm_match_with_bands(a: f64, b: f64, bands: &BpmBands) -> BpmMatch {
+    let mut best: Option<BpmMatch> = None;
+    for relation in TEMPO_RELATIONS {
+        let effective_delta = b - a * relation.ratio();
+        let score = bpm_delta_score(effective_delta, bands) * relation.weight();
+        let better = match best {
+            None => true,
+            Some(current) => {
+                score > current.score
+                    || (score == current.score
+                        && effective_delta.abs() < current.effective_delta.abs() - 1e-9)
+            }
+        };
+        if better {
+            best = Some(BpmMatch {
+                score,
+                relation,
+                effective_delta,
+            });
+        }
+    }
+    best.expect("TEMPO_RELATIONS is non-empty")
+}
+
+/// BPM compatibility score, aware of half-time/double-time and 3:2 mixes.
+/// See [`bpm_match`] for the relation that produced the score.
+pub fn bpm_score(a: f64, b: f64) -> f64 {
+    bpm_match(a, b).score
+}
+
+/// Whether `bpm` fits `[min, max]` directly or at half/double tempo.
+///
+/// Returns the relation that places it in range (preferring direct), so a
+/// 70 BPM track counts for a 130-150 range as double-time.
+pub fn bpm_in_range(bpm: f64, min: f64, max: f64) -> Option<TempoRelation> {
+    [
+        TempoRelation::Direct,
+        TempoRelation::DoubleTime,
+        TempoRelation::HalfTime,
+    ]
+    .into_iter()
+    .find(|relation| {
+        let effective = bpm * relation.ratio();
+        effective >= min && effective <= max
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Pitch-fader key shift
+// ---------------------------------------------------------------------------
+
+/// Pitch-fader change (in %) applied to the incoming track `b` so it
+/// beatmatches the outgoing track `a`, using the best [`TempoRelation`].
+///
+/// 128 → 121 needs roughly +5.8%; 87 → 174 (double-time) needs 0%.
+pub fn pitch_percent(bpm_a: f64, bpm_b: f64) -> f64 {
+    let target = bpm_a * bpm_match(bpm_a, bpm_b).relation.ratio();
+    (target / bpm_b - 1.0) * 100.0
+}
+
+/// Semitones a pitch change moves the key when key lock is off, rounded to
+/// the nearest semitone (≈5.9% per semitone).
+pub fn pitch_semitones(pitch_percent: f64) -> i32 {
+    (12.0 * (1.0 + pitch_percent / 100.0).log2()).round() as i32
+}
+
+/// Transpose a Camelot key by `semitones`. One semitone is 7 steps on the
+/// wheel; the letter (minor/major) never changes.
+pub fn shift_key(key: &CamelotKey, semitones: i32) -> CamelotKey {
+    let number = (key.number as i32 - 1 + 7 * semitones.rem_euclid(12)).rem_euclid(12) + 1;
+    CamelotKey {
+        number: number as u8,
+        letter: key.letter,
+    }
+}
+
+/// Key the incoming track `b` actually sounds in once pitched to `bpm_a`.
+///
+/// With key lock on, or a BPM missing or not positive, this is `b` unchanged.
+pub fn effective_key(
+    b: &CamelotKey,
+    bpm_a: Option<f64>,
+    bpm_b: Option<f64>,
+    key_lock: bool,
+) -> CamelotKey {
+    let usable = |bpm: f64| bpm.is_finite() && bpm > 0.0;
+    match (key_lock, bpm_a, bpm_b) {
+        (false, Some(a), Some(bb)) if usable(a) && usable(bb) => {
+            shift_key(b, pitch_semitones(pitch_percent(a, bb)))
+        }
+        _ => *b,
+    }
+}
+
 /// Energy arc score: rewards build/peak/cooldown progression.
 /// Position-based: first third = build (low energy), middle = peak, last third = cooldown.
 pub fn energy_arc_score(energy: i32, position: usize, total: usize) -> f64 {
@@ -324,6 +1016,43 @@ pub fn energy_arc_score_with_profile(
     (1.0 - diff / 10.0).clamp(0.0, 1.0)
 }
 
+/// Energy arc score against a custom [`EnergyCurve`].
+///
+/// Same scale as [`energy_arc_score_with_profile`]: 1.0 - |actual - ideal| / 10.
+pub fn energy_arc_score_with_curve(
+    energy: i32,
+    position: usize,
+    total: usize,
+    curve: &EnergyCurve,
+) -> f64 {
+    if total <= 1 {
+        return 1.0;
+    }
+
+    let fraction = position as f64 / (total - 1) as f64;
+    let diff = (energy as f64 - curve.target_energy(fraction)).abs();
+    (1.0 - diff / 10.0).clamp(0.0, 1.0)
+}
+
+/// Score of a track's key against a [`KeyPath`] at its position.
+///
+/// Distance is wheel steps (short way round) plus one for a mode change, so
+/// 0-7. Score = 1.0 - distance / 7: 1.0 on target, 0.0 at the far side of
+/// the wheel in the other mode.
+pub fn key_path_score(camelot: &CamelotKey, position: usize, total: usize, path: &KeyPath) -> f64 {
+    let fraction = if total <= 1 {
+        0.0
+    } else {
+        position as f64 / (total - 1) as f64
+    };
+    let Some(target) = path.target_key(fraction) else {
+        return 0.5;
+    };
+    let clockwise = wheel_step(target.number, camelot.number);
+    let distance = clockwise.min(12 - clockwise) + u8::from(target.letter != camelot.letter);
+    (1.0 - distance as f64 / 7.0).clamp(0.0, 1.0)
+}
+
 /// Neutral energy score used in pair-wise transition scoring.
 ///
 /// Energy scoring is intentionally handled as a *positional* concern by
@@ -347,28 +1076,66 @@ pub fn transition_score(
     camelot_b: Option<&CamelotKey>,
     bpm_a: Option<f64>,
     bpm_b: Option<f64>,
+) -> f64 {
+    transition_score_with_key_lock(camelot_a, camelot_b, bpm_a, bpm_b, true)
+}
+
+/// [`transition_score`] with a choice of pitch model.
+///
+/// With `key_lock` off, harmony is scored against the incoming track's key
+/// after the pitch fader has moved it (see [`effective_key`]).
+pub fn transition_score_with_key_lock(
+    camelot_a: Option<&CamelotKey>,
+    camelot_b: Option<&CamelotKey>,
+    bpm_a: Option<f64>,
+    bpm_b: Option<f64>,
+    key_lock: bool,
+) -> f64 {
+    transition_score_with_profile(
+        camelot_a,
+        camelot_b,
+        bpm_a,
+        bpm_b,
+        key_lock,
+        &ScoringProfile::default(),
+    )
+}
+
+/// [`transition_score_with_key_lock`] weighted and banded by a user's
+/// [`ScoringProfile`]. The energy term stays neutral, so with the default
+/// weights the maximum is still 0.9.
+pub fn transition_score_with_profile(
+    camelot_a: Option<&CamelotKey>,
+    camelot_b: Option<&CamelotKey>,
+    bpm_a: Option<f64>,
+    bpm_b: Option<f64>,
+    key_lock: bool,
+    profile: &ScoringProfile,
 ) -> f64 {
     let key_score = match (camelot_a, camelot_b) {
-        (Some(a), Some(b)) => camelot_score(a, b),
+        (Some(a), Some(b)) => camelot_score(a, &effective_key(b, bpm_a, bpm_b, key_lock)),
         _ => 0.5, // neutral when missing
     };
 
     let bpm_s = match (bpm_a, bpm_b) {
-        (Some(a), Some(b)) => bpm_score(a, b),
+        (Some(a), Some(b)) => bpm_match_with_bands(a, b, &profile.bpm_bands).score,
         _ => 0.5, // neutral when missing
     };
 
-    key_score * 0.5 + bpm_s * 0.3 + ENERGY_NEUTRAL * 0.2
+    let total_weight = profile.key_weight + profile.bpm_weight + profile.energy_weight;
+    (key_score * profile.key_weight
+        + bpm_s * profile.bpm_weight
+        + ENERGY_NEUTRAL * profile.energy_weight)
+        / total_weight
 }
 
 // ---------------------------------------------------------------------------
 // Helpers
 // ---------------------------------------------------------------------------
 
-/// Distance on the Camelot wheel (wraps 12↔1).
-fn wheel_distance(a: u8, b: u8) -> u8 {
-    let diff = (a as i8 - b as i8).unsigned_abs();
-    diff.min(12 - diff)
+/// Clockwise steps from `a` to `b` on the Camelot wheel (0-11, wraps 12→1).
+fn wheel_step(a: u8, b: u8) -> u8 {
+    ((b as i16 - a as i16).rem_euclid(12)) as u8
 }
 
 /// Score breakdown for arrangement results.
@@ -377,6 +1144,15 @@ pub struct ScoreBreakdown {
     pub key_compatibility: f64,
     pub bpm_continuity: f64,
     pub energy_arc: f64,
+    /// Average [`key_path_score`] (0-100) when a target key path was given.
+    pub key_journey: Option<f64>,
+    /// Kind of key move for each adjacent pair; `None` when either key is unknown.
+    pub key_transitions: Vec<Option<TransitionType>>,
+    /// Tempo relation for each adjacent pair; `None` when either BPM is unknown.
+    pub tempo_relations: Vec<Option<TempoRelation>>,
+    /// Pitch-fader % applied to the incoming track for each adjacent pair;
+    /// `None` when either BPM is unknown.
+    pub pitch_adjustments: Vec<Option<f64>>,
 }
 
 // ---------------------------------------------------------------------------
@@ -433,6 +1209,69 @@ mod tests {
         assert!(parse_camelot("8C").is_none());
         assert!(parse_camelot("AB").is_none());
         assert!(parse_camelot("A").is_none());
+        assert!(parse_camelot("H").is_none());
+        assert!(parse_camelot("8Am").is_none());
+        assert!(parse_camelot("13m").is_none());
+        assert!(parse_camelot("A dorian").is_none());
+    }
+
+    #[test]
+    fn test_parse_other_notations() {
+        let key = |s: &str| parse_camelot(s).map(|k| k.to_string());
+        // Open Key
+        assert_eq!(key("1m").as_deref(), Some("8A"));
+        assert_eq!(key("1d").as_deref(), Some("8B"));
+        assert_eq!(key("6m").as_deref(), Some("1A"));
+        assert_eq!(key("12d").as_deref(), Some("7B"));
+        // Lowercase Camelot
+        assert_eq!(key("8a").as_deref(), Some("8A"));
+        // Musical and Beatport/Rekordbox
+        assert_eq!(key("A minor").as_deref(), Some("8A"));
+        assert_eq!(key("Db Major").as_deref(), Some("3B"));
+        assert_eq!(key("Am").as_deref(), Some("8A"));
+        assert_eq!(key("F#m").as_deref(), Some("11A"));
+        assert_eq!(key("Abmaj").as_deref(), Some("4B"));
+        assert_eq!(key("Bbm").as_deref(), Some("3A"));
+        assert_eq!(key("bm").as_deref(), Some("10A"));
+        assert_eq!(key("E♭ min.").as_deref(), Some("2A"));
+        assert_eq!(key("Amaj").as_deref(), Some("11B"));
+        // A bare note has no mode
+        assert!(key("C").is_none());
+    }
+
+    #[test]
+    fn test_every_notation_round_trips() {
+        for letter in ['A', 'B'] {
+            for number in 1..=12 {
+                let k = CamelotKey { number, letter };
+                for notation in [
+                    KeyNotation::Camelot,
+                    KeyNotation::OpenKey,
+                    KeyNotation::Musical,
+                    KeyNotation::Beatport,
+                ] {
+                    let text = k.format(notation);
+                    assert_eq!(parse_camelot(&text), Some(k), "{notation} {text}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_format_key_notations() {
+        let a_minor = parse_camelot("8A").unwrap();
+        assert_eq!(a_minor.open_key(), "1m");
+        assert_eq!(a_minor.musical(), "A minor");
+        assert_eq!(a_minor.beatport(), "Am");
+        let d_flat = parse_camelot("3B").unwrap();
+        assert_eq!(d_flat.format(KeyNotation::OpenKey), "8d");
+        assert_eq!(d_flat.format(KeyNotation::Musical), "Db major");
+        assert_eq!(d_flat.format(KeyNotation::Beatport), "Dbmaj");
+        assert_eq!(parse_camelot("12A").unwrap().beatport(), "C#m");
+        assert_eq!(display_key("Am", KeyNotation::Camelot), "8A");
+        assert_eq!(display_key("unknown", KeyNotation::Musical), "unknown");
+        assert_eq!("open-key".parse(), Ok(KeyNotation::OpenKey));
+        assert!("sharp".parse::<KeyNotation>().is_err());
     }
 
     // --- camelot_score ---
@@ -524,6 +1363,95 @@ mod tests {
         assert_eq!(camelot_score(&a, &b), 0.5); // 2 apart on wheel
     }
 
+    // --- classify_transition ---
+
+    fn key(notation: &str) -> CamelotKey {
+        parse_camelot(notation).unwrap()
+    }
+
+    #[test]
+    fn test_classify_standard_moves() {
+        assert_eq!(
+            classify_transition(&key("8A"), &key("8A")),
+            TransitionType::SameKey
+        );
+        assert_eq!(
+            classify_transition(&key("8A"), &key("7A")),
+            TransitionType::Adjacent
+        );
+        assert_eq!(
+            classify_transition(&key("8A"), &key("8B")),
+            TransitionType::RelativeMajorMinor
+        );
+    }
+
+    #[test]
+    fn test_classify_energy_boost() {
+        // +7 on the wheel = one semitone up
+        let kind = classify_transition(&key("8A"), &key("3A"));
+        assert_eq!(kind, TransitionType::EnergyBoost);
+        assert_eq!(kind.to_string(), "energy boost");
+        assert_eq!(camelot_score(&key("8A"), &key("3A")), 0.6);
+        // Wraps past 12
+        assert_eq!(
+            classify_transition(&key("12B"), &key("7B")),
+            TransitionType::EnergyBoost
+        );
+        // The reverse direction (one semitone down) is not a boost
+        assert_eq!(
+            classify_transition(&key("3A"), &key("8A")),
+            TransitionType::Clash
+        );
+    }
+
+    #[test]
+    fn test_classify_two_step_direction() {
+        assert_eq!(
+            classify_transition(&key("8A"), &key("10A")),
+            TransitionType::TwoStepBoost
+        );
+        assert_eq!(
+            classify_transition(&key("10A"), &key("8A")),
+            TransitionType::TwoStepDrop
+        );
+        assert_eq!(
+            classify_transition(&key("11B"), &key("1B")),
+            TransitionType::TwoStepBoost
+        );
+    }
+
+    #[test]
+    fn test_classify_diagonal() {
+        assert_eq!(
+            classify_transition(&key("8A"), &key("9B")),
+            TransitionType::Diagonal
+        );
+        assert_eq!(
+            classify_transition(&key("1B"), &key("12A")),
+            TransitionType::Diagonal
+        );
+        assert_eq!(camelot_score(&key("8A"), &key("9B")), 0.7);
+        // Mirrored directions are not diagonal moves
+        assert_eq!(
+            classify_transition(&key("8A"), &key("7B")),
+            TransitionType::Clash
+        );
+        assert_eq!(
+            classify_transition(&key("8B"), &key("9A")),
+            TransitionType::Clash
+        );
+        // Cross letter two steps away is still a clash
+        assert_eq!(camelot_score(&key("8A"), &key("10B")), 0.0);
+    }
+
+    #[test]
+    fn test_transition_type_serde() {
+        let json = serde_json::to_string(&TransitionType::EnergyBoost).unwrap();
+        assert_eq!(json, "\"energy-boost\"");
+        let parsed: TransitionType = serde_json::from_str("\"two-step-boost\"").unwrap();
+        assert_eq!(parsed, TransitionType::TwoStepBoost);
+    }
+
     // --- bpm_score ---
 
     #[test]
@@ -552,6 +1480,294 @@ mod tests {
         assert_eq!(bpm_score(128.0, 145.0), 0.0);
     }
 
+    #[test]
+    fn test_bpm_score_double_and_half_time() {
+        assert_eq!(bpm_score(87.0, 174.0), 1.0);
+        assert_eq!(bpm_score(140.0, 70.0), 1.0);
+        assert_eq!(bpm_score(70.0, 143.0), 0.8);
+
+        let m = bpm_match(87.0, 174.0);
+        assert_eq!(m.relation, TempoRelation::DoubleTime);
+        assert!(m.effective_delta.abs() < 0.001);
+        assert_eq!(bpm_match(174.0, 87.0).relation, TempoRelation::HalfTime);
+    }
+
+    #[test]
+    fn test_bpm_score_three_two() {
+        // 120 → 80 is exactly 3:2, weighted below a straight mix
+        let m = bpm_match(120.0, 80.0);
+        assert_eq!(m.relation, TempoRelation::ThreeToTwo);
+        assert!((m.score - 0.8).abs() < 0.001);
+        assert_eq!(bpm_match(80.0, 120.0).relation, TempoRelation::TwoToThree);
+    }
+
+    #[test]
+    fn test_bpm_match_prefers_direct() {
+        let m = bpm_match(128.0, 130.0);
+        assert_eq!(m.relation, TempoRelation::Direct);
+        assert!((m.effective_delta - 2.0).abs() < 0.001);
+    }
+
+    // --- EnergyCurve ---
+
+    fn double_peak() -> EnergyCurve {
+        EnergyCurve {
+            breakpoints: vec![
+                EnergyBreakpoint {
+                    position: 0.0,
+                    energy: 4.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.3,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.5,
+                    energy: 5.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.8,
+                    energy: 10.0,
+                },
+                EnergyBreakpoint {
+                    position: 1.0,
+                    energy: 6.0,
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_energy_curve_interpolates() {
+        let curve = double_peak();
+        assert!((curve.target_energy(0.0) - 4.0).abs() < 1e-9);
+        assert!((curve.target_energy(0.15) - 6.5).abs() < 1e-9);
+        assert!((curve.target_energy(0.3) - 9.0).abs() < 1e-9);
+        assert!((curve.target_energy(0.4) - 7.0).abs() < 1e-9);
+        assert!((curve.target_energy(1.0) - 6.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_energy_curve_holds_flat_outside_breakpoints() {
+        let curve = EnergyCurve {
+            breakpoints: vec![
+                EnergyBreakpoint {
+                    position: 0.2,
+                    energy: 3.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.8,
+                    energy: 7.0,
+                },
+            ],
+        };
+        assert!((curve.target_energy(0.0) - 3.0).abs() < 1e-9);
+        assert!((curve.target_energy(1.0) - 7.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_energy_curve_validate() {
+        assert!(double_peak().validate().is_ok());
+
+        let single = EnergyCurve {
+            breakpoints: vec![EnergyBreakpoint {
+                position: 0.0,
+                energy: 5.0,
+            }],
+        };
+        assert!(single.validate().is_err());
+
+        let mut unordered = double_peak();
+        unordered.breakpoints.swap(1, 2);
+        assert!(unordered.validate().unwrap_err().contains("increasing"));
+
+        let mut too_hot = double_peak();
+        too_hot.breakpoints[0].energy = 11.0;
+        assert!(too_hot.validate().unwrap_err().contains("energy"));
+
+        let mut off_end = double_peak();
+        off_end.breakpoints[4].position = 1.5;
+        assert!(off_end.validate().unwrap_err().contains("position"));
+    }
+
+    #[test]
+    fn test_energy_curve_describe() {
+        assert_eq!(
+            double_peak().describe(),
+            "0% → 4, 30% → 9, 50% → 5, 80% → 10, 100% → 6"
+        );
+    }
+
+    #[test]
+    fn test_energy_arc_score_with_curve() {
+        let curve = double_peak();
+        // Position 3 of 11 is 30% through: target 9
+        assert_eq!(energy_arc_score_with_curve(9, 3, 11, &curve), 1.0);
+        assert!((energy_arc_score_with_curve(4, 3, 11, &curve) - 0.5).abs() < 1e-9);
+        assert_eq!(energy_arc_score_with_curve(1, 0, 1, &curve), 1.0);
+    }
+
+    // --- KeyPath ---
+
+    fn climb() -> KeyPath {
+        KeyPath {
+            waypoints: vec![
+                KeyWaypoint {
+                    position: 0.0,
+                    key: "5A".to_string(),
+                },
+                KeyWaypoint {
+                    position: 1.0,
+                    key: "10B".to_string(),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_key_path_walks_the_wheel() {
+        let path = climb();
+        assert_eq!(path.target_key(0.0), Some(key("5A")));
+        assert_eq!(path.target_key(0.2), Some(key("6A")));
+        // Mode switches halfway through the segment
+        assert_eq!(path.target_key(0.6), Some(key("8B")));
+        assert_eq!(path.target_key(1.0), Some(key("10B")));
+
+        // 11A -> 2A goes forward through 12 and 1, not back through 10
+        let wrap = KeyPath {
+            waypoints: vec![
+                KeyWaypoint {
+                    position: 0.2,
+                    key: "11A".to_string(),
+                },
+                KeyWaypoint {
+                    position: 0.8,
+                    key: "Ebm".to_string(),
+                },
+            ],
+        };
+        assert_eq!(wrap.target_key(0.0), Some(key("11A")));
+        assert_eq!(wrap.target_key(0.4), Some(key("12A")));
+        assert_eq!(wrap.target_key(0.6), Some(key("1A")));
+        assert_eq!(wrap.target_key(1.0), Some(key("2A")));
+    }
+
+    #[test]
+    fn test_key_path_validate_and_describe() {
+        assert!(climb().validate().is_ok());
+        assert_eq!(climb().describe(), "0% → 5A, 100% → 10B");
+
+        let mut unknown = climb();
+        unknown.waypoints[1].key = "H minor".to_string();
+        assert!(unknown.validate().unwrap_err().contains("key"));
+
+        let mut unordered = climb();
+        unordered.waypoints.swap(0, 1);
+        assert!(unordered.validate().unwrap_err().contains("increasing"));
+
+        let mut single = climb();
+        single.waypoints.pop();
+        assert!(single.validate().is_err());
+    }
+
+    #[test]
+    fn test_key_path_score() {
+        let path = climb();
+        // Position 2 of 11 is 20% through: target 6A
+        assert_eq!(key_path_score(&key("6A"), 2, 11, &path), 1.0);
+        assert!((key_path_score(&key("6B"), 2, 11, &path) - 6.0 / 7.0).abs() < 1e-9);
+        assert!((key_path_score(&key("4A"), 2, 11, &path) - 5.0 / 7.0).abs() < 1e-9);
+        assert_eq!(key_path_score(&key("12B"), 2, 11, &path), 0.0);
+    }
+
+    #[test]
+    fn test_pitch_percent() {
+        assert!((pitch_percent(128.0, 121.0) - 5.785).abs() < 0.01);
+        assert!((pitch_percent(120.0, 128.0) + 6.25).abs() < 0.01);
+        assert!(pitch_percent(87.0, 174.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_pitch_semitones() {
+        assert_eq!(pitch_semitones(0.0), 0);
+        assert_eq!(pitch_semitones(2.0), 0);
+        assert_eq!(pitch_semitones(6.0), 1);
+        assert_eq!(pitch_semitones(-6.0), -1);
+        assert_eq!(pitch_semitones(12.5), 2);
+    }
+
+    #[test]
+    fn test_shift_key_moves_seven_steps_per_semitone() {
+        let k = parse_camelot("8A").unwrap();
+        assert_eq!(shift_key(&k, 1).to_string(), "3A");
+        assert_eq!(shift_key(&k, -1).to_string(), "1A");
+        assert_eq!(shift_key(&k, 12).to_string(), "8A");
+        assert_eq!(
+            shift_key(&parse_camelot("12B").unwrap(), 1).to_string(),
+            "7B"
+        );
+    }
+
+    #[test]
+    fn test_effective_key_respects_key_lock() {
+        let b = parse_camelot("3A").unwrap();
+        // Pitching 121 → 128 (+5.8%) lifts 3A a semitone to 10A
+        assert_eq!(
+            effective_key(&b, Some(128.0), Some(121.0), false).to_string(),
+            "10A"
+        );
+        assert_eq!(effective_key(&b, Some(128.0), Some(121.0), true), b);
+        assert_eq!(effective_key(&b, None, Some(121.0), false), b);
+    }
+
+    #[test]
+    fn test_effective_key_ignores_zero_bpm() {
+        let b = parse_camelot("3A").unwrap();
+        assert_eq!(effective_key(&b, Some(128.0), Some(0.0), false), b);
+        assert_eq!(effective_key(&b, Some(0.0), Some(128.0), false), b);
+        assert_eq!(effective_key(&b, Some(f64::NAN), Some(128.0), false), b);
+        // Scoring a transition into a 0 BPM track must not panic
+        let a = parse_camelot("8A").unwrap();
+        transition_score_with_key_lock(Some(&a), Some(&b), Some(128.0), Some(0.0), false);
+        assert_eq!(shift_key(&b, i32::MAX), shift_key(&b, i32::MAX % 12));
+    }
+
+    #[test]
+    fn test_transition_score_without_key_lock_scores_shifted_key() {
+        let a = parse_camelot("8A").unwrap();
+        let b = parse_camelot("1A").unwrap();
+        // 1A pitched up a semitone plays as 8A: a same-key mix
+        let locked =
+            transition_score_with_key_lock(Some(&a), Some(&b), Some(128.0), Some(121.0), true);
+        let unlocked =
+            transition_score_with_key_lock(Some(&a), Some(&b), Some(128.0), Some(121.0), false);
+        assert!(unlocked > locked);
+        assert!(
+            (unlocked - (0.5 + bpm_score(128.0, 121.0) * 0.3 + ENERGY_NEUTRAL * 0.2)).abs() < 0.001
+        );
+        assert_eq!(
+            transition_score(Some(&a), Some(&b), Some(128.0), Some(121.0)),
+            locked
+        );
+    }
+
+    #[test]
+    fn test_bpm_in_range() {
+        assert_eq!(
+            bpm_in_range(140.0, 130.0, 150.0),
+            Some(TempoRelation::Direct)
+        );
+        assert_eq!(
+            bpm_in_range(70.0, 130.0, 150.0),
+            Some(TempoRelation::DoubleTime)
+        );
+        assert_eq!(
+            bpm_in_range(174.0, 80.0, 90.0),
+            Some(TempoRelation::HalfTime)
+        );
+        assert_eq!(bpm_in_range(110.0, 130.0, 150.0), None);
+    }
+
     // --- energy_arc_score ---
 
     #[test]
@@ -609,6 +1825,108 @@ mod tests {
         assert!((score - 0.5).abs() < 0.001);
     }
 
+    // --- ScoringProfile ---
+
+    #[test]
+    fn test_default_profile_matches_builtin_scoring() {
+        let a = parse_camelot("8A").unwrap();
+        let b = parse_camelot("3B").unwrap();
+        for (bpm_a, bpm_b) in [(128.0, 128.0), (128.0, 135.0), (87.0, 174.0)] {
+            assert_eq!(
+                transition_score(Some(&a), Some(&b), Some(bpm_a), Some(bpm_b)),
+                transition_score_with_profile(
+                    Some(&a),
+                    Some(&b),
+                    Some(bpm_a),
+                    Some(bpm_b),
+                    true,
+                    &ScoringProfile::default()
+                )
+            );
+        }
+    }
+
+    #[test]
+    fn test_profile_weights_shift_priorities() {
+        // Clashing keys at a matched tempo vs matching keys with an 8 BPM jump
+        let a = parse_camelot("8A").unwrap();
+        let clash = parse_camelot("2B").unwrap();
+        let bpm_first = ScoringProfile {
+            key_weight: 0.1,
+            bpm_weight: 0.9,
+            energy_weight: 0.0,
+            ..Default::default()
+        };
+        let key_first = ScoringProfile {
+            key_weight: 0.9,
+            bpm_weight: 0.1,
+            energy_weight: 0.0,
+            ..Default::default()
+        };
+        let score = |b: &CamelotKey, bpm_b: f64, p: &ScoringProfile| {
+            transition_score_with_profile(Some(&a), Some(b), Some(128.0), Some(bpm_b), true, p)
+        };
+        assert!(score(&clash, 128.0, &bpm_first) > score(&a, 136.0, &bpm_first));
+        assert!(score(&clash, 128.0, &key_first) < score(&a, 136.0, &key_first));
+        // Weights are relative: scaling them all changes nothing
+        let doubled = ScoringProfile {
+            key_weight: 1.8,
+            bpm_weight: 0.2,
+            ..key_first
+        };
+        assert!((score(&a, 136.0, &key_first) - score(&a, 136.0, &doubled)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_custom_bpm_bands() {
+        let tight = BpmBands {
+            perfect: 0.5,
+            close: 1.0,
+            workable: 2.0,
+            stretch: 3.0,
+        };
+        assert_eq!(bpm_match_with_bands(128.0, 130.0, &tight).score, 0.6);
+        assert_eq!(bpm_match_with_bands(128.0, 134.0, &tight).score, 0.0);
+        assert_eq!(bpm_match(128.0, 130.0).score, 1.0);
+    }
+
+    #[test]
+    fn test_scoring_profile_validate() {
+        assert!(ScoringProfile::default().validate().is_ok());
+        let negative = ScoringProfile {
+            bpm_weight: -0.1,
+            ..Default::default()
+        };
+        assert!(negative.validate().unwrap_err().contains("bpm_weight"));
+        let zero = ScoringProfile {
+            key_weight: 0.0,
+            bpm_weight: 0.0,
+            energy_weight: 0.0,
+            ..Default::default()
+        };
+        assert!(zero.validate().is_err());
+        let unordered = ScoringProfile {
+            bpm_bands: BpmBands {
+                perfect: 3.0,
+                close: 3.0,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        assert!(unordered.validate().unwrap_err().contains("bpm_bands"));
+    }
+
+    #[test]
+    fn test_scoring_profile_partial_json_fills_defaults() {
+        let p: ScoringProfile =
+            serde_json::from_str(r#"{"bpm_weight": 0.7, "bpm_bands": {"stretch": 12}}"#).unwrap();
+        assert_eq!(p.bpm_weight, 0.7);
+        assert_eq!(p.key_weight, 0.5);
+        assert_eq!(p.bpm_bands.stretch, 12.0);
+        assert_eq!(p.bpm_bands.perfect, 2.0);
+        assert_eq!(p.bpm_warning_threshold, 6.0);
+    }
+
     // --- from_spotify_key ---
 
     #[test]
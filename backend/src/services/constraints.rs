@@ -0,0 +1,609 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+use crate::api::claude::LlmTrackEntry;
+use crate::db::models::TrackRow;
+use crate::db::setlists as db;
+use crate::services::arrangement::artist_names;
+use crate::services::camelot::{parse_camelot, transition_score_with_profile, ScoringProfile};
+use crate::services::lineup::track_identity;
+use crate::services::setlist::SetlistError;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Most recent setlists that can be excluded in one request.
+pub const MAX_RECENT_SETLISTS: u32 = 20;
+
+/// Excluded tracks listed by name in the prompt; the rest are still enforced.
+const MAX_PROMPT_EXCLUSIONS: usize = 50;
+
+/// A track named in a constraint: either a catalog id (`"t1"`) or an
+/// artist/title pair (`{"artist": "Sting", "title": "Desert Rose"}`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum TrackRef {
+    Id(String),
+    Named { artist: String, title: String },
+}
+
+/// Hard rules on which tracks a generated setlist may contain.
+#[derive(Debug, Clone, Default)]
+pub struct TrackConstraints {
+    pub must_include: Vec<TrackRef>,
+    pub exclude_tracks: Vec<TrackRef>,
+    /// Matched against each credited artist, case-insensitively.
+    pub exclude_artists: Vec<String>,
+    /// Exclude every track in the user's last N kept setlists.
+    pub exclude_recent_setlists: Option<u32>,
+}
+
+/// A must-include track, matched to the catalog where possible.
+#[derive(Debug, Clone)]
+pub struct RequiredTrack {
+    pub artist: String,
+    pub title: String,
+    /// `None` for a track outside the catalog; it goes in as a suggestion.
+    pub row: Option<TrackRow>,
+}
+
+impl RequiredTrack {
+    fn label(&self) -> String {
+        format!("'{}' by '{}'", self.title, self.artist)
+    }
+
+    fn matches(&self, entry: &LlmTrackEntry) -> bool {
+        self.is(entry.track_id.as_deref(), &entry.artist, &entry.title)
+    }
+
+    fn is(&self, track_id: Option<&str>, artist: &str, title: &str) -> bool {
+        let same_id = match (&self.row, track_id) {
+            (Some(row), Some(id)) => row.id == id,
+            _ => false,
+        };
+        same_id || track_identity(artist, title) == track_identity(&self.artist, &self.title)
+    }
+
+    pub(crate) fn to_entry(&self) -> LlmTrackEntry {
+        match self.row {
+            Some(ref row) => catalog_entry(row),
+            None => LlmTrackEntry {
+                position: 0,
+                title: self.title.clone(),
+                artist: self.artist.clone(),
+                bpm: None,
+                key: None,
+                camelot: None,
+                energy: None,
+                transition_note: None,
+                source: Some("suggestion".to_string()),
+                track_id: None,
+                confidence: None,
+                duration_seconds: None,
+            },
+        }
+    }
+}
+
+/// [`TrackConstraints`] resolved against the catalog and the user's history.
+#[derive(Debug, Clone, Default)]
+pub struct ResolvedConstraints {
+    pub required: Vec<RequiredTrack>,
+    exclude_track_ids: HashSet<String>,
+    /// [`track_identity`] of excluded tracks.
+    exclude_tracks: HashSet<String>,
+    /// Lowercased artist names.
+    exclude_artists: HashSet<String>,
+    /// Excluded tracks as shown in the prompt, e.g. "'Desert Rose' by 'Sting'".
+    excluded_labels: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Resolution
+// ---------------------------------------------------------------------------
+
+pub(crate) fn catalog_entry(row: &TrackRow) -> LlmTrackEntry {
+    LlmTrackEntry {
+        position: 0,
+        title: row.title.clone(),
+        artist: row.artist.clone().unwrap_or_default(),
+        bpm: row.bpm,
+        key: None,
+        camelot: row.camelot_key.clone(),
+        energy: row.energy.map(|e| e.round() as i32),
+        transition_note: None,
+        source: Some("catalog".to_string()),
+        track_id: Some(row.id.clone()),
+        confidence: Some("high".to_string()),
+        duration_seconds: row.duration_ms.map(|ms| (ms.max(0) / 1000) as u32),
+    }
+}
+
+fn find_in_catalog<'a>(catalog: &'a [TrackRow], track: &TrackRef) -> Option<&'a TrackRow> {
+    match track {
+        TrackRef::Id(id) => catalog.iter().find(|t| &t.id == id),
+        TrackRef::Named { artist, title } => {
+            let identity = track_identity(artist, title);
+            catalog
+                .iter()
+                .find(|t| track_identity(t.artist.as_deref().unwrap_or(""), &t.title) == identity)
+        }
+    }
+}
+
+/// Check constraints and look up every track they name.
+///
+/// Must-include ids must exist in the catalog; named tracks that are not in
+/// it become suggestions. A must-include track that is also excluded is
+/// rejected rather than silently dropped.
+pub async fn resolve(
+    pool: &sqlx::PgPool,
+    user_id: &str,
+    constraints: &TrackConstraints,
+) -> Result<ResolvedConstraints, SetlistError> {
+    let mut resolved = ResolvedConstraints::default();
+    let catalog = if constraints.must_include.is_empty() && constraints.exclude_tracks.is_empty() {
+        Vec::new()
+    } else {
+        db::load_catalog_tracks(pool).await?
+    };
+
+    for track in &constraints.exclude_tracks {
+        let row = find_in_catalog(&catalog, track);
+        if let Some(row) = row {
+            resolved.exclude_track(
+                Some(&row.id),
+                row.artist.as_deref().unwrap_or(""),
+                &row.title,
+            );
+        } else if let TrackRef::Named { artist, title } = track {
+            resolved.exclude_track(None, artist, title);
+        } else if let TrackRef::Id(id) = track {
+            resolved.exclude_track_ids.insert(id.clone());
+        }
+    }
+    for artist in &constraints.exclude_artists {
+        resolved.exclude_artists.extend(artist_names(artist));
+    }
+    if let Some(n) = constraints.exclude_recent_setlists {
+        if !(1..=MAX_RECENT_SETLISTS).contains(&n) {
+            return Err(SetlistError::InvalidRequest(format!(
+                "exclude_recent_setlists must be between 1 and {MAX_RECENT_SETLISTS}, got {n}"
+            )));
+        }
+        for (track_id, artist, title) in db::recent_setlist_tracks(pool, user_id, n as i64).await? {
+            resolved.exclude_track(track_id.as_deref(), &artist, &title);
+        }
+    }
+
+    for track in &constraints.must_include {
+        let required = match (find_in_catalog(&catalog, track), track) {
+            (Some(row), _) => RequiredTrack {
+                artist: row.artist.clone().unwrap_or_default(),
+                title: row.title.clone(),
+                row: Some(row.clone()),
+            },
+            (None, TrackRef::Named { artist, title }) => RequiredTrack {
+                artist: artist.trim().to_string(),
+                title: title.trim().to_string(),
+                row: None,
+            },
+            (None, TrackRef::Id(id)) => {
+                return Err(SetlistError::InvalidRequest(format!(
+                    "must_include track '{id}' is not in the catalog"
+                )));
+            }
+        };
+        let id = required.row.as_ref().map(|r| r.id.as_str());
+        if let Some(reason) = resolved.violation(id, &required.artist, &required.title) {
+            return Err(SetlistError::InvalidRequest(format!(
+                "must_include track {} is excluded ({reason})",
+                required.label()
+            )));
+        }
+        let entry = required.to_entry();
+        if !resolved.required.iter().any(|r| r.matches(&entry)) {
+            resolved.required.push(required);
+        }
+    }
+    Ok(resolved)
+}
+
+impl ResolvedConstraints {
+    fn exclude_track(&mut self, track_id: Option<&str>, artist: &str, title: &str) {
+        if let Some(id) = track_id {
+            self.exclude_track_ids.insert(id.to_string());
+        }
+        if self.exclude_tracks.insert(track_identity(artist, title)) {
+            self.excluded_labels
+                .push(format!("'{}' by '{}'", title.trim(), artist.trim()));
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.required.is_empty()
+            && self.exclude_track_ids.is_empty()
+            && self.exclude_tracks.is_empty()
+            && self.exclude_artists.is_empty()
+    }
+
+    /// Why a track may not appear, or `None` if it may.
+    pub fn violation(&self, track_id: Option<&str>, artist: &str, title: &str) -> Option<String> {
+        if track_id.is_some_and(|id| self.exclude_track_ids.contains(id))
+            || self.exclude_tracks.contains(&track_identity(artist, title))
+        {
+            return Some("excluded track".to_string());
+        }
+        artist_names(artist)
+            .into_iter()
+            .find(|name| self.exclude_artists.contains(name))
+            .map(|name| format!("excluded artist {name}"))
+    }
+
+    /// Whether the track is one the setlist must include.
+    pub fn requires(&self, track_id: Option<&str>, artist: &str, title: &str) -> bool {
+        self.required.iter().any(|r| r.is(track_id, artist, title))
+    }
+
+    pub fn admits(&self, row: &TrackRow) -> bool {
+        self.violation(
+            Some(&row.id),
+            row.artist.as_deref().unwrap_or(""),
+            &row.title,
+        )
+        .is_none()
+    }
+
+    /// Prompt lines stating the constraints, if there are any.
+    pub fn prompt_section(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut text = "## Hard Constraints".to_string();
+        if !self.required.is_empty() {
+            text.push_str("\nThe setlist MUST include every one of these tracks:");
+            for r in &self.required {
+                match r.row {
+                    Some(ref row) => {
+                        text.push_str(&format!("\n- {} (track_id: {})", r.label(), row.id))
+                    }
+                    None => text.push_str(&format!("\n- {} (suggestion)", r.label())),
+                }
+            }
+        }
+        if !self.excluded_labels.is_empty() {
+            text.push_str("\nNever include these tracks:");
+            for label in self.excluded_labels.iter().take(MAX_PROMPT_EXCLUSIONS) {
+                text.push_str(&format!("\n- {label}"));
+            }
+            let more = self
+                .excluded_labels
+                .len()
+                .saturating_sub(MAX_PROMPT_EXCLUSIONS);
+            if more > 0 {
+                text.push_str(&format!(
+                    "\n- ...and {more} more already removed from the catalog"
+                ));
+            }
+        }
+        if !self.exclude_artists.is_empty() {
+            let mut artists: Vec<&str> = self.exclude_artists.iter().map(String::as_str).collect();
+            artists.sort_unstable();
+            text.push_str(&format!(
+                "\nNever include tracks by (or featuring) these artists: {}",
+                artists.join(", ")
+            ));
+        }
+        Some(text)
+    }
+
+    /// Every way `entries` break the constraints, as prompt-ready lines.
+    pub fn violations(&self, entries: &[LlmTrackEntry]) -> Vec<String> {
+        let mut found: Vec<String> = entries
+            .iter()
+            .filter_map(|e| {
+                self.violation(e.track_id.as_deref(), &e.artist, &e.title)
+                    .map(|reason| {
+                        format!("'{}' by '{}' is not allowed ({reason})", e.title, e.artist)
+                    })
+            })
+            .collect();
+        for r in &self.required {
+            if !entries.iter().any(|e| r.matches(e)) {
+                found.push(format!("{} is missing", r.label()));
+            }
+        }
+        found
+    }
+
+    /// Force `entries` to satisfy the constraints without another LLM call.
+    ///
+    /// Each disallowed track is swapped for the unused catalog track that
+    /// mixes best with its neighbours, or dropped if none is left. Missing
+    /// must-include tracks are appended while there is room, otherwise they
+    /// replace the last tracks that are not themselves required. Returns a
+    /// note per change.
+    pub fn repair(
+        &self,
+        entries: &mut Vec<LlmTrackEntry>,
+        catalog: &[TrackRow],
+        count: usize,
+        key_lock: bool,
+        scoring: &ScoringProfile,
+    ) -> Vec<String> {
+        let mut notes = Vec::new();
+        let mut i = 0;
+        while i < entries.len() {
+            let entry = &entries[i];
+            let Some(reason) =
+                self.violation(entry.track_id.as_deref(), &entry.artist, &entry.title)
+            else {
+                i += 1;
+                continue;
+            };
+            let removed = format!("'{}' by '{}' ({reason})", entry.title, entry.artist);
+            match self.best_substitute(entries, i, catalog, key_lock, scoring) {
+                Some(row) => {
+                    entries[i] = catalog_entry(row);
+                    notes.push(format!(
+                        "Replaced {removed} with '{}' from the catalog.",
+                        row.title
+                    ));
+                    i += 1;
+                }
+                None => {
+                    entries.remove(i);
+                    notes.push(format!("Removed {removed}."));
+                }
+            }
+        }
+
+        for r in &self.required {
+            if entries.iter().any(|e| r.matches(e)) {
+                continue;
+            }
+            if entries.len() < count {
+                entries.push(r.to_entry());
+            } else {
+                let replace = entries
+                    .iter()
+                    .rposition(|e| !self.required.iter().any(|req| req.matches(e)));
+                match replace {
+                    Some(idx) => entries[idx] = r.to_entry(),
+                    None => entries.push(r.to_entry()),
+                }
+            }
+            notes.push(format!("Added must-include track {}.", r.label()));
+        }
+
+        for (i, entry) in entries.iter_mut().enumerate() {
+            entry.position = (i + 1) as i32;
+        }
+        notes
+    }
+
+    /// Allowed, unused catalog track that best fits between the neighbours
+    /// of `entries[index]`.
+    fn best_substitute<'a>(
+        &self,
+        entries: &[LlmTrackEntry],
+        index: usize,
+        catalog: &'a [TrackRow],
+        key_lock: bool,
+        scoring: &ScoringProfile,
+    ) -> Option<&'a TrackRow> {
+        let used: HashSet<String> = entries
+            .iter()
+            .map(|e| track_identity(&e.artist, &e.title))
+            .collect();
+        let neighbours: Vec<&LlmTrackEntry> = [index.checked_sub(1), Some(index + 1)]
+            .into_iter()
+            .flatten()
+            .filter_map(|j| entries.get(j))
+            .collect();
+        let fit = |row: &TrackRow| -> f64 {
+            let key = row.camelot_key.as_deref().and_then(parse_camelot);
+            neighbours
+                .iter()
+                .map(|n| {
+                    let other = n.camelot.as_deref().and_then(parse_camelot);
+                    transition_score_with_profile(
+                        other.as_ref(),
+                        key.as_ref(),
+                        n.bpm,
+                        row.bpm,
+                        key_lock,
+                        scoring,
+                    )
+                })
+                .sum()
+        };
+        catalog
+            .iter()
+            .filter(|row| self.admits(row))
+            .filter(|row| {
+                !entries
+                    .iter()
+                    .any(|e| e.track_id.as_deref() == Some(&row.id))
+            })
+            .filter(|row| {
+                !used.contains(&track_identity(
+                    row.artist.as_deref().unwrap_or(""),
+                    &row.title,
+                ))
+            })
+            .filter(|row| {
+                !self
+                    .required
+                    .iter()
+                    .any(|r| r.row.as_ref().is_some_and(|q| q.id == row.id))
+            })
+            .fold(None, |best: Option<(&TrackRow, f64)>, row| {
+                let score = fit(row);
+                match best {
+                    Some((_, s)) if s >= score => best,
+                    _ => Some((row, score)),
+                }
+            })
+            .map(|(row, _)| row)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row(id: &str, title: &str, artist: &str, camelot: &str) -> TrackRow {
+        TrackRow {
+            id: id.to_string(),
+            title: title.to_string(),
+            artist: Some(artist.to_string()),
+            album: None,
+            duration_ms: Some(300_000),
+            bpm: Some(124.0),
+            camelot_key: Some(camelot.to_string()),
+            energy: Some(6.0),
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        }
+    }
+
+    fn entry(title: &str, artist: &str, camelot: &str) -> LlmTrackEntry {
+        LlmTrackEntry {
+            camelot: Some(camelot.to_string()),
+            bpm: Some(124.0),
+            ..RequiredTrack {
+                artist: artist.to_string(),
+                title: title.to_string(),
+                row: None,
+            }
+            .to_entry()
+        }
+    }
+
+    #[test]
+    fn test_track_ref_accepts_id_or_pair() {
+        let refs: Vec<TrackRef> =
+            serde_json::from_str(r#"["t1", {"artist": "Sting", "title": "Desert Rose"}]"#).unwrap();
+        assert_eq!(refs[0], TrackRef::Id("t1".to_string()));
+        assert!(matches!(refs[1], TrackRef::Named { .. }));
+    }
+
+    #[test]
+    fn test_violation_matches_featured_artists() {
+        let mut resolved = ResolvedConstraints::default();
+        resolved.exclude_artists.insert("cheb mami".to_string());
+        resolved.exclude_track(None, "Sade", "Smooth Operator");
+
+        assert_eq!(
+            resolved.violation(None, "Sting feat. Cheb Mami", "Desert Rose"),
+            Some("excluded artist cheb mami".to_string())
+        );
+        assert!(resolved
+            .violation(None, "SADE", "smooth operator ")
+            .is_some());
+        assert!(resolved
+            .violation(None, "Sting", "Fields of Gold")
+            .is_none());
+    }
+
+    #[test]
+    fn test_repair_substitutes_and_adds_required() {
+        let catalog = vec![
+            row("c1", "Clash", "Other", "3B"),
+            row("c2", "Fits", "Other", "9A"),
+            row("r1", "Required", "Headliner", "8A"),
+        ];
+        let mut resolved = ResolvedConstraints {
+            required: vec![RequiredTrack {
+                artist: "Headliner".to_string(),
+                title: "Required".to_string(),
+                row: Some(catalog[2].clone()),
+            }],
+            ..Default::default()
+        };
+        resolved.exclude_artists.insert("banned".to_string());
+        let mut entries = vec![
+            entry("One", "A", "8A"),
+            entry("Bad", "Banned", "1A"),
+            entry("Three", "B", "10A"),
+        ];
+
+        let notes = resolved.repair(&mut entries, &catalog, 3, true, &ScoringProfile::default());
+        let titles: Vec<&str> = entries.iter().map(|e| e.title.as_str()).collect();
+        // "Fits" sits between 8A and 10A; the required track replaces the closer
+        assert_eq!(titles, vec!["One", "Fits", "Required"]);
+        assert_eq!(entries[2].position, 3);
+        assert_eq!(entries[1].track_id.as_deref(), Some("c2"));
+        assert_eq!(notes.len(), 2);
+        assert!(resolved.violations(&entries).is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_resolve_checks_ids_conflicts_and_history() {
+        let pool = crate::db::create_test_pool().await;
+        sqlx::query(
+            "INSERT INTO tracks (id, title, source) VALUES ('t1', 'Desert Rose', 'spotify')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO setlists (id, user_id, prompt, model) VALUES ('s1', 'u1', 'p', 'm')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query(
+            "INSERT INTO setlist_tracks (id, setlist_id, track_id, position, original_position, title, artist, source) \
+             VALUES ('st1', 's1', NULL, 1, 1, 'Old Favourite', 'Someone', 'suggestion')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let unknown = TrackConstraints {
+            must_include: vec![TrackRef::Id("missing".to_string())],
+            ..Default::default()
+        };
+        assert!(matches!(
+            resolve(&pool, "u1", &unknown).await,
+            Err(SetlistError::InvalidRequest(_))
+        ));
+
+        let conflict = TrackConstraints {
+            must_include: vec![TrackRef::Id("t1".to_string())],
+            exclude_tracks: vec![TrackRef::Id("t1".to_string())],
+            ..Default::default()
+        };
+        assert!(matches!(
+            resolve(&pool, "u1", &conflict).await,
+            Err(SetlistError::InvalidRequest(_))
+        ));
+
+        let history = TrackConstraints {
+            must_include: vec![TrackRef::Id("t1".to_string())],
+            exclude_recent_setlists: Some(1),
+            ..Default::default()
+        };
+        let resolved = resolve(&pool, "u1", &history).await.unwrap();
+        assert_eq!(resolved.required[0].title, "Desert Rose");
+        assert!(resolved
+            .violation(None, "someone", "old favourite")
+            .is_some());
+        assert!(resolved.prompt_section().unwrap().contains("track_id: t1"));
+
+        pool.close().await;
+    }
+}
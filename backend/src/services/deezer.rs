@@ -18,6 +18,12 @@ pub enum DeezerError {
 
     #[error("Database error: {0}")]
     Database(String),
+
+    #[error("Deezer API error: {0}")]
+    Api(String),
+
+    #[error("Not found on Deezer: {0}")]
+    NotFound(String),
 }
 
 impl From<reqwest::Error> for DeezerError {
@@ -55,6 +61,152 @@ struct DeezerSearchResponse {
     data: Vec<DeezerTrack>,
 }
 
+/// Deezer reports errors as HTTP 200 with an `error` object.
+#[derive(Debug, Deserialize)]
+struct DeezerErrorBody {
+    error: DeezerErrorDetail,
+}
+
+#[derive(Debug, Deserialize)]
+struct DeezerErrorDetail {
+    #[serde(default)]
+    message: String,
+    #[serde(default)]
+    code: i64,
+}
+
+/// Deezer's error code for a missing object.
+const NO_DATA: i64 = 800;
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerPlaylist {
+    pub id: i64,
+    pub title: String,
+    #[serde(default)]
+    pub nb_tracks: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerPlaylistTrack {
+    pub id: i64,
+    pub title: String,
+    /// Seconds.
+    #[serde(default)]
+    pub duration: u64,
+    #[serde(default)]
+    pub preview: String,
+    pub artist: DeezerPlaylistArtist,
+    pub album: Option<DeezerAlbum>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerPlaylistArtist {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct DeezerAlbum {
+    pub title: String,
+    pub cover_medium: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeezerTrackPage {
+    pub data: Vec<DeezerPlaylistTrack>,
+    #[serde(default)]
+    pub total: u32,
+    /// URL of the next page; absent on the last one.
+    pub next: Option<String>,
+}
+
+/// The part of `/track/{id}` that playlist listings leave out.
+#[derive(Debug, Deserialize)]
+struct DeezerTrackDetail {
+    #[serde(default)]
+    bpm: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Client
+// ---------------------------------------------------------------------------
+
+/// Client for Deezer's public API, which needs no credentials.
+#[derive(Clone)]
+pub struct DeezerClient {
+    http: Client,
+    base_url: String,
+}
+
+impl Default for DeezerClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DeezerClient {
+    pub fn new() -> Self {
+        Self {
+            http: Client::new(),
+            base_url: "https://api.deezer.com".to_string(),
+        }
+    }
+
+    /// Point the client at another host (for tests).
+    pub fn with_base_url(mut self, base_url: impl Into<String>) -> Self {
+        self.base_url = base_url.into();
+        self
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(
+        &self,
+        path: &str,
+        query: &[(&str, String)],
+    ) -> Result<T, DeezerError> {
+        let url = format!("{}{path}", self.base_url);
+        let body: serde_json::Value = self
+            .http
+            .get(&url)
+            .query(query)
+            .send()
+            .await?
+            .json()
+            .await?;
+        if let Ok(err) = DeezerErrorBody::deserialize(&body) {
+            return Err(if err.error.code == NO_DATA {
+                DeezerError::NotFound(path.to_string())
+            } else {
+                DeezerError::Api(err.error.message)
+            });
+        }
+        serde_json::from_value(body).map_err(|e| DeezerError::ParseError(e.to_string()))
+    }
+
+    pub async fn get_playlist(&self, playlist_id: &str) -> Result<DeezerPlaylist, DeezerError> {
+        self.get(&format!("/playlist/{playlist_id}"), &[]).await
+    }
+
+    /// One page of a playlist's tracks, starting at `index`.
+    pub async fn get_playlist_tracks(
+        &self,
+        playlist_id: &str,
+        index: u32,
+        limit: u32,
+    ) -> Result<DeezerTrackPage, DeezerError> {
+        self.get(
+            &format!("/playlist/{playlist_id}/tracks"),
+            &[("index", index.to_string()), ("limit", limit.to_string())],
+        )
+        .await
+    }
+
+    /// A track's BPM, which only the full track object carries. Deezer
+    /// reports 0 for tracks it has not analysed.
+    pub async fn get_track_bpm(&self, track_id: i64) -> Result<Option<f64>, DeezerError> {
+        let detail: DeezerTrackDetail = self.get(&format!("/track/{track_id}"), &[]).await?;
+        Ok((detail.bpm > 0.0).then_some(detail.bpm))
+    }
+}
+
 // ---------------------------------------------------------------------------
 // Main enrichment function
 // ---------------------------------------------------------------------------
@@ -45,7 +45,6 @@ struct LlmEnrichmentResponse {
 struct LlmEnrichmentEntry {
     position: usize,
     bpm: Option<f64>,
-    #[allow(dead_code)]
     key: Option<String>,
     camelot: Option<String>,
     energy: Option<i32>,
@@ -180,11 +179,9 @@ pub async fn enrich_tracks(
             responded_indices.insert(idx);
             let track = &batch[idx];
 
-            // Validate camelot notation
-            let camelot_str = entry
-                .camelot
-                .as_deref()
-                .and_then(|c| camelot::parse_camelot(c).map(|k| k.to_string()));
+            // Validate the key; accept any notation, falling back to `key`
+            let camelot_str = camelot::normalize_key(entry.camelot.as_deref())
+                .or_else(|| camelot::normalize_key(entry.key.as_deref()));
 
             // Validate BPM and energy ranges
             let bpm = entry.bpm.filter(|b| (0.0..300.0).contains(b));
@@ -319,7 +316,8 @@ mod tests {
         // Verify all 3 tracks were enriched (have non-null BPM/key/energy).
         // Note: exact per-track values depend on ordering, which is non-deterministic
         // in Postgres when created_at timestamps are identical.
-        let bpms: Vec<(String, Option<f64>, Option<String>, Option<f64>)> =
+        type EnrichedRow = (String, Option<f64>, Option<String>, Option<f64>);
+        let bpms: Vec<EnrichedRow> =
             sqlx::query_as("SELECT id, bpm, camelot_key, energy FROM tracks ORDER BY id")
                 .fetch_all(&pool)
                 .await
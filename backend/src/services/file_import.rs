@@ -0,0 +1,809 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::camelot::normalize_key;
+use crate::services::import::{
+    location_path, store_library_track, ImportError, ImportFailure, ImportOrigin, ImportRepository,
+    ImportSummary, LibraryTrackRecord, UpsertResult,
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Rows shown back in a preview.
+pub const PREVIEW_ROWS: usize = 10;
+
+/// Playlist and tracklist files accepted by the generic file import.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileFormat {
+    /// M3U and M3U8, with or without `#EXTINF` lines.
+    M3u,
+    Pls,
+    Csv,
+}
+
+impl FileFormat {
+    /// `ImportOrigin::source` and `tracks.source` for this format.
+    pub fn source(self) -> &'static str {
+        match self {
+            FileFormat::M3u => "m3u",
+            FileFormat::Pls => "pls",
+            FileFormat::Csv => "csv",
+        }
+    }
+
+    /// Format from a file name's extension.
+    pub fn from_file_name(file_name: &str) -> Option<Self> {
+        let (_, ext) = file_name.rsplit_once('.')?;
+        ext.parse().ok()
+    }
+}
+
+impl FromStr for FileFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "m3u" | "m3u8" => Ok(FileFormat::M3u),
+            "pls" => Ok(FileFormat::Pls),
+            "csv" => Ok(FileFormat::Csv),
+            other => Err(format!(
+                "Unsupported file format '{other}'. Valid: m3u, m3u8, pls, csv"
+            )),
+        }
+    }
+}
+
+/// Which CSV column holds each track field, by header name. Unmapped
+/// fields are left empty; `title` must be mapped.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct CsvMapping {
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub artist: Option<String>,
+    #[serde(default)]
+    pub bpm: Option<String>,
+    /// Camelot, Open Key or musical notation.
+    #[serde(default)]
+    pub key: Option<String>,
+    /// 1-10.
+    #[serde(default)]
+    pub energy: Option<String>,
+    /// "m:ss", "h:mm:ss" or seconds.
+    #[serde(default)]
+    pub duration: Option<String>,
+}
+
+/// Header names recognised for each field when suggesting a mapping.
+const TITLE_HEADERS: &[&str] = &["title", "track", "track title", "name", "song"];
+const ARTIST_HEADERS: &[&str] = &["artist", "artists", "artist name"];
+const BPM_HEADERS: &[&str] = &["bpm", "tempo"];
+const KEY_HEADERS: &[&str] = &["key", "camelot", "musical key", "tonality", "initial key"];
+const ENERGY_HEADERS: &[&str] = &["energy", "energy level"];
+const DURATION_HEADERS: &[&str] = &["duration", "length", "time"];
+
+/// A file read into track records, with the rows that could not be read.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFile {
+    /// Column headers, for CSV.
+    pub columns: Vec<String>,
+    /// Tracks with their 1-based row in the file.
+    pub rows: Vec<(u32, LibraryTrackRecord)>,
+    pub failures: Vec<ImportFailure>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct PreviewRow {
+    pub row: u32,
+    pub title: String,
+    pub artist: Option<String>,
+    pub bpm: Option<f64>,
+    pub camelot: Option<String>,
+    pub energy: Option<f64>,
+    pub duration_ms: Option<i64>,
+}
+
+/// What an upload would import, so the user can check or fix the CSV
+/// column mapping before importing.
+#[derive(Debug, Clone, Serialize)]
+pub struct FilePreview {
+    pub format: FileFormat,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    /// The mapping the preview used: the one given, or a suggestion from the
+    /// column headers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mapping: Option<CsvMapping>,
+    pub total_rows: u32,
+    pub sample: Vec<PreviewRow>,
+    pub failures: Vec<ImportFailure>,
+}
+
+// ---------------------------------------------------------------------------
+// Parsing
+// ---------------------------------------------------------------------------
+
+/// Read `content` as `format`. CSV needs a mapping; M3U and PLS ignore it.
+pub fn parse_file(
+    format: FileFormat,
+    content: &str,
+    mapping: Option<&CsvMapping>,
+) -> Result<ParsedFile, ImportError> {
+    match format {
+        FileFormat::M3u => Ok(parse_m3u(content)),
+        FileFormat::Pls => Ok(parse_pls(content)),
+        FileFormat::Csv => {
+            let mapping = match mapping {
+                Some(m) => m.clone(),
+                None => suggest_mapping(&csv_headers(content)?),
+            };
+            parse_csv(content, &mapping)
+        }
+    }
+}
+
+/// Parse an M3U/M3U8 playlist. `#EXTINF:<seconds>,<Artist - Title>` lines
+/// name the entry that follows; entries without one are named after the
+/// file ("Artist - Title.mp3").
+pub fn parse_m3u(content: &str) -> ParsedFile {
+    let mut parsed = ParsedFile::default();
+    let mut pending: Option<(Option<i64>, String)> = None;
+    let mut album: Option<String> = None;
+    let mut position: u32 = 0;
+
+    for line in content
+        .lines()
+        .map(|l| l.trim_start_matches('\u{feff}').trim())
+    {
+        if line.is_empty() {
+            continue;
+        }
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            // "#EXTINF:381 tvg-id=\"x\",Artist - Title": the display text
+            // follows the first comma.
+            let (head, display) = info.split_once(',').unwrap_or((info, ""));
+            let seconds = head
+                .split_whitespace()
+                .next()
+                .and_then(|s| s.parse::<f64>().ok())
+                .filter(|&s| s > 0.0)
+                .map(|s| (s * 1000.0).round() as i64);
+            pending = Some((seconds, display.trim().to_string()));
+            continue;
+        }
+        if let Some(name) = line.strip_prefix("#EXTALB:") {
+            album = Some(name.trim().to_string()).filter(|a| !a.is_empty());
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        position += 1;
+        let (duration_ms, display) = pending.take().unwrap_or_default();
+        push_playlist_entry(
+            &mut parsed,
+            FileFormat::M3u,
+            position,
+            line,
+            &display,
+            duration_ms,
+            album.clone(),
+        );
+    }
+    parsed
+}
+
+/// Parse a PLS playlist (`FileN=`, `TitleN=`, `LengthN=` under `[playlist]`).
+pub fn parse_pls(content: &str) -> ParsedFile {
+    #[derive(Default)]
+    struct Entry {
+        file: Option<String>,
+        title: String,
+        length: Option<i64>,
+    }
+
+    let mut entries: BTreeMap<u32, Entry> = BTreeMap::new();
+    for line in content.lines().map(str::trim) {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let key = key.trim().to_ascii_lowercase();
+        let value = value.trim();
+        let split = |prefix: &str| -> Option<u32> { key.strip_prefix(prefix)?.parse().ok() };
+        if let Some(n) = split("file") {
+            entries.entry(n).or_default().file = Some(value.to_string());
+        } else if let Some(n) = split("title") {
+            entries.entry(n).or_default().title = value.to_string();
+        } else if let Some(n) = split("length") {
+            entries.entry(n).or_default().length = value
+                .parse::<i64>()
+                .ok()
+                .filter(|&s| s > 0)
+                .map(|s| s * 1000);
+        }
+    }
+
+    let mut parsed = ParsedFile::default();
+    for (n, entry) in entries {
+        let Some(file) = entry.file else {
+            parsed.failures.push(ImportFailure {
+                row: n,
+                item: Some(entry.title).filter(|t| !t.is_empty()),
+                reason: format!("Entry {n} has no File{n} line"),
+            });
+            continue;
+        };
+        push_playlist_entry(
+            &mut parsed,
+            FileFormat::Pls,
+            n,
+            &file,
+            &entry.title,
+            entry.length,
+            None,
+        );
+    }
+    parsed
+}
+
+fn push_playlist_entry(
+    parsed: &mut ParsedFile,
+    format: FileFormat,
+    row: u32,
+    location: &str,
+    display: &str,
+    duration_ms: Option<i64>,
+    album: Option<String>,
+) {
+    let file_location = location_path(location);
+    let display = if display.is_empty() {
+        file_stem(file_location.as_deref().unwrap_or(location))
+    } else {
+        display.to_string()
+    };
+    let (artist, title) = split_display(&display);
+    if title.is_empty() {
+        parsed.failures.push(ImportFailure {
+            row,
+            item: Some(location.to_string()),
+            reason: "Entry has no title".to_string(),
+        });
+        return;
+    }
+    parsed.rows.push((
+        row,
+        LibraryTrackRecord {
+            title,
+            artist,
+            album,
+            duration_ms,
+            file_location,
+            source: format.source().to_string(),
+            ..Default::default()
+        },
+    ));
+}
+
+/// File name without directories or extension.
+fn file_stem(path: &str) -> String {
+    let name = path.rsplit(['/', '\\']).next().unwrap_or(path);
+    name.rsplit_once('.')
+        .map_or(name, |(stem, _)| stem)
+        .trim()
+        .to_string()
+}
+
+/// "Artist - Title" into its parts; text without a separator is all title.
+pub(crate) fn split_display(display: &str) -> (Option<String>, String) {
+    match display.split_once(" - ") {
+        Some((artist, title)) if !artist.trim().is_empty() => {
+            (Some(artist.trim().to_string()), title.trim().to_string())
+        }
+        _ => (None, display.trim().to_string()),
+    }
+}
+
+/// Excel in many locales writes semicolon-separated "CSV"; pick whichever
+/// of comma, semicolon or tab the header line uses most.
+fn csv_delimiter(content: &str) -> u8 {
+    let header = content.lines().next().unwrap_or_default();
+    [b',', b';', b'\t']
+        .into_iter()
+        .max_by_key(|&d| header.bytes().filter(|&b| b == d).count())
+        .unwrap_or(b',')
+}
+
+fn csv_reader(content: &str) -> csv::Reader<&[u8]> {
+    csv::ReaderBuilder::new()
+        .delimiter(csv_delimiter(content))
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.trim_start_matches('\u{feff}').as_bytes())
+}
+
+fn csv_headers(content: &str) -> Result<Vec<String>, ImportError> {
+    let headers = csv_reader(content)
+        .headers()
+        .map_err(|e| ImportError::InvalidFile(format!("Could not read CSV header: {e}")))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+    Ok(headers)
+}
+
+/// Guess a mapping from header names ("Track Title", "BPM", "Key"...).
+pub fn suggest_mapping(headers: &[String]) -> CsvMapping {
+    let find = |names: &[&str]| {
+        headers
+            .iter()
+            .find(|h| names.contains(&h.trim().to_lowercase().as_str()))
+            .cloned()
+    };
+    CsvMapping {
+        title: find(TITLE_HEADERS),
+        artist: find(ARTIST_HEADERS),
+        bpm: find(BPM_HEADERS),
+        key: find(KEY_HEADERS),
+        energy: find(ENERGY_HEADERS),
+        duration: find(DURATION_HEADERS),
+    }
+}
+
+/// Parse a CSV tracklist using `mapping`. Rows are numbered by file line,
+/// as a spreadsheet shows them.
+pub fn parse_csv(content: &str, mapping: &CsvMapping) -> Result<ParsedFile, ImportError> {
+    let mut reader = csv_reader(content);
+    let columns: Vec<String> = reader
+        .headers()
+        .map_err(|e| ImportError::InvalidFile(format!("Could not read CSV header: {e}")))?
+        .iter()
+        .map(str::to_string)
+        .collect();
+
+    let index = |field: &str, column: &Option<String>| -> Result<Option<usize>, ImportError> {
+        let Some(column) = column else {
+            return Ok(None);
+        };
+        columns
+            .iter()
+            .position(|c| c.eq_ignore_ascii_case(column.trim()))
+            .map(Some)
+            .ok_or_else(|| {
+                ImportError::InvalidFile(format!(
+                    "Column '{column}' mapped to {field} is not in the CSV header"
+                ))
+            })
+    };
+    let title_col = index("title", &mapping.title)?.ok_or_else(|| {
+        ImportError::InvalidFile("Map a CSV column to the track title".to_string())
+    })?;
+    let artist_col = index("artist", &mapping.artist)?;
+    let bpm_col = index("bpm", &mapping.bpm)?;
+    let key_col = index("key", &mapping.key)?;
+    let energy_col = index("energy", &mapping.energy)?;
+    let duration_col = index("duration", &mapping.duration)?;
+
+    let mut parsed = ParsedFile {
+        columns,
+        ..Default::default()
+    };
+    for (i, record) in reader.records().enumerate() {
+        // Header is line 1.
+        let fallback_row = i as u32 + 2;
+        let record = match record {
+            Ok(r) => r,
+            Err(e) => {
+                parsed.failures.push(ImportFailure {
+                    row: fallback_row,
+                    item: None,
+                    reason: format!("Unreadable row: {e}"),
+                });
+                continue;
+            }
+        };
+        let row = record.position().map_or(fallback_row, |p| p.line() as u32);
+        if record.iter().all(str::is_empty) {
+            continue;
+        }
+        let cell = |col: Option<usize>| {
+            col.and_then(|c| record.get(c))
+                .filter(|v| !v.is_empty())
+                .map(str::to_string)
+        };
+
+        let title = cell(Some(title_col)).unwrap_or_default();
+        let artist = cell(artist_col);
+        let item = Some(match &artist {
+            Some(a) => format!("{a} - {title}"),
+            None => title.clone(),
+        });
+
+        match csv_track(
+            &title,
+            artist,
+            cell(bpm_col),
+            cell(key_col),
+            cell(energy_col),
+            cell(duration_col),
+        ) {
+            Ok(track) => parsed.rows.push((row, track)),
+            Err(reason) => parsed.failures.push(ImportFailure { row, item, reason }),
+        }
+    }
+    Ok(parsed)
+}
+
+fn csv_track(
+    title: &str,
+    artist: Option<String>,
+    bpm: Option<String>,
+    key: Option<String>,
+    energy: Option<String>,
+    duration: Option<String>,
+) -> Result<LibraryTrackRecord, String> {
+    if title.is_empty() {
+        return Err("Missing title".to_string());
+    }
+    let bpm = bpm
+        .map(|b| {
+            b.replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|v| *v > 0.0 && *v < 300.0)
+                .ok_or_else(|| format!("Invalid BPM '{b}'"))
+        })
+        .transpose()?;
+    let camelot_key = key
+        .map(|k| normalize_key(Some(&k)).ok_or_else(|| format!("Unrecognised key '{k}'")))
+        .transpose()?;
+    let energy = energy
+        .map(|e| {
+            e.replace(',', ".")
+                .parse::<f64>()
+                .ok()
+                .filter(|v| (1.0..=10.0).contains(v))
+                .ok_or_else(|| format!("Energy '{e}' is not between 1 and 10"))
+        })
+        .transpose()?;
+    let duration_ms = duration
+        .map(|d| parse_duration(&d).ok_or_else(|| format!("Invalid duration '{d}'")))
+        .transpose()?;
+
+    Ok(LibraryTrackRecord {
+        title: title.to_string(),
+        artist,
+        duration_ms,
+        bpm,
+        camelot_key,
+        energy,
+        source: FileFormat::Csv.source().to_string(),
+        ..Default::default()
+    })
+}
+
+/// Milliseconds from "m:ss", "h:mm:ss" or a number of seconds.
+fn parse_duration(raw: &str) -> Option<i64> {
+    let mut seconds = 0.0;
+    for part in raw.trim().split(':') {
+        seconds = seconds * 60.0 + part.trim().parse::<f64>().ok()?;
+    }
+    let ms = (seconds * 1000.0).round() as i64;
+    (ms > 0).then_some(ms)
+}
+
+// ---------------------------------------------------------------------------
+// Preview and import
+// ---------------------------------------------------------------------------
+
+/// Resolve the format from an explicit choice or the file name.
+pub fn resolve_format(file_name: &str, format: Option<&str>) -> Result<FileFormat, ImportError> {
+    match format {
+        Some(f) => f.parse().map_err(ImportError::InvalidFile),
+        None => FileFormat::from_file_name(file_name).ok_or_else(|| {
+            ImportError::InvalidFile(format!(
+                "Cannot tell the format of '{file_name}'; give one of m3u, m3u8, pls, csv"
+            ))
+        }),
+    }
+}
+
+/// Parse without importing, to show what an import would produce.
+pub fn preview_file(
+    format: FileFormat,
+    content: &str,
+    mapping: Option<&CsvMapping>,
+) -> Result<FilePreview, ImportError> {
+    let (parsed, mapping) = match format {
+        FileFormat::Csv => {
+            let mapping = match mapping {
+                Some(m) => m.clone(),
+                None => suggest_mapping(&csv_headers(content)?),
+            };
+            // A suggestion without a title column still previews the headers.
+            let parsed = if mapping.title.is_some() {
+                parse_csv(content, &mapping)?
+            } else {
+                ParsedFile {
+                    columns: csv_headers(content)?,
+                    ..Default::default()
+                }
+            };
+            (parsed, Some(mapping))
+        }
+        _ => (parse_file(format, content, None)?, None),
+    };
+
+    Ok(FilePreview {
+        format,
+        total_rows: (parsed.rows.len() + parsed.failures.len()) as u32,
+        sample: parsed
+            .rows
+            .iter()
+            .take(PREVIEW_ROWS)
+            .map(|(row, t)| PreviewRow {
+                row: *row,
+                title: t.title.clone(),
+                artist: t.artist.clone(),
+                bpm: t.bpm,
+                camelot: t.camelot_key.clone(),
+                energy: t.energy,
+                duration_ms: t.duration_ms,
+            })
+            .collect(),
+        failures: parsed.failures,
+        columns: parsed.columns,
+        mapping,
+    })
+}
+
+/// Import an uploaded playlist or tracklist as one import. Tracks go in as
+/// library tracks, matched to existing catalog tracks by file or by title
+/// and artist; rows that cannot be read or stored are recorded as failures.
+pub async fn import_file(
+    repo: &dyn ImportRepository,
+    user_id: &str,
+    file_name: &str,
+    format: FileFormat,
+    content: &str,
+    mapping: Option<&CsvMapping>,
+) -> Result<ImportSummary, ImportError> {
+    let parsed = parse_file(format, content, mapping)?;
+    if parsed.rows.is_empty() && parsed.failures.is_empty() {
+        return Err(ImportError::InvalidFile(format!(
+            "'{file_name}' has no tracks"
+        )));
+    }
+
+    let origin = ImportOrigin {
+        file_name: Some(file_name.to_string()),
+        ..ImportOrigin::new(format.source(), file_name, Some(&file_stem(file_name)))
+    };
+    let import_id = repo.create_import(user_id, &origin).await?;
+
+    let mut inserted: u32 = 0;
+    let mut updated: u32 = 0;
+    let total = (parsed.rows.len() + parsed.failures.len()) as u32;
+    let mut failures = parsed.failures;
+    for (row, track) in &parsed.rows {
+        match store_library_track(repo, &import_id, track).await {
+            Ok((_, UpsertResult::Inserted)) => inserted += 1,
+            Ok((_, UpsertResult::Updated)) => updated += 1,
+            Err(e) => failures.push(ImportFailure {
+                row: *row,
+                item: Some(track.title.clone()),
+                reason: e.to_string(),
+            }),
+        }
+    }
+    failures.sort_by_key(|f| f.row);
+
+    let summary = ImportSummary {
+        import_id: import_id.clone(),
+        source: format.source().to_string(),
+        total,
+        inserted,
+        updated,
+        failed: failures.len() as u32,
+        status: "completed".to_string(),
+        failures,
+    };
+    repo.complete_import(&import_id, &summary).await?;
+    Ok(summary)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_from_file_name() {
+        assert_eq!(
+            FileFormat::from_file_name("Set.M3U8"),
+            Some(FileFormat::M3u)
+        );
+        assert_eq!(
+            FileFormat::from_file_name("radio.pls"),
+            Some(FileFormat::Pls)
+        );
+        assert_eq!(
+            FileFormat::from_file_name("gig.tar.csv"),
+            Some(FileFormat::Csv)
+        );
+        assert_eq!(FileFormat::from_file_name("playlist.xspf"), None);
+        assert_eq!(FileFormat::from_file_name("noextension"), None);
+    }
+
+    #[test]
+    fn test_parse_extended_m3u() {
+        let m3u = "\u{feff}#EXTM3U\n\
+            #EXTALB:Friday Warmup\n\
+            #EXTINF:381,Mory Kante - Yeke Yeke\n\
+            /Users/dj/Music/yeke.mp3\n\
+            \n\
+            #EXTINF:-1,Untitled Edit\n\
+            file:///C:/Music/edit%2001.flac\n\
+            Music/Sting - Desert Rose.mp3\n";
+        let parsed = parse_m3u(m3u);
+        assert!(parsed.failures.is_empty());
+        assert_eq!(parsed.rows.len(), 3);
+
+        let (row, yeke) = &parsed.rows[0];
+        assert_eq!(*row, 1);
+        assert_eq!(yeke.title, "Yeke Yeke");
+        assert_eq!(yeke.artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(yeke.album.as_deref(), Some("Friday Warmup"));
+        assert_eq!(yeke.duration_ms, Some(381_000));
+        assert_eq!(
+            yeke.file_location.as_deref(),
+            Some("/Users/dj/Music/yeke.mp3")
+        );
+        assert_eq!(yeke.source, "m3u");
+
+        let (_, edit) = &parsed.rows[1];
+        assert_eq!(edit.title, "Untitled Edit");
+        assert_eq!(edit.artist, None);
+        assert_eq!(edit.duration_ms, None);
+        assert_eq!(edit.file_location.as_deref(), Some("C:/Music/edit 01.flac"));
+
+        let (row, rose) = &parsed.rows[2];
+        assert_eq!(*row, 3);
+        assert_eq!(rose.title, "Desert Rose");
+        assert_eq!(rose.artist.as_deref(), Some("Sting"));
+    }
+
+    #[test]
+    fn test_parse_pls() {
+        let pls = "[playlist]\n\
+            File1=/Music/yeke.mp3\n\
+            Title1=Mory Kante - Yeke Yeke\n\
+            Length1=381\n\
+            Title2=Orphan title\n\
+            File3=/Music/Desert Rose.mp3\n\
+            NumberOfEntries=3\n\
+            Version=2\n";
+        let parsed = parse_pls(pls);
+        assert_eq!(parsed.rows.len(), 2);
+        assert_eq!(parsed.rows[0].1.artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(parsed.rows[0].1.duration_ms, Some(381_000));
+        assert_eq!(parsed.rows[1].0, 3);
+        assert_eq!(parsed.rows[1].1.title, "Desert Rose");
+
+        assert_eq!(parsed.failures.len(), 1);
+        assert_eq!(parsed.failures[0].row, 2);
+        assert_eq!(parsed.failures[0].item.as_deref(), Some("Orphan title"));
+    }
+
+    #[test]
+    fn test_suggest_mapping_from_headers() {
+        let headers: Vec<String> = ["#", "Track Title", "Artist", "BPM", "Initial Key", "Time"]
+            .iter()
+            .map(|h| h.to_string())
+            .collect();
+        let mapping = suggest_mapping(&headers);
+        assert_eq!(mapping.title.as_deref(), Some("Track Title"));
+        assert_eq!(mapping.artist.as_deref(), Some("Artist"));
+        assert_eq!(mapping.bpm.as_deref(), Some("BPM"));
+        assert_eq!(mapping.key.as_deref(), Some("Initial Key"));
+        assert_eq!(mapping.energy, None);
+        assert_eq!(mapping.duration.as_deref(), Some("Time"));
+    }
+
+    #[test]
+    fn test_parse_csv_with_mapping() {
+        let csv = "Name,Who,Tempo,Key,Energy,Length\n\
+            Yeke Yeke,Mory Kante,124,F#m,7,6:21\n\
+            \"Desert Rose, Edit\",Sting,102,8A,,285\n\
+            ,Nobody,120,1A,5,\n\
+            Too Fast,Someone,420,1A,5,\n\
+            Bad Key,Someone,120,H#,5,\n\
+            Loud,Someone,120,1A,11,\n";
+        let mapping = CsvMapping {
+            title: Some("name".to_string()),
+            artist: Some("Who".to_string()),
+            bpm: Some("Tempo".to_string()),
+            key: Some("Key".to_string()),
+            energy: Some("Energy".to_string()),
+            duration: Some("Length".to_string()),
+        };
+        let parsed = parse_csv(csv, &mapping).unwrap();
+        assert_eq!(parsed.rows.len(), 2);
+
+        let (row, yeke) = &parsed.rows[0];
+        assert_eq!(*row, 2);
+        assert_eq!(yeke.bpm, Some(124.0));
+        assert_eq!(yeke.camelot_key.as_deref(), Some("11A"));
+        assert_eq!(yeke.energy, Some(7.0));
+        assert_eq!(yeke.duration_ms, Some(381_000));
+        assert_eq!(yeke.source, "csv");
+
+        let (_, rose) = &parsed.rows[1];
+        assert_eq!(rose.title, "Desert Rose, Edit");
+        assert_eq!(rose.energy, None);
+        assert_eq!(rose.duration_ms, Some(285_000));
+
+        let rows: Vec<u32> = parsed.failures.iter().map(|f| f.row).collect();
+        assert_eq!(rows, vec![4, 5, 6, 7]);
+        assert_eq!(parsed.failures[0].reason, "Missing title");
+        assert!(parsed.failures[1].reason.contains("BPM"));
+        assert!(parsed.failures[2].reason.contains("key"));
+        assert!(parsed.failures[3].reason.contains("Energy"));
+    }
+
+    #[test]
+    fn test_parse_csv_semicolons_and_bad_mapping() {
+        let csv = "Title;Artist\nYeke Yeke;Mory Kante\n";
+        let parsed = parse_file(FileFormat::Csv, csv, None).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.rows[0].1.artist.as_deref(), Some("Mory Kante"));
+
+        let missing_column = CsvMapping {
+            title: Some("Title".to_string()),
+            bpm: Some("BPM".to_string()),
+            ..Default::default()
+        };
+        assert!(matches!(
+            parse_csv(csv, &missing_column).unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+        assert!(matches!(
+            parse_csv(csv, &CsvMapping::default()).unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("6:21"), Some(381_000));
+        assert_eq!(parse_duration("1:02:03"), Some(3_723_000));
+        assert_eq!(parse_duration("285.5"), Some(285_500));
+        assert_eq!(parse_duration("0:00"), None);
+        assert_eq!(parse_duration("long"), None);
+    }
+
+    #[test]
+    fn test_preview_csv_suggests_mapping() {
+        let csv = "Artist,Title,Notes\nMory Kante,Yeke Yeke,opener\n";
+        let preview = preview_file(FileFormat::Csv, csv, None).unwrap();
+        assert_eq!(preview.columns, vec!["Artist", "Title", "Notes"]);
+        assert_eq!(
+            preview.mapping.as_ref().unwrap().title.as_deref(),
+            Some("Title")
+        );
+        assert_eq!(preview.total_rows, 1);
+        assert_eq!(preview.sample[0].title, "Yeke Yeke");
+
+        let unmapped = preview_file(FileFormat::Csv, "A,B\n1,2\n", None).unwrap();
+        assert_eq!(unmapped.columns, vec!["A", "B"]);
+        assert!(unmapped.sample.is_empty());
+    }
+}
@@ -1,8 +1,14 @@
+use std::collections::HashMap;
+
 use regex::Regex;
 use serde::{Deserialize, Serialize};
 
 use crate::api::retry::{retry_with_backoff, RetryConfig};
 use crate::api::spotify::{SpotifyClient, SpotifyError};
+use crate::services::deezer::{DeezerClient, DeezerError};
+use crate::services::local_scan::{self, Artwork, FileStamp};
+use crate::services::soundcloud::{SoundCloudClient, SoundCloudTrack};
+use crate::services::{rekordbox, serato, traktor};
 
 // ---------------------------------------------------------------------------
 // Error
@@ -24,6 +30,13 @@ pub enum ImportError {
 
     #[error("Access denied: {0}")]
     AccessDenied(String),
+
+    #[error("Invalid library file: {0}")]
+    InvalidFile(String),
+
+    /// Deezer or SoundCloud failed or is not configured.
+    #[error("{0}")]
+    Upstream(String),
 }
 
 // ---------------------------------------------------------------------------
@@ -48,6 +61,68 @@ pub struct ArtistRecord {
     pub spotify_uri: String,
 }
 
+/// A track read from a DJ library export rather than a streaming service.
+/// Unlike `TrackRecord` it carries no id: the repository matches it against
+/// the catalog and reports the id it ended up under.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LibraryTrackRecord {
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<i64>,
+    pub bpm: Option<f64>,
+    pub camelot_key: Option<String>,
+    /// 1-10, as stored in `tracks.energy`.
+    pub energy: Option<f64>,
+    /// Star rating, 0-5.
+    pub rating: Option<i16>,
+    /// Colour label as `#RRGGBB`.
+    pub colour: Option<String>,
+    pub file_location: Option<String>,
+    /// Library the track came from ("rekordbox", ...), stored as `tracks.source`.
+    pub source: String,
+}
+
+/// Sources whose BPM and key come from the DJ software's own analysis. For
+/// scanned files these are the tags that software wrote into the file.
+const ANALYSING_SOURCES: [&str; 4] = [
+    rekordbox::SOURCE,
+    traktor::SOURCE,
+    serato::SOURCE,
+    local_scan::SOURCE,
+];
+
+impl LibraryTrackRecord {
+    /// DJ software that analysed this track's BPM and key, if the export
+    /// carried either; recorded so enrichment does not overwrite them.
+    pub fn analysed_by(&self) -> Option<&str> {
+        let analysed = self.bpm.is_some() || self.camelot_key.is_some();
+        (analysed && ANALYSING_SOURCES.contains(&self.source.as_str()))
+            .then_some(self.source.as_str())
+    }
+}
+
+/// A track from a Deezer playlist or SoundCloud set. Like
+/// `LibraryTrackRecord` it carries no catalog id; the repository matches it
+/// by the provider's own id, then by title and artist.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StreamingTrackRecord {
+    /// `DEEZER_SOURCE` or `SOUNDCLOUD_SOURCE`, stored as `tracks.source`.
+    pub source: String,
+    /// Deezer track id or SoundCloud track URN.
+    pub provider_id: String,
+    pub title: String,
+    pub artist: Option<String>,
+    pub album: Option<String>,
+    pub duration_ms: Option<i64>,
+    /// Deezer's analysed BPM.
+    pub bpm: Option<f64>,
+    pub preview_url: Option<String>,
+    pub album_art_url: Option<String>,
+    /// SoundCloud's genre field, stored as a `genre` track tag.
+    pub genre: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub enum UpsertResult {
     Inserted,
@@ -58,14 +133,53 @@ pub enum UpsertResult {
 // Import summary
 // ---------------------------------------------------------------------------
 
+/// Where an import's tracks came from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ImportOrigin {
+    /// "spotify", "deezer", "soundcloud", "rekordbox", "traktor", "serato",
+    /// "m3u", "pls", "csv" or "local".
+    pub source: String,
+    /// The source's own identifier: a Spotify or Deezer playlist id, a
+    /// SoundCloud set URL, a library playlist path, a file name.
+    pub source_ref: String,
+    pub name: Option<String>,
+    /// Name of the uploaded file, for file imports.
+    pub file_name: Option<String>,
+}
+
+impl ImportOrigin {
+    pub fn new(source: &str, source_ref: &str, name: Option<&str>) -> Self {
+        Self {
+            source: source.to_string(),
+            source_ref: source_ref.to_string(),
+            name: name.map(str::to_string),
+            file_name: None,
+        }
+    }
+}
+
+/// A row that could not be imported.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImportFailure {
+    /// 1-based position in the source: playlist position, file line, CSV
+    /// row or position in a scanned folder.
+    pub row: u32,
+    /// What the row held, as far as it could be read.
+    pub item: Option<String>,
+    pub reason: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ImportSummary {
     pub import_id: String,
+    pub source: String,
     pub total: u32,
     pub inserted: u32,
     pub updated: u32,
     pub failed: u32,
     pub status: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub failures: Vec<ImportFailure>,
 }
 
 // ---------------------------------------------------------------------------
@@ -77,8 +191,7 @@ pub trait ImportRepository: Send + Sync {
     async fn create_import(
         &self,
         user_id: &str,
-        playlist_id: &str,
-        playlist_name: Option<&str>,
+        origin: &ImportOrigin,
     ) -> Result<String, ImportError>;
 
     async fn upsert_track(&self, track: &TrackRecord) -> Result<UpsertResult, ImportError>;
@@ -99,6 +212,73 @@ pub trait ImportRepository: Send + Sync {
         import_id: &str,
         track_id: &str,
     ) -> Result<(), ImportError>;
+
+    /// Upsert a library track, updating the catalog track it matches (same
+    /// file, or same title and artist) if there is one. Returns the track id.
+    async fn upsert_library_track(
+        &self,
+        _track: &LibraryTrackRecord,
+    ) -> Result<(String, UpsertResult), ImportError> {
+        Err(ImportError::Database(
+            "library imports are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Find an artist by name, creating it if needed. Returns the artist id.
+    async fn upsert_library_artist(&self, _name: &str) -> Result<String, ImportError> {
+        Err(ImportError::Database(
+            "library imports are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Find the user's crate with this name, creating it if needed. Returns
+    /// the crate id.
+    async fn upsert_crate(&self, _user_id: &str, _name: &str) -> Result<String, ImportError> {
+        Err(ImportError::Database(
+            "crate imports are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Add a library track to a crate. Returns `false` if it was already there.
+    async fn insert_crate_track(
+        &self,
+        _crate_id: &str,
+        _track: &LibraryTrackRecord,
+    ) -> Result<bool, ImportError> {
+        Err(ImportError::Database(
+            "crate imports are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Insert or update a Deezer or SoundCloud track, returning the id it is
+    /// stored under.
+    async fn upsert_streaming_track(
+        &self,
+        _track: &StreamingTrackRecord,
+    ) -> Result<(String, UpsertResult), ImportError> {
+        Err(ImportError::Database(
+            "streaming imports are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Stamps of previously scanned local files, by file location.
+    async fn local_file_stamps(&self) -> Result<HashMap<String, FileStamp>, ImportError> {
+        Err(ImportError::Database(
+            "folder scans are not supported by this repository".to_string(),
+        ))
+    }
+
+    /// Record a scanned file's stamp and embedded artwork on its track.
+    async fn record_local_file(
+        &self,
+        _track_id: &str,
+        _stamp: &FileStamp,
+        _artwork: Option<&Artwork>,
+    ) -> Result<(), ImportError> {
+        Err(ImportError::Database(
+            "folder scans are not supported by this repository".to_string(),
+        ))
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -137,10 +317,64 @@ pub fn validate_playlist_url(input: &str) -> Result<String, ImportError> {
     )))
 }
 
+/// A playlist URL recognised by [`parse_playlist_url`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PlaylistUrl {
+    /// Spotify playlist id.
+    Spotify(String),
+    /// Deezer playlist id.
+    Deezer(String),
+    /// SoundCloud set URL without query string,
+    /// `https://soundcloud.com/{user}/sets/{set}`.
+    SoundCloud(String),
+}
+
+/// Recognise a Spotify playlist, Deezer playlist or SoundCloud set URL.
+///
+/// Besides the Spotify formats of [`validate_playlist_url`]:
+/// - `https://www.deezer.com/playlist/{id}`, optionally with a locale
+///   (`/en/playlist/{id}`) and query string
+/// - `https://soundcloud.com/{user}/sets/{set}`, including private sets'
+///   secret token segment
+pub fn parse_playlist_url(input: &str) -> Result<PlaylistUrl, ImportError> {
+    let input = input.trim();
+    if let Ok(id) = validate_playlist_url(input) {
+        return Ok(PlaylistUrl::Spotify(id));
+    }
+
+    let deezer = Regex::new(
+        r"^https?://(?:www\.)?deezer\.com/(?:[a-z]{2}(?:-[a-z]{2})?/)?playlist/(\d+)/?(?:[?#].*)?$",
+    )
+    .unwrap();
+    if let Some(caps) = deezer.captures(input) {
+        return Ok(PlaylistUrl::Deezer(caps[1].to_string()));
+    }
+
+    let soundcloud = Regex::new(
+        r"^https?://(?:www\.|m\.)?soundcloud\.com/([\w-]+)/sets/([\w-]+)(/s-[\w-]+)?/?(?:[?#].*)?$",
+    )
+    .unwrap();
+    if let Some(caps) = soundcloud.captures(input) {
+        return Ok(PlaylistUrl::SoundCloud(format!(
+            "https://soundcloud.com/{}/sets/{}{}",
+            &caps[1],
+            &caps[2],
+            caps.get(3).map_or("", |m| m.as_str())
+        )));
+    }
+
+    Err(ImportError::InvalidUrl(format!(
+        "Unrecognised playlist URL: {input}. Use a Spotify or Deezer playlist or a SoundCloud set"
+    )))
+}
+
 // ---------------------------------------------------------------------------
 // Import orchestration
 // ---------------------------------------------------------------------------
 
+/// `ImportOrigin::source` for Spotify playlist imports.
+pub const SPOTIFY_SOURCE: &str = "spotify";
+
 pub async fn import_playlist(
     repo: &dyn ImportRepository,
     spotify: &SpotifyClient,
@@ -148,7 +382,12 @@ pub async fn import_playlist(
     user_id: &str,
     playlist_id: &str,
 ) -> Result<ImportSummary, ImportError> {
-    let import_id = repo.create_import(user_id, playlist_id, None).await?;
+    let import_id = repo
+        .create_import(
+            user_id,
+            &ImportOrigin::new(SPOTIFY_SOURCE, playlist_id, None),
+        )
+        .await?;
 
     let retry_cfg = RetryConfig::default();
     let mut offset: u32 = 0;
@@ -156,7 +395,7 @@ pub async fn import_playlist(
     let mut total_tracks: u32 = 0;
     let mut inserted: u32 = 0;
     let mut updated: u32 = 0;
-    let mut failed: u32 = 0;
+    let mut failures: Vec<ImportFailure> = Vec::new();
     let mut first_page = true;
 
     loop {
@@ -177,11 +416,13 @@ pub async fn import_playlist(
                 // API failure mid-import: commit what we have, mark import as failed.
                 let summary = ImportSummary {
                     import_id: import_id.clone(),
+                    source: SPOTIFY_SOURCE.to_string(),
                     total: total_tracks,
                     inserted,
                     updated,
-                    failed,
+                    failed: failures.len() as u32,
                     status: "failed".to_string(),
+                    failures,
                 };
                 let _ = repo.complete_import(&import_id, &summary).await;
                 return Err(e.into());
@@ -193,12 +434,17 @@ pub async fn import_playlist(
             first_page = false;
         }
 
-        for item in &page.items {
+        for (index, item) in page.items.iter().enumerate() {
+            let row = offset + index as u32 + 1;
             let raw_track = match &item.track {
                 Some(t) => t,
                 None => {
                     // Local/unavailable track — skip
-                    failed += 1;
+                    failures.push(ImportFailure {
+                        row,
+                        item: None,
+                        reason: "Track is unavailable or a local file".to_string(),
+                    });
                     continue;
                 }
             };
@@ -219,8 +465,12 @@ pub async fn import_playlist(
             match repo.upsert_track(&track_record).await {
                 Ok(UpsertResult::Inserted) => inserted += 1,
                 Ok(UpsertResult::Updated) => updated += 1,
-                Err(_) => {
-                    failed += 1;
+                Err(e) => {
+                    failures.push(ImportFailure {
+                        row,
+                        item: Some(track_record.title.clone()),
+                        reason: e.to_string(),
+                    });
                     continue;
                 }
             }
@@ -255,11 +505,13 @@ pub async fn import_playlist(
 
     let summary = ImportSummary {
         import_id: import_id.clone(),
+        source: SPOTIFY_SOURCE.to_string(),
         total: total_tracks,
         inserted,
         updated,
-        failed,
+        failed: failures.len() as u32,
         status: "completed".to_string(),
+        failures,
     };
 
     repo.complete_import(&import_id, &summary).await?;
@@ -267,6 +519,521 @@ pub async fn import_playlist(
     Ok(summary)
 }
 
+// ---------------------------------------------------------------------------
+// Deezer and SoundCloud playlist import
+// ---------------------------------------------------------------------------
+
+/// `ImportOrigin::source` and `tracks.source` for Deezer playlist imports.
+pub const DEEZER_SOURCE: &str = "deezer";
+
+/// `ImportOrigin::source` and `tracks.source` for SoundCloud set imports.
+pub const SOUNDCLOUD_SOURCE: &str = "soundcloud";
+
+/// Tracks per page when listing a Deezer playlist.
+const DEEZER_PAGE_SIZE: u32 = 100;
+
+/// Pause between Deezer track lookups; the public API allows 50 requests
+/// per 5 seconds.
+const DEEZER_LOOKUP_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+
+impl From<DeezerError> for ImportError {
+    fn from(e: DeezerError) -> Self {
+        match e {
+            DeezerError::NotFound(m) => ImportError::NotFound(format!("Deezer: {m}")),
+            other => ImportError::Upstream(other.to_string()),
+        }
+    }
+}
+
+/// Import a public Deezer playlist page by page. Each track's BPM comes from
+/// a separate track lookup; a failed lookup leaves the BPM empty.
+pub async fn import_deezer_playlist(
+    repo: &dyn ImportRepository,
+    deezer: &DeezerClient,
+    user_id: &str,
+    playlist_id: &str,
+) -> Result<ImportSummary, ImportError> {
+    let playlist = deezer.get_playlist(playlist_id).await?;
+    let import_id = repo
+        .create_import(
+            user_id,
+            &ImportOrigin::new(DEEZER_SOURCE, playlist_id, Some(&playlist.title)),
+        )
+        .await?;
+
+    let mut summary = ImportSummary {
+        import_id: import_id.clone(),
+        source: DEEZER_SOURCE.to_string(),
+        total: playlist.nb_tracks,
+        inserted: 0,
+        updated: 0,
+        failed: 0,
+        status: "completed".to_string(),
+        failures: Vec::new(),
+    };
+
+    let mut index: u32 = 0;
+    loop {
+        let page = match deezer
+            .get_playlist_tracks(playlist_id, index, DEEZER_PAGE_SIZE)
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => return Err(fail_import(repo, summary, e.into()).await),
+        };
+        if page.total > 0 {
+            summary.total = page.total;
+        }
+
+        for (offset, item) in page.data.iter().enumerate() {
+            tokio::time::sleep(DEEZER_LOOKUP_INTERVAL).await;
+            let bpm = deezer.get_track_bpm(item.id).await.unwrap_or_else(|e| {
+                tracing::warn!("Deezer BPM lookup failed for track {}: {e}", item.id);
+                None
+            });
+            let track = StreamingTrackRecord {
+                source: DEEZER_SOURCE.to_string(),
+                provider_id: item.id.to_string(),
+                title: item.title.clone(),
+                artist: Some(item.artist.name.clone()),
+                album: item.album.as_ref().map(|a| a.title.clone()),
+                duration_ms: (item.duration > 0).then(|| item.duration as i64 * 1000),
+                bpm,
+                preview_url: Some(item.preview.clone()).filter(|p| !p.is_empty()),
+                album_art_url: item.album.as_ref().and_then(|a| a.cover_medium.clone()),
+                genre: None,
+            };
+            let row = index + offset as u32 + 1;
+            record_streaming_track(repo, &import_id, &track, row, &mut summary).await;
+        }
+
+        index += page.data.len() as u32;
+        if page.next.is_none() || page.data.is_empty() {
+            break;
+        }
+    }
+
+    summary.failed = summary.failures.len() as u32;
+    repo.complete_import(&import_id, &summary).await?;
+    Ok(summary)
+}
+
+/// Import a SoundCloud set page by page. The uploader stands in for the
+/// artist unless SoundCloud has publisher metadata naming one.
+pub async fn import_soundcloud_set(
+    repo: &dyn ImportRepository,
+    soundcloud: &mut SoundCloudClient,
+    http: &reqwest::Client,
+    user_id: &str,
+    set_url: &str,
+) -> Result<ImportSummary, ImportError> {
+    let upstream = |e: anyhow::Error| ImportError::Upstream(format!("SoundCloud: {e}"));
+    let playlist = soundcloud
+        .resolve_playlist(http, set_url)
+        .await
+        .map_err(upstream)?;
+    let import_id = repo
+        .create_import(
+            user_id,
+            &ImportOrigin::new(SOUNDCLOUD_SOURCE, set_url, Some(&playlist.title)),
+        )
+        .await?;
+
+    let mut summary = ImportSummary {
+        import_id: import_id.clone(),
+        source: SOUNDCLOUD_SOURCE.to_string(),
+        total: playlist.track_count,
+        inserted: 0,
+        updated: 0,
+        failed: 0,
+        status: "completed".to_string(),
+        failures: Vec::new(),
+    };
+
+    let mut row: u32 = 0;
+    let mut next_href: Option<String> = None;
+    loop {
+        let page = match soundcloud
+            .get_playlist_tracks(http, &playlist.urn, next_href.as_deref())
+            .await
+        {
+            Ok(page) => page,
+            Err(e) => return Err(fail_import(repo, summary, upstream(e)).await),
+        };
+
+        for item in &page.collection {
+            row += 1;
+            let track = soundcloud_track_record(item);
+            record_streaming_track(repo, &import_id, &track, row, &mut summary).await;
+        }
+
+        next_href = page.next_href;
+        if next_href.is_none() || page.collection.is_empty() {
+            break;
+        }
+    }
+
+    // Sets can hold tracks that are no longer available; count what was listed.
+    summary.total = summary.total.max(row);
+    summary.failed = summary.failures.len() as u32;
+    repo.complete_import(&import_id, &summary).await?;
+    Ok(summary)
+}
+
+fn soundcloud_track_record(item: &SoundCloudTrack) -> StreamingTrackRecord {
+    let artist = item
+        .publisher_metadata
+        .as_ref()
+        .and_then(|p| p.artist.clone())
+        .filter(|a| !a.trim().is_empty())
+        .unwrap_or_else(|| item.user.username.clone());
+    StreamingTrackRecord {
+        source: SOUNDCLOUD_SOURCE.to_string(),
+        provider_id: item.urn.clone(),
+        title: item.title.clone(),
+        artist: Some(artist),
+        album: None,
+        duration_ms: item.duration.filter(|&ms| ms > 0),
+        bpm: item.bpm.filter(|&bpm| bpm > 0.0),
+        preview_url: None,
+        album_art_url: item.artwork_url.clone(),
+        genre: item
+            .genre
+            .as_deref()
+            .map(str::trim)
+            .filter(|g| !g.is_empty())
+            .map(str::to_string),
+    }
+}
+
+/// Store one playlist entry, counting it in `summary` or recording why it
+/// failed.
+async fn record_streaming_track(
+    repo: &dyn ImportRepository,
+    import_id: &str,
+    track: &StreamingTrackRecord,
+    row: u32,
+    summary: &mut ImportSummary,
+) {
+    match repo.upsert_streaming_track(track).await {
+        Ok((track_id, result)) => {
+            match result {
+                UpsertResult::Inserted => summary.inserted += 1,
+                UpsertResult::Updated => summary.updated += 1,
+            }
+            link_and_credit(repo, import_id, &track_id, track.artist.as_deref()).await;
+        }
+        Err(e) => summary.failures.push(ImportFailure {
+            row,
+            item: Some(match &track.artist {
+                Some(artist) => format!("{artist} - {}", track.title),
+                None => track.title.clone(),
+            }),
+            reason: e.to_string(),
+        }),
+    }
+}
+
+/// Mark an import failed after a provider error mid-way, keeping the tracks
+/// stored so far, and hand back the error.
+async fn fail_import(
+    repo: &dyn ImportRepository,
+    mut summary: ImportSummary,
+    error: ImportError,
+) -> ImportError {
+    summary.failed = summary.failures.len() as u32;
+    summary.status = "failed".to_string();
+    let _ = repo.complete_import(&summary.import_id, &summary).await;
+    error
+}
+
+// ---------------------------------------------------------------------------
+// Library import (DJ software exports)
+// ---------------------------------------------------------------------------
+
+/// A playlist from a library export, referencing tracks by their key in
+/// `ParsedLibrary::tracks`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryPlaylist {
+    /// Folder path and name, e.g. "Gigs/Friday Warmup".
+    pub path: String,
+    pub name: String,
+    pub track_keys: Vec<String>,
+}
+
+/// A library export parsed into catalog records, independent of the format.
+#[derive(Debug, Clone, Default)]
+pub struct ParsedLibrary {
+    /// Tracks keyed by the export's own track identifier.
+    pub tracks: Vec<(String, LibraryTrackRecord)>,
+    pub playlists: Vec<LibraryPlaylist>,
+}
+
+/// Rekordbox and Traktor both store star ratings as 0, 51, 102, 153, 204 or 255.
+const RATING_STEP: i64 = 51;
+
+/// Stars (1-5) from a 0-255 library rating; unrated tracks give `None`.
+pub(crate) fn rating_stars(raw: &str) -> Option<i16> {
+    let raw: i64 = raw.trim().parse().ok()?;
+    let stars = ((raw + RATING_STEP / 2) / RATING_STEP).clamp(0, 5);
+    (stars > 0).then_some(stars as i16)
+}
+
+/// Filesystem path from a playlist location: `file://` URLs
+/// (`file://localhost/Users/dj/Music/Track%20One.mp3`) are decoded, plain
+/// paths are kept as they are.
+pub(crate) fn location_path(location: &str) -> Option<String> {
+    let location = location.trim();
+    let Some(path) = location
+        .strip_prefix("file://localhost")
+        .or_else(|| location.strip_prefix("file://"))
+    else {
+        return (!location.is_empty()).then(|| location.to_string());
+    };
+    let decoded = urlencoding::decode(path).ok()?.into_owned();
+    // Windows paths come through as "/C:/Music/...".
+    let bytes = decoded.as_bytes();
+    let decoded = if bytes.len() > 2 && bytes[0] == b'/' && bytes[2] == b':' {
+        decoded[1..].to_string()
+    } else {
+        decoded
+    };
+    (!decoded.is_empty()).then_some(decoded)
+}
+
+/// Where a library's playlists end up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaylistTarget {
+    /// One import per playlist, usable as `source_playlist_id`.
+    Imports,
+    /// One crate per playlist, reused by name on re-import.
+    Crates,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlaylistImportSummary {
+    /// Import id, usable as `source_playlist_id` for generation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub import_id: Option<String>,
+    /// Crate id, usable as `source_crate_id` for generation.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub crate_id: Option<String>,
+    pub path: String,
+    pub name: String,
+    pub total: u32,
+    /// Entries whose track failed to import or was missing from the collection.
+    pub missing: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryImportSummary {
+    /// The whole collection, recorded as one import.
+    pub collection: ImportSummary,
+    pub playlists: Vec<PlaylistImportSummary>,
+}
+
+/// Write a parsed library export into the catalog.
+///
+/// The collection becomes one import holding every track. Each playlist
+/// becomes either its own import linked to its tracks, so it can be used as
+/// a generation source like an imported Spotify playlist, or a crate of the
+/// same name, depending on `target`.
+pub async fn import_library(
+    repo: &dyn ImportRepository,
+    user_id: &str,
+    source: &str,
+    library: &ParsedLibrary,
+    target: PlaylistTarget,
+) -> Result<LibraryImportSummary, ImportError> {
+    let import_id = repo
+        .create_import(
+            user_id,
+            &ImportOrigin::new(source, "collection", Some(&format!("{source} collection"))),
+        )
+        .await?;
+
+    let mut catalog: HashMap<&str, (String, &LibraryTrackRecord)> = HashMap::new();
+    let mut inserted: u32 = 0;
+    let mut updated: u32 = 0;
+    let mut failures: Vec<ImportFailure> = Vec::new();
+
+    for (index, (key, track)) in library.tracks.iter().enumerate() {
+        match store_library_track(repo, &import_id, track).await {
+            Ok((track_id, result)) => {
+                match result {
+                    UpsertResult::Inserted => inserted += 1,
+                    UpsertResult::Updated => updated += 1,
+                }
+                catalog.insert(key.as_str(), (track_id, track));
+            }
+            Err(e) => {
+                tracing::warn!("Failed to import {source} track '{}': {e}", track.title);
+                failures.push(ImportFailure {
+                    row: index as u32 + 1,
+                    item: Some(track.title.clone()),
+                    reason: e.to_string(),
+                });
+            }
+        }
+    }
+
+    let collection = ImportSummary {
+        import_id: import_id.clone(),
+        source: source.to_string(),
+        total: library.tracks.len() as u32,
+        inserted,
+        updated,
+        failed: failures.len() as u32,
+        status: "completed".to_string(),
+        failures,
+    };
+    repo.complete_import(&import_id, &collection).await?;
+
+    let mut playlists = Vec::with_capacity(library.playlists.len());
+    for playlist in &library.playlists {
+        let summary = match target {
+            PlaylistTarget::Imports => {
+                import_playlist_links(repo, user_id, source, playlist, &catalog).await?
+            }
+            PlaylistTarget::Crates => {
+                import_playlist_crate(repo, user_id, playlist, &catalog).await?
+            }
+        };
+        playlists.push(summary);
+    }
+
+    Ok(LibraryImportSummary {
+        collection,
+        playlists,
+    })
+}
+
+/// Upsert a library track, link it to `import_id` and credit its artist.
+/// Only the upsert itself can fail the track; link and artist failures are
+/// logged.
+pub(crate) async fn store_library_track(
+    repo: &dyn ImportRepository,
+    import_id: &str,
+    track: &LibraryTrackRecord,
+) -> Result<(String, UpsertResult), ImportError> {
+    let (track_id, result) = repo.upsert_library_track(track).await?;
+    link_and_credit(repo, import_id, &track_id, track.artist.as_deref()).await;
+    Ok((track_id, result))
+}
+
+/// Link a stored track to `import_id` and credit its artist by name,
+/// logging rather than failing on errors.
+async fn link_and_credit(
+    repo: &dyn ImportRepository,
+    import_id: &str,
+    track_id: &str,
+    artist: Option<&str>,
+) {
+    if let Err(e) = repo.insert_import_track_link(import_id, track_id).await {
+        tracing::warn!(
+            "Failed to record import-track link for import={}, track={}: {e}",
+            import_id,
+            track_id
+        );
+    }
+
+    if let Some(artist) = artist.filter(|a| !a.trim().is_empty()) {
+        if let Ok(artist_id) = repo.upsert_library_artist(artist.trim()).await {
+            let _ = repo.upsert_track_artist(track_id, &artist_id).await;
+        }
+    }
+}
+
+async fn import_playlist_links(
+    repo: &dyn ImportRepository,
+    user_id: &str,
+    source: &str,
+    playlist: &LibraryPlaylist,
+    catalog: &HashMap<&str, (String, &LibraryTrackRecord)>,
+) -> Result<PlaylistImportSummary, ImportError> {
+    let import_id = repo
+        .create_import(
+            user_id,
+            &ImportOrigin::new(source, &playlist.path, Some(&playlist.name)),
+        )
+        .await?;
+
+    let mut failures: Vec<ImportFailure> = Vec::new();
+    for (index, key) in playlist.track_keys.iter().enumerate() {
+        let linked = match catalog.get(key.as_str()) {
+            Some((track_id, _)) => repo
+                .insert_import_track_link(&import_id, track_id)
+                .await
+                .map_err(|e| e.to_string()),
+            None => Err("Track is not in the collection or failed to import".to_string()),
+        };
+        if let Err(reason) = linked {
+            failures.push(ImportFailure {
+                row: index as u32 + 1,
+                item: Some(key.clone()),
+                reason,
+            });
+        }
+    }
+
+    let total = playlist.track_keys.len() as u32;
+    let missing = failures.len() as u32;
+    repo.complete_import(
+        &import_id,
+        &ImportSummary {
+            import_id: import_id.clone(),
+            source: source.to_string(),
+            total,
+            inserted: 0,
+            updated: total - missing,
+            failed: missing,
+            status: "completed".to_string(),
+            failures,
+        },
+    )
+    .await?;
+
+    Ok(PlaylistImportSummary {
+        import_id: Some(import_id),
+        crate_id: None,
+        path: playlist.path.clone(),
+        name: playlist.name.clone(),
+        total,
+        missing,
+    })
+}
+
+async fn import_playlist_crate(
+    repo: &dyn ImportRepository,
+    user_id: &str,
+    playlist: &LibraryPlaylist,
+    catalog: &HashMap<&str, (String, &LibraryTrackRecord)>,
+) -> Result<PlaylistImportSummary, ImportError> {
+    // Crates are flat, so nested playlists keep their folder path in the name.
+    let crate_id = repo.upsert_crate(user_id, &playlist.path).await?;
+
+    let mut missing: u32 = 0;
+    for key in &playlist.track_keys {
+        let Some((_, track)) = catalog.get(key.as_str()) else {
+            missing += 1;
+            continue;
+        };
+        if let Err(e) = repo.insert_crate_track(&crate_id, track).await {
+            tracing::warn!("Failed to add '{}' to crate {crate_id}: {e}", track.title);
+            missing += 1;
+        }
+    }
+
+    Ok(PlaylistImportSummary {
+        import_id: None,
+        crate_id: Some(crate_id),
+        path: playlist.path.clone(),
+        name: playlist.name.clone(),
+        total: playlist.track_keys.len() as u32,
+        missing,
+    })
+}
+
 /// Deterministic ID from a Spotify URI (e.g. "spotify:track:abc" → "abc").
 fn deterministic_id(uri: &str) -> String {
     uri.rsplit(':').next().unwrap_or(uri).to_string()
@@ -330,6 +1097,48 @@ mod tests {
         assert_eq!(id, "37i9dQZF1DX0BcQWzuB7ZO");
     }
 
+    #[test]
+    fn test_parse_playlist_url_spotify() {
+        assert_eq!(
+            parse_playlist_url("spotify:playlist:37i9dQZF1DX0BcQWzuB7ZO").unwrap(),
+            PlaylistUrl::Spotify("37i9dQZF1DX0BcQWzuB7ZO".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_playlist_url_deezer() {
+        for url in [
+            "https://www.deezer.com/playlist/908622995",
+            "https://deezer.com/en/playlist/908622995?utm_source=share",
+            "http://www.deezer.com/pt-br/playlist/908622995/",
+        ] {
+            assert_eq!(
+                parse_playlist_url(url).unwrap(),
+                PlaylistUrl::Deezer("908622995".to_string()),
+                "{url}"
+            );
+        }
+        assert!(parse_playlist_url("https://www.deezer.com/album/302127").is_err());
+    }
+
+    #[test]
+    fn test_parse_playlist_url_soundcloud() {
+        assert_eq!(
+            parse_playlist_url("https://m.soundcloud.com/ninja-tune/sets/solid-steel?si=x")
+                .unwrap(),
+            PlaylistUrl::SoundCloud("https://soundcloud.com/ninja-tune/sets/solid-steel".into())
+        );
+        assert_eq!(
+            parse_playlist_url("https://soundcloud.com/dj/sets/promo/s-AbC123").unwrap(),
+            PlaylistUrl::SoundCloud("https://soundcloud.com/dj/sets/promo/s-AbC123".into())
+        );
+        assert!(parse_playlist_url("https://soundcloud.com/dj/a-single-track").is_err());
+        assert!(matches!(
+            parse_playlist_url("not-a-url"),
+            Err(ImportError::InvalidUrl(_))
+        ));
+    }
+
     // ---- Mock repository for import orchestration tests ----
 
     struct MockRepo {
@@ -339,6 +1148,9 @@ mod tests {
         track_artists: Mutex<Vec<(String, String)>>,
         completed: Mutex<Vec<ImportSummary>>,
         import_track_links: Mutex<Vec<(String, String)>>,
+        library_tracks: Mutex<Vec<LibraryTrackRecord>>,
+        crate_tracks: Mutex<Vec<(String, String)>>,
+        streaming_tracks: Mutex<Vec<StreamingTrackRecord>>,
     }
 
     impl MockRepo {
@@ -350,6 +1162,9 @@ mod tests {
                 track_artists: Mutex::new(Vec::new()),
                 completed: Mutex::new(Vec::new()),
                 import_track_links: Mutex::new(Vec::new()),
+                library_tracks: Mutex::new(Vec::new()),
+                crate_tracks: Mutex::new(Vec::new()),
+                streaming_tracks: Mutex::new(Vec::new()),
             }
         }
     }
@@ -359,11 +1174,11 @@ mod tests {
         async fn create_import(
             &self,
             _user_id: &str,
-            _playlist_id: &str,
-            _playlist_name: Option<&str>,
+            _origin: &ImportOrigin,
         ) -> Result<String, ImportError> {
-            let id = "import-001".to_string();
-            self.imports.lock().unwrap().push(id.clone());
+            let mut imports = self.imports.lock().unwrap();
+            let id = format!("import-{:03}", imports.len() + 1);
+            imports.push(id.clone());
             Ok(id)
         }
 
@@ -419,6 +1234,63 @@ mod tests {
                 .push((import_id.to_string(), track_id.to_string()));
             Ok(())
         }
+
+        async fn upsert_streaming_track(
+            &self,
+            track: &StreamingTrackRecord,
+        ) -> Result<(String, UpsertResult), ImportError> {
+            if track.title == "Broken" {
+                return Err(ImportError::Database("insert failed".to_string()));
+            }
+            let mut tracks = self.streaming_tracks.lock().unwrap();
+            let result = if tracks.iter().any(|t| t.provider_id == track.provider_id) {
+                UpsertResult::Updated
+            } else {
+                tracks.push(track.clone());
+                UpsertResult::Inserted
+            };
+            Ok((format!("{}-{}", track.source, track.provider_id), result))
+        }
+
+        async fn upsert_library_track(
+            &self,
+            track: &LibraryTrackRecord,
+        ) -> Result<(String, UpsertResult), ImportError> {
+            if track.title == "Broken" {
+                return Err(ImportError::Database("insert failed".to_string()));
+            }
+            let mut tracks = self.library_tracks.lock().unwrap();
+            if let Some(pos) = tracks
+                .iter()
+                .position(|t| t.file_location == track.file_location)
+            {
+                return Ok((format!("lib-{}", pos + 1), UpsertResult::Updated));
+            }
+            tracks.push(track.clone());
+            Ok((format!("lib-{}", tracks.len()), UpsertResult::Inserted))
+        }
+
+        async fn upsert_library_artist(&self, name: &str) -> Result<String, ImportError> {
+            Ok(format!("artist:{name}"))
+        }
+
+        async fn upsert_crate(&self, _user_id: &str, name: &str) -> Result<String, ImportError> {
+            Ok(format!("crate:{name}"))
+        }
+
+        async fn insert_crate_track(
+            &self,
+            crate_id: &str,
+            track: &LibraryTrackRecord,
+        ) -> Result<bool, ImportError> {
+            let mut tracks = self.crate_tracks.lock().unwrap();
+            let entry = (crate_id.to_string(), track.title.clone());
+            if tracks.contains(&entry) {
+                return Ok(false);
+            }
+            tracks.push(entry);
+            Ok(true)
+        }
     }
 
     // ---- Import orchestration test using wiremock ----
@@ -571,6 +1443,188 @@ mod tests {
         assert_eq!(summary.status, "completed");
     }
 
+    #[tokio::test]
+    async fn test_import_deezer_playlist_pages_and_bpm() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let item = |id: i64, title: &str| {
+            serde_json::json!({
+                "id": id,
+                "title": title,
+                "duration": 240,
+                "preview": format!("https://cdn.example.com/{id}.mp3"),
+                "artist": { "name": "Ali Farka Touré" },
+                "album": { "title": "Talking Timbuktu", "cover_medium": "https://cdn.example.com/c.jpg" }
+            })
+        };
+
+        Mock::given(method("GET"))
+            .and(path("/playlist/42"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(
+                serde_json::json!({ "id": 42, "title": "Desert Blues", "nb_tracks": 3 }),
+            ))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlist/42/tracks"))
+            .and(query_param("index", "0"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [item(1, "Bonde"), item(2, "Broken")],
+                "total": 3,
+                "next": "https://api.deezer.com/playlist/42/tracks?index=2"
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlist/42/tracks"))
+            .and(query_param("index", "2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "data": [item(3, "Diaraby")],
+                "total": 3
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/track/1"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "id": 1, "bpm": 96.5 })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/track/3"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "id": 3, "bpm": 0 })),
+            )
+            .mount(&server)
+            .await;
+
+        let deezer = DeezerClient::new().with_base_url(server.uri());
+        let repo = MockRepo::new();
+        let summary = import_deezer_playlist(&repo, &deezer, "user1", "42")
+            .await
+            .unwrap();
+
+        assert_eq!(summary.source, "deezer");
+        assert_eq!(summary.total, 3);
+        assert_eq!(summary.inserted, 2);
+        assert_eq!(summary.failed, 1);
+        assert_eq!(summary.failures[0].row, 2);
+        assert_eq!(summary.status, "completed");
+
+        let tracks = repo.streaming_tracks.lock().unwrap();
+        assert_eq!(tracks[0].provider_id, "1");
+        assert_eq!(tracks[0].bpm, Some(96.5));
+        assert_eq!(tracks[0].duration_ms, Some(240_000));
+        assert_eq!(tracks[0].album.as_deref(), Some("Talking Timbuktu"));
+        assert_eq!(tracks[1].bpm, None, "unanalysed BPM of 0 is dropped");
+        assert_eq!(repo.import_track_links.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_deezer_playlist_not_found() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/playlist/7"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "error": { "type": "DataException", "message": "no data", "code": 800 }
+            })))
+            .mount(&server)
+            .await;
+
+        let deezer = DeezerClient::new().with_base_url(server.uri());
+        let repo = MockRepo::new();
+        let result = import_deezer_playlist(&repo, &deezer, "user1", "7").await;
+
+        assert!(matches!(result, Err(ImportError::NotFound(_))));
+        assert!(repo.imports.lock().unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_import_soundcloud_set_follows_next_href() {
+        use wiremock::matchers::{method, path, query_param};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let set_url = "https://soundcloud.com/ninja-tune/sets/solid-steel";
+
+        Mock::given(method("POST"))
+            .and(path("/oauth2/token"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "tok",
+                "expires_in": 3600
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/resolve"))
+            .and(query_param("url", set_url))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "urn": "soundcloud:playlists:99",
+                "title": "Solid Steel",
+                "track_count": 2
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/playlists/soundcloud:playlists:99/tracks"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "collection": [{
+                    "urn": "soundcloud:tracks:1",
+                    "title": "Mix One",
+                    "duration": 3600000,
+                    "genre": " Electronic ",
+                    "user": { "username": "ninja-tune" },
+                    "publisher_metadata": { "artist": "Bonobo" }
+                }],
+                "next_href": format!("{}/page-2", server.uri())
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/page-2"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "collection": [{
+                    "urn": "soundcloud:tracks:2",
+                    "title": "Mix Two",
+                    "genre": "",
+                    "user": { "username": "ninja-tune" }
+                }],
+                "next_href": null
+            })))
+            .mount(&server)
+            .await;
+
+        let mut soundcloud = SoundCloudClient::new("id", "secret").with_api_base(server.uri());
+        let repo = MockRepo::new();
+        let summary = import_soundcloud_set(
+            &repo,
+            &mut soundcloud,
+            &reqwest::Client::new(),
+            "user1",
+            set_url,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.source, "soundcloud");
+        assert_eq!(summary.total, 2);
+        assert_eq!(summary.inserted, 2);
+
+        let tracks = repo.streaming_tracks.lock().unwrap();
+        assert_eq!(tracks[0].artist.as_deref(), Some("Bonobo"));
+        assert_eq!(tracks[0].genre.as_deref(), Some("Electronic"));
+        assert_eq!(tracks[0].duration_ms, Some(3_600_000));
+        assert_eq!(tracks[1].artist.as_deref(), Some("ninja-tune"));
+        assert_eq!(tracks[1].genre, None);
+    }
+
     #[test]
     fn test_deterministic_id() {
         assert_eq!(deterministic_id("spotify:track:abc123"), "abc123");
@@ -702,8 +1756,7 @@ mod tests {
             async fn create_import(
                 &self,
                 _user_id: &str,
-                _playlist_id: &str,
-                _playlist_name: Option<&str>,
+                _origin: &ImportOrigin,
             ) -> Result<String, ImportError> {
                 Ok("imp-fail-link".to_string())
             }
@@ -838,4 +1891,154 @@ mod tests {
         assert_eq!(links.len(), 1);
         assert_eq!(links[0].1, "valid1");
     }
+
+    // ---- Library import tests ----
+
+    #[test]
+    fn test_rating_stars_steps() {
+        assert_eq!(rating_stars("0"), None);
+        assert_eq!(rating_stars("51"), Some(1));
+        assert_eq!(rating_stars("153"), Some(3));
+        assert_eq!(rating_stars("255"), Some(5));
+        assert_eq!(rating_stars("junk"), None);
+    }
+
+    fn library_track(title: &str, location: &str) -> LibraryTrackRecord {
+        LibraryTrackRecord {
+            title: title.to_string(),
+            artist: Some("Mory Kante".to_string()),
+            file_location: Some(location.to_string()),
+            source: "rekordbox".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn library() -> ParsedLibrary {
+        ParsedLibrary {
+            tracks: vec![
+                ("1".to_string(), library_track("Yeke Yeke", "/music/a.mp3")),
+                ("2".to_string(), library_track("Tama", "/music/b.mp3")),
+                ("3".to_string(), library_track("Broken", "/music/c.mp3")),
+            ],
+            playlists: vec![LibraryPlaylist {
+                path: "Gigs/Friday".to_string(),
+                name: "Friday".to_string(),
+                track_keys: vec!["2".to_string(), "3".to_string(), "9".to_string()],
+            }],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_import_library_records_collection_and_playlists() {
+        let repo = MockRepo::new();
+        let summary = import_library(
+            &repo,
+            "user-1",
+            "rekordbox",
+            &library(),
+            PlaylistTarget::Imports,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(summary.collection.import_id, "import-001");
+        assert_eq!(summary.collection.total, 3);
+        assert_eq!(summary.collection.inserted, 2);
+        assert_eq!(summary.collection.failed, 1);
+
+        assert_eq!(summary.playlists.len(), 1);
+        let friday = &summary.playlists[0];
+        assert_eq!(friday.import_id.as_deref(), Some("import-002"));
+        assert_eq!(friday.path, "Gigs/Friday");
+        assert_eq!(friday.total, 3);
+        assert_eq!(friday.missing, 2, "failed and unknown tracks are missing");
+
+        let links = repo.import_track_links.lock().unwrap();
+        assert!(links.contains(&("import-001".to_string(), "lib-1".to_string())));
+        assert!(links.contains(&("import-002".to_string(), "lib-2".to_string())));
+        assert_eq!(links.iter().filter(|(i, _)| i == "import-002").count(), 1);
+
+        let track_artists = repo.track_artists.lock().unwrap();
+        assert!(track_artists.contains(&("lib-1".to_string(), "artist:Mory Kante".to_string())));
+        assert_eq!(repo.completed.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_library_reimport_updates() {
+        let repo = MockRepo::new();
+        import_library(
+            &repo,
+            "user-1",
+            "rekordbox",
+            &library(),
+            PlaylistTarget::Imports,
+        )
+        .await
+        .unwrap();
+        let again = import_library(
+            &repo,
+            "user-1",
+            "rekordbox",
+            &library(),
+            PlaylistTarget::Imports,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(again.collection.inserted, 0);
+        assert_eq!(again.collection.updated, 2);
+        assert_eq!(repo.library_tracks.lock().unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_import_library_playlists_as_crates() {
+        let repo = MockRepo::new();
+        let summary = import_library(
+            &repo,
+            "user-1",
+            "traktor",
+            &library(),
+            PlaylistTarget::Crates,
+        )
+        .await
+        .unwrap();
+
+        let friday = &summary.playlists[0];
+        assert_eq!(friday.crate_id.as_deref(), Some("crate:Gigs/Friday"));
+        assert_eq!(friday.import_id, None);
+        assert_eq!(friday.missing, 2);
+        assert_eq!(
+            *repo.crate_tracks.lock().unwrap(),
+            vec![("crate:Gigs/Friday".to_string(), "Tama".to_string())]
+        );
+        // Only the collection is recorded as an import.
+        assert_eq!(repo.imports.lock().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_library_track_analysed_by() {
+        let mut track = library_track("Tama", "/music/b.mp3");
+        assert_eq!(track.analysed_by(), None);
+        track.bpm = Some(120.0);
+        assert_eq!(track.analysed_by(), Some("rekordbox"));
+        track.source = "csv".to_string();
+        assert_eq!(track.analysed_by(), None, "typed-in BPM is not analysed");
+    }
+
+    #[test]
+    fn test_location_path() {
+        assert_eq!(
+            location_path("file://localhost/Users/dj/Track%20One.mp3").as_deref(),
+            Some("/Users/dj/Track One.mp3")
+        );
+        assert_eq!(
+            location_path("file:///C:/Music/a%20b.flac").as_deref(),
+            Some("C:/Music/a b.flac")
+        );
+        assert_eq!(
+            location_path(" /music/100% Pure.mp3 ").as_deref(),
+            Some("/music/100% Pure.mp3")
+        );
+        assert_eq!(location_path(""), None);
+    }
 }
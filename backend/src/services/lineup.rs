@@ -0,0 +1,796 @@
+use serde::Serialize;
+
+use crate::api::claude::ClaudeClientTrait;
+use crate::db::lineups as db;
+use crate::db::models::{LineupRow, LineupSlotRow};
+use crate::services::camelot::{
+    self, bpm_match_with_bands, classify_transition, effective_key, parse_camelot, EnergyProfile,
+    ScoringProfile, TempoRelation, TransitionType,
+};
+use crate::services::constraints::TrackConstraints;
+use crate::services::setlist::{
+    self, GenerateSetlistRequest, GenerationContext, SetlistError, SetlistResponse,
+    SetlistTrackResponse, MAX_TARGET_DURATION_MINUTES, MIN_TARGET_DURATION_MINUTES,
+};
+use crate::services::technique::{self, MixPoint};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Upper bound on slots per lineup (each one is a separate LLM call).
+pub const MAX_LINEUP_SLOTS: usize = 8;
+/// Tracks each DJ plays before handing over in a back-to-back slot.
+pub const DEFAULT_ALTERNATE_EVERY: u32 = 2;
+
+/// One slot of a lineup: who plays, for how long, and with what energy.
+#[derive(Debug, Clone)]
+pub struct LineupSlot {
+    pub dj: String,
+    /// DJs playing back-to-back with `dj`, in turn order after `dj`.
+    pub b2b_with: Vec<String>,
+    /// Tracks per turn in a back-to-back slot; ignored for a solo slot.
+    pub alternate_every: u32,
+    pub duration_minutes: u32,
+    pub energy_profile: Option<EnergyProfile>,
+}
+
+pub struct GenerateLineupRequest {
+    pub user_id: String,
+    /// Shared brief for the whole night; every slot is generated from it.
+    pub prompt: String,
+    pub name: Option<String>,
+    pub slots: Vec<LineupSlot>,
+}
+
+/// The mix from the last track of one slot into the first of the next.
+#[derive(Debug, Clone, Serialize)]
+pub struct Handoff {
+    /// 1-based slot positions either side of the boundary.
+    pub from_slot: i32,
+    pub to_slot: i32,
+    pub from_track: String,
+    pub to_track: String,
+    /// Same 0.0-1.0 scale as a track's `transition_score`.
+    pub transition_score: f64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_transition: Option<TransitionType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempo_relation: Option<TempoRelation>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineupSlotResponse {
+    pub position: i32,
+    pub dj: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub b2b_with: Vec<String>,
+    pub alternate_every: u32,
+    pub duration_minutes: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_profile: Option<String>,
+    /// DJ playing each track of the setlist, for back-to-back slots.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub track_djs: Vec<String>,
+    /// `None` if the slot's setlist has since been deleted.
+    pub setlist: Option<SetlistResponse>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LineupResponse {
+    pub id: String,
+    pub name: Option<String>,
+    pub prompt: String,
+    pub slots: Vec<LineupSlotResponse>,
+    pub handoffs: Vec<Handoff>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_runtime_ms: Option<i64>,
+    pub created_at: Option<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Validation
+// ---------------------------------------------------------------------------
+
+fn validate_slots(slots: &[LineupSlot]) -> Result<(), SetlistError> {
+    let invalid = |msg: String| Err(SetlistError::InvalidRequest(msg));
+    if slots.is_empty() || slots.len() > MAX_LINEUP_SLOTS {
+        return invalid(format!(
+            "A lineup needs 1-{MAX_LINEUP_SLOTS} slots, got {}",
+            slots.len()
+        ));
+    }
+    for (i, slot) in slots.iter().enumerate() {
+        let n = i + 1;
+        if slot.dj.trim().is_empty() || slot.b2b_with.iter().any(|d| d.trim().is_empty()) {
+            return invalid(format!("Slot {n}: DJ names cannot be empty"));
+        }
+        if !(MIN_TARGET_DURATION_MINUTES..=MAX_TARGET_DURATION_MINUTES)
+            .contains(&slot.duration_minutes)
+        {
+            return invalid(format!(
+                "Slot {n}: duration_minutes must be between {MIN_TARGET_DURATION_MINUTES} and {MAX_TARGET_DURATION_MINUTES}, got {}",
+                slot.duration_minutes
+            ));
+        }
+        if slot.alternate_every == 0 {
+            return invalid(format!("Slot {n}: alternate_every must be at least 1"));
+        }
+    }
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Case-insensitive identity of a track, for spotting repeats across sets.
+pub fn track_identity(artist: &str, title: &str) -> String {
+    format!(
+        "{} - {}",
+        artist.trim().to_lowercase(),
+        title.trim().to_lowercase()
+    )
+}
+
+/// DJ on each of `track_count` tracks of a slot. Back-to-back DJs take turns
+/// of `alternate_every` tracks, starting with `slot.dj`; a solo slot gets an
+/// empty list.
+pub fn track_djs(slot: &LineupSlot, track_count: usize) -> Vec<String> {
+    if slot.b2b_with.is_empty() {
+        return vec![];
+    }
+    let turns: Vec<&String> = std::iter::once(&slot.dj).chain(&slot.b2b_with).collect();
+    let every = slot.alternate_every.max(1) as usize;
+    (0..track_count)
+        .map(|i| turns[(i / every) % turns.len()].clone())
+        .collect()
+}
+
+fn pair_score(
+    from: &SetlistTrackResponse,
+    to: &SetlistTrackResponse,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> f64 {
+    camelot::transition_score_with_profile(
+        from.camelot.as_deref().and_then(parse_camelot).as_ref(),
+        to.camelot.as_deref().and_then(parse_camelot).as_ref(),
+        from.bpm,
+        to.bpm,
+        key_lock,
+        scoring,
+    )
+}
+
+/// Move the track that mixes best out of `previous` to the front of `tracks`.
+///
+/// Only tracks within one energy level of the current opener are considered,
+/// so the slot's energy shape survives; ties keep the current opener. When
+/// the order changes, positions are renumbered and transition notes are
+/// rewritten from the new neighbours.
+pub fn open_with_best_handoff(
+    tracks: &mut [SetlistTrackResponse],
+    previous: &SetlistTrackResponse,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) {
+    let Some(opener) = tracks.first() else {
+        return;
+    };
+    let opener_energy = opener.energy;
+    let mut best = 0;
+    let mut best_score = pair_score(previous, opener, key_lock, scoring);
+    for (i, track) in tracks.iter().enumerate().skip(1) {
+        let close_in_energy = match (opener_energy, track.energy) {
+            (Some(a), Some(b)) => (a - b).abs() <= 1.0,
+            _ => true,
+        };
+        let score = pair_score(previous, track, key_lock, scoring);
+        if close_in_energy && score > best_score {
+            best = i;
+            best_score = score;
+        }
+    }
+    if best == 0 {
+        return;
+    }
+
+    tracks[..=best].rotate_right(1);
+    let points: Vec<MixPoint> = tracks
+        .iter()
+        .map(|t| MixPoint::new(t.camelot.as_deref(), t.bpm, t.energy, t.duration_ms))
+        .collect();
+    let notes = technique::transition_notes(&points, key_lock, scoring);
+    for (i, (track, note)) in tracks.iter_mut().zip(notes).enumerate() {
+        track.position = (i + 1) as i32;
+        track.original_position = (i + 1) as i32;
+        track.transition_note = note;
+    }
+}
+
+/// Score the boundary between two consecutive slots.
+fn handoff(
+    from_slot: i32,
+    to_slot: i32,
+    from: &SetlistTrackResponse,
+    to: &SetlistTrackResponse,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> Handoff {
+    let from_key = from.camelot.as_deref().and_then(parse_camelot);
+    let to_key = to.camelot.as_deref().and_then(parse_camelot);
+    Handoff {
+        from_slot,
+        to_slot,
+        from_track: format!("{} - {}", from.artist, from.title),
+        to_track: format!("{} - {}", to.artist, to.title),
+        transition_score: pair_score(from, to, key_lock, scoring),
+        key_transition: from_key
+            .zip(to_key)
+            .map(|(a, b)| classify_transition(&a, &effective_key(&b, from.bpm, to.bpm, key_lock))),
+        tempo_relation: from
+            .bpm
+            .zip(to.bpm)
+            .map(|(a, b)| bpm_match_with_bands(a, b, &scoring.bpm_bands).relation),
+    }
+}
+
+/// Handoffs between every pair of consecutive slots that both have tracks.
+fn handoffs(
+    slots: &[LineupSlotResponse],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> Vec<Handoff> {
+    slots
+        .windows(2)
+        .filter_map(|pair| {
+            let from = pair[0].setlist.as_ref()?.tracks.last()?;
+            let to = pair[1].setlist.as_ref()?.tracks.first()?;
+            Some(handoff(
+                pair[0].position,
+                pair[1].position,
+                from,
+                to,
+                key_lock,
+                scoring,
+            ))
+        })
+        .collect()
+}
+
+/// Prompt lines telling the LLM where this slot sits in the night.
+fn slot_instructions(
+    index: usize,
+    slot_count: usize,
+    slot: &LineupSlot,
+    previous: Option<&SetlistTrackResponse>,
+) -> String {
+    let mut text = format!(
+        "This is slot {} of {slot_count} in a multi-DJ lineup, played by {}",
+        index + 1,
+        slot.dj
+    );
+    if !slot.b2b_with.is_empty() {
+        text.push_str(&format!(
+            " back-to-back with {}, alternating every {} tracks",
+            slot.b2b_with.join(", "),
+            slot.alternate_every
+        ));
+    }
+    text.push('.');
+    if let Some(prev) = previous {
+        let bpm = prev
+            .bpm
+            .map(|b| format!("{b:.0} BPM"))
+            .unwrap_or_else(|| "unknown BPM".to_string());
+        let key = prev.camelot.as_deref().unwrap_or("unknown key");
+        text.push_str(&format!(
+            " The previous DJ closes with \"{}\" by {} ({bpm}, {key}); open with a track that mixes smoothly out of it (compatible key, close tempo). Do not repeat any track the previous DJs played.",
+            prev.title, prev.artist
+        ));
+    }
+    text
+}
+
+fn slot_response(
+    position: i32,
+    slot: &LineupSlot,
+    setlist: Option<SetlistResponse>,
+) -> LineupSlotResponse {
+    let track_count = setlist.as_ref().map_or(0, |s| s.tracks.len());
+    LineupSlotResponse {
+        position,
+        dj: slot.dj.clone(),
+        b2b_with: slot.b2b_with.clone(),
+        alternate_every: slot.alternate_every,
+        duration_minutes: slot.duration_minutes,
+        energy_profile: slot.energy_profile.map(|p| p.to_string()),
+        track_djs: track_djs(slot, track_count),
+        setlist,
+    }
+}
+
+fn total_runtime_ms(slots: &[LineupSlotResponse]) -> Option<i64> {
+    slots
+        .iter()
+        .filter_map(|s| s.setlist.as_ref()?.total_runtime_ms)
+        .reduce(|a, b| a + b)
+}
+
+// ---------------------------------------------------------------------------
+// Service functions
+// ---------------------------------------------------------------------------
+
+/// Generate one setlist per slot, in play order.
+///
+/// Each slot is generated with every track of the earlier slots excluded,
+/// and opens on the track that mixes best out of the previous slot's closer.
+/// Every slot counts as one generation; the request fails up front if the
+/// user's remaining daily allowance cannot cover all of them. The lineup is
+/// saved once every slot has been generated, and if any step fails the
+/// setlists already generated are deleted.
+pub async fn generate_lineup(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: GenerateLineupRequest,
+) -> Result<LineupResponse, SetlistError> {
+    let prompt = req.prompt.trim().to_string();
+    if prompt.is_empty() {
+        return Err(SetlistError::InvalidRequest(
+            "Prompt cannot be empty".to_string(),
+        ));
+    }
+    validate_slots(&req.slots)?;
+    setlist::check_generation_allowance(pool, &req.user_id, req.slots.len() as i64).await?;
+
+    let mut slots = Vec::with_capacity(req.slots.len());
+    let saved = build_lineup(pool, claude, &req, &prompt, &mut slots).await;
+    match saved {
+        Ok(lineup_id) => get_lineup(pool, &lineup_id).await,
+        Err(e) => {
+            let generated: Vec<String> = slots
+                .iter()
+                .filter_map(|s| Some(s.setlist.as_ref()?.id.clone()))
+                .collect();
+            setlist::discard_setlists(pool, &generated).await;
+            Err(e)
+        }
+    }
+}
+
+/// Generate every slot into `slots` and save the lineup, returning its id.
+async fn build_lineup(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: &GenerateLineupRequest,
+    prompt: &str,
+    slots: &mut Vec<LineupSlotResponse>,
+) -> Result<String, SetlistError> {
+    let mut context = GenerationContext::default();
+    for (i, slot) in req.slots.iter().enumerate() {
+        context.instructions = Some(slot_instructions(
+            i,
+            req.slots.len(),
+            slot,
+            context.handoff_from.as_ref(),
+        ));
+        let name = match req.name {
+            Some(ref lineup_name) => format!("{lineup_name} — {}", slot.dj),
+            None => slot.dj.clone(),
+        };
+        let setlist = setlist::generate_setlist_with_context(
+            pool,
+            claude,
+            GenerateSetlistRequest {
+                user_id: req.user_id.clone(),
+                prompt: prompt.to_string(),
+                track_count: None,
+                target_duration_minutes: Some(slot.duration_minutes),
+                energy_profile: slot.energy_profile,
+                energy_curve: None,
+                key_path: None,
+                source_playlist_id: None,
+                source_crate_id: None,
+                seed_tracklist: None,
+                creative_mode: None,
+                bpm_range: None,
+                verify: false,
+                name: Some(name),
+                constraints: TrackConstraints::default(),
+            },
+            &context,
+        )
+        .await?;
+
+        for track in &setlist.tracks {
+            if let Some(ref id) = track.track_id {
+                context.exclude_track_ids.insert(id.clone());
+            }
+            context
+                .exclude_tracks
+                .insert(track_identity(&track.artist, &track.title));
+        }
+        if let Some(last) = setlist.tracks.last() {
+            context.handoff_from = Some(last.clone());
+        }
+        slots.push(slot_response((i + 1) as i32, slot, Some(setlist)));
+    }
+
+    let lineup_id = uuid::Uuid::new_v4().to_string();
+    db::insert_lineup(
+        pool,
+        &LineupRow {
+            id: lineup_id.clone(),
+            user_id: req.user_id.clone(),
+            name: req.name.clone(),
+            prompt: prompt.to_string(),
+            created_at: None,
+        },
+    )
+    .await?;
+    for (slot, response) in req.slots.iter().zip(slots.iter()) {
+        // Setlists that failed to save carry a temporary "unsaved-" id
+        let setlist_id = response
+            .setlist
+            .as_ref()
+            .map(|s| s.id.clone())
+            .filter(|id| !id.starts_with("unsaved-"));
+        db::insert_lineup_slot(
+            pool,
+            &LineupSlotRow {
+                id: uuid::Uuid::new_v4().to_string(),
+                lineup_id: lineup_id.clone(),
+                position: response.position,
+                dj: slot.dj.clone(),
+                b2b_with: (!slot.b2b_with.is_empty())
+                    .then(|| serde_json::to_string(&slot.b2b_with).ok())
+                    .flatten(),
+                alternate_every: slot.alternate_every as i32,
+                duration_minutes: slot.duration_minutes as i32,
+                energy_profile: slot.energy_profile.map(|p| p.to_string()),
+                setlist_id,
+            },
+        )
+        .await?;
+    }
+
+    Ok(lineup_id)
+}
+
+/// Load a lineup with each slot's current setlist and fresh handoff scores.
+pub async fn get_lineup(pool: &sqlx::PgPool, id: &str) -> Result<LineupResponse, SetlistError> {
+    let lineup = db::get_lineup(pool, id)
+        .await?
+        .ok_or_else(|| SetlistError::NotFound(format!("Lineup {id} not found")))?;
+    let rows = db::get_lineup_slots(pool, id).await?;
+
+    let mut slots = Vec::with_capacity(rows.len());
+    for row in rows {
+        let setlist = match row.setlist_id {
+            Some(ref setlist_id) => match setlist::get_setlist(pool, setlist_id).await {
+                Ok(s) => Some(s),
+                Err(SetlistError::NotFound(_)) => None,
+                Err(e) => return Err(e),
+            },
+            None => None,
+        };
+        let slot = LineupSlot {
+            dj: row.dj,
+            b2b_with: row
+                .b2b_with
+                .as_deref()
+                .and_then(|j| serde_json::from_str(j).ok())
+                .unwrap_or_default(),
+            alternate_every: row.alternate_every.max(1) as u32,
+            duration_minutes: row.duration_minutes.max(0) as u32,
+            energy_profile: row.energy_profile.as_deref().and_then(|p| p.parse().ok()),
+        };
+        slots.push(slot_response(row.position, &slot, setlist));
+    }
+
+    let key_lock = crate::db::settings::get_key_lock(pool, &lineup.user_id).await?;
+    let scoring = crate::db::settings::get_scoring_profile(pool, &lineup.user_id).await?;
+    Ok(LineupResponse {
+        id: lineup.id,
+        name: lineup.name,
+        prompt: lineup.prompt,
+        handoffs: handoffs(&slots, key_lock, &scoring),
+        total_runtime_ms: total_runtime_ms(&slots),
+        slots,
+        created_at: lineup
+            .created_at
+            .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::claude::{CacheMetrics, ClaudeError, RequestContentBlock};
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Returns queued responses in order, one per generation, then fails.
+    struct QueuedClaude {
+        responses: Mutex<VecDeque<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ClaudeClientTrait for QueuedClaude {
+        async fn generate_setlist(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: u32,
+        ) -> Result<String, ClaudeError> {
+            unreachable!("lineups generate with blocks")
+        }
+
+        async fn generate_with_blocks(
+            &self,
+            _: Vec<RequestContentBlock>,
+            _: Vec<RequestContentBlock>,
+            _: &str,
+            _: u32,
+        ) -> Result<(String, CacheMetrics), ClaudeError> {
+            let next = self.responses.lock().unwrap().pop_front();
+            next.map(|r| (r, CacheMetrics::default()))
+                .ok_or_else(|| ClaudeError::Api("no queued response".to_string()))
+        }
+    }
+
+    fn track(title: &str, camelot: &str, bpm: f64, energy: f64) -> SetlistTrackResponse {
+        SetlistTrackResponse {
+            position: 0,
+            title: title.to_string(),
+            artist: "Artist".to_string(),
+            bpm: Some(bpm),
+            key: None,
+            camelot: Some(camelot.to_string()),
+            energy: Some(energy),
+            transition_note: Some("LLM note".to_string()),
+            transition_score: None,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: None,
+            start_time_ms: None,
+            original_position: 0,
+            source: "suggestion".to_string(),
+            track_id: None,
+            spotify_uri: None,
+            confidence: None,
+            verification_flag: None,
+            verification_note: None,
+        }
+    }
+
+    fn slot(dj: &str, b2b_with: &[&str]) -> LineupSlot {
+        LineupSlot {
+            dj: dj.to_string(),
+            b2b_with: b2b_with.iter().map(|d| d.to_string()).collect(),
+            alternate_every: DEFAULT_ALTERNATE_EVERY,
+            duration_minutes: 20,
+            energy_profile: None,
+        }
+    }
+
+    #[test]
+    fn test_track_djs_alternate_in_turns() {
+        assert!(track_djs(&slot("Solo", &[]), 4).is_empty());
+        assert_eq!(
+            track_djs(&slot("A", &["B"]), 5),
+            vec!["A", "A", "B", "B", "A"]
+        );
+        let mut trio = slot("A", &["B", "C"]);
+        trio.alternate_every = 1;
+        assert_eq!(track_djs(&trio, 4), vec!["A", "B", "C", "A"]);
+    }
+
+    #[test]
+    fn test_open_with_best_handoff() {
+        let previous = track("Closer", "8A", 124.0, 7.0);
+        let mut tracks = vec![
+            track("Clash", "3B", 124.0, 6.0),
+            track("Peak", "8A", 124.0, 9.0),
+            track("Match", "9A", 124.0, 7.0),
+        ];
+        open_with_best_handoff(&mut tracks, &previous, true, &ScoringProfile::default());
+
+        // "Peak" mixes perfectly but is too far from the opener's energy
+        let titles: Vec<&str> = tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Match", "Clash", "Peak"]);
+        assert_eq!(tracks[0].position, 1);
+        assert_eq!(tracks[2].original_position, 3);
+        assert_eq!(tracks[0].transition_note, None);
+        assert_ne!(tracks[1].transition_note.as_deref(), Some("LLM note"));
+
+        // A good opener is left alone, notes included
+        let mut kept = vec![
+            track("Match", "9A", 124.0, 7.0),
+            track("Other", "3B", 124.0, 7.0),
+        ];
+        open_with_best_handoff(&mut kept, &previous, true, &ScoringProfile::default());
+        assert_eq!(kept[0].title, "Match");
+        assert_eq!(kept[1].transition_note.as_deref(), Some("LLM note"));
+    }
+
+    #[test]
+    fn test_validate_slots() {
+        assert!(validate_slots(&[slot("A", &[])]).is_ok());
+        assert!(validate_slots(&[]).is_err());
+        assert!(validate_slots(&[slot(" ", &[])]).is_err());
+        assert!(validate_slots(&[slot("A", &[""])]).is_err());
+        let mut short = slot("A", &[]);
+        short.duration_minutes = 5;
+        assert!(validate_slots(&[short]).is_err());
+        let mut never = slot("A", &["B"]);
+        never.alternate_every = 0;
+        assert!(validate_slots(&[never]).is_err());
+    }
+
+    fn llm_json(tracks: &[(&str, &str, &str)]) -> String {
+        let tracks: Vec<serde_json::Value> = tracks
+            .iter()
+            .enumerate()
+            .map(|(i, (id, title, camelot))| {
+                serde_json::json!({
+                    "position": i + 1,
+                    "title": title,
+                    "artist": "Catalog Artist",
+                    "bpm": 124.0,
+                    "camelot": camelot,
+                    "energy": 6,
+                    "source": "catalog",
+                    "track_id": id,
+                })
+            })
+            .collect();
+        serde_json::json!({ "tracks": tracks, "notes": "slot" }).to_string()
+    }
+
+    #[tokio::test]
+    async fn test_generate_lineup_links_slots_without_repeats() {
+        let pool = crate::db::create_test_pool().await;
+        for (id, title, camelot) in [
+            ("t1", "One", "7A"),
+            ("t2", "Two", "8A"),
+            ("t3", "Three", "3B"),
+            ("t4", "Four", "9A"),
+        ] {
+            sqlx::query(
+                "INSERT INTO tracks (id, title, source, bpm, camelot_key, energy) VALUES ($1, $2, 'spotify', 124.0, $3, 6.0)",
+            )
+            .bind(id)
+            .bind(title)
+            .bind(camelot)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::from([
+                llm_json(&[("t1", "One", "7A"), ("t2", "Two", "8A")]),
+                // Repeats "Two" and opens on a clash; "Four" follows 8A best
+                llm_json(&[
+                    ("t2", "Two", "8A"),
+                    ("t3", "Three", "3B"),
+                    ("t4", "Four", "9A"),
+                ]),
+            ])),
+        };
+
+        let lineup = generate_lineup(
+            &pool,
+            &claude,
+            GenerateLineupRequest {
+                user_id: "user1".to_string(),
+                prompt: "warehouse all-nighter".to_string(),
+                name: Some("Warehouse".to_string()),
+                slots: vec![slot("Opener", &[]), slot("Headliner", &["Guest"])],
+            },
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(lineup.slots.len(), 2);
+        let second = lineup.slots[1].setlist.as_ref().unwrap();
+        let titles: Vec<&str> = second.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Four", "Three"]);
+        assert_eq!(second.name.as_deref(), Some("Warehouse — Headliner"));
+        assert_eq!(lineup.slots[1].track_djs, vec!["Headliner", "Headliner"]);
+
+        assert_eq!(lineup.handoffs.len(), 1);
+        assert_eq!(lineup.handoffs[0].from_track, "Catalog Artist - Two");
+        assert_eq!(lineup.handoffs[0].to_track, "Catalog Artist - Four");
+        assert_eq!(
+            lineup.handoffs[0].key_transition,
+            Some(TransitionType::Adjacent)
+        );
+
+        // The saved lineup reads back the same way
+        let reread = get_lineup(&pool, &lineup.id).await.unwrap();
+        assert_eq!(reread.slots[1].b2b_with, vec!["Guest"]);
+        assert_eq!(
+            reread.slots[0].setlist.as_ref().unwrap().id,
+            lineup.slots[0].setlist.as_ref().unwrap().id
+        );
+        assert_eq!(reread.handoffs.len(), 1);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_lineup_needs_allowance_for_every_slot() {
+        let pool = crate::db::create_test_pool().await;
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        sqlx::query(
+            "INSERT INTO user_usage (id, user_id, date, generation_count) VALUES ('u1', 'user1', $1, 49)",
+        )
+        .bind(&today)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::from([llm_json(&[
+                ("t1", "One", "7A"),
+                ("t2", "Two", "8A"),
+            ])])),
+        };
+
+        let err = generate_lineup(
+            &pool,
+            &claude,
+            GenerateLineupRequest {
+                user_id: "user1".to_string(),
+                prompt: "warehouse all-nighter".to_string(),
+                name: None,
+                slots: vec![slot("Opener", &[]), slot("Headliner", &[])],
+            },
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SetlistError::GenerationLimitExceeded(_)));
+        // Nothing was generated
+        assert_eq!(claude.responses.lock().unwrap().len(), 1);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_lineup_discards_generated_slots() {
+        let pool = crate::db::create_test_pool().await;
+        // Only the first slot gets a response; the second generation fails
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::from([llm_json(&[
+                ("t1", "One", "7A"),
+                ("t2", "Two", "8A"),
+            ])])),
+        };
+
+        let result = generate_lineup(
+            &pool,
+            &claude,
+            GenerateLineupRequest {
+                user_id: "user1".to_string(),
+                prompt: "warehouse all-nighter".to_string(),
+                name: None,
+                slots: vec![slot("Opener", &[]), slot("Headliner", &[])],
+            },
+        )
+        .await;
+        assert!(result.is_err());
+        let left = crate::db::setlists::list_setlists(&pool, "user1", 10, 0)
+            .await
+            .unwrap();
+        assert!(left.is_empty());
+
+        pool.close().await;
+    }
+}
@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::db::models::{SetlistTrackRow, VersionTrackRow};
+use crate::services::arrangement;
+use crate::services::camelot::{
+    classify_transition, effective_key, parse_camelot, ScoringProfile, TransitionType,
+};
+use crate::services::lineup;
+use crate::services::setlist::{
+    compute_bpm_warnings_generic, compute_catalog_warning, SetlistTrackResponse,
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Runs of equal energy longer than this many tracks are flagged.
+pub const DEFAULT_PLATEAU_LENGTH: usize = 4;
+
+/// Verification flags meaning the track probably does not exist as named.
+const HARD_VERIFICATION_FLAGS: [&str; 3] = ["no_such_track", "constructed_title", "wrong_artist"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Info,
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum FindingKind {
+    KeyClash,
+    BpmJump,
+    EnergyPlateau,
+    RepeatedArtist,
+    DuplicateTrack,
+    MissingMetadata,
+    LowCatalogCoverage,
+    LowConfidence,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Finding {
+    pub kind: FindingKind,
+    pub severity: Severity,
+    /// 1-based positions the finding is about, in set order.
+    pub positions: Vec<i32>,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SeverityCounts {
+    pub error: usize,
+    pub warning: usize,
+    pub info: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct LintReport {
+    pub track_count: usize,
+    pub catalog_percentage: f64,
+    pub counts: SeverityCounts,
+    /// Most severe first, then by first position.
+    pub findings: Vec<Finding>,
+}
+
+/// One setlist or version track, as far as the linter cares.
+#[derive(Debug, Clone, Default)]
+pub struct LintTrack {
+    pub position: i32,
+    pub track_id: Option<String>,
+    pub title: String,
+    pub artist: String,
+    pub bpm: Option<f64>,
+    pub camelot: Option<String>,
+    pub energy: Option<f64>,
+    pub source: String,
+    pub confidence: Option<String>,
+    pub verification_flag: Option<String>,
+    pub verification_note: Option<String>,
+}
+
+impl From<&SetlistTrackRow> for LintTrack {
+    fn from(t: &SetlistTrackRow) -> Self {
+        Self {
+            position: t.position,
+            track_id: t.track_id.clone(),
+            title: t.title.clone(),
+            artist: t.artist.clone(),
+            bpm: t.bpm,
+            camelot: t.camelot.clone(),
+            energy: t.energy,
+            source: t.source.clone(),
+            confidence: t.confidence.clone(),
+            verification_flag: t.verification_flag.clone(),
+            verification_note: t.verification_note.clone(),
+        }
+    }
+}
+
+impl From<&VersionTrackRow> for LintTrack {
+    /// Version tracks carry no verification results; see
+    /// [`LintTrack::with_verification`].
+    fn from(t: &VersionTrackRow) -> Self {
+        Self {
+            position: t.position,
+            track_id: t.track_id.clone(),
+            title: t.title.clone(),
+            artist: t.artist.clone(),
+            bpm: t.bpm,
+            camelot: t.camelot.clone(),
+            energy: t.energy,
+            source: t.source.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&SetlistTrackResponse> for LintTrack {
+    fn from(t: &SetlistTrackResponse) -> Self {
+        Self {
+            position: t.position,
+            track_id: t.track_id.clone(),
+            title: t.title.clone(),
+            artist: t.artist.clone(),
+            bpm: t.bpm,
+            camelot: t.camelot.clone(),
+            energy: t.energy,
+            source: t.source.clone(),
+            confidence: t.confidence.clone(),
+            verification_flag: t.verification_flag.clone(),
+            verification_note: t.verification_note.clone(),
+        }
+    }
+}
+
+impl LintTrack {
+    /// Copy confidence and verification results from the generated track
+    /// this one came from.
+    pub fn with_verification(mut self, original: &SetlistTrackRow) -> Self {
+        self.confidence = original.confidence.clone();
+        self.verification_flag = original.verification_flag.clone();
+        self.verification_note = original.verification_note.clone();
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct LintOptions {
+    pub scoring: ScoringProfile,
+    pub key_lock: bool,
+    pub artist_separation: usize,
+    pub plateau_length: usize,
+}
+
+impl Default for LintOptions {
+    fn default() -> Self {
+        Self {
+            scoring: ScoringProfile::default(),
+            key_lock: true,
+            artist_separation: arrangement::DEFAULT_ARTIST_SEPARATION,
+            plateau_length: DEFAULT_PLATEAU_LENGTH,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Lint
+// ---------------------------------------------------------------------------
+
+/// Check a setlist in play order and report everything worth fixing.
+pub fn lint_tracks(tracks: &[LintTrack], options: &LintOptions) -> LintReport {
+    let mut findings = Vec::new();
+    key_clashes(tracks, options.key_lock, &mut findings);
+    bpm_jumps(tracks, &options.scoring, &mut findings);
+    energy_plateaus(tracks, options.plateau_length, &mut findings);
+    repeated_artists(tracks, options.artist_separation, &mut findings);
+    duplicate_tracks(tracks, &mut findings);
+    missing_metadata(tracks, &mut findings);
+    let catalog_percentage = catalog_coverage(tracks, &mut findings);
+    low_confidence(tracks, &mut findings);
+
+    findings.sort_by(|a, b| {
+        b.severity
+            .cmp(&a.severity)
+            .then(a.positions.first().cmp(&b.positions.first()))
+    });
+    let mut counts = SeverityCounts::default();
+    for f in &findings {
+        match f.severity {
+            Severity::Error => counts.error += 1,
+            Severity::Warning => counts.warning += 1,
+            Severity::Info => counts.info += 1,
+        }
+    }
+
+    LintReport {
+        track_count: tracks.len(),
+        catalog_percentage,
+        counts,
+        findings,
+    }
+}
+
+fn key_clashes(tracks: &[LintTrack], key_lock: bool, findings: &mut Vec<Finding>) {
+    for pair in tracks.windows(2) {
+        let (a, b) = (&pair[0], &pair[1]);
+        let (Some(ka), Some(kb)) = (
+            a.camelot.as_deref().and_then(parse_camelot),
+            b.camelot.as_deref().and_then(parse_camelot),
+        ) else {
+            continue;
+        };
+        let kb_heard = effective_key(&kb, a.bpm, b.bpm, key_lock);
+        if classify_transition(&ka, &kb_heard) == TransitionType::Clash {
+            findings.push(Finding {
+                kind: FindingKind::KeyClash,
+                severity: Severity::Warning,
+                positions: vec![a.position, b.position],
+                message: format!("{ka} into {kb_heard} is not a harmonic move"),
+            });
+        }
+    }
+}
+
+fn bpm_jumps(tracks: &[LintTrack], scoring: &ScoringProfile, findings: &mut Vec<Finding>) {
+    for w in compute_bpm_warnings_generic(tracks, scoring, |t| (t.position, t.bpm)) {
+        // Beyond the last tolerance band the jump scores nothing at all
+        let severity = if w.effective_bpm_delta.abs() > scoring.bpm_bands.stretch {
+            Severity::Error
+        } else {
+            Severity::Warning
+        };
+        findings.push(Finding {
+            kind: FindingKind::BpmJump,
+            severity,
+            positions: vec![w.from_position, w.to_position],
+            message: format!(
+                "{:+.1} BPM jump ({}, {:+.1} effective)",
+                w.bpm_delta, w.tempo_relation, w.effective_bpm_delta
+            ),
+        });
+    }
+}
+
+fn energy_plateaus(tracks: &[LintTrack], max_run: usize, findings: &mut Vec<Finding>) {
+    let mut start = 0;
+    for i in 1..=tracks.len() {
+        let level = |t: &LintTrack| t.energy.map(|e| e.round() as i64);
+        let continues = i < tracks.len()
+            && level(&tracks[i]).is_some()
+            && level(&tracks[i]) == level(&tracks[start]);
+        if continues {
+            continue;
+        }
+        let run = &tracks[start..i];
+        if run.len() > max_run {
+            if let Some(level) = level(&run[0]) {
+                findings.push(Finding {
+                    kind: FindingKind::EnergyPlateau,
+                    severity: Severity::Info,
+                    positions: run.iter().map(|t| t.position).collect(),
+                    message: format!("{} tracks in a row at energy {level}", run.len()),
+                });
+            }
+        }
+        start = i;
+    }
+}
+
+fn repeated_artists(tracks: &[LintTrack], separation: usize, findings: &mut Vec<Finding>) {
+    // Back to back is always worth a look, whatever the user's separation
+    let artists: Vec<Option<&str>> = tracks.iter().map(|t| Some(t.artist.as_str())).collect();
+    for (i, j, artist) in arrangement::separation_violations(&artists, separation.max(1)) {
+        let gap = j - i;
+        findings.push(Finding {
+            kind: FindingKind::RepeatedArtist,
+            severity: if gap == 1 {
+                Severity::Warning
+            } else {
+                Severity::Info
+            },
+            positions: vec![tracks[i].position, tracks[j].position],
+            message: if gap == 1 {
+                format!("{artist} plays back to back")
+            } else {
+                format!("{artist} returns after {} track(s)", gap - 1)
+            },
+        });
+    }
+}
+
+/// Tracks are the same song when their artist and title match or when they
+/// share a catalog id, so a catalog track and a suggestion naming it group
+/// together.
+fn duplicate_tracks(tracks: &[LintTrack], findings: &mut Vec<Finding>) {
+    // Union-find over positions, joined through either key
+    let mut parent: Vec<usize> = (0..tracks.len()).collect();
+    fn root(parent: &mut [usize], mut i: usize) -> usize {
+        while parent[i] != i {
+            parent[i] = parent[parent[i]];
+            i = parent[i];
+        }
+        i
+    }
+    let mut first_seen: HashMap<String, usize> = HashMap::new();
+    for (i, t) in tracks.iter().enumerate() {
+        let identity = lineup::track_identity(&t.artist, &t.title);
+        let keys = std::iter::once(format!("name:{identity}"))
+            .chain(t.track_id.as_ref().map(|id| format!("id:{id}")));
+        for key in keys {
+            let j = *first_seen.entry(key).or_insert(i);
+            let (a, b) = (root(&mut parent, i), root(&mut parent, j));
+            parent[a.max(b)] = a.min(b);
+        }
+    }
+    let mut groups: HashMap<usize, Vec<usize>> = HashMap::new();
+    for i in 0..tracks.len() {
+        let r = root(&mut parent, i);
+        groups.entry(r).or_default().push(i);
+    }
+    let mut dupes: Vec<Vec<usize>> = groups.into_values().filter(|g| g.len() > 1).collect();
+    dupes.sort();
+    for group in dupes {
+        let first = &tracks[group[0]];
+        findings.push(Finding {
+            kind: FindingKind::DuplicateTrack,
+            severity: Severity::Error,
+            positions: group.iter().map(|&i| tracks[i].position).collect(),
+            message: format!(
+                "\"{}\" by {} appears {} times",
+                first.title,
+                first.artist,
+                group.len()
+            ),
+        });
+    }
+}
+
+fn missing_metadata(tracks: &[LintTrack], findings: &mut Vec<Finding>) {
+    for t in tracks {
+        let missing: Vec<&str> = [
+            ("BPM", t.bpm.is_none()),
+            (
+                "key",
+                t.camelot.as_deref().and_then(parse_camelot).is_none(),
+            ),
+            ("energy", t.energy.is_none()),
+        ]
+        .into_iter()
+        .filter_map(|(field, absent)| absent.then_some(field))
+        .collect();
+        if !missing.is_empty() {
+            findings.push(Finding {
+                kind: FindingKind::MissingMetadata,
+                severity: Severity::Info,
+                positions: vec![t.position],
+                message: format!("\"{}\" has no {}", t.title, missing.join(", ")),
+            });
+        }
+    }
+}
+
+/// Flags low catalog coverage and returns the catalog percentage.
+fn catalog_coverage(tracks: &[LintTrack], findings: &mut Vec<Finding>) -> f64 {
+    if tracks.is_empty() {
+        return 0.0;
+    }
+    let outside: Vec<i32> = tracks
+        .iter()
+        .filter(|t| t.source != "catalog")
+        .map(|t| t.position)
+        .collect();
+    let percentage = (tracks.len() - outside.len()) as f64 / tracks.len() as f64 * 100.0;
+    if let Some(message) = compute_catalog_warning(percentage) {
+        findings.push(Finding {
+            kind: FindingKind::LowCatalogCoverage,
+            severity: Severity::Warning,
+            positions: outside,
+            message,
+        });
+    }
+    percentage
+}
+
+fn low_confidence(tracks: &[LintTrack], findings: &mut Vec<Finding>) {
+    for t in tracks.iter().filter(|t| t.source != "catalog") {
+        let hard_flag = t
+            .verification_flag
+            .as_deref()
+            .filter(|f| HARD_VERIFICATION_FLAGS.contains(f));
+        let severity = if hard_flag.is_some() {
+            Severity::Error
+        } else if t.confidence.as_deref() == Some("low") {
+            Severity::Warning
+        } else {
+            continue;
+        };
+        let reason = t
+            .verification_note
+            .clone()
+            .or_else(|| hard_flag.map(|f| f.replace('_', " ")))
+            .unwrap_or_else(|| "low confidence".to_string());
+        findings.push(Finding {
+            kind: FindingKind::LowConfidence,
+            severity,
+            positions: vec![t.position],
+            message: format!("\"{}\" by {} is unverified: {reason}", t.title, t.artist),
+        });
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(position: i32, artist: &str, camelot: &str, bpm: f64, energy: f64) -> LintTrack {
+        LintTrack {
+            position,
+            track_id: Some(format!("t{position}")),
+            title: format!("Title {position}"),
+            artist: artist.to_string(),
+            bpm: Some(bpm),
+            camelot: Some(camelot.to_string()),
+            energy: Some(energy),
+            source: "catalog".to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn kinds(report: &LintReport) -> Vec<FindingKind> {
+        report.findings.iter().map(|f| f.kind).collect()
+    }
+
+    #[test]
+    fn test_clean_setlist_has_no_findings() {
+        let tracks = vec![
+            track(1, "A", "8A", 124.0, 5.0),
+            track(2, "B", "9A", 125.0, 6.0),
+            track(3, "C", "9B", 126.0, 7.0),
+        ];
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        assert!(report.findings.is_empty(), "{:?}", report.findings);
+        assert_eq!(report.catalog_percentage, 100.0);
+    }
+
+    #[test]
+    fn test_transition_findings() {
+        let tracks = vec![
+            track(1, "A", "8A", 124.0, 5.0),
+            track(2, "B", "2B", 124.0, 5.0),
+            track(3, "C", "2B", 140.0, 5.0),
+        ];
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        let clash = &report.findings[report
+            .findings
+            .iter()
+            .position(|f| f.kind == FindingKind::KeyClash)
+            .unwrap()];
+        assert_eq!(clash.positions, vec![1, 2]);
+        // 16 BPM is past every tolerance band
+        assert_eq!(report.findings[0].kind, FindingKind::BpmJump);
+        assert_eq!(report.findings[0].severity, Severity::Error);
+        assert_eq!(report.findings[0].positions, vec![2, 3]);
+    }
+
+    #[test]
+    fn test_energy_plateau() {
+        let tracks: Vec<LintTrack> = (1..=6)
+            .map(|i| {
+                track(
+                    i,
+                    &format!("A{i}"),
+                    "8A",
+                    124.0,
+                    if i == 1 { 3.0 } else { 6.0 },
+                )
+            })
+            .collect();
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        assert_eq!(kinds(&report), vec![FindingKind::EnergyPlateau]);
+        assert_eq!(report.findings[0].positions, vec![2, 3, 4, 5, 6]);
+
+        let options = LintOptions {
+            plateau_length: 5,
+            ..Default::default()
+        };
+        assert!(lint_tracks(&tracks, &options).findings.is_empty());
+    }
+
+    #[test]
+    fn test_repeated_and_duplicate_tracks() {
+        let mut tracks = vec![
+            track(1, "Same", "8A", 124.0, 5.0),
+            track(2, "Same", "8A", 124.0, 6.0),
+            track(3, "Other", "8A", 124.0, 7.0),
+        ];
+        tracks[2].track_id = Some("t1".to_string());
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        assert_eq!(
+            kinds(&report),
+            vec![FindingKind::DuplicateTrack, FindingKind::RepeatedArtist]
+        );
+        assert_eq!(report.findings[0].positions, vec![1, 3]);
+        assert_eq!(report.counts.error, 1);
+        assert_eq!(report.counts.warning, 1);
+    }
+
+    #[test]
+    fn test_duplicates_match_across_catalog_and_suggestions() {
+        let mut tracks = vec![
+            track(1, "A", "8A", 124.0, 5.0),
+            track(2, "B", "8A", 124.0, 6.0),
+            track(3, "C", "8A", 124.0, 7.0),
+            track(4, "D", "8A", 124.0, 8.0),
+        ];
+        // A suggestion naming catalog track 1, differently cased
+        tracks[2].source = "suggestion".to_string();
+        tracks[2].track_id = None;
+        tracks[2].artist = " a ".to_string();
+        tracks[2].title = "TITLE 1".to_string();
+        // A renamed copy of track 2 still shares its id
+        tracks[3].track_id = Some("t2".to_string());
+        tracks[3].title = "Title 2 (Edit)".to_string();
+
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        let dupes: Vec<&Vec<i32>> = report
+            .findings
+            .iter()
+            .filter(|f| f.kind == FindingKind::DuplicateTrack)
+            .map(|f| &f.positions)
+            .collect();
+        assert_eq!(dupes, vec![&vec![1, 3], &vec![2, 4]]);
+    }
+
+    #[test]
+    fn test_suggestion_findings() {
+        let mut tracks = vec![
+            track(1, "A", "8A", 124.0, 5.0),
+            track(2, "B", "8A", 124.0, 6.0),
+            track(3, "C", "8A", 124.0, 7.0),
+            track(4, "D", "8A", 124.0, 8.0),
+        ];
+        for t in &mut tracks[1..] {
+            t.source = "suggestion".to_string();
+            t.track_id = None;
+        }
+        tracks[1].confidence = Some("low".to_string());
+        tracks[2].verification_flag = Some("no_such_track".to_string());
+        tracks[2].energy = None;
+
+        let report = lint_tracks(&tracks, &LintOptions::default());
+        assert_eq!(
+            kinds(&report),
+            vec![
+                FindingKind::LowConfidence,
+                FindingKind::LowCatalogCoverage,
+                FindingKind::LowConfidence,
+                FindingKind::MissingMetadata,
+            ]
+        );
+        assert_eq!(report.findings[0].positions, vec![3]);
+        assert!(report.findings[0].message.ends_with("no such track"));
+        assert_eq!(report.findings[1].positions, vec![2, 3, 4]);
+        assert_eq!(report.findings[3].message, "\"Title 3\" has no energy");
+    }
+}
@@ -0,0 +1,582 @@
+use std::str::FromStr;
+
+use crate::api::claude::{ClaudeClientTrait, LlmTrackEntry};
+use crate::db::models::{SetlistRow, SetlistTrackRow, TrackRow};
+use crate::db::setlists as db;
+use crate::services::camelot::{
+    energy_arc_score, energy_arc_score_with_curve, energy_arc_score_with_profile, parse_camelot,
+    transition_score_with_profile, ScoringProfile,
+};
+use crate::services::constraints::{self, catalog_entry, RequiredTrack};
+use crate::services::setlist::{
+    self, estimate_track_count, GenerateSetlistRequest, GenerationContext, SetlistError,
+    SetlistResponse, SetlistTrackResponse, ValidatedRequest,
+};
+use crate::services::solver::SolverConfig;
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Model name stored on setlists built without the LLM.
+pub const LOCAL_MODEL: &str = "local";
+
+/// How `/setlists/generate` builds a setlist.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum GenerationMode {
+    /// Always ask the LLM; its failures are returned as errors.
+    #[default]
+    Llm,
+    /// Never ask the LLM; pick and order tracks from the catalog.
+    Local,
+    /// Ask the LLM, and fall back to [`GenerationMode::Local`] when it is
+    /// unavailable or the daily generation cap is reached.
+    Auto,
+}
+
+impl FromStr for GenerationMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "llm" => Ok(Self::Llm),
+            "local" => Ok(Self::Local),
+            "auto" => Ok(Self::Auto),
+            other => Err(format!(
+                "unknown generation mode '{other}' (expected llm, local or auto)"
+            )),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Selection
+// ---------------------------------------------------------------------------
+
+/// How well `energy` suits `position` of `total` under the request's energy
+/// target; neutral when the track has no energy.
+fn energy_fit(
+    energy: Option<f64>,
+    position: usize,
+    total: usize,
+    req: &GenerateSetlistRequest,
+) -> f64 {
+    let Some(energy) = energy else {
+        return 0.5;
+    };
+    let energy = energy.round() as i32;
+    match (&req.energy_curve, req.energy_profile) {
+        (Some(curve), _) => energy_arc_score_with_curve(energy, position, total, curve),
+        (None, Some(profile)) => energy_arc_score_with_profile(energy, position, total, profile),
+        (None, None) => energy_arc_score(energy, position, total),
+    }
+}
+
+/// Pick `count` tracks: must-include tracks first, then, slot by slot, the
+/// unused catalog track that best combines fit to the energy target with a
+/// smooth mix from the previous pick. Ties go to the earlier catalog track,
+/// so the same catalog and request always give the same set.
+fn select_tracks(
+    catalog: &[TrackRow],
+    required: &[RequiredTrack],
+    count: usize,
+    req: &GenerateSetlistRequest,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> Vec<LlmTrackEntry> {
+    let mut picked: Vec<LlmTrackEntry> = required.iter().map(RequiredTrack::to_entry).collect();
+    let mut used: Vec<bool> = catalog
+        .iter()
+        .map(|t| picked.iter().any(|p| p.track_id.as_deref() == Some(&t.id)))
+        .collect();
+
+    while picked.len() < count {
+        let position = picked.len();
+        let previous = picked.last();
+        let prev_key = previous.and_then(|p| p.camelot.as_deref().and_then(parse_camelot));
+        let mut best: Option<(usize, f64)> = None;
+        for (i, track) in catalog.iter().enumerate() {
+            if used[i] {
+                continue;
+            }
+            let mix = match previous {
+                Some(p) => transition_score_with_profile(
+                    prev_key.as_ref(),
+                    track
+                        .camelot_key
+                        .as_deref()
+                        .and_then(parse_camelot)
+                        .as_ref(),
+                    p.bpm,
+                    track.bpm,
+                    key_lock,
+                    scoring,
+                ),
+                None => 0.5,
+            };
+            let score = energy_fit(track.energy, position, count, req) + mix;
+            if best.is_none_or(|(_, s)| score > s) {
+                best = Some((i, score));
+            }
+        }
+        let Some((i, _)) = best else {
+            break;
+        };
+        used[i] = true;
+        picked.push(catalog_entry(&catalog[i]));
+    }
+    picked
+}
+
+/// A picked track as a setlist entry, before it is saved.
+fn entry_response(entry: LlmTrackEntry) -> SetlistTrackResponse {
+    SetlistTrackResponse {
+        position: entry.position,
+        title: entry.title,
+        artist: entry.artist,
+        bpm: entry.bpm,
+        key: entry.key,
+        camelot: entry.camelot,
+        energy: entry.energy.map(f64::from),
+        transition_note: None,
+        transition_score: None,
+        key_transition: None,
+        tempo_relation: None,
+        pitch_percent: None,
+        duration_ms: entry
+            .duration_seconds
+            .map(|secs| secs.saturating_mul(1000).min(i32::MAX as u32) as i32),
+        start_time_ms: None,
+        original_position: entry.position,
+        source: entry.source.unwrap_or_else(|| "suggestion".to_string()),
+        track_id: entry.track_id,
+        spotify_uri: None,
+        confidence: entry.confidence,
+        verification_flag: None,
+        verification_note: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Service functions
+// ---------------------------------------------------------------------------
+
+/// Build and save a setlist from the catalog alone, with no LLM call.
+///
+/// Honours the request's source playlist or crate, BPM range, energy target,
+/// key path and must-include/exclude lists. The chosen tracks are ordered by
+/// [`setlist::arrange_setlist`] and saved with `model = "local"`. Local sets
+/// do not count against the daily generation cap. `reason`, if given, says
+/// in the notes why the LLM was not used.
+pub async fn generate_local_setlist(
+    pool: &sqlx::PgPool,
+    req: GenerateSetlistRequest,
+    reason: Option<&str>,
+) -> Result<SetlistResponse, SetlistError> {
+    let ValidatedRequest {
+        prompt,
+        count,
+        target_duration_ms,
+    } = setlist::validate_generation_request(&req)?;
+    let track_constraints = constraints::resolve(pool, &req.user_id, &req.constraints).await?;
+
+    let mut notes = vec![match reason {
+        Some(reason) => format!("Built from your catalog without the AI model ({reason})."),
+        None => "Built from your catalog without the AI model.".to_string(),
+    }];
+    let catalog = setlist::load_generation_catalog(
+        pool,
+        &req,
+        &GenerationContext::default(),
+        &track_constraints,
+        &mut notes,
+    )
+    .await?;
+    if catalog.is_empty() && track_constraints.required.is_empty() {
+        return Err(SetlistError::EmptyCatalog);
+    }
+
+    let count = match target_duration_ms {
+        Some(target) => estimate_track_count(&catalog, target),
+        None => count,
+    } as usize;
+    if track_constraints.required.len() > count {
+        return Err(SetlistError::InvalidRequest(format!(
+            "{} must_include tracks do not fit in a {count}-track setlist",
+            track_constraints.required.len()
+        )));
+    }
+
+    let settings = crate::db::settings::get_mix_settings(pool, &req.user_id).await?;
+    let (key_lock, scoring) = (settings.key_lock, settings.scoring);
+    let entries = select_tracks(
+        &catalog,
+        &track_constraints.required,
+        count,
+        &req,
+        key_lock,
+        &scoring,
+    );
+    if entries.len() < count {
+        notes.push(format!(
+            "Only {} matching tracks were available for {count} slots.",
+            entries.len()
+        ));
+    }
+    let mut tracks: Vec<SetlistTrackResponse> = entries.into_iter().map(entry_response).collect();
+    // Duration estimates are rough; fit the picks to the slot as the LLM
+    // path does
+    if let Some(target) = target_duration_ms {
+        let added = setlist::fit_to_duration(
+            &mut tracks,
+            target,
+            &track_constraints,
+            &catalog,
+            key_lock,
+            &scoring,
+        );
+        if added > 0 {
+            notes.push(format!(
+                "Added {added} catalog track(s) to fill the time slot."
+            ));
+        }
+    }
+
+    let setlist_id = uuid::Uuid::new_v4().to_string();
+    let mut tx = pool.begin().await?;
+    db::insert_setlist(
+        &mut *tx,
+        &SetlistRow {
+            id: setlist_id.clone(),
+            user_id: req.user_id.clone(),
+            prompt,
+            model: LOCAL_MODEL.to_string(),
+            name: req.name.clone(),
+            notes: Some(notes.join(" ")),
+            harmonic_flow_score: None,
+            energy_profile: req.energy_profile.as_ref().map(|p| p.to_string()),
+            energy_curve: req
+                .energy_curve
+                .as_ref()
+                .and_then(|c| serde_json::to_string(c).ok()),
+            key_path: req
+                .key_path
+                .as_ref()
+                .and_then(|k| serde_json::to_string(k).ok()),
+            created_at: None,
+            target_duration_ms,
+        },
+    )
+    .await?;
+    for (i, track) in tracks.into_iter().enumerate() {
+        let position = (i + 1) as i32;
+        db::insert_setlist_track(
+            &mut *tx,
+            &SetlistTrackRow {
+                id: uuid::Uuid::new_v4().to_string(),
+                setlist_id: setlist_id.clone(),
+                track_id: track.track_id,
+                position,
+                original_position: position,
+                title: track.title,
+                artist: track.artist,
+                bpm: track.bpm,
+                key: track.key,
+                camelot: track.camelot,
+                energy: track.energy,
+                // Written by the arrange pass below, once the order is final
+                transition_note: None,
+                transition_score: None,
+                source: track.source,
+                acquisition_info: None,
+                spotify_uri: None,
+                confidence: track.confidence,
+                verification_flag: None,
+                verification_note: None,
+                duration_ms: track.duration_ms,
+            },
+        )
+        .await?;
+    }
+    tx.commit().await?;
+
+    // Stored energy target and key path are picked up from the setlist row
+    setlist::arrange_setlist(
+        pool,
+        &setlist_id,
+        None,
+        None,
+        None,
+        &[],
+        SolverConfig::default(),
+    )
+    .await
+}
+
+/// Whether `e` means the LLM could not be used at all, as opposed to a
+/// problem with the request or its output.
+fn llm_unavailable(e: &SetlistError) -> bool {
+    matches!(
+        e,
+        SetlistError::ClaudeError(_)
+            | SetlistError::ServiceBusy(_)
+            | SetlistError::Timeout
+            | SetlistError::GenerationLimitExceeded(_)
+    )
+}
+
+/// Generate a setlist the way `mode` asks for.
+pub async fn generate_setlist_with_mode(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: GenerateSetlistRequest,
+    mode: GenerationMode,
+) -> Result<SetlistResponse, SetlistError> {
+    match mode {
+        GenerationMode::Llm => setlist::generate_setlist_from_request(pool, claude, req).await,
+        GenerationMode::Local => generate_local_setlist(pool, req, None).await,
+        GenerationMode::Auto => {
+            match setlist::generate_setlist_from_request(pool, claude, req.clone()).await {
+                Err(e) if llm_unavailable(&e) => {
+                    // The detail stays in the log; the saved notes only say why
+                    tracing::warn!("LLM unavailable, generating locally: {e}");
+                    generate_local_setlist(pool, req, Some("LLM unavailable")).await
+                }
+                other => other,
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::claude::{CacheMetrics, ClaudeError, RequestContentBlock};
+    use crate::services::camelot::EnergyProfile;
+    use crate::services::constraints::{TrackConstraints, TrackRef};
+
+    struct DownClaude;
+
+    #[async_trait::async_trait]
+    impl ClaudeClientTrait for DownClaude {
+        async fn generate_setlist(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: u32,
+        ) -> Result<String, ClaudeError> {
+            Err(ClaudeError::Api("invalid x-api-key".to_string()))
+        }
+
+        async fn generate_with_blocks(
+            &self,
+            _: Vec<RequestContentBlock>,
+            _: Vec<RequestContentBlock>,
+            _: &str,
+            _: u32,
+        ) -> Result<(String, CacheMetrics), ClaudeError> {
+            Err(ClaudeError::Api("invalid x-api-key".to_string()))
+        }
+    }
+
+    async fn seed(pool: &sqlx::PgPool) {
+        for (id, title, bpm, camelot, energy) in [
+            ("t1", "Opener", 120.0, "8A", 3.0),
+            ("t2", "Builder", 122.0, "9A", 5.0),
+            ("t3", "Peak", 126.0, "9A", 8.0),
+            ("t4", "Clash", 150.0, "2B", 9.0),
+            ("t5", "Closer", 124.0, "8A", 4.0),
+        ] {
+            sqlx::query(
+                "INSERT INTO tracks (id, title, source, bpm, camelot_key, energy, duration_ms) \
+                 VALUES ($1, $2, 'spotify', $3, $4, $5, 300000)",
+            )
+            .bind(id)
+            .bind(title)
+            .bind(bpm)
+            .bind(camelot)
+            .bind(energy)
+            .execute(pool)
+            .await
+            .unwrap();
+        }
+    }
+
+    fn request(track_count: u32) -> GenerateSetlistRequest {
+        GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "deep house".to_string(),
+            track_count: Some(track_count),
+            target_duration_minutes: None,
+            energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        }
+    }
+
+    #[test]
+    fn test_parse_generation_mode() {
+        assert_eq!("auto".parse::<GenerationMode>(), Ok(GenerationMode::Auto));
+        assert_eq!(GenerationMode::default(), GenerationMode::Llm);
+        assert!("offline".parse::<GenerationMode>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_local_setlist_is_deterministic_and_honours_request() {
+        let pool = crate::db::create_test_pool().await;
+        seed(&pool).await;
+        let mut req = request(3);
+        req.energy_profile = Some(EnergyProfile::WarmUp);
+        req.constraints = TrackConstraints {
+            must_include: vec![TrackRef::Id("t3".to_string())],
+            exclude_tracks: vec![TrackRef::Id("t1".to_string())],
+            ..Default::default()
+        };
+
+        let first = generate_local_setlist(&pool, req.clone(), None)
+            .await
+            .unwrap();
+        let second = generate_local_setlist(&pool, req, None).await.unwrap();
+        assert_eq!(first.model, LOCAL_MODEL);
+        assert_eq!(first.tracks.len(), 3);
+        let ids = |s: &SetlistResponse| -> Vec<String> {
+            s.tracks.iter().filter_map(|t| t.track_id.clone()).collect()
+        };
+        assert_eq!(ids(&first), ids(&second));
+        assert!(ids(&first).contains(&"t3".to_string()));
+        assert!(!ids(&first).contains(&"t1".to_string()));
+        // Arranged and scored like any other setlist, notes included
+        assert!(first.harmonic_flow_score.is_some());
+        assert!(first.tracks[0].transition_note.is_none());
+        assert!(first.tracks[1..]
+            .iter()
+            .all(|t| t.transition_note.is_some()));
+        assert!(first.notes.unwrap().contains("without the AI model"));
+
+        // No generation was counted
+        let used = crate::db::tracks::get_daily_generation_count(&pool, "user1")
+            .await
+            .unwrap();
+        assert_eq!(used, 0);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_local_setlist_fits_target_duration() {
+        let pool = crate::db::create_test_pool().await;
+        seed(&pool).await;
+        // A 45-minute track skews the average to 13 minutes a track
+        sqlx::query("UPDATE tracks SET duration_ms = 2700000 WHERE id = 't4'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        let mut req = request(1);
+        req.track_count = None;
+
+        // 26 minutes estimates 2 tracks; the 10 minutes picked get topped up
+        req.target_duration_minutes = Some(26);
+        req.constraints.must_include = vec![
+            TrackRef::Id("t1".to_string()),
+            TrackRef::Id("t2".to_string()),
+        ];
+        let short = generate_local_setlist(&pool, req.clone(), None)
+            .await
+            .unwrap();
+        assert_eq!(short.tracks.len(), 4);
+        assert_eq!(short.total_runtime_ms, Some(1_200_000));
+        let notes = short.notes.unwrap();
+        assert!(notes.contains("Added 2 catalog track(s)"), "{notes}");
+
+        // 39 minutes estimates 3 tracks; with the long one that overruns
+        req.target_duration_minutes = Some(39);
+        req.constraints.must_include = vec![TrackRef::Id("t4".to_string())];
+        let long = generate_local_setlist(&pool, req, None).await.unwrap();
+        assert_eq!(long.tracks.len(), 2);
+        assert!(long
+            .tracks
+            .iter()
+            .any(|t| t.track_id.as_deref() == Some("t4")));
+        assert_eq!(long.total_runtime_ms, Some(3_000_000));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_auto_mode_falls_back_when_llm_is_down() {
+        let pool = crate::db::create_test_pool().await;
+        seed(&pool).await;
+
+        let err = generate_setlist_with_mode(&pool, &DownClaude, request(2), GenerationMode::Llm)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::ClaudeError(_)));
+
+        let resp = generate_setlist_with_mode(&pool, &DownClaude, request(2), GenerationMode::Auto)
+            .await
+            .unwrap();
+        assert_eq!(resp.model, LOCAL_MODEL);
+        assert_eq!(resp.tracks.len(), 2);
+        // The provider's error is logged, not saved on the setlist
+        let notes = resp.notes.unwrap();
+        assert!(notes.contains("(LLM unavailable)"));
+        assert!(!notes.contains("invalid x-api-key"));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_local_setlist_draws_from_crate() {
+        let pool = crate::db::create_test_pool().await;
+        seed(&pool).await;
+        sqlx::query("INSERT INTO crates (id, user_id, name) VALUES ('c1', 'user1', 'Peak time')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        for (id, title) in [
+            ("ct1", "Builder"),
+            ("ct2", "Peak"),
+            ("ct3", "Not In Catalog"),
+        ] {
+            sqlx::query(
+                "INSERT INTO crate_tracks (id, crate_id, title, artist) VALUES ($1, 'c1', $2, '')",
+            )
+            .bind(id)
+            .bind(title)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+
+        let mut req = request(5);
+        req.source_crate_id = Some("c1".to_string());
+        let resp = generate_local_setlist(&pool, req.clone(), None)
+            .await
+            .unwrap();
+        let mut titles: Vec<&str> = resp.tracks.iter().map(|t| t.title.as_str()).collect();
+        titles.sort_unstable();
+        assert_eq!(titles, vec!["Builder", "Peak"]);
+        let notes = resp.notes.unwrap();
+        assert!(
+            notes.contains("1 crate tracks are not in your catalog"),
+            "{notes}"
+        );
+
+        req.user_id = "someone-else".to_string();
+        assert!(matches!(
+            generate_local_setlist(&pool, req, None).await,
+            Err(SetlistError::NotFound(_))
+        ));
+        pool.close().await;
+    }
+}
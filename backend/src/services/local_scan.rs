@@ -0,0 +1,616 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use serde::{Deserialize, Serialize};
+use symphonia::core::formats::FormatOptions;
+use symphonia::core::io::MediaSourceStream;
+use symphonia::core::meta::{MetadataOptions, MetadataRevision, StandardTagKey, StandardVisualKey};
+use symphonia::core::probe::Hint;
+use walkdir::WalkDir;
+
+use crate::services::camelot::normalize_key;
+use crate::services::file_import::split_display;
+use crate::services::import::{
+    store_library_track, ImportError, ImportFailure, ImportOrigin, ImportRepository, ImportSummary,
+    LibraryTrackRecord, UpsertResult,
+};
+
+// ---------------------------------------------------------------------------
+// Local music folder scan
+// ---------------------------------------------------------------------------
+
+/// `tracks.source` for scanned files.
+pub const SOURCE: &str = "local";
+
+/// Extensions of the containers whose tags can be read: ID3v2 (MP3),
+/// Vorbis comments (FLAC, Ogg), MP4 atoms (M4A) and RIFF INFO (WAV).
+const AUDIO_EXTENSIONS: &[&str] = &["mp3", "flac", "ogg", "oga", "opus", "m4a", "mp4", "wav"];
+
+/// Tag keys that hold the musical key. None of the formats has a standard
+/// key tag, so they are matched by name: `TKEY` (ID3v2), `INITIALKEY`
+/// (Vorbis) and `com.apple.iTunes:initialkey` (MP4).
+const KEY_TAGS: &[&str] = &["tkey", "initialkey", "initial key", "key"];
+
+/// Embedded images larger than this are not stored.
+const MAX_ARTWORK_BYTES: usize = 2 * 1024 * 1024;
+
+/// Size and modification time of a file; a re-scan skips files whose stamp
+/// has not changed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FileStamp {
+    pub size: i64,
+    /// Unix seconds.
+    pub modified: i64,
+}
+
+/// An audio file found in the folder.
+#[derive(Debug, Clone)]
+pub struct LocalFile {
+    pub path: PathBuf,
+    /// `path` as stored in `tracks.file_location`.
+    pub location: String,
+    pub stamp: FileStamp,
+}
+
+/// Cover art embedded in a file.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Artwork {
+    pub media_type: String,
+    pub data: Vec<u8>,
+}
+
+/// A file's tags read into a library track.
+#[derive(Debug, Clone)]
+pub struct LocalTrack {
+    pub track: LibraryTrackRecord,
+    pub artwork: Option<Artwork>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalScanSummary {
+    /// Files that were new or changed since the last scan.
+    pub import: ImportSummary,
+    /// Audio files found in the folder.
+    pub files: u32,
+    /// Files skipped because they had not changed.
+    pub unchanged: u32,
+}
+
+/// URL `album_art_url` points at for embedded artwork.
+pub fn artwork_url(track_id: &str) -> String {
+    format!("/api/tracks/{track_id}/artwork")
+}
+
+// ---------------------------------------------------------------------------
+// Reading the folder
+// ---------------------------------------------------------------------------
+
+/// Audio files under `root`, sorted by path. Unreadable subfolders are
+/// skipped with a warning.
+pub fn list_audio_files(root: &Path) -> Result<Vec<LocalFile>, ImportError> {
+    if !root.is_dir() {
+        return Err(ImportError::InvalidFile(format!(
+            "Music folder '{}' does not exist or is not a directory",
+            root.display()
+        )));
+    }
+
+    let mut files = Vec::new();
+    for entry in WalkDir::new(root).follow_links(true).sort_by_file_name() {
+        let entry = match entry {
+            Ok(e) => e,
+            Err(e) => {
+                tracing::warn!("Skipping unreadable entry in {}: {e}", root.display());
+                continue;
+            }
+        };
+        if !entry.file_type().is_file() || !is_audio(entry.path()) {
+            continue;
+        }
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        let modified = metadata
+            .modified()
+            .ok()
+            .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+            .map_or(0, |d| d.as_secs() as i64);
+        files.push(LocalFile {
+            location: entry.path().to_string_lossy().into_owned(),
+            path: entry.into_path(),
+            stamp: FileStamp {
+                size: metadata.len() as i64,
+                modified,
+            },
+        });
+    }
+    Ok(files)
+}
+
+fn is_audio(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .is_some_and(|e| AUDIO_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+}
+
+/// Read a file's tags, duration and cover art.
+pub fn read_track(file: &LocalFile) -> Result<LocalTrack, String> {
+    let source = File::open(&file.path).map_err(|e| format!("Cannot open file: {e}"))?;
+    let stream = MediaSourceStream::new(Box::new(source), Default::default());
+    let mut hint = Hint::new();
+    if let Some(ext) = file.path.extension().and_then(|e| e.to_str()) {
+        hint.with_extension(ext);
+    }
+
+    let mut probed = symphonia::default::get_probe()
+        .format(
+            &hint,
+            stream,
+            &FormatOptions::default(),
+            &MetadataOptions::default(),
+        )
+        .map_err(|e| format!("Unsupported or corrupt audio file: {e}"))?;
+
+    let mut tags = TagFields::default();
+    // Container tags (Vorbis comments, MP4 atoms) first, then tags found
+    // ahead of the stream such as ID3v2 on MP3.
+    if let Some(revision) = probed.format.metadata().current() {
+        tags.read(revision);
+    }
+    if let Some(revision) = probed.metadata.get().as_ref().and_then(|m| m.current()) {
+        tags.read(revision);
+    }
+
+    let duration_ms = probed.format.default_track().and_then(|track| {
+        let params = &track.codec_params;
+        let frames = params.n_frames?;
+        let seconds = match (params.time_base, params.sample_rate) {
+            (Some(tb), _) => {
+                let time = tb.calc_time(frames);
+                time.seconds as f64 + time.frac
+            }
+            (None, Some(rate)) if rate > 0 => frames as f64 / f64::from(rate),
+            _ => return None,
+        };
+        let ms = (seconds * 1000.0).round() as i64;
+        (ms > 0).then_some(ms)
+    });
+
+    let stem = file
+        .path
+        .file_stem()
+        .map(|s| s.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (title, artist) = match tags.title {
+        Some(title) => (title, tags.artist),
+        // Untagged files are usually named "Artist - Title".
+        None => {
+            let (artist, title) = split_display(&stem);
+            (title, tags.artist.or(artist))
+        }
+    };
+    if title.is_empty() {
+        return Err("File has no title tag or usable file name".to_string());
+    }
+
+    Ok(LocalTrack {
+        track: LibraryTrackRecord {
+            title,
+            artist,
+            album: tags.album,
+            duration_ms,
+            bpm: tags.bpm,
+            camelot_key: tags.camelot_key,
+            file_location: Some(file.location.clone()),
+            source: SOURCE.to_string(),
+            ..Default::default()
+        },
+        artwork: tags.artwork,
+    })
+}
+
+/// Track fields gathered across metadata revisions; the first value found
+/// for each field wins.
+#[derive(Debug, Default)]
+struct TagFields {
+    title: Option<String>,
+    artist: Option<String>,
+    album_artist: Option<String>,
+    album: Option<String>,
+    bpm: Option<f64>,
+    camelot_key: Option<String>,
+    artwork: Option<Artwork>,
+}
+
+impl TagFields {
+    fn read(&mut self, revision: &MetadataRevision) {
+        for tag in revision.tags() {
+            let value = tag.value.to_string();
+            let value = value.trim();
+            if value.is_empty() {
+                continue;
+            }
+            match tag.std_key {
+                Some(StandardTagKey::TrackTitle) => set_once(&mut self.title, value),
+                Some(StandardTagKey::Artist) => set_once(&mut self.artist, value),
+                Some(StandardTagKey::AlbumArtist) => set_once(&mut self.album_artist, value),
+                Some(StandardTagKey::Album) => set_once(&mut self.album, value),
+                Some(StandardTagKey::Bpm) if self.bpm.is_none() => self.bpm = parse_bpm(value),
+                _ if self.camelot_key.is_none() && is_key_tag(&tag.key) => {
+                    self.camelot_key = normalize_key(Some(value));
+                }
+                _ => {}
+            }
+        }
+        if self.artist.is_none() {
+            self.artist = self.album_artist.clone();
+        }
+
+        if self.artwork.is_none() {
+            let visuals = revision.visuals();
+            let cover = visuals
+                .iter()
+                .find(|v| v.usage == Some(StandardVisualKey::FrontCover))
+                .or_else(|| visuals.first());
+            self.artwork = cover
+                .filter(|v| !v.data.is_empty() && v.data.len() <= MAX_ARTWORK_BYTES)
+                .map(|v| Artwork {
+                    media_type: if v.media_type.is_empty() {
+                        "image/jpeg".to_string()
+                    } else {
+                        v.media_type.clone()
+                    },
+                    data: v.data.to_vec(),
+                });
+        }
+    }
+}
+
+fn set_once(field: &mut Option<String>, value: &str) {
+    if field.is_none() {
+        *field = Some(value.to_string());
+    }
+}
+
+fn is_key_tag(key: &str) -> bool {
+    // MP4 freeform keys carry a namespace: "com.apple.iTunes:initialkey".
+    let name = key.rsplit(':').next().unwrap_or(key).trim();
+    KEY_TAGS.contains(&name.to_ascii_lowercase().as_str())
+}
+
+/// BPM from a tag value ("124", "123.97", "124,5").
+fn parse_bpm(raw: &str) -> Option<f64> {
+    raw.replace(',', ".")
+        .parse::<f64>()
+        .ok()
+        .filter(|bpm| *bpm > 0.0 && *bpm < 300.0)
+}
+
+// ---------------------------------------------------------------------------
+// Scan
+// ---------------------------------------------------------------------------
+
+/// Scan `root` for audio files and upsert their tracks as one import.
+///
+/// Files whose size and modification time match the previous scan are
+/// skipped. Files that cannot be read or stored are recorded as import
+/// failures, numbered by their position in the sorted file list.
+pub async fn scan_folder(
+    repo: &dyn ImportRepository,
+    user_id: &str,
+    root: &Path,
+) -> Result<LocalScanSummary, ImportError> {
+    let owned_root = root.to_path_buf();
+    let files = tokio::task::spawn_blocking(move || list_audio_files(&owned_root))
+        .await
+        .map_err(|e| ImportError::Database(format!("Folder scan failed: {e}")))??;
+    let known: HashMap<String, FileStamp> = repo.local_file_stamps().await?;
+
+    let folder_name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| root.display().to_string());
+    let origin = ImportOrigin::new(SOURCE, &root.to_string_lossy(), Some(&folder_name));
+    let import_id = repo.create_import(user_id, &origin).await?;
+
+    let mut unchanged: u32 = 0;
+    let mut inserted: u32 = 0;
+    let mut updated: u32 = 0;
+    let mut failures = Vec::new();
+    for (i, file) in files.iter().enumerate() {
+        if known.get(&file.location) == Some(&file.stamp) {
+            unchanged += 1;
+            continue;
+        }
+        let row = i as u32 + 1;
+        let fail = |reason: String| ImportFailure {
+            row,
+            item: Some(file.location.clone()),
+            reason,
+        };
+
+        let to_read = file.clone();
+        let local = match tokio::task::spawn_blocking(move || read_track(&to_read)).await {
+            Ok(Ok(local)) => local,
+            Ok(Err(reason)) => {
+                failures.push(fail(reason));
+                continue;
+            }
+            Err(e) => {
+                failures.push(fail(format!("Tag reader failed: {e}")));
+                continue;
+            }
+        };
+
+        let track_id = match store_library_track(repo, &import_id, &local.track).await {
+            Ok((track_id, UpsertResult::Inserted)) => {
+                inserted += 1;
+                track_id
+            }
+            Ok((track_id, UpsertResult::Updated)) => {
+                updated += 1;
+                track_id
+            }
+            Err(e) => {
+                failures.push(fail(e.to_string()));
+                continue;
+            }
+        };
+        if let Err(e) = repo
+            .record_local_file(&track_id, &file.stamp, local.artwork.as_ref())
+            .await
+        {
+            // The track is stored; without its stamp it is re-read next scan.
+            tracing::warn!("Failed to record scan of {}: {e}", file.location);
+        }
+    }
+
+    let import = ImportSummary {
+        import_id: import_id.clone(),
+        source: SOURCE.to_string(),
+        total: files.len() as u32 - unchanged,
+        inserted,
+        updated,
+        failed: failures.len() as u32,
+        status: "completed".to_string(),
+        failures,
+    };
+    repo.complete_import(&import_id, &import).await?;
+
+    Ok(LocalScanSummary {
+        import,
+        files: files.len() as u32,
+        unchanged,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PNG: &[u8] = b"\x89PNG\r\n\x1a\nnot really an image";
+
+    fn syncsafe(n: usize) -> [u8; 4] {
+        let n = n as u32;
+        [
+            ((n >> 21) & 0x7F) as u8,
+            ((n >> 14) & 0x7F) as u8,
+            ((n >> 7) & 0x7F) as u8,
+            (n & 0x7F) as u8,
+        ]
+    }
+
+    fn id3_frame(id: &[u8; 4], body: &[u8]) -> Vec<u8> {
+        let mut frame = id.to_vec();
+        frame.extend_from_slice(&syncsafe(body.len()));
+        frame.extend_from_slice(&[0, 0]);
+        frame.extend_from_slice(body);
+        frame
+    }
+
+    fn text_frame(id: &[u8; 4], text: &str) -> Vec<u8> {
+        let mut body = vec![3]; // UTF-8
+        body.extend_from_slice(text.as_bytes());
+        id3_frame(id, &body)
+    }
+
+    /// An MP3 of silent 128 kbps frames, ID3v2.4-tagged unless there is
+    /// nothing to tag.
+    fn mp3(frames: &[(&[u8; 4], &str)], cover: bool) -> Vec<u8> {
+        let mut tag_body: Vec<u8> = frames
+            .iter()
+            .flat_map(|(id, text)| text_frame(id, text))
+            .collect();
+        if cover {
+            let mut apic = vec![0];
+            apic.extend_from_slice(b"image/png\0");
+            apic.push(3); // front cover
+            apic.push(0); // empty description
+            apic.extend_from_slice(PNG);
+            tag_body.extend(id3_frame(b"APIC", &apic));
+        }
+
+        let mut data = Vec::new();
+        if !tag_body.is_empty() {
+            data.extend_from_slice(b"ID3\x04\x00\x00");
+            data.extend_from_slice(&syncsafe(tag_body.len()));
+            data.extend(tag_body);
+        }
+        for _ in 0..40 {
+            let mut frame = vec![0u8; 417];
+            frame[..4].copy_from_slice(&[0xFF, 0xFB, 0x90, 0x00]);
+            data.extend(frame);
+        }
+        data
+    }
+
+    fn local_file(path: &Path) -> LocalFile {
+        let metadata = std::fs::metadata(path).unwrap();
+        LocalFile {
+            path: path.to_path_buf(),
+            location: path.to_string_lossy().into_owned(),
+            stamp: FileStamp {
+                size: metadata.len() as i64,
+                modified: 0,
+            },
+        }
+    }
+
+    #[test]
+    fn test_read_id3_tags_and_artwork() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("yeke.mp3");
+        let tags: &[(&[u8; 4], &str)] = &[
+            (b"TIT2", "Yeke Yeke"),
+            (b"TPE1", "Mory Kante"),
+            (b"TALB", "Akwaba Beach"),
+            (b"TBPM", "124"),
+            (b"TKEY", "F#m"),
+        ];
+        std::fs::write(&path, mp3(tags, true)).unwrap();
+
+        let local = read_track(&local_file(&path)).unwrap();
+        let track = &local.track;
+        assert_eq!(track.title, "Yeke Yeke");
+        assert_eq!(track.artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(track.album.as_deref(), Some("Akwaba Beach"));
+        assert_eq!(track.bpm, Some(124.0));
+        assert_eq!(track.camelot_key.as_deref(), Some("11A"));
+        assert!(track.duration_ms.is_some_and(|ms| ms > 0));
+        assert_eq!(track.source, "local");
+        assert_eq!(track.analysed_by(), Some("local"));
+        assert_eq!(
+            track.file_location.as_deref(),
+            Some(path.to_string_lossy().as_ref())
+        );
+
+        let artwork = local.artwork.unwrap();
+        assert_eq!(artwork.media_type, "image/png");
+        assert_eq!(artwork.data, PNG);
+    }
+
+    #[test]
+    fn test_untagged_file_named_after_artist_and_title() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("Sting - Desert Rose.mp3");
+        std::fs::write(&path, mp3(&[], false)).unwrap();
+
+        let local = read_track(&local_file(&path)).unwrap();
+        assert_eq!(local.track.title, "Desert Rose");
+        assert_eq!(local.track.artist.as_deref(), Some("Sting"));
+        assert_eq!(local.track.analysed_by(), None);
+        assert!(local.artwork.is_none());
+    }
+
+    #[test]
+    fn test_read_rejects_non_audio() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("notes.mp3");
+        std::fs::write(&path, "setlist ideas").unwrap();
+        assert!(read_track(&local_file(&path)).is_err());
+    }
+
+    #[test]
+    fn test_list_audio_files_filters_and_sorts() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("House")).unwrap();
+        std::fs::write(dir.path().join("House/b.FLAC"), b"").unwrap();
+        std::fs::write(dir.path().join("a.mp3"), b"").unwrap();
+        std::fs::write(dir.path().join("cover.jpg"), b"").unwrap();
+        std::fs::write(dir.path().join("set.m3u"), b"").unwrap();
+
+        let files = list_audio_files(dir.path()).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|f| f.path.strip_prefix(dir.path()).unwrap().to_path_buf())
+            .collect();
+        assert_eq!(
+            names,
+            vec![PathBuf::from("House/b.FLAC"), PathBuf::from("a.mp3")]
+        );
+
+        assert!(matches!(
+            list_audio_files(&dir.path().join("missing")).unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+    }
+
+    #[test]
+    fn test_key_tag_names() {
+        assert!(is_key_tag("TKEY"));
+        assert!(is_key_tag("INITIALKEY"));
+        assert!(is_key_tag("com.apple.iTunes:initialkey"));
+        assert!(!is_key_tag("TBPM"));
+        assert_eq!(parse_bpm("123,97"), Some(123.97));
+        assert_eq!(parse_bpm("0"), None);
+    }
+
+    #[tokio::test]
+    async fn test_scan_folder_is_incremental() {
+        let pool = crate::db::create_test_pool().await;
+        let user_id = crate::db::create_test_user(&pool).await;
+        let repo = crate::repo::PgImportRepository::new(pool.clone());
+
+        let dir = tempfile::tempdir().unwrap();
+        let tagged: &[(&[u8; 4], &str)] = &[(b"TIT2", "Yeke Yeke"), (b"TPE1", "Mory Kante")];
+        std::fs::write(dir.path().join("yeke.mp3"), mp3(tagged, true)).unwrap();
+        std::fs::write(dir.path().join("broken.flac"), b"fLaC but not really").unwrap();
+
+        let first = scan_folder(&repo, &user_id, dir.path()).await.unwrap();
+        assert_eq!(first.files, 2);
+        assert_eq!(first.unchanged, 0);
+        assert_eq!(first.import.inserted, 1);
+        assert_eq!(first.import.failed, 1);
+        assert_eq!(first.import.failures[0].row, 1, "broken.flac sorts first");
+
+        let location = dir.path().join("yeke.mp3").to_string_lossy().into_owned();
+        let track_id = crate::db::tracks::find_track_by_location(&pool, &location)
+            .await
+            .unwrap()
+            .unwrap();
+        let (media_type, data) = crate::db::tracks::get_track_artwork(&pool, &track_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(media_type, "image/png");
+        assert_eq!(data, PNG);
+        let art_url: Option<String> =
+            sqlx::query_scalar("SELECT album_art_url FROM tracks WHERE id = $1")
+                .bind(&track_id)
+                .fetch_one(&pool)
+                .await
+                .unwrap();
+        assert_eq!(art_url, Some(artwork_url(&track_id)));
+
+        // Unchanged files are skipped; the broken one is retried.
+        let second = scan_folder(&repo, &user_id, dir.path()).await.unwrap();
+        assert_eq!(second.unchanged, 1);
+        assert_eq!(second.import.total, 1);
+        assert_eq!(second.import.inserted, 0);
+
+        // A changed file is re-read and updates the same track.
+        let retagged: &[(&[u8; 4], &str)] = &[
+            (b"TIT2", "Yeke Yeke"),
+            (b"TPE1", "Mory Kante"),
+            (b"TBPM", "124"),
+        ];
+        std::fs::write(dir.path().join("yeke.mp3"), mp3(retagged, false)).unwrap();
+        let third = scan_folder(&repo, &user_id, dir.path()).await.unwrap();
+        assert_eq!(third.unchanged, 0);
+        assert_eq!(third.import.updated, 1);
+        let bpm: Option<f64> = sqlx::query_scalar("SELECT bpm FROM tracks WHERE id = $1")
+            .bind(&track_id)
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(bpm, Some(124.0));
+
+        pool.close().await;
+    }
+}
@@ -1,12 +1,27 @@
 pub mod arrangement;
+pub mod bridge;
 pub mod camelot;
+pub mod constraints;
 pub mod deezer;
 pub mod enrichment;
+pub mod file_import;
 pub mod import;
+pub mod lineup;
+pub mod lint;
+pub mod local_generation;
+pub mod local_scan;
 pub mod match_scoring;
 pub mod musicbrainz;
+pub mod play_next;
 pub mod purchase_links;
 pub mod quick_commands;
 pub mod refinement;
+pub mod rekordbox;
+pub mod serato;
 pub mod setlist;
+pub mod solver;
 pub mod soundcloud;
+pub mod technique;
+pub mod tracklist;
+pub mod traktor;
+pub mod variants;
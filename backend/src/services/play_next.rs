@@ -0,0 +1,319 @@
+use std::collections::HashSet;
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::TrackRow;
+use crate::services::camelot::{
+    self, parse_camelot, ScoringProfile, TempoRelation, TransitionType,
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+pub const DEFAULT_PLAY_NEXT_LIMIT: usize = 10;
+pub const MAX_PLAY_NEXT_LIMIT: usize = 50;
+
+/// Where the DJ wants the energy to go from the current track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnergyDirection {
+    /// One or two levels higher.
+    Up,
+    /// Same level.
+    #[default]
+    Hold,
+    /// One or two levels lower.
+    Down,
+}
+
+impl EnergyDirection {
+    /// Inclusive range of energy change that scores a perfect 1.0.
+    fn ideal_delta(self) -> (f64, f64) {
+        match self {
+            EnergyDirection::Up => (1.0, 2.0),
+            EnergyDirection::Hold => (-0.5, 0.5),
+            EnergyDirection::Down => (-2.0, -1.0),
+        }
+    }
+}
+
+impl FromStr for EnergyDirection {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "up" => Ok(EnergyDirection::Up),
+            "hold" => Ok(EnergyDirection::Hold),
+            "down" => Ok(EnergyDirection::Down),
+            other => Err(format!(
+                "Unknown energy direction '{other}'. Valid: up, hold, down"
+            )),
+        }
+    }
+}
+
+/// Inputs for [`rank_next_tracks`] besides the tracks themselves.
+#[derive(Debug, Clone)]
+pub struct PlayNextOptions {
+    pub direction: EnergyDirection,
+    /// Track ids never to suggest (e.g., already played).
+    pub exclude: HashSet<String>,
+    pub limit: usize,
+    pub key_lock: bool,
+    pub scoring: ScoringProfile,
+}
+
+impl Default for PlayNextOptions {
+    fn default() -> Self {
+        Self {
+            direction: EnergyDirection::default(),
+            exclude: HashSet::new(),
+            limit: DEFAULT_PLAY_NEXT_LIMIT,
+            key_lock: true,
+            scoring: ScoringProfile::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NextCandidate {
+    pub track_id: String,
+    pub title: String,
+    pub artist: Option<String>,
+    pub bpm: Option<f64>,
+    pub camelot: Option<String>,
+    pub energy: Option<f64>,
+    /// 0-1; `breakdown.transition_score` with its neutral energy term
+    /// replaced by `breakdown.energy_score`.
+    pub score: f64,
+    pub breakdown: NextScoreBreakdown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct NextScoreBreakdown {
+    /// Key + BPM score from the user's scoring profile, energy neutral.
+    pub transition_score: f64,
+    pub key_score: f64,
+    pub bpm_score: f64,
+    /// How well the energy change matches the requested direction.
+    pub energy_score: f64,
+    /// Candidate energy minus current energy.
+    pub energy_delta: Option<f64>,
+    pub key_transition: Option<TransitionType>,
+    pub tempo_relation: Option<TempoRelation>,
+    /// Pitch-fader % to beatmatch the candidate to the current track.
+    pub pitch_percent: Option<f64>,
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+/// Energy score when either track has no energy rating.
+const ENERGY_UNKNOWN: f64 = 0.5;
+
+/// Score lost per energy level outside the ideal range.
+const ENERGY_FALLOFF: f64 = 0.25;
+
+/// Rank `catalog` by how well each track follows `current`, best first.
+///
+/// The current track and `options.exclude` are skipped. Ties are broken by
+/// title so results are stable.
+pub fn rank_next_tracks(
+    current: &TrackRow,
+    catalog: &[TrackRow],
+    options: &PlayNextOptions,
+) -> Vec<NextCandidate> {
+    let mut candidates: Vec<NextCandidate> = catalog
+        .iter()
+        .filter(|t| t.id != current.id && !options.exclude.contains(&t.id))
+        .map(|t| score_candidate(current, t, options))
+        .collect();
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score).then(a.title.cmp(&b.title)));
+    candidates.truncate(options.limit);
+    candidates
+}
+
+fn score_candidate(
+    current: &TrackRow,
+    next: &TrackRow,
+    options: &PlayNextOptions,
+) -> NextCandidate {
+    let key_a = current.camelot_key.as_deref().and_then(parse_camelot);
+    let key_b = next.camelot_key.as_deref().and_then(parse_camelot);
+    let profile = &options.scoring;
+
+    let transition_score = camelot::transition_score_with_profile(
+        key_a.as_ref(),
+        key_b.as_ref(),
+        current.bpm,
+        next.bpm,
+        options.key_lock,
+        profile,
+    );
+
+    let key_transition = match (key_a.as_ref(), key_b.as_ref()) {
+        (Some(a), Some(b)) => Some(camelot::classify_transition(
+            a,
+            &camelot::effective_key(b, current.bpm, next.bpm, options.key_lock),
+        )),
+        _ => None,
+    };
+    let bpm_match = match (current.bpm, next.bpm) {
+        (Some(a), Some(b)) => Some(camelot::bpm_match_with_bands(a, b, &profile.bpm_bands)),
+        _ => None,
+    };
+
+    let energy_delta = match (current.energy, next.energy) {
+        (Some(a), Some(b)) => Some(b - a),
+        _ => None,
+    };
+    let energy_score = energy_delta
+        .map(|d| energy_direction_score(d, options.direction))
+        .unwrap_or(ENERGY_UNKNOWN);
+
+    // transition_score counts energy as neutral (0.5); swap in the real one
+    let total_weight = profile.key_weight + profile.bpm_weight + profile.energy_weight;
+    let score = transition_score + (energy_score - 0.5) * profile.energy_weight / total_weight;
+
+    NextCandidate {
+        track_id: next.id.clone(),
+        title: next.title.clone(),
+        artist: next.artist.clone(),
+        bpm: next.bpm,
+        camelot: key_b.map(|k| k.to_string()),
+        energy: next.energy,
+        score,
+        breakdown: NextScoreBreakdown {
+            transition_score,
+            key_score: key_transition.map(|k| k.score()).unwrap_or(0.5),
+            bpm_score: bpm_match.as_ref().map(|m| m.score).unwrap_or(0.5),
+            energy_score,
+            energy_delta,
+            key_transition,
+            tempo_relation: bpm_match.map(|m| m.relation),
+            pitch_percent: match (current.bpm, next.bpm) {
+                (Some(a), Some(b)) => Some(camelot::pitch_percent(a, b)),
+                _ => None,
+            },
+        },
+    }
+}
+
+/// 1.0 inside the direction's ideal range, falling off linearly outside it.
+fn energy_direction_score(delta: f64, direction: EnergyDirection) -> f64 {
+    let (lo, hi) = direction.ideal_delta();
+    let miss = if delta < lo {
+        lo - delta
+    } else if delta > hi {
+        delta - hi
+    } else {
+        0.0
+    };
+    (1.0 - miss * ENERGY_FALLOFF).max(0.0)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(id: &str, camelot: &str, bpm: f64, energy: f64) -> TrackRow {
+        TrackRow {
+            id: id.to_string(),
+            title: format!("Title {id}"),
+            artist: Some("Artist".to_string()),
+            album: None,
+            duration_ms: None,
+            bpm: Some(bpm),
+            camelot_key: Some(camelot.to_string()),
+            energy: Some(energy),
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        }
+    }
+
+    fn ids(candidates: &[NextCandidate]) -> Vec<&str> {
+        candidates.iter().map(|c| c.track_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_direction_from_str() {
+        assert_eq!("up".parse(), Ok(EnergyDirection::Up));
+        assert!("sideways".parse::<EnergyDirection>().is_err());
+    }
+
+    #[test]
+    fn test_energy_direction_score() {
+        assert_eq!(energy_direction_score(1.5, EnergyDirection::Up), 1.0);
+        assert_eq!(energy_direction_score(0.0, EnergyDirection::Up), 0.75);
+        assert_eq!(energy_direction_score(0.0, EnergyDirection::Hold), 1.0);
+        assert_eq!(energy_direction_score(-2.0, EnergyDirection::Down), 1.0);
+        assert_eq!(energy_direction_score(6.0, EnergyDirection::Down), 0.0);
+    }
+
+    #[test]
+    fn test_harmonic_match_ranks_first() {
+        let current = track("now", "8A", 124.0, 6.0);
+        let catalog = vec![
+            track("clash", "2B", 124.0, 6.0),
+            track("same", "8A", 124.0, 6.0),
+            track("adjacent", "9A", 125.0, 6.0),
+            current.clone(),
+        ];
+        let ranked = rank_next_tracks(&current, &catalog, &PlayNextOptions::default());
+        assert_eq!(ids(&ranked), vec!["same", "adjacent", "clash"]);
+        let best = &ranked[0].breakdown;
+        assert_eq!(best.key_transition, Some(TransitionType::SameKey));
+        assert_eq!(best.energy_delta, Some(0.0));
+    }
+
+    #[test]
+    fn test_direction_reorders_by_energy() {
+        let current = track("now", "8A", 124.0, 6.0);
+        let catalog = vec![
+            track("lower", "8A", 124.0, 4.5),
+            track("level", "8A", 124.0, 6.0),
+            track("higher", "8A", 124.0, 7.5),
+        ];
+        for (direction, first) in [
+            (EnergyDirection::Up, "higher"),
+            (EnergyDirection::Hold, "level"),
+            (EnergyDirection::Down, "lower"),
+        ] {
+            let options = PlayNextOptions {
+                direction,
+                ..Default::default()
+            };
+            let ranked = rank_next_tracks(&current, &catalog, &options);
+            assert_eq!(ranked[0].track_id, first, "{direction:?}");
+            assert!(ranked[0].score > ranked[1].score);
+        }
+    }
+
+    #[test]
+    fn test_exclusions_and_limit() {
+        let current = track("now", "8A", 124.0, 6.0);
+        let catalog: Vec<TrackRow> = (0..5)
+            .map(|i| track(&format!("t{i}"), "8A", 124.0, 6.0))
+            .collect();
+        let options = PlayNextOptions {
+            exclude: HashSet::from(["t0".to_string(), "t3".to_string()]),
+            limit: 2,
+            ..Default::default()
+        };
+        let ranked = rank_next_tracks(&current, &catalog, &options);
+        // Equal scores fall back to title order
+        assert_eq!(ids(&ranked), vec!["t1", "t2"]);
+    }
+}
@@ -1,4 +1,4 @@
-// ST-007: Quick commands (shuffle, sort-by-bpm, reverse, undo)
+// ST-007: Quick commands (shuffle, sort-by-bpm, reverse, undo, bridge)
 
 /// Deterministic operations on a setlist that do not require LLM.
 #[derive(Debug, Clone, PartialEq)]
@@ -8,6 +8,8 @@ pub enum QuickCommand {
     Reverse,
     Undo,
     RevertToVersion(i32),
+    /// Insert catalog bridge tracks between position N and N + 1.
+    Bridge(usize),
 }
 
 /// Attempt to parse a user message as a quick command.
@@ -29,6 +31,24 @@ pub fn parse_quick_command(message: &str) -> Option<QuickCommand> {
         }
     }
 
+    // "bridge N" or "bridge N and N+1"
+    if let Some(rest) = s.strip_prefix("bridge ") {
+        let numbers: Vec<&str> = rest
+            .split(|c: char| !c.is_ascii_digit())
+            .filter(|p| !p.is_empty())
+            .collect();
+        match numbers.as_slice() {
+            [n] => return n.parse().ok().map(QuickCommand::Bridge),
+            [a, b] => {
+                let (a, b): (usize, usize) = (a.parse().ok()?, b.parse().ok()?);
+                if b == a + 1 {
+                    return Some(QuickCommand::Bridge(a));
+                }
+            }
+            _ => {}
+        }
+    }
+
     None
 }
 
@@ -104,6 +124,20 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_parse_bridge() {
+        assert_eq!(
+            parse_quick_command("bridge 3"),
+            Some(QuickCommand::Bridge(3))
+        );
+        assert_eq!(
+            parse_quick_command("Bridge 3 and 4"),
+            Some(QuickCommand::Bridge(3))
+        );
+        assert_eq!(parse_quick_command("bridge 3 and 5"), None);
+        assert_eq!(parse_quick_command("bridge the gap"), None);
+    }
+
     #[test]
     fn test_parse_not_quick_command() {
         assert_eq!(parse_quick_command("swap track 5"), None);
@@ -12,8 +12,11 @@ use crate::api::claude::{
 use crate::db::models::{SetlistConversationRow, SetlistVersionRow, VersionTrackRow};
 use crate::db::refinement as db;
 use crate::db::setlists as db_setlists;
-use crate::services::camelot::{camelot_score, parse_camelot};
+use crate::db::settings::MixSettings;
+use crate::services::bridge::{self, BridgeEnd, BridgeOptions, MAX_BRIDGE_TRACKS};
+use crate::services::camelot::{camelot_score, parse_camelot, ScoringProfile};
 use crate::services::quick_commands::{parse_quick_command, QuickCommand};
+use crate::services::technique::{self, MixPoint};
 
 const MAX_TURNS: usize = 20;
 const REFINEMENT_MODEL: &str = "claude-sonnet-4-20250514";
@@ -155,7 +158,7 @@ pub async fn refine_setlist(
     message: &str,
 ) -> Result<RefinementResponse, RefinementError> {
     // 1. Load setlist (404 if not found)
-    let _setlist = db_setlists::get_setlist(pool, setlist_id)
+    let setlist = db_setlists::get_setlist(pool, setlist_id)
         .await?
         .ok_or_else(|| RefinementError::NotFound(format!("Setlist {setlist_id} not found")))?;
 
@@ -173,9 +176,12 @@ pub async fn refine_setlist(
         return Err(RefinementError::TurnLimitExceeded { limit: MAX_TURNS });
     }
 
+    // Transition notes follow the user's key lock and BPM bands
+    let settings = crate::db::settings::get_mix_settings(pool, &setlist.user_id).await?;
+
     // 4. Try quick command first — no LLM needed
     if let Some(quick_cmd) = parse_quick_command(message) {
-        return handle_quick_command(pool, setlist_id, message, quick_cmd).await;
+        return handle_quick_command(pool, setlist_id, message, quick_cmd, &settings).await;
     }
 
     // 5. LLM path — bootstrap version 0 if no versions exist
@@ -235,7 +241,12 @@ pub async fn refine_setlist(
     let change_warning = compute_change_warning(&parsed.actions, current_tracks.len());
 
     // 11. Apply actions in memory
-    let new_tracks_raw = apply_actions(current_tracks, &parsed.actions);
+    let new_tracks_raw = apply_actions(
+        current_tracks,
+        &parsed.actions,
+        settings.key_lock,
+        &settings.scoring,
+    );
 
     // 12. Next version number
     let latest_version = db::get_latest_version(pool, setlist_id).await?;
@@ -351,6 +362,132 @@ pub async fn get_history(
     })
 }
 
+/// Insert the shortest smooth chain of catalog tracks between positions
+/// `after_position` and `after_position + 1` of the latest version, saved as
+/// a new version. Tracks already in the set are never used as bridges.
+pub async fn bridge_setlist(
+    pool: &PgPool,
+    setlist_id: &str,
+    after_position: usize,
+    max_tracks: usize,
+) -> Result<RefinementResponse, RefinementError> {
+    let setlist = db_setlists::get_setlist(pool, setlist_id)
+        .await?
+        .ok_or_else(|| RefinementError::NotFound(format!("Setlist {setlist_id} not found")))?;
+    if !(1..=MAX_BRIDGE_TRACKS).contains(&max_tracks) {
+        return Err(RefinementError::InvalidRequest(format!(
+            "max_tracks must be between 1 and {MAX_BRIDGE_TRACKS}"
+        )));
+    }
+
+    let current_tracks = match db::get_latest_version(pool, setlist_id).await? {
+        Some(v) => db::get_version_tracks(pool, &v.id).await?,
+        None => bootstrap_version(pool, setlist_id).await?,
+    };
+    if after_position == 0 || after_position >= current_tracks.len() {
+        return Err(RefinementError::InvalidRequest(format!(
+            "Cannot bridge after position {after_position}: there is no track after it"
+        )));
+    }
+    let before = &current_tracks[after_position - 1];
+    let after = &current_tracks[after_position];
+
+    let catalog = db_setlists::load_catalog_tracks(pool).await?;
+    let settings = crate::db::settings::get_mix_settings(pool, &setlist.user_id).await?;
+    let options = BridgeOptions {
+        max_tracks,
+        exclude: current_tracks
+            .iter()
+            .filter_map(|t| t.track_id.clone())
+            .collect(),
+        key_lock: settings.key_lock,
+        scoring: settings.scoring,
+        ..Default::default()
+    };
+    let found = bridge::find_bridge(
+        BridgeEnd::new(before.camelot.as_deref(), before.bpm),
+        BridgeEnd::new(after.camelot.as_deref(), after.bpm),
+        &catalog,
+        &options,
+    )
+    .ok_or_else(|| {
+        RefinementError::NotFound(format!(
+            "No chain of up to {max_tracks} catalog tracks connects positions {after_position} and {}",
+            after_position + 1
+        ))
+    })?;
+
+    let explanation = format!(
+        "Bridged \"{}\" into \"{}\" with {} track(s): {}",
+        before.title,
+        after.title,
+        found.tracks.len(),
+        found
+            .tracks
+            .iter()
+            .map(|t| t.title.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+
+    let latest_version = db::get_latest_version(pool, setlist_id).await?;
+    let next_version_num = latest_version.map(|v| v.version_number + 1).unwrap_or(1);
+    let new_version_id = uuid::Uuid::new_v4().to_string();
+    let new_version = SetlistVersionRow {
+        id: new_version_id.clone(),
+        setlist_id: setlist_id.to_string(),
+        version_number: next_version_num,
+        parent_version_id: None,
+        action: Some("bridge".to_string()),
+        action_summary: Some(truncate(&explanation, 200)),
+        created_at: None,
+    };
+
+    let bridge_rows = found.tracks.iter().map(|t| {
+        let row = catalog.iter().find(|c| c.id == t.track_id);
+        VersionTrackRow {
+            id: String::new(),
+            version_id: String::new(),
+            track_id: Some(t.track_id.clone()),
+            position: 0,
+            original_position: 0,
+            title: t.title.clone(),
+            artist: t.artist.clone().unwrap_or_default(),
+            bpm: t.bpm,
+            key: None,
+            camelot: t.camelot.clone(),
+            energy: t.energy,
+            transition_note: None,
+            transition_score: None,
+            source: "catalog".to_string(),
+            acquisition_info: None,
+            spotify_uri: row.and_then(|r| r.spotify_uri.clone()),
+        }
+    });
+    let mut new_tracks = current_tracks.clone();
+    new_tracks.splice(after_position..after_position, bridge_rows);
+    renumber_version_tracks(&mut new_tracks, settings.key_lock, &settings.scoring);
+    for t in &mut new_tracks {
+        t.id = uuid::Uuid::new_v4().to_string();
+        t.version_id = new_version_id.clone();
+    }
+
+    let mut tx = pool.begin().await?;
+    db::insert_version(&mut tx, &new_version).await?;
+    db::insert_version_tracks(&mut tx, &new_tracks).await?;
+    tx.commit().await?;
+
+    let score = compute_harmonic_score(&new_tracks);
+    db_setlists::update_setlist_harmonic_score(pool, setlist_id, score).await?;
+
+    Ok(RefinementResponse {
+        version_number: next_version_num,
+        tracks: new_tracks,
+        explanation,
+        change_warning: None,
+    })
+}
+
 // ---------------------------------------------------------------------------
 // Quick command handling
 // ---------------------------------------------------------------------------
@@ -360,7 +497,9 @@ async fn handle_quick_command(
     setlist_id: &str,
     message: &str,
     cmd: QuickCommand,
+    settings: &MixSettings,
 ) -> Result<RefinementResponse, RefinementError> {
+    let (key_lock, scoring) = (settings.key_lock, &settings.scoring);
     match cmd {
         QuickCommand::Undo => {
             let versions = db::get_versions_by_setlist(pool, setlist_id).await?;
@@ -373,6 +512,27 @@ async fn handle_quick_command(
             revert_setlist(pool, setlist_id, prev.version_number).await
         }
         QuickCommand::RevertToVersion(n) => revert_setlist(pool, setlist_id, n).await,
+        QuickCommand::Bridge(after_position) => {
+            let response =
+                bridge_setlist(pool, setlist_id, after_position, MAX_BRIDGE_TRACKS).await?;
+            let version = db::get_version_by_number(pool, setlist_id, response.version_number)
+                .await?
+                .ok_or_else(|| {
+                    RefinementError::NotFound(format!(
+                        "Version {} not found for setlist {setlist_id}",
+                        response.version_number
+                    ))
+                })?;
+            insert_conversation_pair(
+                pool,
+                setlist_id,
+                &version.id,
+                message,
+                &response.explanation,
+            )
+            .await?;
+            Ok(response)
+        }
         QuickCommand::Shuffle | QuickCommand::SortByBpm | QuickCommand::Reverse => {
             let latest = db::get_latest_version(pool, setlist_id).await?;
             let current_tracks = if let Some(v) = latest {
@@ -385,7 +545,7 @@ async fn handle_quick_command(
                 QuickCommand::Shuffle => {
                     let mut t = current_tracks.clone();
                     t.shuffle(&mut rand::thread_rng());
-                    renumber_version_tracks(&mut t);
+                    renumber_version_tracks(&mut t, key_lock, scoring);
                     ("shuffle", t)
                 }
                 QuickCommand::SortByBpm => {
@@ -398,12 +558,12 @@ async fn handle_quick_command(
                         (None, Some(_)) => std::cmp::Ordering::Greater,
                         (None, None) => std::cmp::Ordering::Equal,
                     });
-                    renumber_version_tracks(&mut t);
+                    renumber_version_tracks(&mut t, key_lock, scoring);
                     ("sort_by_bpm", t)
                 }
                 QuickCommand::Reverse => {
                     let mut t: Vec<_> = current_tracks.iter().rev().cloned().collect();
-                    renumber_version_tracks(&mut t);
+                    renumber_version_tracks(&mut t, key_lock, scoring);
                     ("reverse", t)
                 }
                 _ => unreachable!(),
@@ -525,12 +685,33 @@ async fn insert_conversation_pair(
     Ok(())
 }
 
-fn renumber_version_tracks(tracks: &mut [VersionTrackRow]) {
+fn renumber_version_tracks(
+    tracks: &mut [VersionTrackRow],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) {
     for (i, track) in tracks.iter_mut().enumerate() {
         track.position = (i + 1) as i32;
-        track.transition_note = None;
         track.transition_score = None;
     }
+    refresh_transition_notes(tracks, key_lock, scoring);
+}
+
+/// Recommend a technique for every transition from the current neighbours.
+/// Version tracks carry no durations, so only key, BPM and energy count.
+fn refresh_transition_notes(
+    tracks: &mut [VersionTrackRow],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) {
+    let points: Vec<MixPoint> = tracks
+        .iter()
+        .map(|t| MixPoint::new(t.camelot.as_deref(), t.bpm, t.energy, None))
+        .collect();
+    let notes = technique::transition_notes(&points, key_lock, scoring);
+    for (track, note) in tracks.iter_mut().zip(notes) {
+        track.transition_note = note;
+    }
 }
 
 fn conversations_to_messages(conversations: &[SetlistConversationRow]) -> Vec<ConversationMessage> {
@@ -678,6 +859,8 @@ pub fn validate_actions(actions: &[LlmAction], track_count: usize) -> Result<(),
 pub fn apply_actions(
     mut tracks: Vec<VersionTrackRow>,
     actions: &[LlmAction],
+    key_lock: bool,
+    scoring: &ScoringProfile,
 ) -> Vec<VersionTrackRow> {
     for action in actions {
         match action {
@@ -752,6 +935,7 @@ pub fn apply_actions(
     for (i, track) in tracks.iter_mut().enumerate() {
         track.position = (i + 1) as i32;
     }
+    refresh_transition_notes(&mut tracks, key_lock, scoring);
 
     tracks
 }
@@ -967,7 +1151,7 @@ mod tests {
             bpm: Some(128.0),
             key: None,
         }];
-        let result = apply_actions(tracks, &actions);
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
         assert_eq!(result[1].title, "Delta");
         assert_eq!(result[1].artist, "New Artist");
         assert_eq!(result[1].bpm, Some(128.0));
@@ -988,7 +1172,7 @@ mod tests {
             bpm: Some(130.0),
             key: None,
         }];
-        let result = apply_actions(tracks, &actions);
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
         assert_eq!(result.len(), 3);
         assert_eq!(result[0].title, "Alpha");
         assert_eq!(result[1].title, "Inserted");
@@ -1012,7 +1196,7 @@ mod tests {
             bpm: None,
             key: None,
         }];
-        let result = apply_actions(tracks, &actions);
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
         assert_eq!(result[0].title, "First");
         assert_eq!(result[1].title, "Alpha");
     }
@@ -1025,7 +1209,7 @@ mod tests {
             make_version_track(3, "Gamma"),
         ];
         let actions = vec![LlmAction::Remove { position: 2 }];
-        let result = apply_actions(tracks, &actions);
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
         assert_eq!(result.len(), 2);
         assert_eq!(result[0].title, "Alpha");
         assert_eq!(result[1].title, "Gamma");
@@ -1044,12 +1228,31 @@ mod tests {
             from_position: 3,
             to_position: 1,
         }];
-        let result = apply_actions(tracks, &actions);
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
         assert_eq!(result[0].title, "Gamma");
         assert_eq!(result[1].title, "Alpha");
         assert_eq!(result[2].title, "Beta");
     }
 
+    #[test]
+    fn test_apply_recomputes_transition_notes() {
+        let mut tracks = vec![
+            make_version_track(1, "Alpha"),
+            make_version_track(2, "Beta"),
+        ];
+        tracks[0].transition_note = Some("Open with pads".to_string());
+        tracks[1].bpm = Some(60.0);
+        tracks[1].transition_note = Some("Stale note".to_string());
+        let actions = vec![LlmAction::Reorder {
+            from_position: 2,
+            to_position: 1,
+        }];
+        let result = apply_actions(tracks, &actions, true, &ScoringProfile::default());
+        assert_eq!(result[0].transition_note, None);
+        let note = result[1].transition_note.as_deref().unwrap();
+        assert!(note.starts_with("Halftime drop:"), "{note}");
+    }
+
     // -----------------------------------------------------------------------
     // Unit tests: validate_actions
     // -----------------------------------------------------------------------
@@ -1291,6 +1494,42 @@ mod tests {
         pool.close().await;
     }
 
+    #[tokio::test]
+    async fn test_bridge_inserts_catalog_tracks() {
+        let pool = create_test_pool().await;
+        let setlist_id = insert_setlist(&pool).await;
+        insert_setlist_tracks(&pool, &setlist_id, 3).await;
+        sqlx::query(
+            "INSERT INTO tracks (id, title, source, bpm, camelot_key) \
+             VALUES ('bridge-cat', 'Bridge', 'spotify', 121.5, '2A')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+
+        let claude = MockClaude::new(vec![]);
+        let resp = refine_setlist(&pool, &claude, &setlist_id, "user-1", "bridge 1 and 2")
+            .await
+            .unwrap();
+        let titles: Vec<&str> = resp.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Track 1", "Bridge", "Track 2", "Track 3"]);
+        assert_eq!(resp.tracks[1].track_id.as_deref(), Some("bridge-cat"));
+        assert_eq!(resp.tracks[1].source, "catalog");
+
+        let latest = db::get_latest_version(&pool, &setlist_id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(latest.action.as_deref(), Some("bridge"));
+
+        // Already in the set, so nothing is left to bridge with
+        let result = bridge_setlist(&pool, &setlist_id, 1, 1).await;
+        assert!(matches!(result, Err(RefinementError::NotFound(_))));
+        let result = bridge_setlist(&pool, &setlist_id, 4, 1).await;
+        assert!(matches!(result, Err(RefinementError::InvalidRequest(_))));
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_malformed_response_retry_succeeds() {
         let pool = create_test_pool().await;
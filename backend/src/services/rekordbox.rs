@@ -0,0 +1,264 @@
+use std::collections::HashMap;
+
+use roxmltree::{Document, Node};
+
+use crate::services::camelot::normalize_key;
+use crate::services::import::{
+    location_path, rating_stars, ImportError, LibraryPlaylist, LibraryTrackRecord, ParsedLibrary,
+};
+
+// ---------------------------------------------------------------------------
+// Rekordbox XML (File > Export Collection in xml format)
+// ---------------------------------------------------------------------------
+
+/// `tracks.source` and import-id prefix for Rekordbox imports.
+pub const SOURCE: &str = "rekordbox";
+
+/// `NODE Type` of a playlist; folders are type 0.
+const PLAYLIST_NODE: &str = "1";
+
+/// Parse a `rekordbox.xml` collection export.
+///
+/// Tracks are keyed by their `TrackID`. Playlist entries that reference
+/// tracks by location (`KeyType="1"`) are resolved to the same keys.
+pub fn parse_library(xml: &str) -> Result<ParsedLibrary, ImportError> {
+    let doc = Document::parse(xml)
+        .map_err(|e| ImportError::InvalidFile(format!("Not a valid XML document: {e}")))?;
+
+    let root = doc.root_element();
+    if !root.has_tag_name("DJ_PLAYLISTS") {
+        return Err(ImportError::InvalidFile(
+            "Not a Rekordbox collection export (expected <DJ_PLAYLISTS>)".to_string(),
+        ));
+    }
+
+    let collection = child(root, "COLLECTION").ok_or_else(|| {
+        ImportError::InvalidFile("Rekordbox export has no <COLLECTION>".to_string())
+    })?;
+
+    let mut library = ParsedLibrary::default();
+    let mut keys_by_location: HashMap<String, String> = HashMap::new();
+    for node in collection.children().filter(|n| n.has_tag_name("TRACK")) {
+        let Some(track_id) = node.attribute("TrackID") else {
+            continue;
+        };
+        let Some(track) = parse_track(node) else {
+            continue;
+        };
+        if let Some(location) = node.attribute("Location") {
+            keys_by_location.insert(location.to_string(), track_id.to_string());
+        }
+        library.tracks.push((track_id.to_string(), track));
+    }
+
+    if let Some(root_node) = child(root, "PLAYLISTS").and_then(|p| child(p, "NODE")) {
+        // The top node is the unnamed "ROOT" folder.
+        for node in root_node.children().filter(|n| n.has_tag_name("NODE")) {
+            collect_playlists(node, "", &keys_by_location, &mut library.playlists);
+        }
+    }
+
+    Ok(library)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn parse_track(node: Node) -> Option<LibraryTrackRecord> {
+    let title = node.attribute("Name")?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    let text = |name: &str| {
+        node.attribute(name)
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+
+    Some(LibraryTrackRecord {
+        title: title.to_string(),
+        artist: text("Artist"),
+        album: text("Album"),
+        duration_ms: node
+            .attribute("TotalTime")
+            .and_then(|t| t.parse::<i64>().ok())
+            .filter(|&secs| secs > 0)
+            .map(|secs| secs * 1000),
+        bpm: node
+            .attribute("AverageBpm")
+            .and_then(|b| b.parse::<f64>().ok())
+            .filter(|&bpm| bpm > 0.0),
+        camelot_key: normalize_key(node.attribute("Tonality")),
+        energy: None,
+        rating: node.attribute("Rating").and_then(rating_stars),
+        colour: node.attribute("Colour").and_then(parse_colour),
+        file_location: node.attribute("Location").and_then(location_path),
+        source: SOURCE.to_string(),
+    })
+}
+
+/// `#RRGGBB` from Rekordbox's `0xRRGGBB` colour attribute.
+fn parse_colour(raw: &str) -> Option<String> {
+    let hex = raw
+        .trim()
+        .strip_prefix("0x")
+        .or_else(|| raw.trim().strip_prefix("0X"))?;
+    (hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()))
+        .then(|| format!("#{}", hex.to_ascii_uppercase()))
+}
+
+fn collect_playlists(
+    node: Node,
+    parent_path: &str,
+    keys_by_location: &HashMap<String, String>,
+    playlists: &mut Vec<LibraryPlaylist>,
+) {
+    let name = node
+        .attribute("Name")
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    let path = if parent_path.is_empty() {
+        name.clone()
+    } else {
+        format!("{parent_path}/{name}")
+    };
+
+    if node.attribute("Type") != Some(PLAYLIST_NODE) {
+        for sub in node.children().filter(|n| n.has_tag_name("NODE")) {
+            collect_playlists(sub, &path, keys_by_location, playlists);
+        }
+        return;
+    }
+
+    let by_location = node.attribute("KeyType") == Some("1");
+    let track_keys = node
+        .children()
+        .filter(|n| n.has_tag_name("TRACK"))
+        .filter_map(|n| n.attribute("Key"))
+        .map(|key| {
+            if by_location {
+                keys_by_location
+                    .get(key)
+                    .cloned()
+                    .unwrap_or_else(|| key.to_string())
+            } else {
+                key.to_string()
+            }
+        })
+        .collect();
+
+    playlists.push(LibraryPlaylist {
+        path,
+        name,
+        track_keys,
+    });
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXPORT: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<DJ_PLAYLISTS Version="1.0.0">
+  <PRODUCT Name="rekordbox" Version="6.7.4" Company="AlphaTheta"/>
+  <COLLECTION Entries="3">
+    <TRACK TrackID="101" Name="Desert Rose" Artist="Sting feat. Cheb Mami" Album="Brand New Day"
+           TotalTime="285" AverageBpm="102.00" Tonality="Am" Rating="204" Colour="0xFF007F"
+           Location="file://localhost/Users/dj/Music/Desert%20Rose.mp3">
+      <TEMPO Inizio="0.025" Bpm="102.00" Metro="4/4" Battito="1"/>
+    </TRACK>
+    <TRACK TrackID="102" Name="Yeke Yeke" Artist="Mory Kante" TotalTime="381"
+           AverageBpm="124.50" Tonality="F#m" Rating="0"
+           Location="file://localhost/C:/Music/Yeke%20Yeke.flac"/>
+    <TRACK TrackID="103" Name="" Artist="Nobody"/>
+  </COLLECTION>
+  <PLAYLISTS>
+    <NODE Type="0" Name="ROOT" Count="2">
+      <NODE Type="0" Name="Gigs" Count="1">
+        <NODE Name="Friday Warmup" Type="1" KeyType="0" Entries="2">
+          <TRACK Key="102"/>
+          <TRACK Key="101"/>
+        </NODE>
+      </NODE>
+      <NODE Name="By File" Type="1" KeyType="1" Entries="1">
+        <TRACK Key="file://localhost/Users/dj/Music/Desert%20Rose.mp3"/>
+      </NODE>
+    </NODE>
+  </PLAYLISTS>
+</DJ_PLAYLISTS>"#;
+
+    #[test]
+    fn test_parse_collection_tracks() {
+        let library = parse_library(EXPORT).unwrap();
+        assert_eq!(library.tracks.len(), 2, "nameless track is skipped");
+
+        let (key, rose) = &library.tracks[0];
+        assert_eq!(key, "101");
+        assert_eq!(rose.title, "Desert Rose");
+        assert_eq!(rose.artist.as_deref(), Some("Sting feat. Cheb Mami"));
+        assert_eq!(rose.album.as_deref(), Some("Brand New Day"));
+        assert_eq!(rose.duration_ms, Some(285_000));
+        assert_eq!(rose.bpm, Some(102.0));
+        assert_eq!(rose.camelot_key.as_deref(), Some("8A"));
+        assert_eq!(rose.rating, Some(4));
+        assert_eq!(rose.colour.as_deref(), Some("#FF007F"));
+        assert_eq!(
+            rose.file_location.as_deref(),
+            Some("/Users/dj/Music/Desert Rose.mp3")
+        );
+        assert_eq!(rose.source, "rekordbox");
+
+        let (_, yeke) = &library.tracks[1];
+        assert_eq!(yeke.camelot_key.as_deref(), Some("11A"));
+        assert_eq!(yeke.rating, None);
+        assert_eq!(yeke.colour, None);
+        assert_eq!(yeke.album, None);
+        assert_eq!(
+            yeke.file_location.as_deref(),
+            Some("C:/Music/Yeke Yeke.flac")
+        );
+    }
+
+    #[test]
+    fn test_parse_playlists_with_folder_paths() {
+        let library = parse_library(EXPORT).unwrap();
+        assert_eq!(library.playlists.len(), 2);
+
+        let warmup = &library.playlists[0];
+        assert_eq!(warmup.path, "Gigs/Friday Warmup");
+        assert_eq!(warmup.name, "Friday Warmup");
+        assert_eq!(warmup.track_keys, vec!["102", "101"]);
+    }
+
+    #[test]
+    fn test_parse_playlist_keyed_by_location() {
+        let library = parse_library(EXPORT).unwrap();
+        let by_file = &library.playlists[1];
+        assert_eq!(by_file.path, "By File");
+        assert_eq!(by_file.track_keys, vec!["101"]);
+    }
+
+    #[test]
+    fn test_parse_rejects_other_xml() {
+        let err = parse_library("<NML VERSION=\"19\"><COLLECTION/></NML>").unwrap_err();
+        assert!(matches!(err, ImportError::InvalidFile(_)));
+        assert!(matches!(
+            parse_library("not xml at all").unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_colour() {
+        assert_eq!(parse_colour("0x25fdE9").as_deref(), Some("#25FDE9"));
+        assert_eq!(parse_colour("FF007F"), None);
+        assert_eq!(parse_colour("0xFF00"), None);
+    }
+}
@@ -0,0 +1,300 @@
+use crate::services::camelot::normalize_key;
+use crate::services::import::{ImportError, LibraryPlaylist, LibraryTrackRecord, ParsedLibrary};
+
+// ---------------------------------------------------------------------------
+// Serato `_Serato_/database V2` and `_Serato_/Subcrates/*.crate`
+// ---------------------------------------------------------------------------
+
+/// `tracks.source` and import-id prefix for Serato imports.
+pub const SOURCE: &str = "serato";
+
+/// Separator Serato uses in crate file names for nested crates
+/// ("House%%Deep.crate" is "Deep" inside "House").
+const SUBCRATE_SEPARATOR: &str = "%%";
+
+/// Serato's default track colour, meaning "no colour set".
+const NO_COLOUR: u32 = 0xFF_FF_FF;
+
+/// A `.crate` file as uploaded: its file name and raw bytes.
+#[derive(Debug, Clone)]
+pub struct CrateFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+/// Parse Serato's `database V2` and any number of `.crate` files.
+///
+/// Both are sequences of tagged fields: a four-byte ASCII tag, a big-endian
+/// length, then the payload. `otrk` fields nest further fields; tags starting
+/// with `t` or `p` hold UTF-16BE text and tags starting with `u` a `u32`.
+/// Tracks are keyed by their file path (`pfil`), which is how crates
+/// reference them (`ptrk`).
+pub fn parse_library(database: &[u8], crates: &[CrateFile]) -> Result<ParsedLibrary, ImportError> {
+    let fields = read_fields(database)?;
+    if !fields.iter().any(|(tag, _)| tag == b"vrsn") {
+        return Err(ImportError::InvalidFile(
+            "Not a Serato database (no version header)".to_string(),
+        ));
+    }
+
+    let mut library = ParsedLibrary::default();
+    for (_, payload) in fields.iter().filter(|(tag, _)| tag == b"otrk") {
+        if let Some((key, track)) = parse_track(payload)? {
+            library.tracks.push((key, track));
+        }
+    }
+
+    for file in crates {
+        library.playlists.push(parse_crate(file)?);
+    }
+
+    Ok(library)
+}
+
+type Field<'a> = ([u8; 4], &'a [u8]);
+
+fn read_fields(mut data: &[u8]) -> Result<Vec<Field<'_>>, ImportError> {
+    let mut fields = Vec::new();
+    while !data.is_empty() {
+        if data.len() < 8 {
+            return Err(truncated());
+        }
+        let tag: [u8; 4] = data[..4].try_into().expect("slice of four bytes");
+        let len = u32::from_be_bytes(data[4..8].try_into().expect("slice of four bytes")) as usize;
+        let payload = data.get(8..8 + len).ok_or_else(truncated)?;
+        fields.push((tag, payload));
+        data = &data[8 + len..];
+    }
+    Ok(fields)
+}
+
+fn truncated() -> ImportError {
+    ImportError::InvalidFile("Serato file is truncated or corrupt".to_string())
+}
+
+fn text(payload: &[u8]) -> Option<String> {
+    let units: Vec<u16> = payload
+        .chunks_exact(2)
+        .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+        .collect();
+    let decoded = String::from_utf16_lossy(&units);
+    let trimmed = decoded.trim_matches(char::from(0)).trim();
+    (!trimmed.is_empty()).then(|| trimmed.to_string())
+}
+
+fn parse_track(payload: &[u8]) -> Result<Option<(String, LibraryTrackRecord)>, ImportError> {
+    let mut track = LibraryTrackRecord {
+        source: SOURCE.to_string(),
+        ..Default::default()
+    };
+    let mut path = None;
+
+    for (tag, value) in read_fields(payload)? {
+        match &tag {
+            b"pfil" => path = text(value),
+            b"tsng" => track.title = text(value).unwrap_or_default(),
+            b"tart" => track.artist = text(value),
+            b"talb" => track.album = text(value),
+            b"tlen" => track.duration_ms = text(value).as_deref().and_then(parse_length),
+            b"tbpm" => {
+                track.bpm = text(value)
+                    .and_then(|b| b.parse::<f64>().ok())
+                    .filter(|&bpm| bpm > 0.0)
+            }
+            b"tkey" => track.camelot_key = normalize_key(text(value).as_deref()),
+            b"ulbl" if value.len() == 4 => {
+                let rgb =
+                    u32::from_be_bytes(value.try_into().expect("slice of four bytes")) & 0xFF_FF_FF;
+                track.colour = (rgb != NO_COLOUR).then(|| format!("#{rgb:06X}"));
+            }
+            _ => {}
+        }
+    }
+
+    let Some(path) = path else {
+        return Ok(None);
+    };
+    if track.title.is_empty() {
+        // Untagged files show under their file name in Serato.
+        let file_name = path.rsplit('/').next().unwrap_or(&path);
+        track.title = file_name
+            .rsplit_once('.')
+            .map_or(file_name, |(stem, _)| stem)
+            .to_string();
+    }
+    track.file_location = Some(file_path(&path));
+    Ok(Some((path, track)))
+}
+
+/// Milliseconds from Serato's length text ("05:23.71", "5:23").
+fn parse_length(raw: &str) -> Option<i64> {
+    let (minutes, seconds) = raw.trim().split_once(':')?;
+    let minutes: f64 = minutes.parse().ok()?;
+    let seconds: f64 = seconds.parse().ok()?;
+    let ms = ((minutes * 60.0 + seconds) * 1000.0).round() as i64;
+    (ms > 0).then_some(ms)
+}
+
+/// Serato stores paths relative to the drive holding `_Serato_`, without the
+/// leading slash on macOS ("Users/dj/Music/a.mp3").
+fn file_path(path: &str) -> String {
+    let bytes = path.as_bytes();
+    if path.starts_with('/') || (bytes.len() > 1 && bytes[1] == b':') {
+        path.to_string()
+    } else {
+        format!("/{path}")
+    }
+}
+
+fn parse_crate(file: &CrateFile) -> Result<LibraryPlaylist, ImportError> {
+    let stem = file.name.rsplit(['/', '\\']).next().unwrap_or(&file.name);
+    let stem = stem.strip_suffix(".crate").unwrap_or(stem);
+    let parts: Vec<&str> = stem.split(SUBCRATE_SEPARATOR).collect();
+    let name = parts.last().copied().unwrap_or_default().to_string();
+    if name.is_empty() {
+        return Err(ImportError::InvalidFile(format!(
+            "Crate file '{}' has no name",
+            file.name
+        )));
+    }
+
+    let mut track_keys = Vec::new();
+    for (tag, payload) in read_fields(&file.data)? {
+        if &tag != b"otrk" {
+            continue;
+        }
+        let path = read_fields(payload)?
+            .into_iter()
+            .find(|(tag, _)| tag == b"ptrk")
+            .and_then(|(_, value)| text(value));
+        if let Some(path) = path {
+            track_keys.push(path);
+        }
+    }
+
+    Ok(LibraryPlaylist {
+        path: parts.join("/"),
+        name,
+        track_keys,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field(tag: &[u8; 4], payload: &[u8]) -> Vec<u8> {
+        let mut out = tag.to_vec();
+        out.extend_from_slice(&(payload.len() as u32).to_be_bytes());
+        out.extend_from_slice(payload);
+        out
+    }
+
+    fn utf16(s: &str) -> Vec<u8> {
+        s.encode_utf16().flat_map(u16::to_be_bytes).collect()
+    }
+
+    fn text_field(tag: &[u8; 4], s: &str) -> Vec<u8> {
+        field(tag, &utf16(s))
+    }
+
+    fn database() -> Vec<u8> {
+        let mut yeke = text_field(b"pfil", "Users/dj/Music/yeke.mp3");
+        yeke.extend(text_field(b"tsng", "Yeke Yeke"));
+        yeke.extend(text_field(b"tart", "Mory Kante"));
+        yeke.extend(text_field(b"talb", "Akwaba Beach"));
+        yeke.extend(text_field(b"tlen", "06:20.73"));
+        yeke.extend(text_field(b"tbpm", "124.00"));
+        yeke.extend(text_field(b"tkey", "F#m"));
+        yeke.extend(field(b"ulbl", &0x00FF_99FFu32.to_be_bytes()));
+        yeke.extend(field(b"bhrt", &[1]));
+
+        let mut untagged = text_field(b"pfil", "Users/dj/Music/rip 04.wav");
+        untagged.extend(field(b"ulbl", &NO_COLOUR.to_be_bytes()));
+
+        let mut db = text_field(b"vrsn", "2.0/Serato Scratch LIVE Database");
+        db.extend(field(b"otrk", &yeke));
+        db.extend(field(b"otrk", &untagged));
+        db
+    }
+
+    fn crate_file(name: &str, paths: &[&str]) -> CrateFile {
+        let mut data = text_field(b"vrsn", "1.0/Serato ScratchLive Crate");
+        data.extend(field(b"osrt", &text_field(b"tvcn", "song")));
+        for path in paths {
+            data.extend(field(b"otrk", &text_field(b"ptrk", path)));
+        }
+        CrateFile {
+            name: name.to_string(),
+            data,
+        }
+    }
+
+    #[test]
+    fn test_parse_database_tracks() {
+        let library = parse_library(&database(), &[]).unwrap();
+        assert_eq!(library.tracks.len(), 2);
+
+        let (key, yeke) = &library.tracks[0];
+        assert_eq!(key, "Users/dj/Music/yeke.mp3");
+        assert_eq!(yeke.title, "Yeke Yeke");
+        assert_eq!(yeke.artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(yeke.album.as_deref(), Some("Akwaba Beach"));
+        assert_eq!(yeke.duration_ms, Some(380_730));
+        assert_eq!(yeke.bpm, Some(124.0));
+        assert_eq!(yeke.camelot_key.as_deref(), Some("11A"));
+        assert_eq!(yeke.colour.as_deref(), Some("#FF99FF"));
+        assert_eq!(
+            yeke.file_location.as_deref(),
+            Some("/Users/dj/Music/yeke.mp3")
+        );
+        assert_eq!(yeke.analysed_by(), Some("serato"));
+
+        let (_, untagged) = &library.tracks[1];
+        assert_eq!(untagged.title, "rip 04");
+        assert_eq!(untagged.colour, None);
+        assert_eq!(untagged.analysed_by(), None);
+    }
+
+    #[test]
+    fn test_parse_nested_crate() {
+        let crates = [crate_file(
+            "House%%Deep.crate",
+            &["Users/dj/Music/yeke.mp3", "Users/dj/Music/gone.mp3"],
+        )];
+        let library = parse_library(&database(), &crates).unwrap();
+
+        let deep = &library.playlists[0];
+        assert_eq!(deep.path, "House/Deep");
+        assert_eq!(deep.name, "Deep");
+        assert_eq!(
+            deep.track_keys,
+            vec!["Users/dj/Music/yeke.mp3", "Users/dj/Music/gone.mp3"]
+        );
+    }
+
+    #[test]
+    fn test_parse_rejects_truncated_database() {
+        let mut db = database();
+        db.truncate(db.len() - 3);
+        assert!(matches!(
+            parse_library(&db, &[]).unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+        assert!(matches!(
+            parse_library(b"<NML/>", &[]).unwrap_err(),
+            ImportError::InvalidFile(_)
+        ));
+    }
+
+    #[test]
+    fn test_parse_length() {
+        assert_eq!(parse_length("05:23.71"), Some(323_710));
+        assert_eq!(parse_length("5:23"), Some(323_000));
+        assert_eq!(parse_length("unknown"), None);
+    }
+}
@@ -1,16 +1,32 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
 use axum::http::StatusCode;
 use axum::response::{IntoResponse, Response};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
 use crate::api::claude::{
     build_enhanced_system_prompt, build_enhanced_user_prompt, strip_markdown_fences,
-    ClaudeClientTrait, ClaudeError, LlmSetlistResponse,
+    ClaudeClientTrait, ClaudeError, LlmSetlistResponse, LlmTrackEntry,
 };
 use crate::db::imports as db_imports;
 use crate::db::models::{SetlistRow, SetlistTrackRow, TrackRow};
 use crate::db::setlists as db;
-use crate::services::arrangement::{self, ArrangementTrack};
-use crate::services::camelot::{parse_camelot, EnergyProfile};
+use crate::services::arrangement::{
+    self, ArrangementError, ArrangementOptions, ArrangementResult, ArrangementTrack,
+    PositionConstraint,
+};
+use crate::services::camelot::{
+    bpm_in_range, bpm_match, bpm_match_with_bands, classify_transition, display_key, effective_key,
+    normalize_key, parse_camelot, pitch_percent, transition_score_with_profile, EnergyCurve,
+    EnergyProfile, KeyNotation, KeyPath, ScoringProfile, TempoRelation, TransitionType,
+};
+use crate::services::constraints::{self, ResolvedConstraints, TrackConstraints};
+use crate::services::lineup;
+use crate::services::lint::{self, LintOptions, LintReport, LintTrack};
+use crate::services::solver::{self, Solver, SolverConfig};
+use crate::services::technique::{self, MixPoint};
+use crate::services::tracklist::{self, TracklistEntry, TracklistScore, MAX_TRACKLIST_ENTRIES};
 
 // ---------------------------------------------------------------------------
 // Error
@@ -45,9 +61,18 @@ pub enum SetlistError {
     #[error("Invalid energy profile: {0}")]
     InvalidEnergyProfile(String),
 
+    #[error("Invalid energy curve: {0}")]
+    InvalidEnergyCurve(String),
+
+    #[error("Invalid key path: {0}")]
+    InvalidKeyPath(String),
+
     #[error("Invalid BPM range: {0}")]
     InvalidBpmRange(String),
 
+    #[error("Infeasible constraints: {0}")]
+    InfeasibleConstraints(String),
+
     #[error("Playlist not found: {0}")]
     PlaylistNotFound(String),
 
@@ -91,9 +116,20 @@ impl IntoResponse for SetlistError {
             SetlistError::InvalidEnergyProfile(m) => {
                 (StatusCode::BAD_REQUEST, "INVALID_ENERGY_PROFILE", m.clone())
             }
+            SetlistError::InvalidEnergyCurve(m) => {
+                (StatusCode::BAD_REQUEST, "INVALID_ENERGY_CURVE", m.clone())
+            }
+            SetlistError::InvalidKeyPath(m) => {
+                (StatusCode::BAD_REQUEST, "INVALID_KEY_PATH", m.clone())
+            }
             SetlistError::InvalidBpmRange(m) => {
                 (StatusCode::BAD_REQUEST, "INVALID_BPM_RANGE", m.clone())
             }
+            SetlistError::InfeasibleConstraints(m) => (
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "INFEASIBLE_CONSTRAINTS",
+                m.clone(),
+            ),
             SetlistError::PlaylistNotFound(m) => {
                 (StatusCode::NOT_FOUND, "PLAYLIST_NOT_FOUND", m.clone())
             }
@@ -141,6 +177,19 @@ pub struct ScoreBreakdownResponse {
     pub key_compatibility: f64,
     pub bpm_continuity: f64,
     pub energy_arc: f64,
+    /// How closely keys follow the requested key path; absent without one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_journey: Option<f64>,
+    /// `key_transitions[i]` is the move from position `i + 1` to `i + 2`;
+    /// each track also carries its own label in `key_transition`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub key_transitions: Vec<Option<TransitionType>>,
+    /// `tempo_relations[i]` is how position `i + 2` is beatmatched to `i + 1`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tempo_relations: Vec<Option<TempoRelation>>,
+    /// `pitch_adjustments[i]` is the pitch-fader % applied to position `i + 2`.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pitch_adjustments: Vec<Option<f64>>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -158,42 +207,147 @@ pub struct SetlistResponse {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub energy_profile: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    pub energy_curve: Option<EnergyCurve>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_path: Option<KeyPath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub catalog_percentage: Option<f64>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub catalog_warning: Option<String>,
     #[serde(skip_serializing_if = "Vec::is_empty")]
     pub bpm_warnings: Vec<BpmWarning>,
+    /// Tracks by the same artist closer together than the user's
+    /// artist separation allows.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub artist_warnings: Vec<ArtistWarning>,
+    /// Which solver ordered the tracks; only set by arrange.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arrangement: Option<ArrangementReport>,
+    /// Sum of track durations; unknown durations count as
+    /// [`DEFAULT_TRACK_DURATION_MS`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_runtime_ms: Option<i64>,
+    /// Requested set length, when generated for a time slot.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target_duration_ms: Option<i64>,
+    /// Set when the runtime misses the target by more than the tolerance.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_warning: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArrangementReport {
+    pub solver: Solver,
+    /// Harmonic flow of the greedy + 2-opt order the solver started from.
+    pub greedy_flow_score: f64,
+    /// Harmonic flow points gained over `greedy_flow_score`; negative when
+    /// the solver gave up flow for energy, key path or artist spacing.
+    pub improvement_over_greedy: f64,
+    /// The time budget ran out before the solver finished.
+    pub timed_out: bool,
+}
+
+impl From<&ArrangementResult> for ArrangementReport {
+    fn from(r: &ArrangementResult) -> Self {
+        Self {
+            solver: r.solver,
+            greedy_flow_score: r.greedy_flow_score,
+            improvement_over_greedy: r.improvement_over_greedy,
+            timed_out: r.timed_out,
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize)]
 pub struct BpmWarning {
     pub from_position: i32,
     pub to_position: i32,
+    /// Raw BPM change between the two tracks.
     pub bpm_delta: f64,
+    /// Closest tempo relation (e.g., double-time) used to judge the jump.
+    pub tempo_relation: TempoRelation,
+    /// BPM change after applying `tempo_relation`; this is what gets flagged.
+    pub effective_bpm_delta: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ArtistWarning {
+    pub from_position: i32,
+    pub to_position: i32,
+    /// The artist both tracks credit, lowercased.
+    pub artist: String,
+    /// Distance in positions; 1 means back to back.
+    pub gap: i32,
 }
 
 // ---------------------------------------------------------------------------
 // Request types
 // ---------------------------------------------------------------------------
 
+#[derive(Clone)]
 pub struct GenerateSetlistRequest {
     pub user_id: String,
     pub prompt: String,
     pub track_count: Option<u32>,
+    /// Set length to aim for instead of a fixed `track_count`.
+    pub target_duration_minutes: Option<u32>,
     pub energy_profile: Option<EnergyProfile>,
+    /// Custom energy curve; mutually exclusive with `energy_profile`.
+    pub energy_curve: Option<EnergyCurve>,
+    /// Target harmonic journey over the set.
+    pub key_path: Option<KeyPath>,
     pub source_playlist_id: Option<String>,
+    /// Restrict the catalog to tracks in one of the user's crates.
+    pub source_crate_id: Option<String>,
     pub seed_tracklist: Option<String>,
     pub creative_mode: Option<bool>,
     pub bpm_range: Option<BpmRange>,
     pub verify: bool,
     pub name: Option<String>,
+    /// Must-include and exclude lists.
+    pub constraints: TrackConstraints,
 }
 
+#[derive(Clone)]
 pub struct BpmRange {
     pub min: f64,
     pub max: f64,
 }
 
+/// Extra inputs when a setlist is generated as one part of a larger plan,
+/// such as a slot in a [`crate::services::lineup`].
+#[derive(Default)]
+pub struct GenerationContext {
+    /// Catalog track ids that must not appear (already played elsewhere).
+    pub exclude_track_ids: HashSet<String>,
+    /// [`lineup::track_identity`] of tracks that must not appear; catches
+    /// suggestions, which have no track id.
+    pub exclude_tracks: HashSet<String>,
+    /// Appended to the user prompt.
+    pub instructions: Option<String>,
+    /// Track playing just before this set starts. The opener is chosen to
+    /// mix smoothly out of it.
+    pub handoff_from: Option<SetlistTrackResponse>,
+}
+
+impl GenerationContext {
+    fn excludes(&self, track_id: Option<&str>, artist: &str, title: &str) -> bool {
+        track_id.is_some_and(|id| self.exclude_track_ids.contains(id))
+            || self
+                .exclude_tracks
+                .contains(&lineup::track_identity(artist, title))
+    }
+}
+
+/// Placement rule for one track when arranging a saved setlist.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TrackConstraint {
+    /// Current 1-based position of the track the rule applies to.
+    pub track_position: i32,
+    #[serde(flatten)]
+    pub rule: PositionConstraint,
+}
+
 #[derive(Debug, Clone, Serialize)]
 pub struct SetlistTrackResponse {
     pub position: i32,
@@ -205,6 +359,23 @@ pub struct SetlistTrackResponse {
     pub energy: Option<f64>,
     pub transition_note: Option<String>,
     pub transition_score: Option<f64>,
+    /// Kind of key move from the previous track (e.g. "energy-boost").
+    /// Derived from the `camelot` of the two neighbours, so it is recomputed
+    /// whenever a setlist is read or reordered rather than stored.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub key_transition: Option<TransitionType>,
+    /// How this track's tempo relates to the previous one (e.g. "double-time").
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tempo_relation: Option<TempoRelation>,
+    /// Pitch-fader % needed to beatmatch this track to the previous one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pitch_percent: Option<f64>,
+    /// Catalog duration, or the LLM's estimate for suggestions.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub duration_ms: Option<i32>,
+    /// When this track starts, counted from the start of the set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time_ms: Option<i64>,
     pub original_position: i32,
     pub source: String,
     pub track_id: Option<String>,
@@ -226,6 +397,11 @@ impl From<SetlistTrackRow> for SetlistTrackResponse {
             energy: row.energy,
             transition_note: row.transition_note,
             transition_score: row.transition_score,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: row.duration_ms,
+            start_time_ms: None,
             original_position: row.original_position,
             source: row.source,
             track_id: row.track_id,
@@ -243,7 +419,7 @@ impl From<SetlistTrackRow> for SetlistTrackResponse {
 
 fn serialize_catalog(tracks: &[TrackRow]) -> String {
     let mut lines = Vec::with_capacity(tracks.len() + 1);
-    lines.push("ID | Title - Artist | BPM | Key | Energy".to_string());
+    lines.push("ID | Title - Artist | BPM | Key | Energy | Duration".to_string());
     for t in tracks {
         let artist = t.artist.as_deref().unwrap_or("Unknown");
         let bpm = t
@@ -255,9 +431,13 @@ fn serialize_catalog(tracks: &[TrackRow]) -> String {
             .energy
             .map(|e| format!("{e:.0}"))
             .unwrap_or_else(|| "--".to_string());
+        let duration = t
+            .duration_ms
+            .map(|ms| format_runtime(ms as i64))
+            .unwrap_or_else(|| "--".to_string());
         lines.push(format!(
-            "{} | {} - {} | {} | {} | {}",
-            t.id, t.title, artist, bpm, key, energy
+            "{} | {} - {} | {} | {} | {} | {}",
+            t.id, t.title, artist, bpm, key, energy, duration
         ));
     }
     lines.join("\n")
@@ -272,6 +452,13 @@ const MAX_PROMPT_LEN: usize = 2000;
 const DEFAULT_TRACK_COUNT: u32 = 10;
 const MIN_TRACK_COUNT: u32 = 1;
 const MAX_TRACK_COUNT: u32 = 50;
+pub const MIN_TARGET_DURATION_MINUTES: u32 = 10;
+pub const MAX_TARGET_DURATION_MINUTES: u32 = 300;
+/// Assumed length of a track whose duration is unknown (a typical club edit).
+pub const DEFAULT_TRACK_DURATION_MS: i64 = 6 * 60 * 1000;
+/// A set within this share of its target (or 2 minutes, if larger) is on time.
+const DURATION_TOLERANCE: f64 = 0.05;
+const MIN_DURATION_TOLERANCE_MS: i64 = 2 * 60 * 1000;
 /// Maximum setlist generations per user per day.
 const DAILY_GENERATION_CAP: i64 = 50;
 
@@ -288,12 +475,17 @@ pub async fn generate_setlist(
         prompt: prompt.to_string(),
         track_count,
         energy_profile: None,
+        energy_curve: None,
+        key_path: None,
         source_playlist_id: None,
+        source_crate_id: None,
         seed_tracklist: None,
         creative_mode: None,
         bpm_range: None,
         verify: false,
         name: None,
+        target_duration_minutes: None,
+        constraints: TrackConstraints::default(),
     };
     generate_setlist_from_request(pool, claude, req).await
 }
@@ -303,6 +495,70 @@ pub async fn generate_setlist_from_request(
     claude: &dyn ClaudeClientTrait,
     req: GenerateSetlistRequest,
 ) -> Result<SetlistResponse, SetlistError> {
+    generate_setlist_with_context(pool, claude, req, &GenerationContext::default()).await
+}
+
+/// LLM entries that can go into the setlist: those with a title and artist
+/// that the wider plan in `context` has not already used. Fails when more
+/// than half the entries lack a title or artist.
+fn usable_entries(
+    tracks: &[LlmTrackEntry],
+    context: &GenerationContext,
+) -> Result<Vec<LlmTrackEntry>, SetlistError> {
+    // M3: Filter out entries with missing title or artist, log warnings
+    let total_entries = tracks.len();
+    let valid_entries: Vec<_> = tracks
+        .iter()
+        .filter(|entry| {
+            if entry.title.is_empty() || entry.artist.is_empty() {
+                tracing::warn!(
+                    "Skipping LLM track entry with missing title='{}' or artist='{}'",
+                    entry.title,
+                    entry.artist
+                );
+                false
+            } else {
+                true
+            }
+        })
+        .collect();
+
+    let skipped_count = total_entries - valid_entries.len();
+    if total_entries > 0 && skipped_count > total_entries / 2 {
+        return Err(SetlistError::GenerationFailed(format!(
+            "Too many invalid entries from LLM: {skipped_count}/{total_entries} were missing title or artist"
+        )));
+    }
+
+    // Tracks the wider plan has already used are dropped, not reclassified
+    Ok(valid_entries
+        .into_iter()
+        .filter(|entry| {
+            let used = context.excludes(entry.track_id.as_deref(), &entry.artist, &entry.title);
+            if used {
+                tracing::warn!(
+                    "Skipping '{}' by '{}': already used elsewhere in the plan",
+                    entry.title,
+                    entry.artist
+                );
+            }
+            !used
+        })
+        .cloned()
+        .collect())
+}
+
+/// A generation request after the checks that need no database.
+pub(crate) struct ValidatedRequest {
+    pub prompt: String,
+    pub count: u32,
+    pub target_duration_ms: Option<i64>,
+}
+
+/// Check prompt, size, energy target, key path, source and BPM range.
+pub(crate) fn validate_generation_request(
+    req: &GenerateSetlistRequest,
+) -> Result<ValidatedRequest, SetlistError> {
     // Validate prompt
     let prompt = req.prompt.trim().to_string();
     if prompt.is_empty() {
@@ -328,6 +584,30 @@ pub async fn generate_setlist_from_request(
         None => DEFAULT_TRACK_COUNT,
     };
 
+    let target_duration_ms = match req.target_duration_minutes {
+        Some(_) if req.track_count.is_some() => {
+            return Err(SetlistError::InvalidRequest(
+                "track_count and target_duration_minutes are mutually exclusive".to_string(),
+            ));
+        }
+        Some(m) if !(MIN_TARGET_DURATION_MINUTES..=MAX_TARGET_DURATION_MINUTES).contains(&m) => {
+            return Err(SetlistError::InvalidRequest(format!(
+                "target_duration_minutes must be between {MIN_TARGET_DURATION_MINUTES} and {MAX_TARGET_DURATION_MINUTES}, got {m}"
+            )));
+        }
+        Some(m) => Some(m as i64 * 60 * 1000),
+        None => None,
+    };
+
+    validate_energy_target(req.energy_profile.as_ref(), req.energy_curve.as_ref())?;
+    validate_key_path(req.key_path.as_ref())?;
+
+    if req.source_playlist_id.is_some() && req.source_crate_id.is_some() {
+        return Err(SetlistError::InvalidRequest(
+            "source_playlist_id and source_crate_id are mutually exclusive".to_string(),
+        ));
+    }
+
     // Validate BPM range
     if let Some(ref bpm_range) = req.bpm_range {
         if bpm_range.min < 60.0 || bpm_range.max > 200.0 || bpm_range.min > bpm_range.max {
@@ -338,18 +618,23 @@ pub async fn generate_setlist_from_request(
         }
     }
 
-    // DF-03: Check daily generation cap before calling LLM
-    let daily_gen_count = crate::db::tracks::get_daily_generation_count(pool, &req.user_id)
-        .await
-        .map_err(|e| SetlistError::Database(e.to_string()))?;
-    if daily_gen_count >= DAILY_GENERATION_CAP {
-        return Err(SetlistError::GenerationLimitExceeded(format!(
-            "Daily generation limit of {DAILY_GENERATION_CAP} reached ({daily_gen_count} used today)"
-        )));
-    }
+    Ok(ValidatedRequest {
+        prompt,
+        count,
+        target_duration_ms,
+    })
+}
 
-    // Catalog loading: source_playlist_id filtering or full catalog
-    let mut extra_notes: Vec<String> = Vec::new();
+/// Tracks a generation may draw from: the source playlist or crate (or the
+/// whole catalog), narrowed by BPM range and exclusions, plus must-include
+/// catalog tracks. Notes for the user are pushed to `extra_notes`.
+pub(crate) async fn load_generation_catalog(
+    pool: &sqlx::PgPool,
+    req: &GenerateSetlistRequest,
+    context: &GenerationContext,
+    track_constraints: &ResolvedConstraints,
+    extra_notes: &mut Vec<String>,
+) -> Result<Vec<TrackRow>, SetlistError> {
     let catalog = if let Some(ref playlist_id) = req.source_playlist_id {
         // Verify the import exists
         let import = db_imports::get_import(pool, playlist_id)
@@ -386,24 +671,154 @@ pub async fn generate_setlist_from_request(
             }
             playlist_tracks
         }
+    } else if let Some(ref crate_id) = req.source_crate_id {
+        load_crate_catalog(pool, &req.user_id, crate_id, extra_notes).await?
     } else {
         db::load_catalog_tracks(pool).await?
     };
 
+    // BPM range: keep tracks that fit directly or at half/double tempo.
+    // Tracks without a BPM stay in; the LLM can still judge them by genre.
+    let catalog = match req.bpm_range {
+        Some(ref range) => filter_catalog_by_bpm_range(catalog, range),
+        None => catalog,
+    };
+    let mut catalog: Vec<TrackRow> = catalog
+        .into_iter()
+        .filter(|t| !context.exclude_track_ids.contains(&t.id) && track_constraints.admits(t))
+        .collect();
+    // Must-include tracks stay available even when a filter above dropped them
+    for required in &track_constraints.required {
+        if let Some(ref row) = required.row {
+            if !catalog.iter().any(|t| t.id == row.id) {
+                catalog.push(row.clone());
+            }
+        }
+    }
+    Ok(catalog)
+}
+
+/// Catalog tracks that are in one of the user's crates, matched by artist
+/// and title. Crate entries not in the catalog are left out with a note.
+async fn load_crate_catalog(
+    pool: &sqlx::PgPool,
+    user_id: &str,
+    crate_id: &str,
+    extra_notes: &mut Vec<String>,
+) -> Result<Vec<TrackRow>, SetlistError> {
+    let owned = crate::db::crates::get_crate(pool, crate_id)
+        .await?
+        .is_some_and(|c| c.user_id == user_id);
+    if !owned {
+        return Err(SetlistError::NotFound(format!(
+            "Crate '{crate_id}' not found"
+        )));
+    }
+    let wanted: HashSet<String> = crate::db::crates::get_crate_tracks(pool, crate_id)
+        .await?
+        .iter()
+        .map(|t| lineup::track_identity(&t.artist, &t.title))
+        .collect();
+    let tracks: Vec<TrackRow> = db::load_catalog_tracks(pool)
+        .await?
+        .into_iter()
+        .filter(|t| {
+            wanted.contains(&lineup::track_identity(
+                t.artist.as_deref().unwrap_or(""),
+                &t.title,
+            ))
+        })
+        .collect();
+    if tracks.is_empty() {
+        return Err(SetlistError::EmptyCatalog);
+    }
+    let missing = wanted.len().saturating_sub(tracks.len());
+    if missing > 0 {
+        extra_notes.push(format!(
+            "{missing} crate tracks are not in your catalog and were left out."
+        ));
+    }
+    Ok(tracks)
+}
+
+/// [`generate_setlist_from_request`] as part of a larger plan: tracks in
+/// `context` are kept out of the set, and the opener is picked to follow
+/// `context.handoff_from`.
+pub async fn generate_setlist_with_context(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: GenerateSetlistRequest,
+    context: &GenerationContext,
+) -> Result<SetlistResponse, SetlistError> {
+    let ValidatedRequest {
+        prompt,
+        count,
+        target_duration_ms,
+    } = validate_generation_request(&req)?;
+
+    let track_constraints = constraints::resolve(pool, &req.user_id, &req.constraints).await?;
+
+    // DF-03: Check daily generation cap before calling LLM
+    check_generation_allowance(pool, &req.user_id, 1).await?;
+
+    let settings = crate::db::settings::get_mix_settings(pool, &req.user_id).await?;
+    let key_lock = settings.key_lock;
+    let scoring = settings.scoring;
+
+    let mut extra_notes: Vec<String> = Vec::new();
+    let catalog =
+        load_generation_catalog(pool, &req, context, &track_constraints, &mut extra_notes).await?;
+
     // Empty catalog is OK — LLM will generate purely from suggestions
 
     // Build catalog IDs set for validation
     let catalog_ids: std::collections::HashSet<String> =
         catalog.iter().map(|t| t.id.clone()).collect();
+    let catalog_durations: std::collections::HashMap<&str, i32> = catalog
+        .iter()
+        .filter_map(|t| t.duration_ms.map(|d| (t.id.as_str(), d)))
+        .collect();
+
+    // A time slot becomes a track count from the catalog's typical length
+    let count = match target_duration_ms {
+        Some(target) => estimate_track_count(&catalog, target),
+        None => count,
+    };
+    if track_constraints.required.len() > count as usize {
+        return Err(SetlistError::InvalidRequest(format!(
+            "{} must_include tracks do not fit in a {count}-track setlist",
+            track_constraints.required.len()
+        )));
+    }
 
     // Build prompts using enhanced prompt builders
     let catalog_text = serialize_catalog(&catalog);
     let creative_mode = req.creative_mode.unwrap_or(false);
     let bpm_range_tuple = req.bpm_range.as_ref().map(|r| (r.min, r.max));
 
-    let system_blocks =
-        build_enhanced_system_prompt(&catalog_text, req.energy_profile.as_ref(), creative_mode);
-    let user_text = format!("Create a setlist of {count} tracks based on this prompt: {prompt}");
+    let system_blocks = build_enhanced_system_prompt(
+        &catalog_text,
+        req.energy_profile.as_ref(),
+        req.energy_curve.as_ref(),
+        req.key_path.as_ref(),
+        creative_mode,
+    );
+    let user_text = match req.target_duration_minutes {
+        Some(minutes) => format!(
+            "Create a setlist of about {count} tracks lasting about {minutes} minutes in total \
+             (catalog durations are listed; give duration_seconds for every track, estimating \
+             for suggestions) based on this prompt: {prompt}"
+        ),
+        None => format!("Create a setlist of {count} tracks based on this prompt: {prompt}"),
+    };
+    let user_text = match context.instructions {
+        Some(ref extra) => format!("{user_text}\n\n{extra}"),
+        None => user_text,
+    };
+    let user_text = match track_constraints.prompt_section() {
+        Some(section) => format!("{user_text}\n\n{section}"),
+        None => user_text,
+    };
     let user_blocks =
         build_enhanced_user_prompt(&user_text, req.seed_tracklist.as_deref(), bpm_range_tuple);
 
@@ -415,7 +830,7 @@ pub async fn generate_setlist_from_request(
 
     // Parse response (with retry on failure)
     let cleaned = strip_markdown_fences(&raw_response);
-    let llm_response: LlmSetlistResponse = match serde_json::from_str(cleaned) {
+    let mut llm_response: LlmSetlistResponse = match serde_json::from_str(cleaned) {
         Ok(r) => r,
         Err(first_err) => {
             // Retry with stricter prompt
@@ -429,7 +844,12 @@ pub async fn generate_setlist_from_request(
                 bpm_range_tuple,
             );
             let (retry_response, _) = claude
-                .generate_with_blocks(system_blocks, retry_user_blocks, DEFAULT_MODEL, 4096)
+                .generate_with_blocks(
+                    system_blocks.clone(),
+                    retry_user_blocks,
+                    DEFAULT_MODEL,
+                    4096,
+                )
                 .await
                 .map_err(SetlistError::from)?;
 
@@ -442,30 +862,48 @@ pub async fn generate_setlist_from_request(
         }
     };
 
-    // M3: Filter out entries with missing title or artist, log warnings
-    let total_entries = llm_response.tracks.len();
-    let valid_entries: Vec<_> = llm_response
-        .tracks
-        .iter()
-        .filter(|entry| {
-            if entry.title.is_empty() || entry.artist.is_empty() {
-                tracing::warn!(
-                    "Skipping LLM track entry with missing title='{}' or artist='{}'",
-                    entry.title,
-                    entry.artist
-                );
-                false
-            } else {
-                true
-            }
-        })
-        .collect();
+    let mut valid_entries = usable_entries(&llm_response.tracks, context)?;
+    // Order the LLM wrote its transition notes for
+    let mut llm_order = entry_identities(&valid_entries);
 
-    let skipped_count = total_entries - valid_entries.len();
-    if total_entries > 0 && skipped_count > total_entries / 2 {
-        return Err(SetlistError::GenerationFailed(format!(
-            "Too many invalid entries from LLM: {skipped_count}/{total_entries} were missing title or artist"
-        )));
+    // Hard constraints: ask once for a corrected set, then repair whatever
+    // is still wrong from the catalog
+    let violations = track_constraints.violations(&valid_entries);
+    if !violations.is_empty() {
+        tracing::warn!(
+            "Setlist broke {} constraint(s), re-asking",
+            violations.len()
+        );
+        let repair_text = format!(
+            "{user_text}\n\nYour previous setlist broke these rules:\n- {}\nReturn the full corrected setlist as JSON.",
+            violations.join("\n- ")
+        );
+        let repair_blocks = build_enhanced_user_prompt(
+            &repair_text,
+            req.seed_tracklist.as_deref(),
+            bpm_range_tuple,
+        );
+        let corrected = claude
+            .generate_with_blocks(system_blocks, repair_blocks, DEFAULT_MODEL, 4096)
+            .await
+            .ok()
+            .and_then(|(raw, _)| {
+                serde_json::from_str::<LlmSetlistResponse>(strip_markdown_fences(&raw)).ok()
+            })
+            .and_then(|r| Some((usable_entries(&r.tracks, context).ok()?, r.notes)));
+        if let Some((entries, notes)) = corrected {
+            valid_entries = entries;
+            llm_response.notes = notes;
+            llm_order = entry_identities(&valid_entries);
+        }
+        let repairs = track_constraints.repair(
+            &mut valid_entries,
+            &catalog,
+            count as usize,
+            key_lock,
+            &scoring,
+        );
+        extra_notes.extend(repairs);
     }
 
     // Generate setlist ID and persist
@@ -480,7 +918,16 @@ pub async fn generate_setlist_from_request(
         notes: llm_response.notes.clone(),
         harmonic_flow_score: None,
         energy_profile: req.energy_profile.as_ref().map(|p| p.to_string()),
+        energy_curve: req
+            .energy_curve
+            .as_ref()
+            .and_then(|c| serde_json::to_string(c).ok()),
+        key_path: req
+            .key_path
+            .as_ref()
+            .and_then(|k| serde_json::to_string(k).ok()),
         created_at: None,
+        target_duration_ms,
     };
 
     // M4: Attempt DB write; on failure, return partial response with warning
@@ -519,16 +966,31 @@ pub async fn generate_setlist_from_request(
             ),
         };
 
+        let duration_ms = validated_track_id
+            .as_deref()
+            .and_then(|id| catalog_durations.get(id).copied())
+            .or_else(|| {
+                entry
+                    .duration_seconds
+                    .map(|secs| secs.saturating_mul(1000).min(i32::MAX as u32) as i32)
+            });
+
         track_responses.push(SetlistTrackResponse {
             position,
             title: entry.title.clone(),
             artist: entry.artist.clone(),
             bpm: entry.bpm,
             key: entry.key.clone(),
-            camelot: entry.camelot.clone(),
+            camelot: normalize_key(entry.camelot.as_deref())
+                .or_else(|| normalize_key(entry.key.as_deref())),
             energy: entry.energy.map(|e| e as f64),
             transition_note: entry.transition_note.clone(),
             transition_score: None,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms,
+            start_time_ms: None,
             original_position: position,
             source,
             track_id: validated_track_id,
@@ -543,6 +1005,32 @@ pub async fn generate_setlist_from_request(
         });
     }
 
+    // Time slot: drop tracks while that brings the runtime closer to the
+    // target, or top a short set up from the unused catalog
+    if let Some(target) = target_duration_ms {
+        let added = fit_to_duration(
+            &mut track_responses,
+            target,
+            &track_constraints,
+            &catalog,
+            key_lock,
+            &scoring,
+        );
+        if added > 0 {
+            extra_notes.push(format!(
+                "Added {added} catalog track(s) to fill the time slot."
+            ));
+        }
+    }
+
+    // Repair, trimming and top-up change neighbours; redo those notes
+    refresh_moved_notes(&mut track_responses, &llm_order, key_lock, &scoring);
+
+    // Handoff: open with the track that mixes best out of the previous set
+    if let Some(ref previous) = context.handoff_from {
+        lineup::open_with_best_handoff(&mut track_responses, previous, key_lock, &scoring);
+    }
+
     // MusicBrainz grounding: verify tracks against real database (35M+ recordings)
     // Additive only — upgrades confidence for verified tracks, doesn't penalize unverified ones
     // Cap at 20 tracks to limit latency (1 req/sec rate limit)
@@ -623,6 +1111,7 @@ pub async fn generate_setlist_from_request(
                 confidence: track.confidence.clone(),
                 verification_flag: track.verification_flag.clone(),
                 verification_note: track.verification_note.clone(),
+                duration_ms: track.duration_ms,
             };
             if let Err(e) = db::insert_setlist_track(pool, &track_row).await {
                 tracing::error!("Failed to persist setlist track: {e}");
@@ -672,6 +1161,7 @@ pub async fn generate_setlist_from_request(
                 confidence: r.confidence.clone(),
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: r.duration_ms,
             })
             .collect();
         let match_count = compute_seed_match_count(seed_text, &track_rows);
@@ -685,9 +1175,14 @@ pub async fn generate_setlist_from_request(
     }
 
     // Quality validation
+    annotate_key_transitions(&mut track_responses, key_lock);
+    annotate_tempo_relations(&mut track_responses);
+    apply_key_notation(&mut track_responses, settings.key_notation);
     let catalog_percentage = compute_catalog_percentage(&track_responses);
     let catalog_warning = compute_catalog_warning(catalog_percentage);
-    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses);
+    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses, &scoring);
+    let artist_warnings = compute_artist_warnings(&track_responses, settings.artist_separation);
+    let timeline = annotate_timeline(&mut track_responses, target_duration_ms);
 
     // DF-03: Increment generation counter (best-effort; failure does not block response)
     if let Err(e) = crate::db::tracks::increment_generation_usage(pool, &req.user_id).await {
@@ -723,12 +1218,66 @@ pub async fn generate_setlist_from_request(
         score_breakdown: None,
         created_at,
         energy_profile: req.energy_profile.as_ref().map(|p| p.to_string()),
+        energy_curve: req.energy_curve.clone(),
+        key_path: req.key_path.clone(),
         catalog_percentage: Some(catalog_percentage),
         catalog_warning,
         bpm_warnings,
+        artist_warnings,
+        arrangement: None,
+        total_runtime_ms: timeline.total_runtime_ms,
+        target_duration_ms,
+        duration_warning: timeline.duration_warning,
     })
 }
 
+/// Fail unless `user_id` has `generations` left under [`DAILY_GENERATION_CAP`]
+/// today. Each setlist generated counts as one.
+pub async fn check_generation_allowance(
+    pool: &sqlx::PgPool,
+    user_id: &str,
+    generations: i64,
+) -> Result<(), SetlistError> {
+    let daily_gen_count = crate::db::tracks::get_daily_generation_count(pool, user_id)
+        .await
+        .map_err(|e| SetlistError::Database(e.to_string()))?;
+    if daily_gen_count + generations > DAILY_GENERATION_CAP {
+        let reason = if generations == 1 {
+            "reached".to_string()
+        } else {
+            format!("too low for {generations} more")
+        };
+        return Err(SetlistError::GenerationLimitExceeded(format!(
+            "Daily generation limit of {DAILY_GENERATION_CAP} {reason} ({daily_gen_count} used today)"
+        )));
+    }
+    Ok(())
+}
+
+/// Delete setlists a multi-setlist request saved before it failed, so a
+/// half-built batch leaves nothing behind. Ids of setlists that never saved
+/// are skipped; a failed delete is logged rather than masking the original
+/// error.
+pub async fn discard_setlists(pool: &sqlx::PgPool, ids: &[String]) {
+    for id in ids.iter().filter(|id| !id.starts_with("unsaved-")) {
+        if let Err(e) = db::delete_setlist(pool, id).await {
+            tracing::warn!("Failed to discard setlist {id}: {e}");
+        }
+    }
+}
+
+/// Keep catalog tracks whose BPM fits `range` directly or at half/double tempo.
+/// Tracks without a BPM are kept.
+fn filter_catalog_by_bpm_range(catalog: Vec<TrackRow>, range: &BpmRange) -> Vec<TrackRow> {
+    catalog
+        .into_iter()
+        .filter(|t| {
+            t.bpm
+                .is_none_or(|bpm| bpm_in_range(bpm, range.min, range.max).is_some())
+        })
+        .collect()
+}
+
 /// Count tracks that have BPM, key, or energy data (i.e., are enriched).
 fn count_enriched_tracks(tracks: &[TrackRow]) -> usize {
     tracks
@@ -737,18 +1286,130 @@ fn count_enriched_tracks(tracks: &[TrackRow]) -> usize {
         .count()
 }
 
+/// Map an arrangement failure to an API error, naming tracks by their
+/// current setlist position rather than their internal index.
+fn constraint_error(e: ArrangementError, tracks: &[SetlistTrackRow]) -> SetlistError {
+    match e {
+        ArrangementError::InvalidConstraint { index, reason } => {
+            SetlistError::InvalidRequest(format!(
+                "invalid constraint on the track at position {}: {reason}",
+                tracks[index].position
+            ))
+        }
+        ArrangementError::Infeasible(_) => SetlistError::InfeasibleConstraints(e.to_string()),
+        ArrangementError::TooManyTracks { .. } => SetlistError::InvalidRequest(e.to_string()),
+    }
+}
+
+/// Build the solver settings for an arrange request. The budget defaults to
+/// [`solver::DEFAULT_TIME_BUDGET`] and may not exceed [`solver::MAX_TIME_BUDGET`].
+pub fn parse_solver_config(
+    solver: Option<&str>,
+    time_budget_ms: Option<u64>,
+) -> Result<SolverConfig, SetlistError> {
+    let kind = match solver {
+        Some(s) => s.parse::<Solver>().map_err(SetlistError::InvalidRequest)?,
+        None => Solver::default(),
+    };
+    let time_budget = match time_budget_ms {
+        Some(0) => {
+            return Err(SetlistError::InvalidRequest(
+                "time_budget_ms must be positive".to_string(),
+            ))
+        }
+        Some(ms) if Duration::from_millis(ms) > solver::MAX_TIME_BUDGET => {
+            return Err(SetlistError::InvalidRequest(format!(
+                "time_budget_ms may be at most {}",
+                solver::MAX_TIME_BUDGET.as_millis()
+            )))
+        }
+        Some(ms) => Duration::from_millis(ms),
+        None => solver::DEFAULT_TIME_BUDGET,
+    };
+    Ok(SolverConfig { kind, time_budget })
+}
+
+/// A request may carry a preset profile or a custom curve, not both, and any
+/// curve must be well-formed.
+fn validate_energy_target(
+    profile: Option<&EnergyProfile>,
+    curve: Option<&EnergyCurve>,
+) -> Result<(), SetlistError> {
+    if profile.is_some() && curve.is_some() {
+        return Err(SetlistError::InvalidEnergyCurve(
+            "energy_profile and energy_curve are mutually exclusive".to_string(),
+        ));
+    }
+    if let Some(curve) = curve {
+        curve.validate().map_err(SetlistError::InvalidEnergyCurve)?;
+    }
+    Ok(())
+}
+
+/// Decode the `setlists.energy_curve` column; unreadable JSON is ignored.
+fn stored_energy_curve(json: Option<&str>) -> Option<EnergyCurve> {
+    json.and_then(|j| serde_json::from_str(j).ok())
+}
+
+fn validate_key_path(path: Option<&KeyPath>) -> Result<(), SetlistError> {
+    match path {
+        Some(path) => path.validate().map_err(SetlistError::InvalidKeyPath),
+        None => Ok(()),
+    }
+}
+
+/// Decode the `setlists.key_path` column; unreadable JSON is ignored.
+fn stored_key_path(json: Option<&str>) -> Option<KeyPath> {
+    json.and_then(|j| serde_json::from_str(j).ok())
+}
+
+/// Resolve the curve for a request: an inline curve, or one of the user's
+/// saved curves by id. Supplying both is an error.
+pub async fn resolve_energy_curve(
+    pool: &sqlx::PgPool,
+    user_id: &str,
+    inline: Option<EnergyCurve>,
+    curve_id: Option<&str>,
+) -> Result<Option<EnergyCurve>, SetlistError> {
+    let Some(curve_id) = curve_id else {
+        return Ok(inline);
+    };
+    if inline.is_some() {
+        return Err(SetlistError::InvalidEnergyCurve(
+            "energy_curve and energy_curve_id are mutually exclusive".to_string(),
+        ));
+    }
+    let row = crate::db::energy_curves::get_energy_curve(pool, curve_id)
+        .await?
+        .filter(|r| r.user_id == user_id)
+        .ok_or_else(|| SetlistError::NotFound(format!("Energy curve {curve_id} not found")))?;
+    let breakpoints = serde_json::from_str(&row.breakpoints)
+        .map_err(|e| SetlistError::Database(format!("Corrupt energy curve {curve_id}: {e}")))?;
+    Ok(Some(EnergyCurve { breakpoints }))
+}
+
 pub async fn get_setlist(pool: &sqlx::PgPool, id: &str) -> Result<SetlistResponse, SetlistError> {
     let setlist = db::get_setlist(pool, id)
         .await?
         .ok_or_else(|| SetlistError::NotFound(format!("Setlist {id} not found")))?;
 
     let tracks = db::get_setlist_tracks(pool, id).await?;
-    let track_responses: Vec<SetlistTrackResponse> =
+    let mut track_responses: Vec<SetlistTrackResponse> =
         tracks.into_iter().map(SetlistTrackResponse::from).collect();
-
-    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses);
+    let key_lock = crate::db::settings::get_key_lock(pool, &setlist.user_id).await?;
+    let scoring = crate::db::settings::get_scoring_profile(pool, &setlist.user_id).await?;
+    let artist_separation =
+        crate::db::settings::get_artist_separation(pool, &setlist.user_id).await?;
+    let key_notation = crate::db::settings::get_key_notation(pool, &setlist.user_id).await?;
+    annotate_key_transitions(&mut track_responses, key_lock);
+    annotate_tempo_relations(&mut track_responses);
+    apply_key_notation(&mut track_responses, key_notation);
+
+    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses, &scoring);
+    let artist_warnings = compute_artist_warnings(&track_responses, artist_separation);
     let catalog_percentage = compute_catalog_percentage(&track_responses);
     let catalog_warning = compute_catalog_warning(catalog_percentage);
+    let timeline = annotate_timeline(&mut track_responses, setlist.target_duration_ms);
 
     Ok(SetlistResponse {
         id: setlist.id,
@@ -763,26 +1424,136 @@ pub async fn get_setlist(pool: &sqlx::PgPool, id: &str) -> Result<SetlistRespons
             .created_at
             .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
         energy_profile: setlist.energy_profile,
+        energy_curve: stored_energy_curve(setlist.energy_curve.as_deref()),
+        key_path: stored_key_path(setlist.key_path.as_deref()),
         catalog_percentage: Some(catalog_percentage),
         catalog_warning,
         bpm_warnings,
+        artist_warnings,
+        arrangement: None,
+        total_runtime_ms: timeline.total_runtime_ms,
+        target_duration_ms: setlist.target_duration_ms,
+        duration_warning: timeline.duration_warning,
     })
 }
 
-/// L1: Extracted arrange logic from route handler into service layer.
-/// Loads setlist, runs arrangement algorithm, persists results, returns response.
-///
-/// If `energy_profile` is `Some`, uses that profile for arrangement scoring.
-/// If `None`, falls back to the stored `energy_profile` on the setlist row.
-/// If neither exists (pre-ST-006 setlists), uses default energy arc scoring.
-pub async fn arrange_setlist(
+#[derive(Debug, Serialize)]
+pub struct LintResponse {
+    pub setlist_id: String,
+    /// Refinement version checked; `None` for a setlist never refined.
+    pub version_number: Option<i32>,
+    #[serde(flatten)]
+    pub report: LintReport,
+}
+
+/// Health report for one version of a setlist: `version` if given, else the
+/// latest version, else the tracks as generated.
+pub async fn lint_setlist(
     pool: &sqlx::PgPool,
     id: &str,
-    energy_profile: Option<EnergyProfile>,
-) -> Result<SetlistResponse, SetlistError> {
-    // Load the setlist
-    let setlist_row = db::get_setlist(pool, id)
-        .await
+    version: Option<i32>,
+) -> Result<LintResponse, SetlistError> {
+    let setlist = db::get_setlist(pool, id)
+        .await?
+        .ok_or_else(|| SetlistError::NotFound(format!("Setlist {id} not found")))?;
+    let generated = db::get_setlist_tracks(pool, id).await?;
+
+    let version_row = match version {
+        Some(n) => Some(
+            crate::db::refinement::get_version_by_number(pool, id, n)
+                .await?
+                .ok_or_else(|| {
+                    SetlistError::NotFound(format!("Version {n} not found for setlist {id}"))
+                })?,
+        ),
+        None => crate::db::refinement::get_latest_version(pool, id).await?,
+    };
+    let tracks: Vec<LintTrack> = match &version_row {
+        Some(v) => crate::db::refinement::get_version_tracks(pool, &v.id)
+            .await?
+            .iter()
+            .map(|t| {
+                // Verification results live on the generated track it came from
+                let lint_track = LintTrack::from(t);
+                match generated
+                    .iter()
+                    .find(|g| t.original_position > 0 && g.original_position == t.original_position)
+                {
+                    Some(g) if g.title == t.title => lint_track.with_verification(g),
+                    _ => lint_track,
+                }
+            })
+            .collect(),
+        None => generated.iter().map(LintTrack::from).collect(),
+    };
+
+    let options = LintOptions {
+        scoring: crate::db::settings::get_scoring_profile(pool, &setlist.user_id).await?,
+        key_lock: crate::db::settings::get_key_lock(pool, &setlist.user_id).await?,
+        artist_separation: crate::db::settings::get_artist_separation(pool, &setlist.user_id)
+            .await?,
+        ..Default::default()
+    };
+
+    Ok(LintResponse {
+        setlist_id: setlist.id,
+        version_number: version_row.map(|v| v.version_number),
+        report: lint::lint_tracks(&tracks, &options),
+    })
+}
+
+/// Score a tracklist that is not a stored setlist, with `user_id`'s scoring
+/// settings. Nothing is persisted.
+pub async fn score_tracklist(
+    pool: &sqlx::PgPool,
+    user_id: &str,
+    entries: &[TracklistEntry],
+    energy_profile: Option<EnergyProfile>,
+    suggest_order: bool,
+) -> Result<TracklistScore, SetlistError> {
+    if entries.len() < 2 || entries.len() > MAX_TRACKLIST_ENTRIES {
+        return Err(SetlistError::InvalidRequest(format!(
+            "tracks must have between 2 and {MAX_TRACKLIST_ENTRIES} entries"
+        )));
+    }
+    for (i, entry) in entries.iter().enumerate() {
+        entry
+            .validate()
+            .map_err(|e| SetlistError::InvalidRequest(format!("entry {i}: {e}")))?;
+    }
+    let catalog = db::load_catalog_tracks(pool).await?;
+    let options = ArrangementOptions {
+        energy_profile,
+        key_lock: crate::db::settings::get_key_lock(pool, user_id).await?,
+        scoring: crate::db::settings::get_scoring_profile(pool, user_id).await?,
+        artist_separation: crate::db::settings::get_artist_separation(pool, user_id).await?,
+        ..Default::default()
+    };
+    tracklist::score_tracklist(entries, &catalog, &options, suggest_order)
+        .map_err(|e| SetlistError::InvalidRequest(e.to_string()))
+}
+
+/// L1: Extracted arrange logic from route handler into service layer.
+/// Loads setlist, runs arrangement algorithm, persists results, returns response.
+///
+/// If `energy_profile` is `Some`, uses that profile for arrangement scoring.
+/// If `None`, falls back to the stored `energy_profile` on the setlist row.
+/// If neither exists (pre-ST-006 setlists), uses default energy arc scoring.
+pub async fn arrange_setlist(
+    pool: &sqlx::PgPool,
+    id: &str,
+    energy_profile: Option<EnergyProfile>,
+    energy_curve: Option<EnergyCurve>,
+    key_path: Option<KeyPath>,
+    constraints: &[TrackConstraint],
+    solver: SolverConfig,
+) -> Result<SetlistResponse, SetlistError> {
+    validate_energy_target(energy_profile.as_ref(), energy_curve.as_ref())?;
+    validate_key_path(key_path.as_ref())?;
+
+    // Load the setlist
+    let setlist_row = db::get_setlist(pool, id)
+        .await
         .map_err(|e| SetlistError::Database(e.to_string()))?
         .ok_or_else(|| SetlistError::NotFound(format!("Setlist {id} not found")))?;
 
@@ -790,13 +1561,25 @@ pub async fn arrange_setlist(
         .await
         .map_err(|e| SetlistError::Database(e.to_string()))?;
 
-    // Resolve energy profile: explicit param > stored on setlist > None (default)
-    let resolved_profile = energy_profile.or_else(|| {
-        setlist_row
-            .energy_profile
-            .as_deref()
-            .and_then(|s| s.parse::<EnergyProfile>().ok())
-    });
+    let key_lock = crate::db::settings::get_key_lock(pool, &setlist_row.user_id).await?;
+    let scoring = crate::db::settings::get_scoring_profile(pool, &setlist_row.user_id).await?;
+    let artist_separation =
+        crate::db::settings::get_artist_separation(pool, &setlist_row.user_id).await?;
+    let key_notation = crate::db::settings::get_key_notation(pool, &setlist_row.user_id).await?;
+
+    // Resolve energy target: explicit curve/profile > stored on setlist > None (default)
+    let (resolved_profile, resolved_curve) = match (energy_profile, energy_curve) {
+        (_, Some(curve)) => (None, Some(curve)),
+        (Some(profile), None) => (Some(profile), None),
+        (None, None) => (
+            setlist_row
+                .energy_profile
+                .as_deref()
+                .and_then(|s| s.parse::<EnergyProfile>().ok()),
+            stored_energy_curve(setlist_row.energy_curve.as_deref()),
+        ),
+    };
+    let resolved_key_path = key_path.or_else(|| stored_key_path(setlist_row.key_path.as_deref()));
 
     // M5: Handle 0 tracks with 400 INVALID_REQUEST
     if tracks.is_empty() {
@@ -805,10 +1588,58 @@ pub async fn arrange_setlist(
         ));
     }
 
+    // Attach position constraints, keyed by each track's current position
+    let mut track_constraints: Vec<Option<PositionConstraint>> = vec![None; tracks.len()];
+    for c in constraints {
+        let idx = tracks
+            .iter()
+            .position(|t| t.position == c.track_position)
+            .ok_or_else(|| {
+                SetlistError::InvalidRequest(format!(
+                    "constraint refers to position {}, but the setlist has no such track",
+                    c.track_position
+                ))
+            })?;
+        if track_constraints[idx].replace(c.rule).is_some() {
+            return Err(SetlistError::InvalidRequest(format!(
+                "more than one constraint for the track at position {}",
+                c.track_position
+            )));
+        }
+    }
+
+    // Convert to arrangement tracks
+    let arrangement_tracks: Vec<ArrangementTrack> = tracks
+        .iter()
+        .zip(track_constraints)
+        .enumerate()
+        .map(|(i, (t, constraint))| ArrangementTrack {
+            index: i,
+            camelot: t.camelot.as_deref().and_then(parse_camelot),
+            bpm: t.bpm,
+            energy: t.energy.map(|e| e as i32),
+            artist: Some(t.artist.clone()),
+            constraint,
+        })
+        .collect();
+
     // M5: Handle 1 track — return as-is with perfect score
     if tracks.len() == 1 {
+        // Still reject constraints a single track cannot meet (e.g., pinned to 2)
+        let result = arrangement::arrange_tracks_with_options(
+            &arrangement_tracks,
+            &ArrangementOptions {
+                key_path: resolved_key_path.clone(),
+                solver,
+                ..Default::default()
+            },
+        )
+        .map_err(|e| constraint_error(e, &tracks))?;
+
         let track = &tracks[0];
-        let track_response = SetlistTrackResponse::from(track.clone());
+        let mut track_responses = vec![SetlistTrackResponse::from(track.clone())];
+        apply_key_notation(&mut track_responses, key_notation);
+        let timeline = annotate_timeline(&mut track_responses, setlist_row.target_duration_ms);
 
         // Update harmonic flow score in DB
         db::update_setlist_harmonic_score(pool, id, 100.0)
@@ -820,40 +1651,62 @@ pub async fn arrange_setlist(
             prompt: setlist_row.prompt,
             model: setlist_row.model,
             name: setlist_row.name,
-            tracks: vec![track_response],
+            tracks: track_responses,
             notes: setlist_row.notes,
             harmonic_flow_score: Some(100.0),
             score_breakdown: Some(ScoreBreakdownResponse {
                 key_compatibility: 100.0,
                 bpm_continuity: 100.0,
                 energy_arc: 100.0,
+                key_journey: result.score_breakdown.key_journey,
+                key_transitions: vec![],
+                tempo_relations: vec![],
+                pitch_adjustments: vec![],
             }),
             created_at: setlist_row
                 .created_at
                 .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
             energy_profile: setlist_row.energy_profile,
+            energy_curve: resolved_curve,
+            key_path: resolved_key_path,
             catalog_percentage: None,
             catalog_warning: None,
             bpm_warnings: vec![],
+            artist_warnings: vec![],
+            arrangement: Some(ArrangementReport::from(&result)),
+            total_runtime_ms: timeline.total_runtime_ms,
+            target_duration_ms: setlist_row.target_duration_ms,
+            duration_warning: timeline.duration_warning,
         });
     }
 
-    // Convert to arrangement tracks
-    let arrangement_tracks: Vec<ArrangementTrack> = tracks
-        .iter()
-        .enumerate()
-        .map(|(i, t)| ArrangementTrack {
-            index: i,
-            camelot: t.camelot.as_deref().and_then(parse_camelot),
-            bpm: t.bpm,
-            energy: t.energy.map(|e| e as i32),
-        })
-        .collect();
-
     // Run arrangement algorithm with resolved energy profile
-    let result = arrangement::arrange_tracks(&arrangement_tracks, resolved_profile);
+    let result = arrangement::arrange_tracks_with_options(
+        &arrangement_tracks,
+        &ArrangementOptions {
+            energy_profile: resolved_profile,
+            energy_curve: resolved_curve.clone(),
+            key_path: resolved_key_path.clone(),
+            key_lock,
+            scoring,
+            solver,
+            artist_separation,
+        },
+    )
+    .map_err(|e| constraint_error(e, &tracks))?;
+
+    // Notes describe the mix in from the new predecessor, so redo them all
+    let transition_notes = technique::transition_notes(
+        &result
+            .ordered_indices
+            .iter()
+            .map(|&i| mix_point(&tracks[i]))
+            .collect::<Vec<_>>(),
+        key_lock,
+        &scoring,
+    );
 
-    // Update positions and scores in DB
+    // Update positions, scores and notes in DB
     for (new_pos, &original_idx) in result.ordered_indices.iter().enumerate() {
         let track = &tracks[original_idx];
         let t_score = if new_pos > 0 {
@@ -861,9 +1714,15 @@ pub async fn arrange_setlist(
         } else {
             None
         };
-        db::update_setlist_track_position(pool, &track.id, (new_pos + 1) as i32, t_score)
-            .await
-            .map_err(|e| SetlistError::Database(e.to_string()))?;
+        db::update_setlist_track_position(
+            pool,
+            &track.id,
+            (new_pos + 1) as i32,
+            t_score,
+            transition_notes[new_pos].as_deref(),
+        )
+        .await
+        .map_err(|e| SetlistError::Database(e.to_string()))?;
     }
 
     // Update harmonic flow score
@@ -888,8 +1747,13 @@ pub async fn arrange_setlist(
             key: track.key.clone(),
             camelot: track.camelot.clone(),
             energy: track.energy,
-            transition_note: track.transition_note.clone(),
+            transition_note: transition_notes[new_pos].clone(),
             transition_score: t_score,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: track.duration_ms,
+            start_time_ms: None,
             original_position: track.original_position,
             source: track.source.clone(),
             track_id: track.track_id.clone(),
@@ -900,8 +1764,15 @@ pub async fn arrange_setlist(
         });
     }
 
+    annotate_key_transitions(&mut track_responses, key_lock);
+    annotate_tempo_relations(&mut track_responses);
+    apply_key_notation(&mut track_responses, key_notation);
+
     // C1: Map score_breakdown from ArrangementResult into response
-    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses);
+    let report = ArrangementReport::from(&result);
+    let bpm_warnings = compute_bpm_warnings_from_responses(&track_responses, &scoring);
+    let artist_warnings = compute_artist_warnings(&track_responses, artist_separation);
+    let timeline = annotate_timeline(&mut track_responses, setlist_row.target_duration_ms);
 
     Ok(SetlistResponse {
         id: setlist_row.id,
@@ -915,14 +1786,25 @@ pub async fn arrange_setlist(
             key_compatibility: result.score_breakdown.key_compatibility,
             bpm_continuity: result.score_breakdown.bpm_continuity,
             energy_arc: result.score_breakdown.energy_arc,
+            key_journey: result.score_breakdown.key_journey,
+            key_transitions: result.score_breakdown.key_transitions,
+            tempo_relations: result.score_breakdown.tempo_relations,
+            pitch_adjustments: result.score_breakdown.pitch_adjustments,
         }),
         created_at: setlist_row
             .created_at
             .map(|dt| dt.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
         energy_profile: setlist_row.energy_profile,
+        energy_curve: resolved_curve,
+        key_path: resolved_key_path,
         catalog_percentage: None,
         catalog_warning: None,
         bpm_warnings,
+        artist_warnings,
+        arrangement: Some(report),
+        total_runtime_ms: timeline.total_runtime_ms,
+        target_duration_ms: setlist_row.target_duration_ms,
+        duration_warning: timeline.duration_warning,
     })
 }
 
@@ -932,7 +1814,13 @@ pub async fn arrange_setlist(
 
 /// Generic BPM warning computation. Accepts a slice of any type and a closure
 /// that extracts `(position, bpm)` from each element.
-fn compute_bpm_warnings_generic<T, F>(tracks: &[T], extract: F) -> Vec<BpmWarning>
+/// A transition is flagged when its effective BPM change exceeds the
+/// profile's `bpm_warning_threshold`.
+pub(crate) fn compute_bpm_warnings_generic<T, F>(
+    tracks: &[T],
+    profile: &ScoringProfile,
+    extract: F,
+) -> Vec<BpmWarning>
 where
     F: Fn(&T) -> (i32, Option<f64>),
 {
@@ -941,12 +1829,14 @@ where
         let (pos_a, bpm_a) = extract(&tracks[i]);
         let (pos_b, bpm_b) = extract(&tracks[i + 1]);
         if let (Some(bpm_a), Some(bpm_b)) = (bpm_a, bpm_b) {
-            let delta = bpm_b - bpm_a;
-            if delta.abs() > 6.0 {
+            let m = bpm_match_with_bands(bpm_a, bpm_b, &profile.bpm_bands);
+            if m.effective_delta.abs() > profile.bpm_warning_threshold {
                 warnings.push(BpmWarning {
                     from_position: pos_a,
                     to_position: pos_b,
-                    bpm_delta: delta,
+                    bpm_delta: bpm_b - bpm_a,
+                    tempo_relation: m.relation,
+                    effective_bpm_delta: m.effective_delta,
                 });
             }
         }
@@ -955,13 +1845,375 @@ where
 }
 
 /// Compute BPM warnings from SetlistTrackRow slices (used by get_setlist and arrange).
-pub fn compute_bpm_warnings(tracks: &[SetlistTrackRow]) -> Vec<BpmWarning> {
-    compute_bpm_warnings_generic(tracks, |t| (t.position, t.bpm))
+pub fn compute_bpm_warnings(
+    tracks: &[SetlistTrackRow],
+    profile: &ScoringProfile,
+) -> Vec<BpmWarning> {
+    compute_bpm_warnings_generic(tracks, profile, |t| (t.position, t.bpm))
 }
 
 /// Compute BPM warnings from SetlistTrackResponse slices (used during generation).
-fn compute_bpm_warnings_from_responses(tracks: &[SetlistTrackResponse]) -> Vec<BpmWarning> {
-    compute_bpm_warnings_generic(tracks, |t| (t.position, t.bpm))
+fn compute_bpm_warnings_from_responses(
+    tracks: &[SetlistTrackResponse],
+    profile: &ScoringProfile,
+) -> Vec<BpmWarning> {
+    compute_bpm_warnings_generic(tracks, profile, |t| (t.position, t.bpm))
+}
+
+/// Flag every pair of tracks that share an artist and sit `artist_separation`
+/// or fewer positions apart. A separation of 0 never warns.
+fn compute_artist_warnings(
+    tracks: &[SetlistTrackResponse],
+    artist_separation: usize,
+) -> Vec<ArtistWarning> {
+    let artists: Vec<Option<&str>> = tracks.iter().map(|t| Some(t.artist.as_str())).collect();
+    arrangement::separation_violations(&artists, artist_separation)
+        .into_iter()
+        .map(|(i, j, artist)| ArtistWarning {
+            from_position: tracks[i].position,
+            to_position: tracks[j].position,
+            artist,
+            gap: (j - i) as i32,
+        })
+        .collect()
+}
+
+/// Rewrite each track's `key` in the user's preferred notation, from its
+/// Camelot key when known. With no preference, keys are left as stored.
+pub fn apply_key_notation(tracks: &mut [SetlistTrackResponse], notation: Option<KeyNotation>) {
+    let Some(notation) = notation else {
+        return;
+    };
+    for t in tracks {
+        t.key = t
+            .camelot
+            .as_deref()
+            .or(t.key.as_deref())
+            .map(|k| display_key(k, notation));
+    }
+}
+
+/// Label each track with the kind of key move from its predecessor.
+///
+/// With `key_lock` off, the move is judged on the key the track sounds in
+/// after pitching to the previous BPM. The first track and any pair with an
+/// unparseable Camelot key get `None`.
+pub fn annotate_key_transitions(tracks: &mut [SetlistTrackResponse], key_lock: bool) {
+    for i in 0..tracks.len() {
+        tracks[i].key_transition = if i == 0 {
+            None
+        } else {
+            match (
+                tracks[i - 1].camelot.as_deref().and_then(parse_camelot),
+                tracks[i].camelot.as_deref().and_then(parse_camelot),
+            ) {
+                (Some(a), Some(b)) => {
+                    let b = effective_key(&b, tracks[i - 1].bpm, tracks[i].bpm, key_lock);
+                    Some(classify_transition(&a, &b))
+                }
+                _ => None,
+            }
+        };
+    }
+}
+
+/// Label each track with how its tempo relates to its predecessor and the
+/// pitch-fader move needed to beatmatch it.
+///
+/// The first track and any pair with a missing BPM get `None`.
+pub fn annotate_tempo_relations(tracks: &mut [SetlistTrackResponse]) {
+    for i in 0..tracks.len() {
+        let pair = match (i.checked_sub(1).and_then(|p| tracks[p].bpm), tracks[i].bpm) {
+            (Some(a), Some(b)) => Some((bpm_match(a, b).relation, pitch_percent(a, b))),
+            _ => None,
+        };
+        tracks[i].tempo_relation = pair.map(|(relation, _)| relation);
+        tracks[i].pitch_percent = pair.map(|(_, pitch)| pitch);
+    }
+}
+
+/// `artist - title` identity of each entry, in order.
+fn entry_identities(entries: &[LlmTrackEntry]) -> Vec<String> {
+    entries
+        .iter()
+        .map(|e| lineup::track_identity(&e.artist, &e.title))
+        .collect()
+}
+
+/// Recompute the transition note of every track whose predecessor is not the
+/// one it had in `llm_order`, the order the LLM wrote its notes for. Notes on
+/// transitions that still happen are kept.
+fn refresh_moved_notes(
+    tracks: &mut [SetlistTrackResponse],
+    llm_order: &[String],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) {
+    let llm_predecessor: std::collections::HashMap<&str, Option<&str>> = llm_order
+        .iter()
+        .enumerate()
+        .map(|(i, id)| (id.as_str(), i.checked_sub(1).map(|p| llm_order[p].as_str())))
+        .collect();
+    let ids: Vec<String> = tracks
+        .iter()
+        .map(|t| lineup::track_identity(&t.artist, &t.title))
+        .collect();
+    let points: Vec<MixPoint> = tracks
+        .iter()
+        .map(|t| MixPoint::new(t.camelot.as_deref(), t.bpm, t.energy, t.duration_ms))
+        .collect();
+    for i in 0..tracks.len() {
+        let predecessor = i.checked_sub(1);
+        if llm_predecessor.get(ids[i].as_str()) == Some(&predecessor.map(|p| ids[p].as_str())) {
+            continue;
+        }
+        tracks[i].transition_note = predecessor
+            .map(|p| technique::transition_note(&points[p], &points[i], key_lock, scoring));
+    }
+}
+
+fn mix_point(track: &SetlistTrackRow) -> MixPoint {
+    MixPoint::new(
+        track.camelot.as_deref(),
+        track.bpm,
+        track.energy,
+        track.duration_ms,
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Set duration
+// ---------------------------------------------------------------------------
+
+fn track_duration_ms(track: &SetlistTrackResponse) -> i64 {
+    track
+        .duration_ms
+        .map(i64::from)
+        .unwrap_or(DEFAULT_TRACK_DURATION_MS)
+}
+
+/// How far a runtime may miss its target and still count as on time.
+fn duration_tolerance_ms(target_ms: i64) -> i64 {
+    ((target_ms as f64 * DURATION_TOLERANCE) as i64).max(MIN_DURATION_TOLERANCE_MS)
+}
+
+/// `m:ss`, or `h:mm:ss` from an hour up.
+pub fn format_runtime(ms: i64) -> String {
+    let secs = ms.max(0) / 1000;
+    let (h, m, s) = (secs / 3600, secs / 60 % 60, secs % 60);
+    if h > 0 {
+        format!("{h}:{m:02}:{s:02}")
+    } else {
+        format!("{m}:{s:02}")
+    }
+}
+
+/// Tracks needed to fill `target_ms`, judged by the catalog's average
+/// duration (or [`DEFAULT_TRACK_DURATION_MS`] if none are known).
+pub(crate) fn estimate_track_count(catalog: &[TrackRow], target_ms: i64) -> u32 {
+    let known: Vec<i64> = catalog
+        .iter()
+        .filter_map(|t| t.duration_ms)
+        .filter(|&d| d > 0)
+        .map(i64::from)
+        .collect();
+    let average = if known.is_empty() {
+        DEFAULT_TRACK_DURATION_MS
+    } else {
+        known.iter().sum::<i64>() / known.len() as i64
+    };
+    let count = (target_ms as f64 / average as f64).round() as u32;
+    count.clamp(MIN_TRACK_COUNT, MAX_TRACK_COUNT)
+}
+
+/// Trim an overlong set, then top up a short one from `catalog`, to fit
+/// `target_ms`. Returns how many catalog tracks were added.
+pub(crate) fn fit_to_duration(
+    tracks: &mut Vec<SetlistTrackResponse>,
+    target_ms: i64,
+    track_constraints: &ResolvedConstraints,
+    catalog: &[TrackRow],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> usize {
+    trim_to_duration(tracks, target_ms, track_constraints);
+    top_up_to_duration(tracks, target_ms, catalog, key_lock, scoring)
+}
+
+/// Drop tracks while the set overruns `target_ms` by more than the tolerance
+/// and a removal brings it closer. The opener and closer are kept; among the
+/// rest, the track whose length best matches the overrun goes first, and
+/// must-include tracks are never dropped. Positions are renumbered
+/// afterwards.
+fn trim_to_duration(
+    tracks: &mut Vec<SetlistTrackResponse>,
+    target_ms: i64,
+    track_constraints: &ResolvedConstraints,
+) {
+    let tolerance = duration_tolerance_ms(target_ms);
+    loop {
+        let total: i64 = tracks.iter().map(track_duration_ms).sum();
+        let overrun = total - target_ms;
+        if overrun <= tolerance || tracks.len() <= 2 {
+            break;
+        }
+        let Some(drop) = (1..tracks.len() - 1)
+            .filter(|&i| {
+                let t = &tracks[i];
+                !track_constraints.requires(t.track_id.as_deref(), &t.artist, &t.title)
+            })
+            .min_by_key(|&i| (track_duration_ms(&tracks[i]) - overrun).abs())
+        else {
+            break;
+        };
+        let after = overrun - track_duration_ms(&tracks[drop]);
+        if after.abs() >= overrun {
+            break;
+        }
+        tracks.remove(drop);
+    }
+    renumber(tracks);
+}
+
+/// Add unused catalog tracks while the set falls short of `target_ms` by
+/// more than the tolerance. Each addition goes in before the closer and is
+/// the candidate that mixes best out of the track it follows, among those
+/// with a known duration that do not overrun the target. Returns how many
+/// tracks were added.
+fn top_up_to_duration(
+    tracks: &mut Vec<SetlistTrackResponse>,
+    target_ms: i64,
+    catalog: &[TrackRow],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> usize {
+    let tolerance = duration_tolerance_ms(target_ms);
+    let mut used: HashSet<String> = tracks.iter().filter_map(|t| t.track_id.clone()).collect();
+    let mut added = 0;
+    while tracks.len() < MAX_TRACK_COUNT as usize {
+        let total: i64 = tracks.iter().map(track_duration_ms).sum();
+        let shortfall = target_ms - total;
+        if shortfall <= tolerance {
+            break;
+        }
+        // Before the closer; a lone opener keeps its place
+        let at = match tracks.len() {
+            0 | 1 => tracks.len(),
+            n => n - 1,
+        };
+        let previous = at.checked_sub(1).map(|i| &tracks[i]);
+        let score = |row: &TrackRow| match previous {
+            Some(prev) => transition_score_with_profile(
+                prev.camelot.as_deref().and_then(parse_camelot).as_ref(),
+                row.camelot_key.as_deref().and_then(parse_camelot).as_ref(),
+                prev.bpm,
+                row.bpm,
+                key_lock,
+                scoring,
+            ),
+            None => 0.0,
+        };
+        let Some(pick) = catalog
+            .iter()
+            .filter(|row| !used.contains(row.id.as_str()))
+            .filter(|row| {
+                row.duration_ms
+                    .is_some_and(|d| d > 0 && i64::from(d) <= shortfall + tolerance)
+            })
+            .max_by(|a, b| score(a).total_cmp(&score(b)))
+        else {
+            break;
+        };
+        used.insert(pick.id.clone());
+        tracks.insert(at, catalog_track_response(pick));
+        added += 1;
+    }
+    renumber(tracks);
+    added
+}
+
+/// A catalog track as a setlist entry, for tracks the service adds itself.
+fn catalog_track_response(row: &TrackRow) -> SetlistTrackResponse {
+    SetlistTrackResponse {
+        position: 0,
+        title: row.title.clone(),
+        artist: row.artist.clone().unwrap_or_default(),
+        bpm: row.bpm,
+        key: None,
+        camelot: normalize_key(row.camelot_key.as_deref()),
+        energy: row.energy,
+        transition_note: None,
+        transition_score: None,
+        key_transition: None,
+        tempo_relation: None,
+        pitch_percent: None,
+        duration_ms: row.duration_ms,
+        start_time_ms: None,
+        original_position: 0,
+        source: "catalog".to_string(),
+        track_id: Some(row.id.clone()),
+        spotify_uri: None,
+        confidence: Some("high".to_string()),
+        verification_flag: None,
+        verification_note: None,
+    }
+}
+
+/// Number positions 1..=n in list order.
+fn renumber(tracks: &mut [SetlistTrackResponse]) {
+    for (i, track) in tracks.iter_mut().enumerate() {
+        track.position = (i + 1) as i32;
+        track.original_position = (i + 1) as i32;
+    }
+}
+
+/// Runtime figures for a [`SetlistResponse`].
+struct Timeline {
+    total_runtime_ms: Option<i64>,
+    duration_warning: Option<String>,
+}
+
+/// Fill in each track's `start_time_ms` and total up the set. Warns when the
+/// runtime misses `target_ms` by more than the tolerance.
+fn annotate_timeline(tracks: &mut [SetlistTrackResponse], target_ms: Option<i64>) -> Timeline {
+    if tracks.is_empty() {
+        return Timeline {
+            total_runtime_ms: None,
+            duration_warning: None,
+        };
+    }
+    let mut elapsed = 0;
+    for track in tracks.iter_mut() {
+        track.start_time_ms = Some(elapsed);
+        elapsed += track_duration_ms(track);
+    }
+
+    let duration_warning = target_ms.and_then(|target| {
+        let miss = elapsed - target;
+        if miss.abs() <= duration_tolerance_ms(target) {
+            return None;
+        }
+        let unknown = tracks.iter().filter(|t| t.duration_ms.is_none()).count();
+        let mut warning = format!(
+            "Set runs {} {} ({} vs {} target).",
+            format_runtime(miss.abs()),
+            if miss > 0 { "long" } else { "short" },
+            format_runtime(elapsed),
+            format_runtime(target)
+        );
+        if unknown > 0 {
+            warning.push_str(&format!(
+                " {unknown} track(s) have no known duration and were counted as {}.",
+                format_runtime(DEFAULT_TRACK_DURATION_MS)
+            ));
+        }
+        Some(warning)
+    });
+
+    Timeline {
+        total_runtime_ms: Some(elapsed),
+        duration_warning,
+    }
 }
 
 /// Compute catalog percentage: count of tracks with source == "catalog" / total × 100.
@@ -1217,6 +2469,7 @@ pub(crate) mod test_utils {
 mod tests {
     use super::*;
     use crate::api::claude::ClaudeClientTrait;
+    use crate::services::constraints::TrackRef;
     use test_utils::MockClaude;
 
     struct MalformedClaude;
@@ -1419,6 +2672,288 @@ mod tests {
         )
     }
 
+    /// Desert Rose (catalog, 5:00) followed by four 8-minute suggestions.
+    fn timed_llm_json() -> String {
+        let mut tracks = vec![serde_json::json!({
+            "position": 1, "title": "Desert Rose", "artist": "Sting", "bpm": 102.0,
+            "camelot": "8A", "source": "catalog", "track_id": "t1",
+            "duration_seconds": 999
+        })];
+        for i in 2..=5 {
+            tracks.push(serde_json::json!({
+                "position": i, "title": format!("Long Track {i}"), "artist": "Someone",
+                "bpm": 104.0, "camelot": "8A", "source": "suggestion", "track_id": null,
+                "duration_seconds": 480, "transition_note": "LLM note"
+            }));
+        }
+        serde_json::json!({"tracks": tracks, "notes": "timed"}).to_string()
+    }
+
+    fn timed_request(minutes: u32) -> GenerateSetlistRequest {
+        GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "a set for my slot".to_string(),
+            track_count: None,
+            target_duration_minutes: Some(minutes),
+            energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        }
+    }
+
+    async fn setup_pool_with_timed_tracks() -> sqlx::PgPool {
+        let pool = setup_pool_with_tracks().await;
+        sqlx::query("UPDATE tracks SET duration_ms = 300000 WHERE id = 't1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_target_duration_trims_to_slot() {
+        let pool = setup_pool_with_timed_tracks().await;
+        let claude = MockClaude {
+            response: timed_llm_json(),
+        };
+        // 37 minutes generated for a 30 minute slot: one 8-minute track goes
+        let resp = generate_setlist_from_request(&pool, &claude, timed_request(30))
+            .await
+            .unwrap();
+        assert_eq!(resp.tracks.len(), 4);
+        assert_eq!(resp.tracks[0].title, "Desert Rose");
+        assert_eq!(resp.tracks[3].title, "Long Track 5");
+        let positions: Vec<i32> = resp.tracks.iter().map(|t| t.position).collect();
+        assert_eq!(positions, vec![1, 2, 3, 4]);
+
+        // Catalog duration wins over the LLM's guess
+        assert_eq!(resp.tracks[0].duration_ms, Some(300_000));
+        let starts: Vec<Option<i64>> = resp.tracks.iter().map(|t| t.start_time_ms).collect();
+        assert_eq!(
+            starts,
+            vec![Some(0), Some(300_000), Some(780_000), Some(1_260_000)]
+        );
+        assert_eq!(resp.total_runtime_ms, Some(1_740_000));
+        assert_eq!(resp.target_duration_ms, Some(1_800_000));
+        assert!(resp.duration_warning.is_none());
+
+        // Durations and target survive a reload
+        let reread = get_setlist(&pool, &resp.id).await.unwrap();
+        assert_eq!(reread.total_runtime_ms, Some(1_740_000));
+        assert_eq!(reread.target_duration_ms, Some(1_800_000));
+        assert_eq!(reread.tracks[2].start_time_ms, Some(780_000));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_target_duration_trim_keeps_must_include_tracks() {
+        let pool = setup_pool_with_timed_tracks().await;
+        let claude = MockClaude {
+            response: timed_llm_json(),
+        };
+        // Long Track 2 would be the first to go without the constraint
+        let mut req = timed_request(30);
+        req.constraints.must_include = vec![TrackRef::Named {
+            artist: "Someone".to_string(),
+            title: "Long Track 2".to_string(),
+        }];
+        let resp = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap();
+        let titles: Vec<&str> = resp.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(
+            titles,
+            vec![
+                "Desert Rose",
+                "Long Track 2",
+                "Long Track 4",
+                "Long Track 5"
+            ]
+        );
+        assert_eq!(resp.total_runtime_ms, Some(1_740_000));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_target_duration_warns_when_short() {
+        let pool = setup_pool_with_timed_tracks().await;
+        let claude = MockClaude {
+            response: timed_llm_json(),
+        };
+        let resp = generate_setlist_from_request(&pool, &claude, timed_request(60))
+            .await
+            .unwrap();
+        assert_eq!(resp.tracks.len(), 5);
+        assert_eq!(resp.total_runtime_ms, Some(2_220_000));
+        assert_eq!(
+            resp.duration_warning.as_deref(),
+            Some("Set runs 23:00 short (37:00 vs 1:00:00 target).")
+        );
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_target_duration_tops_up_short_set_from_catalog() {
+        let pool = setup_pool_with_timed_tracks().await;
+        for (id, title, camelot, minutes) in [
+            ("t2", "Nour", "8A", 7),
+            ("t3", "Sahara", "9A", 8),
+            ("t4", "Oasis", "3B", 6),
+            ("t5", "Marathon Mix", "8A", 40),
+        ] {
+            sqlx::query(
+                "INSERT INTO tracks (id, title, source, bpm, camelot_key, duration_ms)
+                 VALUES ($1, $2, 'spotify', 104.0, $3, $4)",
+            )
+            .bind(id)
+            .bind(title)
+            .bind(camelot)
+            .bind(minutes * 60_000)
+            .execute(&pool)
+            .await
+            .unwrap();
+        }
+        let claude = MockClaude {
+            response: timed_llm_json(),
+        };
+        // 37 minutes generated for an hour: 21 minutes of catalog added
+        let resp = generate_setlist_from_request(&pool, &claude, timed_request(60))
+            .await
+            .unwrap();
+        assert_eq!(resp.tracks.len(), 8);
+        assert_eq!(resp.total_runtime_ms, Some(3_480_000));
+        assert!(resp.duration_warning.is_none());
+        assert_eq!(resp.tracks[7].title, "Long Track 5", "closer stays last");
+        let added: Vec<&str> = resp.tracks[4..7]
+            .iter()
+            .map(|t| t.track_id.as_deref().unwrap())
+            .collect();
+        assert!(!added.contains(&"t5"), "too long for the slot");
+        // LLM notes stay where the neighbours did; the rest are recomputed
+        assert_eq!(resp.tracks[1].transition_note.as_deref(), Some("LLM note"));
+        for track in &resp.tracks[4..] {
+            let note = track.transition_note.as_deref().unwrap();
+            assert_ne!(note, "LLM note", "{}", track.title);
+        }
+        let positions: Vec<i32> = resp.tracks.iter().map(|t| t.position).collect();
+        assert_eq!(positions, (1..=8).collect::<Vec<_>>());
+        assert!(resp
+            .notes
+            .unwrap()
+            .contains("Added 3 catalog track(s) to fill the time slot."));
+        let reread = get_setlist(&pool, &resp.id).await.unwrap();
+        assert_eq!(reread.total_runtime_ms, Some(3_480_000));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_target_duration_validation() {
+        let pool = setup_pool_with_tracks().await;
+        let claude = MockClaude {
+            response: valid_llm_json(None),
+        };
+        let mut both = timed_request(60);
+        both.track_count = Some(10);
+        assert!(matches!(
+            generate_setlist_from_request(&pool, &claude, both).await,
+            Err(SetlistError::InvalidRequest(_))
+        ));
+        assert!(matches!(
+            generate_setlist_from_request(&pool, &claude, timed_request(5)).await,
+            Err(SetlistError::InvalidRequest(_))
+        ));
+        pool.close().await;
+    }
+
+    #[test]
+    fn test_format_runtime() {
+        assert_eq!(format_runtime(0), "0:00");
+        assert_eq!(format_runtime(390_000), "6:30");
+        assert_eq!(format_runtime(5_400_000), "1:30:00");
+    }
+
+    #[test]
+    fn test_estimate_track_count_uses_catalog_average() {
+        let track = |duration_ms| TrackRow {
+            id: String::new(),
+            title: String::new(),
+            artist: None,
+            album: None,
+            duration_ms,
+            bpm: None,
+            camelot_key: None,
+            energy: None,
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        };
+        let catalog = vec![track(Some(240_000)), track(Some(360_000)), track(None)];
+        // 90 minutes of 5-minute tracks
+        assert_eq!(estimate_track_count(&catalog, 5_400_000), 18);
+        // No known durations: 6-minute default
+        assert_eq!(estimate_track_count(&[track(None)], 5_400_000), 15);
+        // Capped at the track-count limit
+        assert_eq!(
+            estimate_track_count(&catalog, 300 * 60_000),
+            MAX_TRACK_COUNT
+        );
+    }
+
+    #[test]
+    fn test_timeline_counts_unknown_durations_as_default() {
+        let mut tracks: Vec<SetlistTrackResponse> = (1..=3)
+            .map(|i| {
+                SetlistTrackResponse::from(SetlistTrackRow {
+                    id: i.to_string(),
+                    setlist_id: "s".into(),
+                    track_id: None,
+                    position: i,
+                    original_position: i,
+                    title: format!("Track {i}"),
+                    artist: "Artist".into(),
+                    bpm: None,
+                    key: None,
+                    camelot: None,
+                    energy: None,
+                    transition_note: None,
+                    transition_score: None,
+                    source: "suggestion".into(),
+                    acquisition_info: None,
+                    spotify_uri: None,
+                    confidence: None,
+                    verification_flag: None,
+                    verification_note: None,
+                    duration_ms: if i == 2 { None } else { Some(240_000) },
+                })
+            })
+            .collect();
+        let timeline = annotate_timeline(&mut tracks, Some(30 * 60_000));
+        assert_eq!(
+            tracks[2].start_time_ms,
+            Some(240_000 + DEFAULT_TRACK_DURATION_MS)
+        );
+        assert_eq!(timeline.total_runtime_ms, Some(840_000));
+        let warning = timeline.duration_warning.unwrap();
+        assert!(warning.starts_with("Set runs 16:00 short"));
+        assert!(warning.contains("1 track(s) have no known duration"));
+
+        assert!(annotate_timeline(&mut [], Some(60_000))
+            .total_runtime_ms
+            .is_none());
+    }
+
     #[tokio::test]
     async fn test_empty_prompt_returns_error() {
         let pool = setup_pool_with_tracks().await;
@@ -1733,6 +3268,9 @@ mod tests {
             notes: None,
             harmonic_flow_score: None,
             energy_profile: energy_profile.map(|s| s.to_string()),
+            energy_curve: None,
+            key_path: None,
+            target_duration_ms: None,
             created_at: None,
         };
         db::insert_setlist(&pool, &setlist_row).await.unwrap();
@@ -1767,54 +3305,534 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             };
             db::insert_setlist_track(&pool, &track_row).await.unwrap();
         }
 
-        (pool, setlist_id)
+        (pool, setlist_id)
+    }
+
+    #[tokio::test]
+    async fn test_arrange_uses_user_key_lock_setting() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        crate::db::settings::set_key_lock(&pool, "user1", false)
+            .await
+            .unwrap();
+        let result = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+
+        let breakdown = result.score_breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.pitch_adjustments.len(), result.tracks.len() - 1);
+        for (i, track) in result.tracks.iter().enumerate().skip(1) {
+            // Per-track labels use the same pitched-key model as the scorer
+            assert_eq!(track.key_transition, breakdown.key_transitions[i - 1]);
+            let pitch = track.pitch_percent.unwrap();
+            assert!((pitch - breakdown.pitch_adjustments[i - 1].unwrap()).abs() < 1e-9);
+        }
+
+        let reread = get_setlist(&pool, &id).await.unwrap();
+        let labels: Vec<_> = reread.tracks.iter().map(|t| t.key_transition).collect();
+        let arranged: Vec<_> = result.tracks.iter().map(|t| t.key_transition).collect();
+        assert_eq!(labels, arranged);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_arrange_labels_key_transitions_per_track() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let result = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+
+        assert!(result.tracks[0].key_transition.is_none());
+        let breakdown = result.score_breakdown.as_ref().unwrap();
+        assert_eq!(breakdown.key_transitions.len(), result.tracks.len() - 1);
+        for (i, track) in result.tracks.iter().enumerate().skip(1) {
+            assert_eq!(track.key_transition, breakdown.key_transitions[i - 1]);
+        }
+
+        // Labels are derived from neighbours, so a plain read returns them too
+        let reread = get_setlist(&pool, &id).await.unwrap();
+        let labels: Vec<_> = reread.tracks.iter().map(|t| t.key_transition).collect();
+        let arranged: Vec<_> = result.tracks.iter().map(|t| t.key_transition).collect();
+        assert_eq!(labels, arranged);
+
+        // Transition notes are rewritten for the new neighbours and stored
+        assert!(result.tracks[0].transition_note.is_none());
+        assert!(result.tracks[1..]
+            .iter()
+            .all(|t| t.transition_note.is_some()));
+        let notes: Vec<_> = reread.tracks.iter().map(|t| &t.transition_note).collect();
+        let arranged: Vec<_> = result.tracks.iter().map(|t| &t.transition_note).collect();
+        assert_eq!(notes, arranged);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_lint_setlist_checks_requested_version() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        sqlx::query("UPDATE setlist_tracks SET confidence = 'low' WHERE id = 't7-2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let report = lint_setlist(&pool, &id, None).await.unwrap();
+        assert_eq!(report.version_number, None);
+        assert_eq!(report.report.track_count, 5);
+        let kinds: Vec<_> = report.report.findings.iter().map(|f| f.kind).collect();
+        assert!(kinds.contains(&lint::FindingKind::LowCatalogCoverage));
+        assert!(kinds.contains(&lint::FindingKind::LowConfidence));
+
+        // A reversed version keeps its verification results by original position
+        let claude = test_utils::MockClaude {
+            response: String::new(),
+        };
+        crate::services::refinement::refine_setlist(&pool, &claude, &id, "user1", "reverse")
+            .await
+            .unwrap();
+        let report = lint_setlist(&pool, &id, None).await.unwrap();
+        assert_eq!(report.version_number, Some(1));
+        let low = report
+            .report
+            .findings
+            .iter()
+            .find(|f| f.kind == lint::FindingKind::LowConfidence)
+            .unwrap();
+        assert_eq!(low.positions, vec![4]);
+
+        let report = lint_setlist(&pool, &id, Some(0)).await.unwrap();
+        assert_eq!(report.version_number, Some(0));
+        let err = lint_setlist(&pool, &id, Some(9)).await.unwrap_err();
+        assert!(matches!(err, SetlistError::NotFound(_)));
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_arrange_reports_solver() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let greedy = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+        let report = greedy.arrangement.unwrap();
+        assert_eq!(report.solver, Solver::Greedy);
+        assert_eq!(report.improvement_over_greedy, 0.0);
+
+        let config = parse_solver_config(Some("auto"), Some(1000)).unwrap();
+        let exact = arrange_setlist(&pool, &id, None, None, None, &[], config)
+            .await
+            .unwrap();
+        let report = exact.arrangement.unwrap();
+        assert_eq!(report.solver, Solver::HeldKarp);
+        assert!(exact.harmonic_flow_score.unwrap() >= report.greedy_flow_score - 1e-9);
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_artist_separation_warns_and_arrange_spreads() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        sqlx::query("UPDATE setlist_tracks SET artist = 'Artist D feat. Guest' WHERE id = 't7-1'")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        // Positions 1 and 4 share Artist D: fine at the default gap of 1
+        let before = get_setlist(&pool, &id).await.unwrap();
+        assert!(before.artist_warnings.is_empty());
+
+        crate::db::settings::set_artist_separation(&pool, "user1", 3)
+            .await
+            .unwrap();
+        let before = get_setlist(&pool, &id).await.unwrap();
+        assert_eq!(before.artist_warnings.len(), 1);
+        let warning = &before.artist_warnings[0];
+        assert_eq!((warning.from_position, warning.to_position), (1, 4));
+        assert_eq!(warning.artist, "artist d");
+        assert_eq!(warning.gap, 3);
+
+        // Five tracks and a gap of 3: the two must open and close the set
+        let result = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+        assert!(result.artist_warnings.is_empty());
+        let ends = [
+            result.tracks[0].artist.as_str(),
+            result.tracks[4].artist.as_str(),
+        ];
+        assert!(ends.contains(&"Artist D"), "{ends:?}");
+        assert!(get_setlist(&pool, &id)
+            .await
+            .unwrap()
+            .artist_warnings
+            .is_empty());
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_key_notation_preference_rewrites_keys() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let stored = get_setlist(&pool, &id).await.unwrap();
+        assert!(stored.tracks.iter().all(|t| t.key.is_none()));
+
+        crate::db::settings::set_key_notation(&pool, "user1", KeyNotation::Musical)
+            .await
+            .unwrap();
+        let musical = get_setlist(&pool, &id).await.unwrap();
+        assert_eq!(musical.tracks[0].key.as_deref(), Some("A minor"));
+        // The canonical Camelot key is untouched
+        assert_eq!(musical.tracks[0].camelot.as_deref(), Some("8A"));
+
+        crate::db::settings::set_key_notation(&pool, "user1", KeyNotation::OpenKey)
+            .await
+            .unwrap();
+        let arranged = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+        for t in &arranged.tracks {
+            let camelot = parse_camelot(t.camelot.as_deref().unwrap()).unwrap();
+            assert_eq!(t.key.as_deref(), Some(camelot.open_key().as_str()));
+        }
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_arrange_uses_stored_scoring_profile() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let profile = ScoringProfile {
+            key_weight: 0.0,
+            bpm_weight: 1.0,
+            energy_weight: 0.0,
+            bpm_bands: crate::services::camelot::BpmBands {
+                perfect: 20.0,
+                close: 21.0,
+                workable: 22.0,
+                stretch: 23.0,
+            },
+            bpm_warning_threshold: 1.0,
+        };
+        crate::db::settings::set_scoring_profile(&pool, "user1", Some(&profile))
+            .await
+            .unwrap();
+
+        let result = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+        // Every tempo change fits the widened band, so BPM-only flow is perfect
+        assert!((result.harmonic_flow_score.unwrap() - 100.0).abs() < 1e-9);
+        // ...but every change is over the 1 BPM warning threshold
+        assert_eq!(result.bpm_warnings.len(), 4);
+
+        let reread = get_setlist(&pool, &id).await.unwrap();
+        assert_eq!(reread.bpm_warnings.len(), 4);
+        pool.close().await;
+    }
+
+    #[test]
+    fn test_parse_solver_config() {
+        assert_eq!(
+            parse_solver_config(None, None).unwrap(),
+            SolverConfig::default()
+        );
+        let config = parse_solver_config(Some("or-opt"), Some(500)).unwrap();
+        assert_eq!(config.kind, Solver::OrOpt);
+        assert_eq!(config.time_budget, Duration::from_millis(500));
+        assert!(matches!(
+            parse_solver_config(Some("tabu"), None),
+            Err(SetlistError::InvalidRequest(_))
+        ));
+        assert!(parse_solver_config(None, Some(0)).is_err());
+        assert!(parse_solver_config(None, Some(60_000)).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_arrange_with_explicit_profile() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let result = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::WarmUp),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(result.tracks.len(), 5);
+        assert!(result.harmonic_flow_score.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_arrange_reads_stored_profile() {
+        let (pool, id) = setup_setlist_for_arrange(Some("warm-up")).await;
+
+        // Call with None — should read "warm-up" from stored setlist
+        let result_stored =
+            arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+                .await
+                .unwrap();
+
+        // Call with explicit WarmUp — should produce same result
+        let result_explicit = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::WarmUp),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        // Both should produce the same track ordering
+        let stored_order: Vec<String> = result_stored
+            .tracks
+            .iter()
+            .map(|t| t.title.clone())
+            .collect();
+        let explicit_order: Vec<String> = result_explicit
+            .tracks
+            .iter()
+            .map(|t| t.title.clone())
+            .collect();
+        assert_eq!(stored_order, explicit_order);
+    }
+
+    fn double_peak_curve() -> EnergyCurve {
+        use crate::services::camelot::EnergyBreakpoint;
+        EnergyCurve {
+            breakpoints: vec![
+                EnergyBreakpoint {
+                    position: 0.0,
+                    energy: 3.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.25,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.5,
+                    energy: 4.0,
+                },
+                EnergyBreakpoint {
+                    position: 0.75,
+                    energy: 9.0,
+                },
+                EnergyBreakpoint {
+                    position: 1.0,
+                    energy: 3.0,
+                },
+            ],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_arrange_reads_stored_energy_curve() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let curve = double_peak_curve();
+        sqlx::query("UPDATE setlists SET energy_curve = $1 WHERE id = $2")
+            .bind(serde_json::to_string(&curve).unwrap())
+            .bind(&id)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        let stored = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
+        let explicit = arrange_setlist(
+            &pool,
+            &id,
+            None,
+            Some(curve.clone()),
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        let default = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::Steady),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(stored.energy_curve.as_ref(), Some(&curve));
+        let energy = |r: &SetlistResponse| r.score_breakdown.as_ref().unwrap().energy_arc;
+        assert!((energy(&stored) - energy(&explicit)).abs() < 1e-9);
+        // An explicit profile replaces the stored curve
+        assert!(default.energy_curve.is_none());
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_arrange_rejects_invalid_energy_curve() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let mut curve = double_peak_curve();
+        curve.breakpoints.truncate(1);
+
+        let err = arrange_setlist(
+            &pool,
+            &id,
+            None,
+            Some(curve),
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidEnergyCurve(_)));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_resolve_energy_curve_by_saved_id() {
+        let pool = crate::db::create_test_pool().await;
+        let curve = double_peak_curve();
+        crate::db::energy_curves::create_energy_curve(
+            &pool,
+            "curve-1",
+            "user1",
+            "Double peak",
+            &serde_json::to_string(&curve.breakpoints).unwrap(),
+        )
+        .await
+        .unwrap();
+
+        let resolved = resolve_energy_curve(&pool, "user1", None, Some("curve-1"))
+            .await
+            .unwrap();
+        assert_eq!(resolved, Some(curve.clone()));
+
+        // Another user's curve is invisible
+        let err = resolve_energy_curve(&pool, "user2", None, Some("curve-1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::NotFound(_)));
+
+        let err = resolve_energy_curve(&pool, "user1", Some(curve), Some("curve-1"))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidEnergyCurve(_)));
+
+        pool.close().await;
     }
 
     #[tokio::test]
-    async fn test_arrange_with_explicit_profile() {
+    async fn test_arrange_respects_track_constraints() {
         let (pool, id) = setup_setlist_for_arrange(None).await;
-        let result = arrange_setlist(&pool, &id, Some(EnergyProfile::WarmUp))
-            .await
-            .unwrap();
-        assert_eq!(result.tracks.len(), 5);
-        assert!(result.harmonic_flow_score.is_some());
+        // Position 2 is "Track High"; make it the opener, and close on "Track Low"
+        let constraints = vec![
+            TrackConstraint {
+                track_position: 2,
+                rule: PositionConstraint::First,
+            },
+            TrackConstraint {
+                track_position: 1,
+                rule: PositionConstraint::Last,
+            },
+            TrackConstraint {
+                track_position: 3,
+                rule: PositionConstraint::Pinned { position: 3 },
+            },
+        ];
+        let result = arrange_setlist(
+            &pool,
+            &id,
+            None,
+            None,
+            None,
+            &constraints,
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        let titles: Vec<&str> = result.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles[0], "Track High");
+        assert_eq!(titles[2], "Track Mid");
+        assert_eq!(titles[4], "Track Low");
+
+        pool.close().await;
     }
 
     #[tokio::test]
-    async fn test_arrange_reads_stored_profile() {
-        let (pool, id) = setup_setlist_for_arrange(Some("warm-up")).await;
+    async fn test_arrange_reports_infeasible_constraints() {
+        let (pool, id) = setup_setlist_for_arrange(None).await;
+        let constraints = vec![
+            TrackConstraint {
+                track_position: 1,
+                rule: PositionConstraint::First,
+            },
+            TrackConstraint {
+                track_position: 2,
+                rule: PositionConstraint::Window { min: 1, max: 1 },
+            },
+        ];
+        let err = arrange_setlist(
+            &pool,
+            &id,
+            None,
+            None,
+            None,
+            &constraints,
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SetlistError::InfeasibleConstraints(_)));
 
-        // Call with None — should read "warm-up" from stored setlist
-        let result_stored = arrange_setlist(&pool, &id, None).await.unwrap();
+        let unknown = vec![TrackConstraint {
+            track_position: 9,
+            rule: PositionConstraint::First,
+        }];
+        let err = arrange_setlist(
+            &pool,
+            &id,
+            None,
+            None,
+            None,
+            &unknown,
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidRequest(_)));
 
-        // Call with explicit WarmUp — should produce same result
-        let result_explicit = arrange_setlist(&pool, &id, Some(EnergyProfile::WarmUp))
-            .await
-            .unwrap();
+        pool.close().await;
+    }
 
-        // Both should produce the same track ordering
-        let stored_order: Vec<String> = result_stored
-            .tracks
-            .iter()
-            .map(|t| t.title.clone())
-            .collect();
-        let explicit_order: Vec<String> = result_explicit
-            .tracks
-            .iter()
-            .map(|t| t.title.clone())
-            .collect();
-        assert_eq!(stored_order, explicit_order);
+    #[test]
+    fn test_track_constraint_deserializes_flat() {
+        let c: TrackConstraint = serde_json::from_value(serde_json::json!({
+            "track_position": 4,
+            "type": "window",
+            "min": 2,
+            "max": 5
+        }))
+        .unwrap();
+        assert_eq!(c.track_position, 4);
+        assert_eq!(c.rule, PositionConstraint::Window { min: 2, max: 5 });
     }
 
     #[tokio::test]
     async fn test_arrange_pre_st006_setlist_uses_default() {
         // No stored profile, no explicit profile → default energy arc
         let (pool, id) = setup_setlist_for_arrange(None).await;
-        let result = arrange_setlist(&pool, &id, None).await.unwrap();
+        let result = arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+            .await
+            .unwrap();
         assert_eq!(result.tracks.len(), 5);
         assert!(result.harmonic_flow_score.is_some());
         // Should still produce a valid arrangement
@@ -1826,10 +3844,21 @@ mod tests {
         // Store "steady" but pass "warm-up" explicitly
         let (pool, id) = setup_setlist_for_arrange(Some("steady")).await;
 
-        let result_override = arrange_setlist(&pool, &id, Some(EnergyProfile::WarmUp))
-            .await
-            .unwrap();
-        let result_stored = arrange_setlist(&pool, &id, None).await.unwrap();
+        let result_override = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::WarmUp),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        let result_stored =
+            arrange_setlist(&pool, &id, None, None, None, &[], SolverConfig::default())
+                .await
+                .unwrap();
 
         let override_order: Vec<String> = result_override
             .tracks
@@ -1856,12 +3885,28 @@ mod tests {
     async fn test_arrange_different_profiles_affect_scoring() {
         let (pool, id) = setup_setlist_for_arrange(None).await;
 
-        let result_warmup = arrange_setlist(&pool, &id, Some(EnergyProfile::WarmUp))
-            .await
-            .unwrap();
-        let result_peaktime = arrange_setlist(&pool, &id, Some(EnergyProfile::PeakTime))
-            .await
-            .unwrap();
+        let result_warmup = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::WarmUp),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        let result_peaktime = arrange_setlist(
+            &pool,
+            &id,
+            Some(EnergyProfile::PeakTime),
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
 
         // Different profiles should produce different energy arc scores
         let warmup_energy = result_warmup.score_breakdown.as_ref().unwrap().energy_arc;
@@ -1889,13 +3934,18 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "warm up set".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: Some(EnergyProfile::WarmUp),
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -1909,15 +3959,161 @@ mod tests {
         assert_eq!(row.energy_profile.as_deref(), Some("warm-up"));
     }
 
+    #[tokio::test]
+    async fn test_generate_persists_energy_curve() {
+        let pool = setup_pool_with_tracks().await;
+        let claude = MockClaude {
+            response: valid_llm_json(None),
+        };
+        let curve = double_peak_curve();
+        let req = GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "festival set".to_string(),
+            track_count: None,
+            target_duration_minutes: None,
+            energy_profile: None,
+            energy_curve: Some(curve.clone()),
+            key_path: None,
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        };
+        let resp = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap();
+        assert_eq!(resp.energy_curve.as_ref(), Some(&curve));
+
+        let reread = get_setlist(&pool, &resp.id).await.unwrap();
+        assert_eq!(reread.energy_curve, Some(curve));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_persists_key_path_and_arrange_scores_it() {
+        use crate::services::camelot::KeyWaypoint;
+
+        let pool = setup_pool_with_tracks().await;
+        let claude = MockClaude {
+            response: valid_llm_json(None),
+        };
+        let path = KeyPath {
+            waypoints: vec![
+                KeyWaypoint {
+                    position: 0.0,
+                    key: "5A".to_string(),
+                },
+                KeyWaypoint {
+                    position: 1.0,
+                    key: "10B".to_string(),
+                },
+            ],
+        };
+        let req = GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "climbing set".to_string(),
+            track_count: None,
+            target_duration_minutes: None,
+            energy_profile: None,
+            energy_curve: None,
+            key_path: Some(path.clone()),
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        };
+        let resp = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap();
+        assert_eq!(resp.key_path.as_ref(), Some(&path));
+
+        // Arrange without a path picks up the stored one
+        let arranged = arrange_setlist(
+            &pool,
+            &resp.id,
+            None,
+            None,
+            None,
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap();
+        assert_eq!(arranged.key_path, Some(path.clone()));
+        assert!(arranged.score_breakdown.unwrap().key_journey.is_some());
+
+        let mut bad = path;
+        bad.waypoints[1].position = 0.0;
+        let err = arrange_setlist(
+            &pool,
+            &resp.id,
+            None,
+            None,
+            Some(bad),
+            &[],
+            SolverConfig::default(),
+        )
+        .await
+        .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidKeyPath(_)));
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_rejects_profile_and_curve_together() {
+        let pool = setup_pool_with_tracks().await;
+        let claude = MockClaude {
+            response: valid_llm_json(None),
+        };
+        let req = GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "festival set".to_string(),
+            track_count: None,
+            target_duration_minutes: None,
+            energy_profile: Some(EnergyProfile::Journey),
+            energy_curve: Some(double_peak_curve()),
+            key_path: None,
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        };
+        let err = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidEnergyCurve(_)));
+
+        pool.close().await;
+    }
+
     #[tokio::test]
     async fn test_generate_with_source_playlist_filters_catalog() {
         let pool = setup_pool_with_tracks().await;
         let user_id = crate::db::create_test_user(&pool).await;
 
         // Create an import with linked tracks
-        crate::db::imports::create_import(&pool, "imp-gen", &user_id, "pl1", Some("Test PL"))
-            .await
-            .unwrap();
+        crate::db::imports::create_import(
+            &pool,
+            "imp-gen",
+            &user_id,
+            &crate::services::import::ImportOrigin::new("spotify", "pl1", Some("Test PL")),
+        )
+        .await
+        .unwrap();
         crate::db::imports::insert_import_tracks(&pool, "imp-gen", &["t1".to_string()])
             .await
             .unwrap();
@@ -1929,13 +4125,18 @@ mod tests {
             user_id: user_id.clone(),
             prompt: "test playlist filter".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: Some("imp-gen".to_string()),
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -1949,9 +4150,14 @@ mod tests {
         let user_id = crate::db::create_test_user(&pool).await;
 
         // Create an import with no linked tracks
-        crate::db::imports::create_import(&pool, "imp-empty", &user_id, "pl2", None)
-            .await
-            .unwrap();
+        crate::db::imports::create_import(
+            &pool,
+            "imp-empty",
+            &user_id,
+            &crate::services::import::ImportOrigin::new("spotify", "pl2", None),
+        )
+        .await
+        .unwrap();
 
         let claude = MockClaude {
             response: valid_llm_json(None),
@@ -1960,13 +4166,18 @@ mod tests {
             user_id: user_id.clone(),
             prompt: "test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: Some("imp-empty".to_string()),
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let result = generate_setlist_from_request(&pool, &claude, req).await;
         assert!(matches!(result, Err(SetlistError::EmptyCatalog)));
@@ -1982,13 +4193,18 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: Some("nonexistent-playlist".to_string()),
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let result = generate_setlist_from_request(&pool, &claude, req).await;
         assert!(matches!(result, Err(SetlistError::PlaylistNotFound(_))));
@@ -2009,9 +4225,14 @@ mod tests {
             .unwrap();
 
         // Create import linking only the unenriched track
-        crate::db::imports::create_import(&pool, "imp-unenriched", &user_id, "pl3", None)
-            .await
-            .unwrap();
+        crate::db::imports::create_import(
+            &pool,
+            "imp-unenriched",
+            &user_id,
+            &crate::services::import::ImportOrigin::new("spotify", "pl3", None),
+        )
+        .await
+        .unwrap();
         crate::db::imports::insert_import_tracks(
             &pool,
             "imp-unenriched",
@@ -2027,13 +4248,18 @@ mod tests {
             user_id: user_id.clone(),
             prompt: "test ext 3c".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: Some("imp-unenriched".to_string()),
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2067,9 +4293,14 @@ mod tests {
             .unwrap();
 
         // Link both to import
-        crate::db::imports::create_import(&pool, "imp-partial", &user_id, "pl4", None)
-            .await
-            .unwrap();
+        crate::db::imports::create_import(
+            &pool,
+            "imp-partial",
+            &user_id,
+            &crate::services::import::ImportOrigin::new("spotify", "pl4", None),
+        )
+        .await
+        .unwrap();
         crate::db::imports::insert_import_tracks(
             &pool,
             "imp-partial",
@@ -2085,13 +4316,18 @@ mod tests {
             user_id: user_id.clone(),
             prompt: "test ext 24b".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: Some("imp-partial".to_string()),
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2115,8 +4351,12 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "house set".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: Some(
                 "1. Daft Punk - Around the World\n2. Chemical Brothers".to_string(),
             ),
@@ -2124,6 +4364,7 @@ mod tests {
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         // Should not error — seed_tracklist is passed to Claude prompt
         let resp = generate_setlist_from_request(&pool, &claude, req)
@@ -2142,13 +4383,18 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "creative set".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: Some(true),
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2166,8 +4412,12 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "constrained bpm".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: Some(BpmRange {
@@ -2176,6 +4426,7 @@ mod tests {
             }),
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2193,8 +4444,12 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: Some(BpmRange {
@@ -2203,6 +4458,7 @@ mod tests {
             }),
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let result = generate_setlist_from_request(&pool, &claude, req).await;
         assert!(matches!(result, Err(SetlistError::InvalidBpmRange(_))));
@@ -2218,8 +4474,12 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: Some(BpmRange {
@@ -2228,6 +4488,7 @@ mod tests {
             }),
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let result = generate_setlist_from_request(&pool, &claude, req).await;
         assert!(matches!(result, Err(SetlistError::InvalidBpmRange(_))));
@@ -2243,8 +4504,12 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: Some(BpmRange {
@@ -2253,6 +4518,7 @@ mod tests {
             }),
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let result = generate_setlist_from_request(&pool, &claude, req).await;
         assert!(matches!(result, Err(SetlistError::InvalidBpmRange(_))));
@@ -2269,13 +4535,18 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "catalog test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2296,13 +4567,18 @@ mod tests {
             user_id: "user1".to_string(),
             prompt: "suggestion test".to_string(),
             track_count: None,
+            target_duration_minutes: None,
             energy_profile: None,
+            energy_curve: None,
+            key_path: None,
             source_playlist_id: None,
+            source_crate_id: None,
             seed_tracklist: None,
             creative_mode: None,
             bpm_range: None,
             verify: false,
             name: None,
+            constraints: TrackConstraints::default(),
         };
         let resp = generate_setlist_from_request(&pool, &claude, req)
             .await
@@ -2357,6 +4633,7 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
             SetlistTrackRow {
                 id: "2".into(),
@@ -2378,9 +4655,10 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
         ];
-        let warnings = compute_bpm_warnings(&tracks);
+        let warnings = compute_bpm_warnings(&tracks, &ScoringProfile::default());
         assert!(
             warnings.is_empty(),
             "Deltas <= 6 should produce no warnings"
@@ -2410,6 +4688,7 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
             SetlistTrackRow {
                 id: "2".into(),
@@ -2431,13 +4710,35 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
         ];
-        let warnings = compute_bpm_warnings(&tracks);
+        let warnings = compute_bpm_warnings(&tracks, &ScoringProfile::default());
         assert_eq!(warnings.len(), 1);
         assert_eq!(warnings[0].from_position, 1);
         assert_eq!(warnings[0].to_position, 2);
         assert!((warnings[0].bpm_delta - 8.5).abs() < 0.01);
+        assert_eq!(warnings[0].tempo_relation, TempoRelation::Direct);
+        assert!((warnings[0].effective_bpm_delta - 8.5).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_bpm_warnings_allow_double_time() {
+        let mut tracks = vec![
+            make_response_track(1, "A", "X", None),
+            make_response_track(2, "B", "Y", None),
+            make_response_track(3, "C", "Z", None),
+        ];
+        tracks[0].bpm = Some(87.0);
+        tracks[1].bpm = Some(174.0);
+        tracks[2].bpm = Some(160.0);
+
+        let warnings = compute_bpm_warnings_from_responses(&tracks, &ScoringProfile::default());
+        // 87 → 174 is a clean double-time mix; 174 → 160 is a real jump
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].from_position, 2);
+        assert_eq!(warnings[0].tempo_relation, TempoRelation::Direct);
+        assert!((warnings[0].effective_bpm_delta + 14.0).abs() < 0.01);
     }
 
     #[test]
@@ -2463,6 +4764,7 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
             SetlistTrackRow {
                 id: "2".into(),
@@ -2484,9 +4786,10 @@ mod tests {
                 confidence: None,
                 verification_flag: None,
                 verification_note: None,
+                duration_ms: None,
             },
         ];
-        let warnings = compute_bpm_warnings(&tracks);
+        let warnings = compute_bpm_warnings(&tracks, &ScoringProfile::default());
         assert!(warnings.is_empty(), "Missing BPM should be skipped");
     }
 
@@ -2502,6 +4805,11 @@ mod tests {
             energy: None,
             transition_note: None,
             transition_score: None,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: None,
+            start_time_ms: None,
             original_position: 1,
             source: "catalog".into(),
             track_id: Some("t1".into()),
@@ -2525,6 +4833,11 @@ mod tests {
             energy: None,
             transition_note: None,
             transition_score: None,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: None,
+            start_time_ms: None,
             original_position: 1,
             source: "suggestion".into(),
             track_id: None,
@@ -2570,6 +4883,7 @@ mod tests {
             confidence: None,
             verification_flag: None,
             verification_note: None,
+            duration_ms: None,
         }];
         assert_eq!(compute_seed_match_count("Desert Rose", &tracks), 1);
     }
@@ -2596,6 +4910,7 @@ mod tests {
             confidence: None,
             verification_flag: None,
             verification_note: None,
+            duration_ms: None,
         }];
         assert_eq!(
             compute_seed_match_count("desert rose", &tracks),
@@ -2626,6 +4941,7 @@ mod tests {
             confidence: None,
             verification_flag: None,
             verification_note: None,
+            duration_ms: None,
         }];
         assert_eq!(compute_seed_match_count("Desert Rose", &tracks), 0);
     }
@@ -2650,6 +4966,11 @@ mod tests {
             energy: None,
             transition_note: None,
             transition_score: None,
+            key_transition: None,
+            tempo_relation: None,
+            pitch_percent: None,
+            duration_ms: None,
+            start_time_ms: None,
             original_position: position,
             source: "suggestion".into(),
             track_id: None,
@@ -2660,6 +4981,112 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_annotate_key_transitions() {
+        let mut tracks = vec![
+            make_response_track(1, "A", "X", None),
+            make_response_track(2, "B", "Y", None),
+            make_response_track(3, "C", "Z", None),
+            make_response_track(4, "D", "W", None),
+        ];
+        tracks[0].camelot = Some("8A".into());
+        tracks[1].camelot = Some("3A".into());
+        tracks[2].camelot = None;
+        tracks[3].camelot = Some("4A".into());
+
+        annotate_key_transitions(&mut tracks, true);
+
+        assert_eq!(tracks[0].key_transition, None);
+        assert_eq!(tracks[1].key_transition, Some(TransitionType::EnergyBoost));
+        assert_eq!(tracks[2].key_transition, None);
+        assert_eq!(tracks[3].key_transition, None);
+
+        let json = serde_json::to_value(&tracks[1]).unwrap();
+        assert_eq!(json["key_transition"], "energy-boost");
+    }
+
+    #[test]
+    fn test_annotate_key_transitions_without_key_lock() {
+        let mut tracks = vec![
+            make_response_track(1, "A", "X", None),
+            make_response_track(2, "B", "Y", None),
+        ];
+        tracks[0].camelot = Some("8A".into());
+        tracks[0].bpm = Some(128.0);
+        tracks[1].camelot = Some("1A".into());
+        tracks[1].bpm = Some(121.0);
+
+        annotate_key_transitions(&mut tracks, true);
+        assert_eq!(tracks[1].key_transition, Some(TransitionType::Clash));
+
+        // +5.8% on the pitch fader lifts 1A to 8A
+        annotate_key_transitions(&mut tracks, false);
+        assert_eq!(tracks[1].key_transition, Some(TransitionType::SameKey));
+    }
+
+    #[test]
+    fn test_annotate_tempo_relations() {
+        let mut tracks = vec![
+            make_response_track(1, "A", "X", None),
+            make_response_track(2, "B", "Y", None),
+            make_response_track(3, "C", "Z", None),
+            make_response_track(4, "D", "W", None),
+        ];
+        tracks[0].bpm = Some(140.0);
+        tracks[1].bpm = Some(70.0);
+        tracks[2].bpm = None;
+        tracks[3].bpm = Some(128.0);
+
+        annotate_tempo_relations(&mut tracks);
+
+        assert_eq!(tracks[0].tempo_relation, None);
+        assert_eq!(tracks[1].tempo_relation, Some(TempoRelation::HalfTime));
+        assert_eq!(tracks[2].tempo_relation, None);
+        assert_eq!(tracks[3].tempo_relation, None);
+        assert_eq!(tracks[1].pitch_percent, Some(0.0));
+        assert_eq!(tracks[3].pitch_percent, None);
+
+        let json = serde_json::to_value(&tracks[1]).unwrap();
+        assert_eq!(json["tempo_relation"], "half-time");
+    }
+
+    #[test]
+    fn test_filter_catalog_by_bpm_range_keeps_half_and_double_time() {
+        let track = |id: &str, bpm: Option<f64>| TrackRow {
+            id: id.to_string(),
+            title: id.to_string(),
+            artist: None,
+            album: None,
+            duration_ms: None,
+            bpm,
+            camelot_key: None,
+            energy: None,
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        };
+        let catalog = vec![
+            track("direct", Some(172.0)),
+            track("half", Some(87.0)),
+            track("out", Some(128.0)),
+            track("unknown", None),
+        ];
+        let range = BpmRange {
+            min: 170.0,
+            max: 176.0,
+        };
+
+        let kept: Vec<String> = filter_catalog_by_bpm_range(catalog, &range)
+            .into_iter()
+            .map(|t| t.id)
+            .collect();
+        assert_eq!(kept, vec!["direct", "half", "unknown"]);
+    }
+
     #[tokio::test]
     async fn test_verify_setlist_propagates_flag_and_note() {
         let tracks = vec![
@@ -2828,4 +5255,74 @@ mod tests {
             "Generation count should be incremented after success"
         );
     }
+
+    #[tokio::test]
+    async fn test_generate_repairs_constraint_violations() {
+        let pool = setup_pool_with_tracks().await;
+        sqlx::query(
+            "INSERT INTO tracks (id, title, source, bpm, camelot_key) VALUES ('t2', 'Sandstorm', 'spotify', 102.0, '9A')",
+        )
+        .execute(&pool)
+        .await
+        .unwrap();
+        sqlx::query("INSERT INTO artists (id, name) VALUES ('a2', 'Darude')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO track_artists (track_id, artist_id) VALUES ('t2', 'a2')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        // The model ignores the rules both times it is asked
+        let claude = MockClaude {
+            response: valid_llm_json(Some("t1")),
+        };
+        let mut req = timed_request(60);
+        req.target_duration_minutes = None;
+        req.track_count = Some(2);
+        req.constraints = TrackConstraints {
+            must_include: vec![TrackRef::Named {
+                artist: "Cheb Khaled".to_string(),
+                title: "Aicha".to_string(),
+            }],
+            exclude_artists: vec!["sting".to_string()],
+            ..Default::default()
+        };
+
+        let resp = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap();
+        let titles: Vec<&str> = resp.tracks.iter().map(|t| t.title.as_str()).collect();
+        assert_eq!(titles, vec!["Sandstorm", "Aicha"]);
+        assert_eq!(resp.tracks[0].source, "catalog");
+        assert_eq!(resp.tracks[1].source, "suggestion");
+        let notes = resp.notes.unwrap();
+        assert!(notes.contains("excluded artist sting"), "{notes}");
+    }
+
+    #[tokio::test]
+    async fn test_generate_rejects_unfulfillable_constraints() {
+        let pool = setup_pool_with_tracks().await;
+        let claude = MockClaude {
+            response: valid_llm_json(Some("t1")),
+        };
+        let mut req = timed_request(60);
+        req.target_duration_minutes = None;
+        req.track_count = Some(2);
+        req.constraints.must_include = vec![
+            TrackRef::Id("t1".to_string()),
+            TrackRef::Named {
+                artist: "A".to_string(),
+                title: "B".to_string(),
+            },
+            TrackRef::Named {
+                artist: "C".to_string(),
+                title: "D".to_string(),
+            },
+        ];
+        let err = generate_setlist_from_request(&pool, &claude, req)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::InvalidRequest(_)));
+    }
 }
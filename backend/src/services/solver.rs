@@ -0,0 +1,713 @@
+use std::str::FromStr;
+use std::time::{Duration, Instant};
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Largest set Held-Karp will take on. The table has `2^n * n` cells, so 14
+/// tracks is ~230k cells and finishes well inside the default budget.
+pub const HELD_KARP_MAX_TRACKS: usize = 14;
+
+pub const DEFAULT_TIME_BUDGET: Duration = Duration::from_millis(250);
+pub const MAX_TIME_BUDGET: Duration = Duration::from_secs(5);
+
+/// Cost of one artist-separation violation. Transition scores are in 0-1, so
+/// removing a violation always outweighs any loss in flow.
+pub const SEPARATION_PENALTY: f64 = 2.0;
+
+/// Which algorithm improves the order after the greedy pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Solver {
+    /// Greedy nearest-neighbour plus 2-opt only.
+    #[default]
+    Greedy,
+    /// Exact dynamic programming; only for sets up to [`HELD_KARP_MAX_TRACKS`].
+    HeldKarp,
+    /// Simulated annealing over segment reversals and relocations.
+    Annealing,
+    /// Local search moving runs of 1-3 tracks to a better slot.
+    OrOpt,
+    /// Held-Karp when the set is small enough, annealing otherwise.
+    Auto,
+}
+
+impl Solver {
+    /// The concrete solver that runs for a set of `n` tracks.
+    pub fn resolve(self, n: usize) -> Solver {
+        match self {
+            Solver::Auto if n <= HELD_KARP_MAX_TRACKS => Solver::HeldKarp,
+            Solver::Auto => Solver::Annealing,
+            other => other,
+        }
+    }
+}
+
+impl FromStr for Solver {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "greedy" => Ok(Solver::Greedy),
+            "held-karp" => Ok(Solver::HeldKarp),
+            "annealing" => Ok(Solver::Annealing),
+            "or-opt" => Ok(Solver::OrOpt),
+            "auto" => Ok(Solver::Auto),
+            other => Err(format!(
+                "Unknown solver '{other}'. Valid: greedy, held-karp, annealing, or-opt, auto"
+            )),
+        }
+    }
+}
+
+/// Solver choice plus how long it may run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolverConfig {
+    pub kind: Solver,
+    pub time_budget: Duration,
+}
+
+impl Default for SolverConfig {
+    fn default() -> Self {
+        Self {
+            kind: Solver::default(),
+            time_budget: DEFAULT_TIME_BUDGET,
+        }
+    }
+}
+
+/// A set to order: directional transition scores between every pair of
+/// tracks, and the 0-based inclusive slot range each track may occupy.
+pub struct Problem<'a> {
+    pub scores: Vec<Vec<f64>>,
+    /// `placement[i][pos]` scores track `i` sitting at slot `pos` (e.g., how
+    /// well its energy fits the arc there); `None` means position is free.
+    pub placement: Option<Vec<Vec<f64>>>,
+    pub ranges: &'a [(usize, usize)],
+    /// Pairs that must not land close together; `None` means no rule.
+    pub separation: Option<Separation>,
+}
+
+/// Tracks `a` and `b` with `conflicts[a][b]` (e.g., a shared artist) may not
+/// sit `min_gap` or fewer slots apart.
+pub struct Separation {
+    pub conflicts: Vec<Vec<bool>>,
+    pub min_gap: usize,
+}
+
+impl Problem<'_> {
+    pub fn track_count(&self) -> usize {
+        self.scores.len()
+    }
+
+    /// Sum of transition scores along `order`.
+    pub fn flow(&self, order: &[usize]) -> f64 {
+        order.windows(2).map(|w| self.scores[w[0]][w[1]]).sum()
+    }
+
+    /// Sum of placement scores along `order`.
+    pub fn placement(&self, order: &[usize]) -> f64 {
+        order
+            .iter()
+            .enumerate()
+            .map(|(pos, &i)| self.place(i, pos))
+            .sum()
+    }
+
+    /// The objective every solver maximises: [`Problem::flow`] plus
+    /// [`Problem::placement`], less [`SEPARATION_PENALTY`] per separation
+    /// violation.
+    pub fn total(&self, order: &[usize]) -> f64 {
+        self.flow(order) + self.placement(order)
+            - SEPARATION_PENALTY * self.violations(order) as f64
+    }
+
+    /// Placement score of track `i` at slot `pos`.
+    pub fn place(&self, i: usize, pos: usize) -> f64 {
+        self.placement.as_ref().map_or(0.0, |p| p[i][pos])
+    }
+
+    /// Number of conflicting pairs sitting within the separation gap.
+    pub fn violations(&self, order: &[usize]) -> usize {
+        let Some(sep) = &self.separation else {
+            return 0;
+        };
+        (0..order.len())
+            .flat_map(|i| (i + 1..order.len().min(i + sep.min_gap + 1)).map(move |j| (i, j)))
+            .filter(|&(i, j)| sep.conflicts[order[i]][order[j]])
+            .count()
+    }
+
+    /// Violations between a track in `order[i..=j]` and one outside it.
+    /// Reversing that segment keeps every other pair's distance, so these
+    /// are the only violations the reversal can change.
+    pub fn segment_violations(&self, order: &[usize], i: usize, j: usize) -> usize {
+        let Some(sep) = &self.separation else {
+            return 0;
+        };
+        let last = order.len() - 1;
+        (i..=j)
+            .flat_map(|p| {
+                (p.saturating_sub(sep.min_gap)..=(p + sep.min_gap).min(last))
+                    .filter(|&q| q < i || q > j)
+                    .map(move |q| (p, q))
+            })
+            .filter(|&(p, q)| sep.conflicts[order[p]][order[q]])
+            .count()
+    }
+
+    /// Score of the edge `a -> b`, penalised if the pair conflicts. Only
+    /// sees adjacent pairs; wider gaps are caught by [`Problem::total`].
+    fn edge(&self, a: usize, b: usize) -> f64 {
+        match &self.separation {
+            Some(sep) if sep.min_gap > 0 && sep.conflicts[a][b] => {
+                self.scores[a][b] - SEPARATION_PENALTY
+            }
+            _ => self.scores[a][b],
+        }
+    }
+
+    /// Does every track in `order` sit inside its allowed range?
+    pub fn fits(&self, order: &[usize]) -> bool {
+        order.iter().enumerate().all(|(pos, &i)| {
+            let (lo, hi) = self.ranges[i];
+            lo <= pos && pos <= hi
+        })
+    }
+}
+
+pub struct SolveOutcome {
+    pub order: Vec<usize>,
+    /// The budget ran out before the solver finished.
+    pub timed_out: bool,
+}
+
+/// Common interface for order-improvement solvers. `seed` is a valid order
+/// (the greedy result); implementations never return anything that scores
+/// worse than it or breaks a position range.
+pub trait OrderSolver {
+    fn solve(&self, problem: &Problem, seed: Vec<usize>, deadline: Instant) -> SolveOutcome;
+}
+
+/// The solver implementation for a resolved [`Solver`].
+pub fn solver_for(kind: Solver) -> Box<dyn OrderSolver> {
+    match kind {
+        Solver::Greedy | Solver::Auto => Box::new(Identity),
+        Solver::HeldKarp => Box::new(HeldKarp),
+        Solver::Annealing => Box::new(Annealing::default()),
+        Solver::OrOpt => Box::new(OrOpt),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Identity (greedy already ran)
+// ---------------------------------------------------------------------------
+
+struct Identity;
+
+impl OrderSolver for Identity {
+    fn solve(&self, _problem: &Problem, seed: Vec<usize>, _deadline: Instant) -> SolveOutcome {
+        SolveOutcome {
+            order: seed,
+            timed_out: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Held-Karp
+// ---------------------------------------------------------------------------
+
+/// Exact best open path. `best[mask][last]` is the highest score of a path
+/// visiting exactly `mask` and ending at `last`; a track may only be added at
+/// slot `popcount(mask)` if that slot is inside its range, and it scores its
+/// placement there. Separation is only
+/// exact for a gap of 1; wider gaps are enforced by comparing against the seed.
+struct HeldKarp;
+
+impl OrderSolver for HeldKarp {
+    fn solve(&self, problem: &Problem, seed: Vec<usize>, deadline: Instant) -> SolveOutcome {
+        let n = problem.track_count();
+        if !(3..=HELD_KARP_MAX_TRACKS).contains(&n) {
+            return SolveOutcome {
+                order: seed,
+                timed_out: false,
+            };
+        }
+
+        let full = (1usize << n) - 1;
+        let mut best = vec![vec![f64::NEG_INFINITY; n]; full + 1];
+        let mut parent = vec![vec![usize::MAX; n]; full + 1];
+        for i in 0..n {
+            if problem.ranges[i].0 == 0 {
+                best[1 << i][i] = problem.place(i, 0);
+            }
+        }
+
+        for mask in 1..=full {
+            if mask % 1024 == 0 && Instant::now() >= deadline {
+                return SolveOutcome {
+                    order: seed,
+                    timed_out: true,
+                };
+            }
+            let pos = mask.count_ones() as usize;
+            if pos == n {
+                continue;
+            }
+            for last in 0..n {
+                let score = best[mask][last];
+                if score == f64::NEG_INFINITY {
+                    continue;
+                }
+                for next in 0..n {
+                    let (lo, hi) = problem.ranges[next];
+                    if mask & (1 << next) != 0 || pos < lo || pos > hi {
+                        continue;
+                    }
+                    let candidate = score + problem.edge(last, next) + problem.place(next, pos);
+                    let cell = &mut best[mask | (1 << next)][next];
+                    if candidate > *cell {
+                        *cell = candidate;
+                        parent[mask | (1 << next)][next] = last;
+                    }
+                }
+            }
+        }
+
+        let Some(end) = (0..n)
+            .filter(|&i| best[full][i] > f64::NEG_INFINITY)
+            .max_by(|&a, &b| best[full][a].total_cmp(&best[full][b]))
+        else {
+            return SolveOutcome {
+                order: seed,
+                timed_out: false,
+            };
+        };
+
+        let mut order = Vec::with_capacity(n);
+        let (mut mask, mut last) = (full, end);
+        while last != usize::MAX {
+            order.push(last);
+            let prev = parent[mask][last];
+            mask &= !(1 << last);
+            last = prev;
+        }
+        order.reverse();
+
+        SolveOutcome {
+            order: keep_better(problem, seed, order),
+            timed_out: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Simulated annealing
+// ---------------------------------------------------------------------------
+
+/// Random segment reversals and relocations, accepting worse orders with a
+/// probability that cools geometrically. The RNG is seeded, so runs that
+/// finish inside the budget are reproducible.
+struct Annealing {
+    rng_seed: u64,
+    iterations_per_track: usize,
+    start_temperature: f64,
+    end_temperature: f64,
+}
+
+impl Default for Annealing {
+    fn default() -> Self {
+        Self {
+            rng_seed: 0x5e7_1157,
+            iterations_per_track: 2_000,
+            start_temperature: 0.1,
+            end_temperature: 0.001,
+        }
+    }
+}
+
+impl OrderSolver for Annealing {
+    fn solve(&self, problem: &Problem, seed: Vec<usize>, deadline: Instant) -> SolveOutcome {
+        let n = problem.track_count();
+        if n < 3 {
+            return SolveOutcome {
+                order: seed,
+                timed_out: false,
+            };
+        }
+
+        let mut rng = StdRng::seed_from_u64(self.rng_seed);
+        let iterations = self.iterations_per_track * n;
+        let cooling = (self.end_temperature / self.start_temperature).powf(1.0 / iterations as f64);
+
+        let mut current = seed.clone();
+        let mut current_score = problem.total(&current);
+        let mut best = seed;
+        let mut best_score = current_score;
+        let mut temperature = self.start_temperature;
+
+        for step in 0..iterations {
+            if step % 256 == 0 && Instant::now() >= deadline {
+                return SolveOutcome {
+                    order: best,
+                    timed_out: true,
+                };
+            }
+            temperature *= cooling;
+
+            let mut candidate = current.clone();
+            if rng.gen_bool(0.5) {
+                let i = rng.gen_range(0..n - 1);
+                let j = rng.gen_range(i + 1..n);
+                candidate[i..=j].reverse();
+            } else {
+                let len = rng.gen_range(1..=3.min(n - 1));
+                let from = rng.gen_range(0..=n - len);
+                let to = rng.gen_range(0..=n - len);
+                if from == to {
+                    continue;
+                }
+                relocate(&mut candidate, from, len, to);
+            }
+            if !problem.fits(&candidate) {
+                continue;
+            }
+
+            let score = problem.total(&candidate);
+            let delta = score - current_score;
+            if delta >= 0.0 || rng.gen::<f64>() < (delta / temperature).exp() {
+                current = candidate;
+                current_score = score;
+                if current_score > best_score {
+                    best = current.clone();
+                    best_score = current_score;
+                }
+            }
+        }
+
+        SolveOutcome {
+            order: best,
+            timed_out: false,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Or-opt
+// ---------------------------------------------------------------------------
+
+/// First-improvement local search: move a run of 1-3 consecutive tracks to
+/// any other slot, keeping the run's direction, until no move helps.
+struct OrOpt;
+
+impl OrderSolver for OrOpt {
+    fn solve(&self, problem: &Problem, seed: Vec<usize>, deadline: Instant) -> SolveOutcome {
+        let n = problem.track_count();
+        let mut order = seed;
+        let mut score = problem.total(&order);
+
+        loop {
+            let mut improved = false;
+            for len in 1..=3.min(n.saturating_sub(1)) {
+                for from in 0..=n - len {
+                    for to in 0..=n - len {
+                        if Instant::now() >= deadline {
+                            return SolveOutcome {
+                                order,
+                                timed_out: true,
+                            };
+                        }
+                        if to == from {
+                            continue;
+                        }
+                        let mut candidate = order.clone();
+                        relocate(&mut candidate, from, len, to);
+                        if !problem.fits(&candidate) {
+                            continue;
+                        }
+                        let candidate_score = problem.total(&candidate);
+                        if candidate_score > score + 1e-9 {
+                            order = candidate;
+                            score = candidate_score;
+                            improved = true;
+                        }
+                    }
+                }
+            }
+            if !improved {
+                return SolveOutcome {
+                    order,
+                    timed_out: false,
+                };
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Helpers
+// ---------------------------------------------------------------------------
+
+/// Move `order[from..from + len]` so that it starts at `to` in the result.
+fn relocate(order: &mut Vec<usize>, from: usize, len: usize, to: usize) {
+    let run: Vec<usize> = order.drain(from..from + len).collect();
+    order.splice(to..to, run);
+}
+
+/// `candidate` if it is valid and strictly better than `seed`, else `seed`.
+fn keep_better(problem: &Problem, seed: Vec<usize>, candidate: Vec<usize>) -> Vec<usize> {
+    if candidate.len() == seed.len()
+        && problem.fits(&candidate)
+        && problem.total(&candidate) > problem.total(&seed) + 1e-9
+    {
+        candidate
+    } else {
+        seed
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn far_future() -> Instant {
+        Instant::now() + Duration::from_secs(60)
+    }
+
+    /// Scores that reward `i -> i + 1` and punish everything else, so the
+    /// identity order is the unique optimum.
+    fn chain_problem(n: usize, ranges: &[(usize, usize)]) -> Problem<'_> {
+        let scores = (0..n)
+            .map(|i| (0..n).map(|j| if j == i + 1 { 1.0 } else { 0.1 }).collect())
+            .collect();
+        Problem {
+            scores,
+            placement: None,
+            ranges,
+            separation: None,
+        }
+    }
+
+    fn brute_force_best(problem: &Problem) -> f64 {
+        fn permute(problem: &Problem, order: &mut Vec<usize>, used: &mut [bool], best: &mut f64) {
+            if order.len() == used.len() {
+                if problem.fits(order) {
+                    *best = best.max(problem.total(order));
+                }
+                return;
+            }
+            for i in 0..used.len() {
+                if !used[i] {
+                    used[i] = true;
+                    order.push(i);
+                    permute(problem, order, used, best);
+                    order.pop();
+                    used[i] = false;
+                }
+            }
+        }
+        let mut best = f64::NEG_INFINITY;
+        permute(
+            problem,
+            &mut vec![],
+            &mut vec![false; problem.track_count()],
+            &mut best,
+        );
+        best
+    }
+
+    fn pseudo_random_problem(n: usize, ranges: &[(usize, usize)]) -> Problem<'_> {
+        let scores = (0..n)
+            .map(|i| {
+                (0..n)
+                    .map(|j| ((i * 7 + j * 13 + i * j) % 17) as f64 / 16.0)
+                    .collect()
+            })
+            .collect();
+        Problem {
+            scores,
+            placement: None,
+            ranges,
+            separation: None,
+        }
+    }
+
+    #[test]
+    fn test_solver_from_str() {
+        assert_eq!("held-karp".parse::<Solver>(), Ok(Solver::HeldKarp));
+        assert_eq!("or-opt".parse::<Solver>(), Ok(Solver::OrOpt));
+        assert!("tabu"
+            .parse::<Solver>()
+            .unwrap_err()
+            .contains("Unknown solver"));
+    }
+
+    #[test]
+    fn test_auto_resolves_by_size() {
+        assert_eq!(Solver::Auto.resolve(HELD_KARP_MAX_TRACKS), Solver::HeldKarp);
+        assert_eq!(
+            Solver::Auto.resolve(HELD_KARP_MAX_TRACKS + 1),
+            Solver::Annealing
+        );
+        assert_eq!(Solver::OrOpt.resolve(3), Solver::OrOpt);
+    }
+
+    #[test]
+    fn test_held_karp_matches_brute_force() {
+        let ranges = vec![(0, 6); 7];
+        let problem = pseudo_random_problem(7, &ranges);
+        let seed: Vec<usize> = (0..7).collect();
+        let out = HeldKarp.solve(&problem, seed, far_future());
+        assert!(!out.timed_out);
+        assert!((problem.total(&out.order) - brute_force_best(&problem)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_held_karp_scores_placement() {
+        let ranges = vec![(0, 6); 7];
+        let mut problem = pseudo_random_problem(7, &ranges);
+        // Strongly prefer track i at slot 6 - i: the reverse order
+        problem.placement = Some(
+            (0..7)
+                .map(|i| {
+                    (0..7)
+                        .map(|pos| if pos == 6 - i { 2.0 } else { 0.0 })
+                        .collect()
+                })
+                .collect(),
+        );
+        let seed: Vec<usize> = (0..7).collect();
+        let out = HeldKarp.solve(&problem, seed, far_future());
+        assert!((problem.total(&out.order) - brute_force_best(&problem)).abs() < 1e-9);
+        assert_eq!(out.order, (0..7).rev().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_held_karp_respects_ranges() {
+        // Track 0 must close the set, which rules out the natural chain.
+        let mut ranges = vec![(0, 5); 6];
+        ranges[0] = (5, 5);
+        let problem = pseudo_random_problem(6, &ranges);
+        let seed = vec![1, 2, 3, 4, 5, 0];
+        let out = HeldKarp.solve(&problem, seed, far_future());
+        assert!(problem.fits(&out.order));
+        assert_eq!(out.order[5], 0);
+        assert!((problem.total(&out.order) - brute_force_best(&problem)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_held_karp_expired_deadline_returns_seed() {
+        let ranges = vec![(0, 11); 12];
+        let problem = pseudo_random_problem(12, &ranges);
+        let seed: Vec<usize> = (0..12).rev().collect();
+        let out = HeldKarp.solve(&problem, seed.clone(), Instant::now());
+        assert!(out.timed_out);
+        assert_eq!(out.order, seed);
+    }
+
+    #[test]
+    fn test_annealing_and_or_opt_never_worse_than_seed() {
+        let ranges = vec![(0, 29); 30];
+        let problem = pseudo_random_problem(30, &ranges);
+        let seed: Vec<usize> = (0..30).collect();
+        let seed_score = problem.total(&seed);
+        for kind in [Solver::Annealing, Solver::OrOpt] {
+            let out = solver_for(kind).solve(&problem, seed.clone(), far_future());
+            assert_eq!(out.order.len(), 30);
+            assert!(problem.total(&out.order) >= seed_score, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_or_opt_repairs_displaced_track() {
+        let ranges = vec![(0, 7); 8];
+        let problem = chain_problem(8, &ranges);
+        // Track 3 pulled to the front; moving it back restores the chain.
+        let seed = vec![3, 0, 1, 2, 4, 5, 6, 7];
+        let out = OrOpt.solve(&problem, seed, far_future());
+        assert_eq!(out.order, (0..8).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_annealing_respects_ranges() {
+        let mut ranges = vec![(0, 19); 20];
+        ranges[4] = (0, 0);
+        ranges[9] = (19, 19);
+        let problem = pseudo_random_problem(20, &ranges);
+        let mut seed: Vec<usize> = (0..20).filter(|&i| i != 4 && i != 9).collect();
+        seed.insert(0, 4);
+        seed.push(9);
+        let out = Annealing::default().solve(&problem, seed, far_future());
+        assert!(problem.fits(&out.order));
+    }
+
+    #[test]
+    fn test_annealing_is_deterministic() {
+        let ranges = vec![(0, 15); 16];
+        let problem = pseudo_random_problem(16, &ranges);
+        let seed: Vec<usize> = (0..16).collect();
+        let a = Annealing::default().solve(&problem, seed.clone(), far_future());
+        let b = Annealing::default().solve(&problem, seed, far_future());
+        assert_eq!(a.order, b.order);
+    }
+
+    #[test]
+    fn test_solvers_break_up_separation_violations() {
+        // The chain 0 -> 1 -> ... is optimal, but 2 and 3 share an artist,
+        // so every solver should trade some flow to keep them apart.
+        let ranges = vec![(0, 7); 8];
+        let mut problem = chain_problem(8, &ranges);
+        let mut conflicts = vec![vec![false; 8]; 8];
+        conflicts[2][3] = true;
+        conflicts[3][2] = true;
+        problem.separation = Some(Separation {
+            conflicts,
+            min_gap: 1,
+        });
+        let seed: Vec<usize> = (0..8).collect();
+        assert_eq!(problem.violations(&seed), 1);
+        for kind in [Solver::HeldKarp, Solver::Annealing, Solver::OrOpt] {
+            let out = solver_for(kind).solve(&problem, seed.clone(), far_future());
+            assert_eq!(problem.violations(&out.order), 0, "{kind:?}");
+        }
+    }
+
+    #[test]
+    fn test_segment_violations_track_reversal_delta() {
+        let ranges = vec![(0, 9); 10];
+        let mut problem = chain_problem(10, &ranges);
+        let mut conflicts = vec![vec![false; 10]; 10];
+        for (a, b) in [(0, 3), (2, 5), (4, 9), (6, 7), (1, 8)] {
+            conflicts[a][b] = true;
+            conflicts[b][a] = true;
+        }
+        problem.separation = Some(Separation {
+            conflicts,
+            min_gap: 3,
+        });
+        let seed: Vec<usize> = (0..10).collect();
+        for i in 0..10 {
+            for j in i..10 {
+                let mut order = seed.clone();
+                let before = problem.segment_violations(&order, i, j);
+                order[i..=j].reverse();
+                let after = problem.segment_violations(&order, i, j);
+                assert_eq!(
+                    problem.violations(&order) as isize - problem.violations(&seed) as isize,
+                    after as isize - before as isize,
+                    "reverse {i}..={j}"
+                );
+            }
+        }
+    }
+}
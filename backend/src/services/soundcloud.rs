@@ -10,6 +10,7 @@ use crate::services::match_scoring::is_acceptable_match;
 // ---------------------------------------------------------------------------
 
 pub struct SoundCloudClient {
+    api_base: String,
     client_id: String,
     client_secret: String,
     token: Option<String>,
@@ -18,6 +19,52 @@ pub struct SoundCloudClient {
     disabled_until: Option<DateTime<Utc>>,
 }
 
+/// A SoundCloud set (playlist) resolved from its URL.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundCloudPlaylist {
+    pub urn: String,
+    pub title: String,
+    #[serde(default)]
+    pub track_count: u32,
+}
+
+/// A track in a set, with the metadata SoundCloud exposes.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundCloudTrack {
+    pub urn: String,
+    pub title: String,
+    /// Milliseconds.
+    pub duration: Option<i64>,
+    pub genre: Option<String>,
+    pub bpm: Option<f64>,
+    pub artwork_url: Option<String>,
+    pub permalink_url: Option<String>,
+    pub user: SoundCloudUser,
+    pub publisher_metadata: Option<SoundCloudPublisher>,
+}
+
+/// Label-supplied credits; `artist` is often more accurate than the uploader.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundCloudPublisher {
+    pub artist: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct SoundCloudUser {
+    pub username: String,
+}
+
+/// One page of a set's tracks.
+#[derive(Debug, Deserialize)]
+pub struct SoundCloudTrackPage {
+    pub collection: Vec<SoundCloudTrack>,
+    /// Full URL of the next page; absent on the last one.
+    pub next_href: Option<String>,
+}
+
+/// Tracks per page when listing a set.
+const SET_PAGE_SIZE: u32 = 200;
+
 #[derive(Debug)]
 pub struct SoundCloudMatch {
     pub stream_url: String,
@@ -64,15 +111,26 @@ impl SoundCloudClient {
         if client_id.is_empty() || client_secret.is_empty() {
             return None;
         }
-        Some(Self {
-            client_id,
-            client_secret,
+        Some(Self::new(client_id, client_secret))
+    }
+
+    pub fn new(client_id: impl Into<String>, client_secret: impl Into<String>) -> Self {
+        Self {
+            api_base: "https://api.soundcloud.com".to_string(),
+            client_id: client_id.into(),
+            client_secret: client_secret.into(),
             token: None,
             // Initialise to now — the token is None so it will be fetched on first use.
             token_expiry: Utc::now(),
             consecutive_failures: 0,
             disabled_until: None,
-        })
+        }
+    }
+
+    /// Point the client at another host (for tests).
+    pub fn with_api_base(mut self, api_base: impl Into<String>) -> Self {
+        self.api_base = api_base.into();
+        self
     }
 
     /// Returns a valid OAuth access token, fetching one if necessary.
@@ -99,7 +157,7 @@ impl SoundCloudClient {
 
         // --- Fetch a fresh token ---
         let result = http
-            .post("https://api.soundcloud.com/oauth2/token")
+            .post(format!("{}/oauth2/token", self.api_base))
             .form(&[
                 ("grant_type", "client_credentials"),
                 ("client_id", self.client_id.as_str()),
@@ -162,7 +220,7 @@ impl SoundCloudClient {
 
         let resp = tokio::time::timeout(
             Duration::from_secs(2),
-            http.get("https://api.soundcloud.com/tracks")
+            http.get(format!("{}/tracks", self.api_base))
                 .query(&[("q", q.as_str()), ("limit", "5")])
                 .header("Authorization", format!("OAuth {token}"))
                 .send(),
@@ -177,7 +235,7 @@ impl SoundCloudClient {
             let retry_token = self.ensure_token(http).await.ok()?;
             let retry_resp = tokio::time::timeout(
                 Duration::from_secs(2),
-                http.get("https://api.soundcloud.com/tracks")
+                http.get(format!("{}/tracks", self.api_base))
                     .query(&[("q", q.as_str()), ("limit", "5")])
                     .header("Authorization", format!("OAuth {retry_token}"))
                     .send(),
@@ -196,6 +254,68 @@ impl SoundCloudClient {
             .await
     }
 
+    /// GET an API URL with the OAuth token, refreshing the token once on 401.
+    async fn get_json<T: serde::de::DeserializeOwned>(
+        &mut self,
+        http: &reqwest::Client,
+        url: &str,
+        query: &[(&str, String)],
+    ) -> Result<T> {
+        let mut token = self.ensure_token(http).await?;
+        for attempt in 0..2 {
+            let resp = http
+                .get(url)
+                .query(query)
+                .header("Authorization", format!("OAuth {token}"))
+                .send()
+                .await?;
+            match resp.status() {
+                reqwest::StatusCode::UNAUTHORIZED if attempt == 0 => {
+                    self.token = None;
+                    token = self.ensure_token(http).await?;
+                }
+                reqwest::StatusCode::NOT_FOUND => anyhow::bail!("SoundCloud has no {url}"),
+                status if !status.is_success() => {
+                    anyhow::bail!("SoundCloud returned HTTP {status} for {url}")
+                }
+                _ => return Ok(resp.json().await?),
+            }
+        }
+        anyhow::bail!("SoundCloud rejected the access token")
+    }
+
+    /// Resolve a set URL (`https://soundcloud.com/{user}/sets/{set}`).
+    pub async fn resolve_playlist(
+        &mut self,
+        http: &reqwest::Client,
+        set_url: &str,
+    ) -> Result<SoundCloudPlaylist> {
+        let url = format!("{}/resolve", self.api_base);
+        self.get_json(http, &url, &[("url", set_url.to_string())])
+            .await
+    }
+
+    /// One page of a set's tracks: the first page when `next_href` is
+    /// `None`, otherwise the page it points at.
+    pub async fn get_playlist_tracks(
+        &mut self,
+        http: &reqwest::Client,
+        playlist_urn: &str,
+        next_href: Option<&str>,
+    ) -> Result<SoundCloudTrackPage> {
+        match next_href {
+            Some(next) => self.get_json(http, next, &[]).await,
+            None => {
+                let url = format!("{}/playlists/{playlist_urn}/tracks", self.api_base);
+                let query = [
+                    ("linked_partitioning", "true".to_string()),
+                    ("limit", SET_PAGE_SIZE.to_string()),
+                ];
+                self.get_json(http, &url, &query).await
+            }
+        }
+    }
+
     /// Find the best match and resolve the stream_url to a direct CDN URL.
     async fn find_and_resolve_match(
         &self,
@@ -0,0 +1,359 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::services::camelot::{
+    bpm_match_with_bands, classify_transition, effective_key, parse_camelot, BpmMatch, CamelotKey,
+    ScoringProfile, TempoRelation, TransitionType,
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// How to mix from one track into the next.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Technique {
+    /// Long overlap, for harmonic pairs at a matched tempo.
+    LongBlend,
+    /// Standard 16-bar blend trading the low end on a phrase.
+    BassSwap,
+    /// Tail the outgoing track off with an echo, then start the next clean.
+    EchoOut,
+    /// Hard switch on a downbeat.
+    Cut,
+    /// Bring the next track in at half or double time.
+    HalftimeDrop,
+    /// Loop the outro and ride the pitch fader toward the next tempo.
+    LoopAndPitch,
+}
+
+impl Technique {
+    /// One-line instruction shown in the transition note.
+    pub fn instruction(self) -> &'static str {
+        match self {
+            Technique::LongBlend => "overlap 32 bars or more and swap the lows halfway",
+            Technique::BassSwap => "blend over 16 bars and swap the low end on a phrase",
+            Technique::EchoOut => "echo out the outgoing track and start the next one clean",
+            Technique::Cut => "cut across on the first downbeat of a phrase",
+            Technique::HalftimeDrop => "drop the next track in on the one at the new tempo",
+            Technique::LoopAndPitch => "loop the outro and ride the pitch toward the next tempo",
+        }
+    }
+}
+
+impl fmt::Display for Technique {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Technique::LongBlend => write!(f, "Long blend"),
+            Technique::BassSwap => write!(f, "Bass swap"),
+            Technique::EchoOut => write!(f, "Echo out"),
+            Technique::Cut => write!(f, "Cut"),
+            Technique::HalftimeDrop => write!(f, "Halftime drop"),
+            Technique::LoopAndPitch => write!(f, "Loop and pitch"),
+        }
+    }
+}
+
+/// What the recommender looks at on either side of a transition.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MixPoint {
+    pub camelot: Option<CamelotKey>,
+    pub bpm: Option<f64>,
+    pub energy: Option<f64>,
+    pub duration_ms: Option<i32>,
+}
+
+impl MixPoint {
+    pub fn new(
+        camelot: Option<&str>,
+        bpm: Option<f64>,
+        energy: Option<f64>,
+        duration_ms: Option<i32>,
+    ) -> Self {
+        Self {
+            camelot: camelot.and_then(parse_camelot),
+            bpm,
+            energy,
+            duration_ms,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Rules
+// ---------------------------------------------------------------------------
+
+/// Beyond this many BPM apart, a straight blend drifts out of time.
+const MAX_BLEND_BPM_DELTA: f64 = 4.0;
+
+/// Long blends need both tracks within this many BPM.
+const MAX_LONG_BLEND_BPM_DELTA: f64 = 2.0;
+
+/// Energy change (in levels) that calls for a cut up or an echo down.
+const ENERGY_JUMP: f64 = 2.0;
+
+/// Tracks shorter than this (radio edits) lack the intro and outro a long
+/// blend needs.
+const MIN_LONG_BLEND_TRACK_MS: i32 = 210_000;
+
+/// Pick a technique for mixing `from` into `to`.
+///
+/// Tempo decides first (half/double time, then gaps too wide to blend),
+/// then clashing keys, then big energy jumps. Of what is left, harmonic
+/// pairs at a matched tempo get a long blend unless either track is a short
+/// edit; everything else gets a bass swap. Missing data never rules a
+/// technique out. Keys and tempos are judged with the user's `key_lock` and
+/// BPM bands, so the technique agrees with the scores shown beside it.
+pub fn recommend_technique(
+    from: &MixPoint,
+    to: &MixPoint,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> Technique {
+    let key = key_transition(from, to, key_lock);
+    let tempo = tempo_match(from, to, scoring);
+    let energy_delta = energy_delta(from, to);
+    let clash = key == Some(TransitionType::Clash);
+
+    if let Some(m) = tempo {
+        if matches!(
+            m.relation,
+            TempoRelation::HalfTime | TempoRelation::DoubleTime
+        ) {
+            return Technique::HalftimeDrop;
+        }
+        if m.relation != TempoRelation::Direct || m.effective_delta.abs() > MAX_BLEND_BPM_DELTA {
+            return if clash {
+                Technique::EchoOut
+            } else {
+                Technique::LoopAndPitch
+            };
+        }
+    }
+
+    if clash {
+        return if energy_delta.is_some_and(|d| d > 0.0) {
+            Technique::Cut
+        } else {
+            Technique::EchoOut
+        };
+    }
+    match energy_delta {
+        Some(d) if d >= ENERGY_JUMP => return Technique::Cut,
+        Some(d) if d <= -ENERGY_JUMP => return Technique::EchoOut,
+        _ => {}
+    }
+
+    match key {
+        // Key changes that only work when the new key lands all at once
+        Some(
+            TransitionType::EnergyBoost
+            | TransitionType::TwoStepBoost
+            | TransitionType::TwoStepDrop,
+        ) => Technique::Cut,
+        Some(t)
+            if t.score() >= 0.9
+                && tempo.is_none_or(|m| m.effective_delta.abs() <= MAX_LONG_BLEND_BPM_DELTA)
+                && !is_short(from)
+                && !is_short(to) =>
+        {
+            Technique::LongBlend
+        }
+        _ => Technique::BassSwap,
+    }
+}
+
+/// Transition note for mixing `from` into `to`, e.g.
+/// "Bass swap: blend over 16 bars and swap the low end on a phrase (adjacent, +3 BPM)".
+pub fn transition_note(
+    from: &MixPoint,
+    to: &MixPoint,
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> String {
+    let technique = recommend_technique(from, to, key_lock, scoring);
+    let mut reasons: Vec<String> = Vec::new();
+    if let Some(key) = key_transition(from, to, key_lock) {
+        reasons.push(key.to_string());
+    }
+    if let Some(m) = tempo_match(from, to, scoring) {
+        reasons.push(match m.relation {
+            TempoRelation::Direct => format!("{:+.0} BPM", m.effective_delta),
+            relation => relation.to_string(),
+        });
+    }
+    if let Some(d) = energy_delta(from, to).filter(|d| *d != 0.0) {
+        reasons.push(format!("energy {d:+.0}"));
+    }
+
+    let note = format!("{technique}: {}", technique.instruction());
+    if reasons.is_empty() {
+        note
+    } else {
+        format!("{note} ({})", reasons.join(", "))
+    }
+}
+
+/// Note for each track describing the mix into it from its predecessor.
+/// The opening track has no incoming transition and gets `None`.
+pub fn transition_notes(
+    points: &[MixPoint],
+    key_lock: bool,
+    scoring: &ScoringProfile,
+) -> Vec<Option<String>> {
+    (0..points.len())
+        .map(|i| {
+            i.checked_sub(1)
+                .map(|prev| transition_note(&points[prev], &points[i], key_lock, scoring))
+        })
+        .collect()
+}
+
+fn key_transition(from: &MixPoint, to: &MixPoint, key_lock: bool) -> Option<TransitionType> {
+    match (from.camelot, to.camelot) {
+        (Some(a), Some(b)) => Some(classify_transition(
+            &a,
+            &effective_key(&b, from.bpm, to.bpm, key_lock),
+        )),
+        _ => None,
+    }
+}
+
+fn tempo_match(from: &MixPoint, to: &MixPoint, scoring: &ScoringProfile) -> Option<BpmMatch> {
+    match (from.bpm, to.bpm) {
+        (Some(a), Some(b)) => Some(bpm_match_with_bands(a, b, &scoring.bpm_bands)),
+        _ => None,
+    }
+}
+
+fn energy_delta(from: &MixPoint, to: &MixPoint) -> Option<f64> {
+    match (from.energy, to.energy) {
+        (Some(a), Some(b)) => Some(b - a),
+        _ => None,
+    }
+}
+
+fn is_short(point: &MixPoint) -> bool {
+    point
+        .duration_ms
+        .is_some_and(|ms| ms < MIN_LONG_BLEND_TRACK_MS)
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn point(camelot: &str, bpm: f64, energy: f64) -> MixPoint {
+        MixPoint::new(Some(camelot), Some(bpm), Some(energy), None)
+    }
+
+    /// Recommendation under the default settings: key lock on, built-in bands.
+    fn recommend(from: &MixPoint, to: &MixPoint) -> Technique {
+        recommend_technique(from, to, true, &ScoringProfile::default())
+    }
+
+    #[test]
+    fn test_harmonic_matched_pair_gets_long_blend() {
+        let from = point("8A", 124.0, 6.0);
+        assert_eq!(
+            recommend(&from, &point("9A", 125.0, 6.0)),
+            Technique::LongBlend
+        );
+        // A radio edit has no room for a long overlap
+        let edit = MixPoint {
+            duration_ms: Some(180_000),
+            ..point("9A", 125.0, 6.0)
+        };
+        assert_eq!(recommend(&from, &edit), Technique::BassSwap);
+    }
+
+    #[test]
+    fn test_tempo_rules() {
+        let from = point("8A", 87.0, 6.0);
+        assert_eq!(
+            recommend(&from, &point("8A", 174.0, 6.0)),
+            Technique::HalftimeDrop
+        );
+        let from = point("8A", 120.0, 6.0);
+        assert_eq!(
+            recommend(&from, &point("9A", 126.0, 6.0)),
+            Technique::LoopAndPitch
+        );
+        assert_eq!(
+            recommend(&from, &point("2B", 126.0, 6.0)),
+            Technique::EchoOut
+        );
+    }
+
+    #[test]
+    fn test_key_and_energy_rules() {
+        let from = point("8A", 124.0, 6.0);
+        assert_eq!(
+            recommend(&from, &point("2B", 124.0, 5.0)),
+            Technique::EchoOut
+        );
+        assert_eq!(recommend(&from, &point("2B", 124.0, 7.0)), Technique::Cut);
+        assert_eq!(recommend(&from, &point("8A", 124.0, 8.0)), Technique::Cut);
+        assert_eq!(
+            recommend(&from, &point("8A", 124.0, 4.0)),
+            Technique::EchoOut
+        );
+        assert_eq!(recommend(&from, &point("3A", 124.0, 6.0)), Technique::Cut);
+        assert_eq!(
+            recommend(&from, &point("9B", 124.0, 6.0)),
+            Technique::BassSwap
+        );
+    }
+
+    #[test]
+    fn test_missing_data_falls_back_to_bass_swap() {
+        let unknown = MixPoint::default();
+        assert_eq!(recommend(&unknown, &unknown), Technique::BassSwap);
+        assert_eq!(
+            transition_note(&unknown, &unknown, true, &ScoringProfile::default()),
+            "Bass swap: blend over 16 bars and swap the low end on a phrase"
+        );
+    }
+
+    #[test]
+    fn test_notes_follow_neighbours() {
+        let points = [
+            point("8A", 124.0, 5.0),
+            point("8A", 126.0, 6.0),
+            point("8A", 126.0, 8.0),
+        ];
+        let notes = transition_notes(&points, true, &ScoringProfile::default());
+        assert_eq!(notes[0], None);
+        assert_eq!(
+            notes[1].as_deref(),
+            Some(
+                "Long blend: overlap 32 bars or more and swap the lows halfway \
+                 (same key, +2 BPM, energy +1)"
+            )
+        );
+        assert!(notes[2].as_deref().unwrap().starts_with("Cut:"));
+    }
+
+    #[test]
+    fn test_key_lock_off_judges_the_pitched_key() {
+        // Pitching 127 BPM down to 120 drops the key a semitone, 8A to 1A
+        let from = point("8A", 120.0, 6.0);
+        let to = point("8A", 127.0, 6.0);
+        let profile = ScoringProfile::default();
+        assert_eq!(
+            recommend_technique(&from, &to, true, &profile),
+            Technique::LoopAndPitch
+        );
+        assert_eq!(
+            recommend_technique(&from, &to, false, &profile),
+            Technique::EchoOut
+        );
+        assert!(transition_note(&from, &to, false, &profile).contains("clash"));
+    }
+}
@@ -0,0 +1,300 @@
+use serde::{Deserialize, Serialize};
+
+use crate::db::models::TrackRow;
+use crate::services::arrangement::{self, ArrangementError, ArrangementOptions, ArrangementTrack};
+use crate::services::camelot::{normalize_key, parse_camelot, ScoreBreakdown};
+use crate::services::match_scoring::{artist_similarity, is_acceptable_match, title_similarity};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Largest tracklist scored in one request.
+pub const MAX_TRACKLIST_ENTRIES: usize = 200;
+
+/// One line of a tracklist from outside the app: a friend's set, a past gig,
+/// a spreadsheet draft.
+#[derive(Debug, Clone, Deserialize)]
+pub struct TracklistEntry {
+    pub title: String,
+    pub artist: String,
+    #[serde(default)]
+    pub bpm: Option<f64>,
+    /// Any notation `parse_camelot` accepts (8A, 3m, A minor, Am...).
+    #[serde(default)]
+    pub key: Option<String>,
+    #[serde(default)]
+    pub energy: Option<f64>,
+}
+
+impl TracklistEntry {
+    /// Reject tempos and energies the scorer cannot use.
+    pub fn validate(&self) -> Result<(), String> {
+        if let Some(bpm) = self.bpm {
+            if !bpm.is_finite() || bpm <= 0.0 {
+                return Err(format!("bpm must be a positive number, got {bpm}"));
+            }
+        }
+        if let Some(energy) = self.energy {
+            if !(1.0..=10.0).contains(&energy) {
+                return Err(format!("energy must be between 1 and 10, got {energy}"));
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoredEntry {
+    /// 1-based position in the tracklist as given.
+    pub position: i32,
+    pub title: String,
+    pub artist: String,
+    pub bpm: Option<f64>,
+    pub camelot: Option<String>,
+    pub energy: Option<f64>,
+    /// Catalog track this entry resolved to, if any.
+    pub catalog_track_id: Option<String>,
+    /// Average of title and artist similarity to the catalog match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub match_score: Option<f64>,
+    /// Fields the entry left blank and the catalog match supplied.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub filled_from_catalog: Vec<&'static str>,
+    /// Score of the transition into this entry; `None` for the opener.
+    pub transition_score: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SuggestedOrder {
+    /// Positions from the tracklist as given, in the suggested play order.
+    pub positions: Vec<i32>,
+    pub transition_scores: Vec<f64>,
+    pub harmonic_flow_score: f64,
+    pub score_breakdown: ScoreBreakdown,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TracklistScore {
+    pub tracks: Vec<ScoredEntry>,
+    pub matched_count: usize,
+    pub harmonic_flow_score: f64,
+    pub score_breakdown: ScoreBreakdown,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggested_order: Option<SuggestedOrder>,
+}
+
+// ---------------------------------------------------------------------------
+// Scoring
+// ---------------------------------------------------------------------------
+
+/// Best catalog match for `entry` and its similarity, among tracks that pass
+/// [`is_acceptable_match`].
+pub fn match_entry<'a>(
+    entry: &TracklistEntry,
+    catalog: &'a [TrackRow],
+) -> Option<(&'a TrackRow, f64)> {
+    catalog
+        .iter()
+        .filter_map(|t| {
+            let artist = t.artist.as_deref()?;
+            if !is_acceptable_match(&entry.title, &entry.artist, &t.title, artist) {
+                return None;
+            }
+            let score = (title_similarity(&entry.title, &t.title)
+                + artist_similarity(&entry.artist, artist))
+                / 2.0;
+            Some((t, score))
+        })
+        .max_by(|a, b| a.1.total_cmp(&b.1))
+}
+
+/// Score `entries` in the order given, filling gaps in their metadata from
+/// the catalog. With `suggest_order`, also arrange them and report the best
+/// order found. Nothing is persisted.
+pub fn score_tracklist(
+    entries: &[TracklistEntry],
+    catalog: &[TrackRow],
+    options: &ArrangementOptions,
+    suggest_order: bool,
+) -> Result<TracklistScore, ArrangementError> {
+    let mut tracks: Vec<ScoredEntry> = entries
+        .iter()
+        .enumerate()
+        .map(|(i, entry)| resolve(i, entry, catalog))
+        .collect();
+
+    let arrangement_tracks: Vec<ArrangementTrack> = tracks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| ArrangementTrack {
+            index: i,
+            camelot: t.camelot.as_deref().and_then(parse_camelot),
+            bpm: t.bpm,
+            energy: t.energy.map(|e| e.round() as i32),
+            artist: Some(t.artist.clone()),
+            constraint: None,
+        })
+        .collect();
+
+    let given: Vec<usize> = (0..tracks.len()).collect();
+    let scored = arrangement::score_order(&arrangement_tracks, &given, options);
+    for (track, score) in tracks.iter_mut().skip(1).zip(&scored.transition_scores) {
+        track.transition_score = Some(*score);
+    }
+
+    let suggested_order = if suggest_order {
+        let result = arrangement::arrange_tracks_with_options(&arrangement_tracks, options)?;
+        Some(SuggestedOrder {
+            positions: result
+                .ordered_indices
+                .iter()
+                .map(|&i| (i + 1) as i32)
+                .collect(),
+            transition_scores: result.transition_scores,
+            harmonic_flow_score: result.harmonic_flow_score,
+            score_breakdown: result.score_breakdown,
+        })
+    } else {
+        None
+    };
+
+    Ok(TracklistScore {
+        matched_count: tracks
+            .iter()
+            .filter(|t| t.catalog_track_id.is_some())
+            .count(),
+        tracks,
+        harmonic_flow_score: scored.harmonic_flow_score,
+        score_breakdown: scored.score_breakdown,
+        suggested_order,
+    })
+}
+
+fn resolve(index: usize, entry: &TracklistEntry, catalog: &[TrackRow]) -> ScoredEntry {
+    let matched = match_entry(entry, catalog);
+    let catalog_track = matched.map(|(t, _)| t);
+    let mut filled = Vec::new();
+
+    let given_key = normalize_key(entry.key.as_deref());
+    let camelot = given_key.or_else(|| {
+        let key = normalize_key(catalog_track?.camelot_key.as_deref())?;
+        filled.push("key");
+        Some(key)
+    });
+    let bpm = entry.bpm.or_else(|| {
+        let bpm = catalog_track?.bpm?;
+        filled.push("bpm");
+        Some(bpm)
+    });
+    let energy = entry.energy.or_else(|| {
+        let energy = catalog_track?.energy?;
+        filled.push("energy");
+        Some(energy)
+    });
+
+    ScoredEntry {
+        position: (index + 1) as i32,
+        title: entry.title.clone(),
+        artist: entry.artist.clone(),
+        bpm,
+        camelot,
+        energy,
+        catalog_track_id: catalog_track.map(|t| t.id.clone()),
+        match_score: matched.map(|(_, score)| score),
+        filled_from_catalog: filled,
+        transition_score: None,
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn catalog_track(id: &str, title: &str, artist: &str, camelot: &str, bpm: f64) -> TrackRow {
+        TrackRow {
+            id: id.to_string(),
+            title: title.to_string(),
+            artist: Some(artist.to_string()),
+            album: None,
+            duration_ms: None,
+            bpm: Some(bpm),
+            camelot_key: Some(camelot.to_string()),
+            energy: Some(6.0),
+            source: "spotify".to_string(),
+            spotify_uri: None,
+            spotify_preview_url: None,
+            album_art_url: None,
+            deezer_id: None,
+            deezer_preview_url: None,
+            created_at: None,
+        }
+    }
+
+    fn entry(title: &str, artist: &str, key: Option<&str>, bpm: Option<f64>) -> TracklistEntry {
+        TracklistEntry {
+            title: title.to_string(),
+            artist: artist.to_string(),
+            bpm,
+            key: key.map(str::to_string),
+            energy: None,
+        }
+    }
+
+    #[test]
+    fn test_fills_missing_metadata_from_catalog() {
+        let catalog = vec![
+            catalog_track("c1", "Strings of Life", "Derrick May", "8A", 124.0),
+            catalog_track("c2", "Unrelated", "Someone Else", "2B", 90.0),
+        ];
+        let entries = vec![
+            entry(
+                "Strings of Life (Original Mix)",
+                "Derrick May",
+                None,
+                Some(125.0),
+            ),
+            entry("Not In Catalog", "Nobody", Some("A minor"), Some(126.0)),
+        ];
+        let result =
+            score_tracklist(&entries, &catalog, &ArrangementOptions::default(), false).unwrap();
+
+        assert_eq!(result.matched_count, 1);
+        let first = &result.tracks[0];
+        assert_eq!(first.catalog_track_id.as_deref(), Some("c1"));
+        assert_eq!(first.camelot.as_deref(), Some("8A"));
+        // Given values win over the catalog's
+        assert_eq!(first.bpm, Some(125.0));
+        assert_eq!(first.filled_from_catalog, vec!["key", "energy"]);
+
+        let second = &result.tracks[1];
+        assert!(second.catalog_track_id.is_none());
+        assert_eq!(second.camelot.as_deref(), Some("8A"));
+        assert!(second.transition_score.unwrap() > 0.85);
+        assert!(result.suggested_order.is_none());
+    }
+
+    #[test]
+    fn test_suggested_order_improves_flow() {
+        let entries = vec![
+            entry("One", "A", Some("8A"), Some(124.0)),
+            entry("Two", "B", Some("2B"), Some(124.0)),
+            entry("Three", "C", Some("8A"), Some(124.0)),
+        ];
+        let result = score_tracklist(&entries, &[], &ArrangementOptions::default(), true).unwrap();
+        assert_eq!(result.tracks[0].transition_score, None);
+        assert_eq!(result.score_breakdown.key_transitions.len(), 2);
+
+        let suggested = result.suggested_order.unwrap();
+        assert_eq!(suggested.positions.len(), 3);
+        assert!(suggested.harmonic_flow_score > result.harmonic_flow_score);
+        // The two 8A tracks end up next to each other
+        let one = suggested.positions.iter().position(|&p| p == 1).unwrap();
+        let three = suggested.positions.iter().position(|&p| p == 3).unwrap();
+        assert_eq!(one.abs_diff(three), 1);
+    }
+}
@@ -0,0 +1,309 @@
+use roxmltree::{Document, Node};
+
+use crate::services::camelot::{from_spotify_key, normalize_key, CamelotKey};
+use crate::services::import::{
+    rating_stars, ImportError, LibraryPlaylist, LibraryTrackRecord, ParsedLibrary,
+};
+
+// ---------------------------------------------------------------------------
+// Traktor collection.nml
+// ---------------------------------------------------------------------------
+
+/// `tracks.source` and import-id prefix for Traktor imports.
+pub const SOURCE: &str = "traktor";
+
+/// Traktor's seven colour tags (`INFO COLOR="1"` .. `"7"`).
+const COLOURS: [&str; 7] = [
+    "#FF0000", "#FF8000", "#FFFF00", "#00FF00", "#0000FF", "#8000FF", "#FF00FF",
+];
+
+/// Parse a Traktor `collection.nml`.
+///
+/// Tracks are keyed the way Traktor's playlists reference them: volume,
+/// directory and file name joined (`Macintosh HD/:Users/:dj/:Music/:a.mp3`).
+/// Smart playlists and Traktor's own `_LOOPS` / `_RECORDINGS` lists are
+/// skipped.
+pub fn parse_library(xml: &str) -> Result<ParsedLibrary, ImportError> {
+    let doc = Document::parse(xml)
+        .map_err(|e| ImportError::InvalidFile(format!("Not a valid XML document: {e}")))?;
+
+    let root = doc.root_element();
+    if !root.has_tag_name("NML") {
+        return Err(ImportError::InvalidFile(
+            "Not a Traktor collection (expected <NML>)".to_string(),
+        ));
+    }
+
+    let collection = child(root, "COLLECTION").ok_or_else(|| {
+        ImportError::InvalidFile("Traktor collection has no <COLLECTION>".to_string())
+    })?;
+
+    let mut library = ParsedLibrary::default();
+    for entry in collection.children().filter(|n| n.has_tag_name("ENTRY")) {
+        let Some(location) = child(entry, "LOCATION") else {
+            continue;
+        };
+        let Some(track) = parse_entry(entry, location) else {
+            continue;
+        };
+        let key = format!(
+            "{}{}{}",
+            location.attribute("VOLUME").unwrap_or_default(),
+            location.attribute("DIR").unwrap_or_default(),
+            location.attribute("FILE").unwrap_or_default(),
+        );
+        library.tracks.push((key, track));
+    }
+
+    // PLAYLISTS > NODE "$ROOT" > SUBNODES > NODE...
+    if let Some(root_node) = child(root, "PLAYLISTS").and_then(|p| child(p, "NODE")) {
+        collect_playlists(root_node, "", &mut library.playlists);
+    }
+
+    Ok(library)
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn parse_entry(entry: Node, location: Node) -> Option<LibraryTrackRecord> {
+    let title = entry.attribute("TITLE")?.trim();
+    if title.is_empty() {
+        return None;
+    }
+    let info = child(entry, "INFO");
+    let info_attr = |name: &str| info.and_then(|i| i.attribute(name));
+
+    // MUSICAL_KEY is Traktor's analysed key; INFO KEY is the display text and
+    // may have been typed in by hand.
+    let camelot_key = child(entry, "MUSICAL_KEY")
+        .and_then(|k| k.attribute("VALUE"))
+        .and_then(|v| v.parse::<i32>().ok())
+        .and_then(musical_key)
+        .map(|k| k.to_string())
+        .or_else(|| normalize_key(info_attr("KEY")));
+
+    Some(LibraryTrackRecord {
+        title: title.to_string(),
+        artist: entry
+            .attribute("ARTIST")
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string),
+        album: child(entry, "ALBUM")
+            .and_then(|a| a.attribute("TITLE"))
+            .map(str::trim)
+            .filter(|a| !a.is_empty())
+            .map(str::to_string),
+        duration_ms: info_attr("PLAYTIME_FLOAT")
+            .or_else(|| info_attr("PLAYTIME"))
+            .and_then(|t| t.parse::<f64>().ok())
+            .filter(|&secs| secs > 0.0)
+            .map(|secs| (secs * 1000.0).round() as i64),
+        bpm: child(entry, "TEMPO")
+            .and_then(|t| t.attribute("BPM"))
+            .and_then(|b| b.parse::<f64>().ok())
+            .filter(|&bpm| bpm > 0.0)
+            .map(|bpm| (bpm * 100.0).round() / 100.0),
+        camelot_key,
+        energy: None,
+        rating: info_attr("RANKING").and_then(rating_stars),
+        colour: info_attr("COLOR")
+            .and_then(|c| c.parse::<usize>().ok())
+            .and_then(|c| COLOURS.get(c.checked_sub(1)?))
+            .map(|c| c.to_string()),
+        file_location: file_path(location),
+        source: SOURCE.to_string(),
+    })
+}
+
+/// Traktor's `MUSICAL_KEY VALUE`: 0-11 are C..B major, 12-23 C..B minor.
+fn musical_key(value: i32) -> Option<CamelotKey> {
+    match value {
+        0..=11 => from_spotify_key(value, 1),
+        12..=23 => from_spotify_key(value - 12, 0),
+        _ => None,
+    }
+}
+
+/// Filesystem path from a `LOCATION` element. Traktor separates directories
+/// with "/:"; Windows volumes are drive letters ("C:"), macOS volumes are
+/// disk names that are not part of the path.
+fn file_path(location: Node) -> Option<String> {
+    let file = location.attribute("FILE").filter(|f| !f.is_empty())?;
+    let dir = location
+        .attribute("DIR")
+        .unwrap_or_default()
+        .replace("/:", "/");
+    let volume = location.attribute("VOLUME").unwrap_or_default();
+    if volume.len() == 2 && volume.ends_with(':') {
+        Some(format!("{volume}{dir}{file}"))
+    } else {
+        Some(format!("{dir}{file}"))
+    }
+}
+
+fn collect_playlists(node: Node, parent_path: &str, playlists: &mut Vec<LibraryPlaylist>) {
+    let name = node
+        .attribute("NAME")
+        .unwrap_or_default()
+        .trim()
+        .to_string();
+    match node.attribute("TYPE") {
+        Some("FOLDER") => {
+            // The root folder is named "$ROOT" and is not part of the path.
+            let path = if name == "$ROOT" {
+                String::new()
+            } else if parent_path.is_empty() {
+                name
+            } else {
+                format!("{parent_path}/{name}")
+            };
+            let subnodes = child(node, "SUBNODES")
+                .into_iter()
+                .flat_map(|s| s.children());
+            for sub in subnodes.filter(|n| n.has_tag_name("NODE")) {
+                collect_playlists(sub, &path, playlists);
+            }
+        }
+        Some("PLAYLIST") if !name.starts_with('_') => {
+            let track_keys = child(node, "PLAYLIST")
+                .into_iter()
+                .flat_map(|p| p.children())
+                .filter(|n| n.has_tag_name("ENTRY"))
+                .filter_map(|e| child(e, "PRIMARYKEY"))
+                .filter(|k| k.attribute("TYPE") == Some("TRACK"))
+                .filter_map(|k| k.attribute("KEY"))
+                .map(str::to_string)
+                .collect();
+            let path = if parent_path.is_empty() {
+                name.clone()
+            } else {
+                format!("{parent_path}/{name}")
+            };
+            playlists.push(LibraryPlaylist {
+                path,
+                name,
+                track_keys,
+            });
+        }
+        _ => {}
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NML: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="no" ?>
+<NML VERSION="19">
+  <HEAD COMPANY="www.native-instruments.com" PROGRAM="Traktor"/>
+  <COLLECTION ENTRIES="2">
+    <ENTRY TITLE="Yeke Yeke" ARTIST="Mory Kante">
+      <LOCATION DIR="/:Users/:dj/:Music/:" FILE="yeke.mp3" VOLUME="Macintosh HD" VOLUMEID="Macintosh HD"/>
+      <ALBUM TITLE="Akwaba Beach"/>
+      <INFO BITRATE="320000" KEY="11A" PLAYTIME="381" PLAYTIME_FLOAT="380.734" RANKING="153" COLOR="4"/>
+      <TEMPO BPM="124.000031" BPM_QUALITY="100.000000"/>
+      <MUSICAL_KEY VALUE="18"/>
+    </ENTRY>
+    <ENTRY TITLE="Desert Rose" ARTIST="Sting">
+      <LOCATION DIR="/:Music/:" FILE="rose.flac" VOLUME="C:" VOLUMEID="1234"/>
+      <INFO PLAYTIME="285" KEY="Am"/>
+    </ENTRY>
+    <ENTRY ARTIST="Untitled">
+      <LOCATION DIR="/:Music/:" FILE="none.mp3" VOLUME="C:"/>
+    </ENTRY>
+  </COLLECTION>
+  <PLAYLISTS>
+    <NODE TYPE="FOLDER" NAME="$ROOT">
+      <SUBNODES COUNT="3">
+        <NODE TYPE="PLAYLIST" NAME="_RECORDINGS">
+          <PLAYLIST ENTRIES="0" TYPE="LIST" UUID="r"></PLAYLIST>
+        </NODE>
+        <NODE TYPE="FOLDER" NAME="Gigs">
+          <SUBNODES COUNT="1">
+            <NODE TYPE="PLAYLIST" NAME="Friday">
+              <PLAYLIST ENTRIES="2" TYPE="LIST" UUID="f">
+                <ENTRY><PRIMARYKEY TYPE="TRACK" KEY="C:/:Music/:rose.flac"/></ENTRY>
+                <ENTRY><PRIMARYKEY TYPE="TRACK" KEY="Macintosh HD/:Users/:dj/:Music/:yeke.mp3"/></ENTRY>
+              </PLAYLIST>
+            </NODE>
+          </SUBNODES>
+        </NODE>
+        <NODE TYPE="SMARTLIST" NAME="Fresh">
+          <SMARTLIST UUID="s"/>
+        </NODE>
+      </SUBNODES>
+    </NODE>
+  </PLAYLISTS>
+</NML>"#;
+
+    #[test]
+    fn test_parse_entries() {
+        let library = parse_library(NML).unwrap();
+        assert_eq!(library.tracks.len(), 2, "untitled entry is skipped");
+
+        let (key, yeke) = &library.tracks[0];
+        assert_eq!(key, "Macintosh HD/:Users/:dj/:Music/:yeke.mp3");
+        assert_eq!(yeke.title, "Yeke Yeke");
+        assert_eq!(yeke.artist.as_deref(), Some("Mory Kante"));
+        assert_eq!(yeke.album.as_deref(), Some("Akwaba Beach"));
+        assert_eq!(yeke.duration_ms, Some(380_734));
+        assert_eq!(yeke.bpm, Some(124.0));
+        // MUSICAL_KEY 18 = F# minor
+        assert_eq!(yeke.camelot_key.as_deref(), Some("11A"));
+        assert_eq!(yeke.rating, Some(3));
+        assert_eq!(yeke.colour.as_deref(), Some("#00FF00"));
+        assert_eq!(
+            yeke.file_location.as_deref(),
+            Some("/Users/dj/Music/yeke.mp3")
+        );
+        assert_eq!(yeke.analysed_by(), Some("traktor"));
+
+        let (_, rose) = &library.tracks[1];
+        assert_eq!(
+            rose.camelot_key.as_deref(),
+            Some("8A"),
+            "falls back to INFO KEY"
+        );
+        assert_eq!(rose.bpm, None);
+        assert_eq!(rose.file_location.as_deref(), Some("C:/Music/rose.flac"));
+    }
+
+    #[test]
+    fn test_parse_playlists() {
+        let library = parse_library(NML).unwrap();
+        assert_eq!(library.playlists.len(), 1);
+
+        let friday = &library.playlists[0];
+        assert_eq!(friday.path, "Gigs/Friday");
+        assert_eq!(friday.name, "Friday");
+        assert_eq!(
+            friday.track_keys,
+            vec![
+                "C:/:Music/:rose.flac",
+                "Macintosh HD/:Users/:dj/:Music/:yeke.mp3"
+            ]
+        );
+        let keys: Vec<&str> = library.tracks.iter().map(|(k, _)| k.as_str()).collect();
+        assert!(friday.track_keys.iter().all(|k| keys.contains(&k.as_str())));
+    }
+
+    #[test]
+    fn test_musical_key_values() {
+        assert_eq!(musical_key(0).unwrap().to_string(), "8B");
+        assert_eq!(musical_key(21).unwrap().to_string(), "8A");
+        assert_eq!(musical_key(24), None);
+    }
+
+    #[test]
+    fn test_parse_rejects_rekordbox_export() {
+        let err = parse_library("<DJ_PLAYLISTS><COLLECTION/></DJ_PLAYLISTS>").unwrap_err();
+        assert!(matches!(err, ImportError::InvalidFile(_)));
+    }
+}
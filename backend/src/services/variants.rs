@@ -0,0 +1,459 @@
+use std::collections::HashSet;
+
+use serde::Serialize;
+
+use crate::api::claude::ClaudeClientTrait;
+use crate::db::setlists as db;
+use crate::services::arrangement::{self, ArrangementOptions, ArrangementTrack};
+use crate::services::camelot::parse_camelot;
+use crate::services::lineup::track_identity;
+use crate::services::lint::{self, LintOptions, LintReport, LintTrack};
+use crate::services::setlist::{
+    self, GenerateSetlistRequest, GenerationContext, SetlistError, SetlistResponse,
+};
+
+// ---------------------------------------------------------------------------
+// Types
+// ---------------------------------------------------------------------------
+
+/// Upper bound on candidates per request (each one is a separate LLM call).
+pub const MAX_VARIANTS: u32 = 5;
+
+/// Tracks shared between one variant and another.
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantOverlap {
+    pub setlist_id: String,
+    pub shared_tracks: usize,
+    /// Shared tracks over the tracks in either set (0.0-1.0).
+    pub jaccard: f64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SetlistVariant {
+    /// 1 is the best candidate.
+    pub rank: usize,
+    pub setlist: SetlistResponse,
+    /// Harmonic flow of the best order `arrange_tracks` finds for these tracks.
+    pub arranged_flow_score: f64,
+    pub lint: LintReport,
+    pub overlap: Vec<VariantOverlap>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct VariantsResponse {
+    /// Shared by every candidate; keeping one discards or keeps the rest.
+    pub variant_group: String,
+    /// Best first.
+    pub variants: Vec<SetlistVariant>,
+    /// Distinct tracks across all candidates.
+    pub unique_tracks: usize,
+    pub total_tracks: usize,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct KeepVariantResponse {
+    pub setlist: SetlistResponse,
+    /// Ids of the other drafts that were deleted.
+    pub discarded: Vec<String>,
+}
+
+// ---------------------------------------------------------------------------
+// Ranking
+// ---------------------------------------------------------------------------
+
+fn identities(setlist: &SetlistResponse) -> HashSet<String> {
+    setlist
+        .tracks
+        .iter()
+        .map(|t| track_identity(&t.artist, &t.title))
+        .collect()
+}
+
+/// Overlap of every variant with every other, in input order.
+fn overlaps(setlists: &[SetlistResponse]) -> Vec<Vec<VariantOverlap>> {
+    let sets: Vec<HashSet<String>> = setlists.iter().map(identities).collect();
+    sets.iter()
+        .enumerate()
+        .map(|(i, a)| {
+            sets.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .map(|(j, b)| {
+                    let shared = a.intersection(b).count();
+                    let union = a.union(b).count();
+                    VariantOverlap {
+                        setlist_id: setlists[j].id.clone(),
+                        shared_tracks: shared,
+                        jaccard: if union == 0 {
+                            0.0
+                        } else {
+                            shared as f64 / union as f64
+                        },
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Flow of the arranged order; 0.0 for an empty set.
+fn arranged_flow_score(setlist: &SetlistResponse, options: &ArrangementOptions) -> f64 {
+    let tracks: Vec<ArrangementTrack> = setlist
+        .tracks
+        .iter()
+        .enumerate()
+        .map(|(i, t)| ArrangementTrack {
+            index: i,
+            camelot: t.camelot.as_deref().and_then(parse_camelot),
+            bpm: t.bpm,
+            energy: t.energy.map(|e| e as i32),
+            artist: Some(t.artist.clone()),
+            constraint: None,
+        })
+        .collect();
+    arrangement::arrange_tracks_with_options(&tracks, options)
+        .map(|r| r.harmonic_flow_score)
+        .unwrap_or(0.0)
+}
+
+/// Score and order candidates: fewest lint errors first, then best arranged
+/// flow, then fewest lint warnings.
+fn rank_variants(
+    setlists: Vec<SetlistResponse>,
+    arrangement_options: &ArrangementOptions,
+    lint_options: &LintOptions,
+) -> Vec<SetlistVariant> {
+    let overlaps = overlaps(&setlists);
+    let mut variants: Vec<SetlistVariant> = setlists
+        .into_iter()
+        .zip(overlaps)
+        .map(|(setlist, overlap)| {
+            let lint_tracks: Vec<LintTrack> = setlist.tracks.iter().map(LintTrack::from).collect();
+            SetlistVariant {
+                rank: 0,
+                arranged_flow_score: arranged_flow_score(&setlist, arrangement_options),
+                lint: lint::lint_tracks(&lint_tracks, lint_options),
+                setlist,
+                overlap,
+            }
+        })
+        .collect();
+    variants.sort_by(|a, b| {
+        a.lint
+            .counts
+            .error
+            .cmp(&b.lint.counts.error)
+            .then(b.arranged_flow_score.total_cmp(&a.arranged_flow_score))
+            .then(a.lint.counts.warning.cmp(&b.lint.counts.warning))
+    });
+    for (i, v) in variants.iter_mut().enumerate() {
+        v.rank = i + 1;
+    }
+    variants
+}
+
+/// Prompt lines steering a later candidate away from the earlier ones.
+fn alternative_instructions(index: u32, count: u32, used: &[String]) -> String {
+    format!(
+        "This is alternative {} of {count} for the same brief. Make it clearly different from \
+         the earlier alternatives: reuse at most half of these tracks and vary the opener and \
+         closer: {}",
+        index + 1,
+        used.join("; ")
+    )
+}
+
+// ---------------------------------------------------------------------------
+// Service functions
+// ---------------------------------------------------------------------------
+
+/// Generate `count` alternative setlists for one request and rank them.
+///
+/// Candidates are generated one after another so each can be told what the
+/// earlier ones used. Every candidate is saved as a draft in a shared variant
+/// group and counts as one generation; the request fails up front if the
+/// user's remaining daily allowance cannot cover all of them. If a candidate
+/// fails, the drafts already generated are deleted.
+pub async fn generate_variants(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: GenerateSetlistRequest,
+    count: u32,
+) -> Result<VariantsResponse, SetlistError> {
+    if !(2..=MAX_VARIANTS).contains(&count) {
+        return Err(SetlistError::InvalidRequest(format!(
+            "variants must be between 2 and {MAX_VARIANTS}, got {count}"
+        )));
+    }
+    setlist::check_generation_allowance(pool, &req.user_id, count as i64).await?;
+
+    let arrangement_options = ArrangementOptions {
+        energy_profile: req.energy_profile,
+        energy_curve: req.energy_curve.clone(),
+        key_path: req.key_path.clone(),
+        key_lock: crate::db::settings::get_key_lock(pool, &req.user_id).await?,
+        scoring: crate::db::settings::get_scoring_profile(pool, &req.user_id).await?,
+        artist_separation: crate::db::settings::get_artist_separation(pool, &req.user_id).await?,
+        ..Default::default()
+    };
+    let lint_options = LintOptions {
+        scoring: arrangement_options.scoring,
+        key_lock: arrangement_options.key_lock,
+        artist_separation: arrangement_options.artist_separation,
+        ..Default::default()
+    };
+
+    let variant_group = uuid::Uuid::new_v4().to_string();
+    let mut setlists = Vec::with_capacity(count as usize);
+    if let Err(e) = generate_batch(pool, claude, &req, count, &variant_group, &mut setlists).await {
+        let generated: Vec<String> = setlists.iter().map(|s| s.id.clone()).collect();
+        setlist::discard_setlists(pool, &generated).await;
+        return Err(e);
+    }
+
+    let total_tracks = setlists.iter().map(|s| s.tracks.len()).sum();
+    let unique_tracks = setlists
+        .iter()
+        .flat_map(identities)
+        .collect::<HashSet<_>>()
+        .len();
+    Ok(VariantsResponse {
+        variant_group,
+        variants: rank_variants(setlists, &arrangement_options, &lint_options),
+        unique_tracks,
+        total_tracks,
+    })
+}
+
+/// Generate `count` candidates into `setlists`, each told what the earlier
+/// ones used, and mark the saved ones as drafts of `variant_group`.
+async fn generate_batch(
+    pool: &sqlx::PgPool,
+    claude: &dyn ClaudeClientTrait,
+    req: &GenerateSetlistRequest,
+    count: u32,
+    variant_group: &str,
+    setlists: &mut Vec<SetlistResponse>,
+) -> Result<(), SetlistError> {
+    let mut context = GenerationContext::default();
+    let mut used: Vec<String> = Vec::new();
+    for i in 0..count {
+        context.instructions = (i > 0).then(|| alternative_instructions(i, count, &used));
+        let generated =
+            setlist::generate_setlist_with_context(pool, claude, req.clone(), &context).await?;
+        for t in &generated.tracks {
+            let label = format!("{} - {}", t.artist, t.title);
+            if !used.contains(&label) {
+                used.push(label);
+            }
+        }
+        let id = generated.id.clone();
+        setlists.push(generated);
+        if !id.starts_with("unsaved-") {
+            db::mark_setlist_variant(pool, &id, variant_group).await?;
+        }
+    }
+    Ok(())
+}
+
+/// Keep one variant: it stops being a draft, and unless `discard_others` is
+/// false the other drafts in its group are deleted.
+pub async fn keep_variant(
+    pool: &sqlx::PgPool,
+    id: &str,
+    discard_others: bool,
+) -> Result<KeepVariantResponse, SetlistError> {
+    db::keep_setlist(pool, id).await?;
+    let mut discarded = Vec::new();
+    if discard_others {
+        for sibling in db::list_sibling_drafts(pool, id).await? {
+            if db::delete_setlist(pool, &sibling).await? {
+                discarded.push(sibling);
+            }
+        }
+    }
+    Ok(KeepVariantResponse {
+        setlist: setlist::get_setlist(pool, id).await?,
+        discarded,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Tests
+// ---------------------------------------------------------------------------
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::api::claude::{CacheMetrics, ClaudeError, RequestContentBlock};
+    use crate::services::constraints::TrackConstraints;
+    use std::collections::VecDeque;
+    use std::sync::Mutex;
+
+    /// Returns queued responses in order, one per generation, then fails.
+    struct QueuedClaude {
+        responses: Mutex<VecDeque<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl ClaudeClientTrait for QueuedClaude {
+        async fn generate_setlist(
+            &self,
+            _: &str,
+            _: &str,
+            _: &str,
+            _: u32,
+        ) -> Result<String, ClaudeError> {
+            unreachable!("variants generate with blocks")
+        }
+
+        async fn generate_with_blocks(
+            &self,
+            _: Vec<RequestContentBlock>,
+            _: Vec<RequestContentBlock>,
+            _: &str,
+            _: u32,
+        ) -> Result<(String, CacheMetrics), ClaudeError> {
+            let next = self.responses.lock().unwrap().pop_front();
+            next.map(|r| (r, CacheMetrics::default()))
+                .ok_or_else(|| ClaudeError::Api("no queued response".to_string()))
+        }
+    }
+
+    fn llm_json(tracks: &[(&str, &str)]) -> String {
+        let tracks: Vec<serde_json::Value> = tracks
+            .iter()
+            .enumerate()
+            .map(|(i, (title, camelot))| {
+                serde_json::json!({
+                    "position": i + 1,
+                    "title": title,
+                    "artist": format!("Artist {title}"),
+                    "bpm": 124.0,
+                    "camelot": camelot,
+                    "energy": 6,
+                })
+            })
+            .collect();
+        serde_json::json!({ "tracks": tracks, "notes": null }).to_string()
+    }
+
+    fn request() -> GenerateSetlistRequest {
+        GenerateSetlistRequest {
+            user_id: "user1".to_string(),
+            prompt: "sunset set".to_string(),
+            track_count: Some(3),
+            target_duration_minutes: None,
+            energy_profile: None,
+            energy_curve: None,
+            key_path: None,
+            source_playlist_id: None,
+            source_crate_id: None,
+            seed_tracklist: None,
+            creative_mode: None,
+            bpm_range: None,
+            verify: false,
+            name: None,
+            constraints: TrackConstraints::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_generate_variants_ranks_and_keeps_one() {
+        let pool = crate::db::create_test_pool().await;
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::from([
+                // Clashing keys all the way through
+                llm_json(&[("A", "8A"), ("B", "3B"), ("C", "11A")]),
+                // Smooth walk round the wheel, sharing "A"
+                llm_json(&[("A", "8A"), ("D", "9A"), ("E", "10A")]),
+            ])),
+        };
+
+        let result = generate_variants(&pool, &claude, request(), 2)
+            .await
+            .unwrap();
+        assert_eq!(result.variants.len(), 2);
+        assert_eq!(result.total_tracks, 6);
+        assert_eq!(result.unique_tracks, 5);
+
+        let best = &result.variants[0];
+        assert_eq!(best.rank, 1);
+        assert_eq!(best.setlist.tracks[1].title, "D");
+        assert!(best.arranged_flow_score > result.variants[1].arranged_flow_score);
+        assert_eq!(best.overlap.len(), 1);
+        assert_eq!(best.overlap[0].shared_tracks, 1);
+        assert!((best.overlap[0].jaccard - 0.2).abs() < 1e-9);
+
+        // Both count against the daily cap
+        let used = crate::db::tracks::get_daily_generation_count(&pool, "user1")
+            .await
+            .unwrap();
+        assert_eq!(used, 2);
+
+        let kept = keep_variant(&pool, &best.setlist.id, true).await.unwrap();
+        let other = &result.variants[1].setlist.id;
+        assert_eq!(kept.discarded, vec![other.clone()]);
+        assert!(matches!(
+            setlist::get_setlist(&pool, other).await,
+            Err(SetlistError::NotFound(_))
+        ));
+        let listed = db::list_setlists(&pool, "user1", 10, 0).await.unwrap();
+        assert_eq!(listed.len(), 1);
+        assert!(!listed[0].is_draft);
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_generate_variants_needs_allowance_for_all() {
+        let pool = crate::db::create_test_pool().await;
+        let today = chrono::Utc::now().format("%Y-%m-%d").to_string();
+        sqlx::query(
+            "INSERT INTO user_usage (id, user_id, date, generation_count) VALUES ('u1', 'user1', $1, 48)",
+        )
+        .bind(&today)
+        .execute(&pool)
+        .await
+        .unwrap();
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::new()),
+        };
+
+        let err = generate_variants(&pool, &claude, request(), 3)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, SetlistError::GenerationLimitExceeded(_)));
+        for count in [1, 9] {
+            let err = generate_variants(&pool, &claude, request(), count)
+                .await
+                .unwrap_err();
+            assert!(
+                matches!(err, SetlistError::InvalidRequest(ref m) if m.contains("between 2 and")),
+                "{err}"
+            );
+        }
+
+        pool.close().await;
+    }
+
+    #[tokio::test]
+    async fn test_failed_batch_discards_drafts() {
+        let pool = crate::db::create_test_pool().await;
+        // Two of three candidates generate; the third fails
+        let claude = QueuedClaude {
+            responses: Mutex::new(VecDeque::from([
+                llm_json(&[("A", "8A"), ("B", "9A"), ("C", "10A")]),
+                llm_json(&[("D", "8A"), ("E", "9A"), ("F", "10A")]),
+            ])),
+        };
+
+        assert!(generate_variants(&pool, &claude, request(), 3)
+            .await
+            .is_err());
+        let left = db::list_setlists(&pool, "user1", 10, 0).await.unwrap();
+        assert!(left.is_empty());
+
+        pool.close().await;
+    }
+}